@@ -19,7 +19,8 @@ use xelis_vm::ValueCell;
 use crate::{
     account::{CiphertextCache, Nonce, VersionedBalance, VersionedNonce},
     block::{Algorithm, BlockVersion, TopoHeight, EXTRA_NONCE_SIZE},
-    crypto::{elgamal::RISTRETTO_COMPRESSED_SIZE, Address, Hash},
+    contract::{vm::ExitValue, ContractAbi, ContractEventKind},
+    crypto::{elgamal::RISTRETTO_COMPRESSED_SIZE, Address, Hash, HumanReadableProof, Signature},
     difficulty::{CumulativeDifficulty, Difficulty},
     network::Network,
     time::{TimestampMillis, TimestampSeconds},
@@ -214,6 +215,14 @@ pub struct GetMempoolSummaryResult<'a> {
     pub transactions: Vec<MempoolTransactionSummary<'a>>,
     // How many TXs in total available in mempool
     pub total: usize,
+    // Total size in bytes of all TXs in mempool
+    pub total_size: usize,
+    // Maximum count of TXs allowed in mempool, 0 means no limit
+    pub max_txs: usize,
+    // Maximum size in bytes of all TXs in mempool, 0 means no limit
+    pub max_size: usize,
+    // Time to live in seconds of a TX in mempool, 0 means no expiration
+    pub tx_ttl: u64,
 }
 
 pub type BlockResponse = RPCBlockResponse<'static>;
@@ -335,6 +344,25 @@ pub struct GetBalanceAtTopoHeightParams<'a> {
     pub topoheight: TopoHeight
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct VerifyHumanReadableProofParams<'a> {
+    // Address of the prover
+    pub address: Cow<'a, Address>,
+    // Proof encoded as a bech32 string
+    #[schemars(with = "String")]
+    pub proof: HumanReadableProof
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct VerifyHumanReadableProofResult {
+    pub valid: bool,
+    // Amount proven: whole balance for a balance proof
+    // or the minimum owned for an ownership proof
+    pub amount: u64,
+    pub asset: Hash,
+    pub topoheight: TopoHeight
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetBalancesAtMaximumTopoHeightParams<'a> {
     pub address: Cow<'a, Address>,
@@ -590,6 +618,40 @@ pub struct AccountHistoryEntry {
     pub block_timestamp: TimestampMillis
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetAccountTransactionsParams<'a> {
+    pub address: Cow<'a, Address>,
+    // Only the TXs that moved this asset for the account
+    pub asset: Option<Cow<'a, Hash>>,
+    pub minimum_topoheight: Option<TopoHeight>,
+    pub maximum_topoheight: Option<TopoHeight>,
+    // TXs in which the account received funds
+    #[serde(default = "default_true_value")]
+    pub incoming_flow: bool,
+    // TXs sent by the account
+    #[serde(default = "default_true_value")]
+    pub outgoing_flow: bool,
+    // Last transaction returned by the previous page
+    pub after: Option<AccountTransactionCursor>,
+    pub maximum: Option<usize>
+}
+
+// Position of a transaction in the archival index
+#[derive(Serialize, Deserialize, JsonSchema, Clone)]
+pub struct AccountTransactionCursor {
+    pub topoheight: TopoHeight,
+    pub hash: Hash
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct AccountTransactionEntry {
+    pub topoheight: TopoHeight,
+    pub hash: Hash,
+    pub incoming: bool,
+    pub outgoing: bool,
+    pub assets: IndexSet<Hash>
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetAccountAssetsParams<'a> {
     pub address: Cow<'a, Address>,
@@ -653,6 +715,93 @@ pub struct PruneChainResult {
     pub pruned_topoheight: TopoHeight,
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct BackupDBParams<'a> {
+    /// Empty directory in which the backup is created
+    pub path: Cow<'a, String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct BackupDBResult {
+    /// Topoheight of the chain stored in the backup
+    pub topoheight: TopoHeight,
+    /// Hash of the block at this topoheight
+    pub top_hash: Hash,
+}
+
+// Payout scheme used by the GetWork server in pool mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum PoolPayoutScheme {
+    // Each share is paid its expected value when submitted
+    Pps,
+    // Each block reward is split between the last N shares
+    #[default]
+    Pplns
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct PoolWorkerEntry<'a> {
+    pub address: Cow<'a, Address>,
+    pub worker: Cow<'a, str>,
+    // Valid shares submitted
+    pub shares: u64,
+    // Invalid or duplicated shares submitted
+    pub invalid_shares: u64,
+    pub blocks_found: u64,
+    // Estimated hashrate in H/s based on the recent shares
+    pub hashrate: u64,
+    // Difficulty of the last share, adjusted for each session
+    pub share_difficulty: u64,
+    pub last_share: TimestampMillis
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetPoolSharesResult<'a> {
+    // Starting difficulty of the shares for a new session, capped to the block difficulty
+    pub share_difficulty: Difficulty,
+    pub workers: Vec<PoolWorkerEntry<'a>>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct PoolBalanceEntry<'a> {
+    pub address: Cow<'a, Address>,
+    // Amount credited to the miner, in atomic units
+    pub amount: u64
+}
+
+// State of a block found by the pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolBlockStatus {
+    // Not yet stable, its reward is not credited
+    Pending,
+    // Stable and ordered in the DAG, its reward is credited
+    Confirmed,
+    // Stable but not ordered in the DAG, nothing is credited
+    Orphaned
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct PoolBlockEntry<'a> {
+    pub hash: Cow<'a, Hash>,
+    pub height: u64,
+    // Miner reward of the block
+    pub reward: u64,
+    pub timestamp: TimestampMillis,
+    pub status: PoolBlockStatus
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetPoolPayoutsResult<'a> {
+    pub scheme: PoolPayoutScheme,
+    // Address receiving the block rewards
+    pub address: Cow<'a, Address>,
+    pub balances: Vec<PoolBalanceEntry<'a>>,
+    pub blocks: Vec<PoolBlockEntry<'a>>
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetContractsParams {
     pub skip: Option<usize>,
@@ -909,6 +1058,26 @@ pub struct GetContractLogsParams<'a> {
     pub caller: Cow<'a, Hash>
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetContractEventsParams<'a> {
+    pub contract: Cow<'a, Hash>,
+    // Only the events fired with this id
+    pub id: Option<u64>,
+    pub minimum_topoheight: Option<TopoHeight>,
+    pub maximum_topoheight: Option<TopoHeight>,
+    // Last event returned by the previous page
+    pub after: Option<ContractEventCursor>,
+    pub maximum: Option<usize>
+}
+
+// Position of an event in the archival index
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy)]
+pub struct ContractEventCursor {
+    pub topoheight: TopoHeight,
+    pub event_id: u64,
+    pub index: u32
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetContractScheduledExecutionsAtTopoHeightParams {
     pub topoheight: TopoHeight,
@@ -933,9 +1102,30 @@ pub struct GetContractsOutputsResult<'a> {
 
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetContractModuleParams<'a> {
+    pub contract: Cow<'a, Hash>,
+    // Retrieve the module used at this topoheight
+    // If not set, the latest module is returned
+    #[serde(default)]
+    pub topoheight: Option<TopoHeight>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct GetContractAbiParams<'a> {
     pub contract: Cow<'a, Hash>
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct RegisterContractAbiParams<'a> {
+    pub contract: Cow<'a, Hash>,
+    pub abi: Cow<'a, ContractAbi>,
+    // Topoheight at which the described module was deployed
+    pub topoheight: TopoHeight,
+    // Must be greater than the version registered for this module
+    pub version: u64,
+    // Signature of the ABI signing bytes by the contract deployer
+    pub signature: Signature
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetContractDataParams<'a> {
     pub contract: Cow<'a, Hash>,
@@ -1004,6 +1194,85 @@ pub struct RegisteredExecution<'a> {
     pub execution_topoheight: TopoHeight,
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CallContractViewParams<'a> {
+    pub contract: Cow<'a, Hash>,
+    // chunk to call
+    pub chunk_id: u16,
+    // parameters passed to the chunk
+    #[serde(default)]
+    pub parameters: Vec<ValueCell>,
+    // maximum gas usable by the call
+    pub max_gas: Option<u64>,
+    // topoheight at which the call is executed
+    // if not set, the current topoheight is used
+    pub topoheight: Option<TopoHeight>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CallContractViewResult {
+    // topoheight at which the call was executed
+    pub topoheight: TopoHeight,
+    pub used_gas: u64,
+    pub exit_value: ExitValue,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct SimulateTransactionParams {
+    pub data: String // should be in hex format
+}
+
+// Plaintext balance change of a contract during the simulation
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct SimulatedContractBalance<'a> {
+    pub contract: Cow<'a, Hash>,
+    pub asset: Cow<'a, Hash>,
+    // balance before the execution
+    pub previous: u64,
+    // balance after the execution
+    pub current: u64,
+}
+
+// Plaintext transfer made by a contract to an account
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct SimulatedTransfer<'a> {
+    pub destination: Cow<'a, Address>,
+    pub asset: Cow<'a, Hash>,
+    pub amount: u64,
+}
+
+// Event fired by a contract during the simulation
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct SimulatedContractEvent<'a> {
+    pub contract: Cow<'a, Hash>,
+    pub event_id: u64,
+    pub data: Cow<'a, ValueCell>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct SimulateTransactionResult<'a> {
+    pub hash: Cow<'a, Hash>,
+    // topoheight at which the TX would be executed
+    pub topoheight: TopoHeight,
+    // TX is valid and its contract call (if any) exited successfully
+    pub success: bool,
+    // verification or execution error
+    pub error: Option<String>,
+    // TX fees paid to the miner
+    pub fees_paid: u64,
+    // TX fees burned
+    pub fees_burned: u64,
+    // gas used by the contract call, refunded gas excluded
+    pub used_gas: u64,
+    // gas refunded to the sender
+    pub refunded_gas: u64,
+    pub contract_logs: Vec<RPCContractLog<'a>>,
+    pub events: Vec<SimulatedContractEvent<'a>>,
+    pub contract_balances: Vec<SimulatedContractBalance<'a>>,
+    pub transfers: Vec<SimulatedTransfer<'a>>,
+    pub assets_created: Vec<Cow<'a, Hash>>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum NotifyEvent {
@@ -1031,6 +1300,10 @@ pub enum NotifyEvent {
     // When a new transaction is added in mempool
     // it contains TransactionAddedInMempoolEvent struct as value
     TransactionAddedInMempool,
+    // When a pending transaction in mempool has been replaced
+    // by another one using the same source and nonce with a higher fee
+    // it contains TransactionReplacedEvent struct as value
+    TransactionReplaced,
     // When a transaction has been included in a valid block & executed on chain
     // it contains TransactionExecutedEvent struct as value
     TransactionExecuted,
@@ -1056,9 +1329,17 @@ pub enum NotifyEvent {
     },
     // When a new contract has been deployed
     ContractDeploy,
+    // When a contract has replaced its module
+    // It contains ContractUpgradeEvent struct as value
+    ContractUpgrade {
+        contract: Hash
+    },
     // When a new asset has been registered
     // It contains NewAssetEvent struct as value
     NewAsset,
+    // When the metadata or pause state of an asset has been updated by its owner
+    // It contains AssetUpdateEvent struct as value
+    AssetUpdate,
     // When a new peer has connected to us
     // It contains PeerConnectedEvent struct as value
     PeerConnected,
@@ -1125,6 +1406,21 @@ pub type TransactionAddedInMempoolEvent = MempoolTransactionSummary<'static>;
 // Value of NotifyEvent::TransactionOrphaned
 pub type TransactionOrphanedEvent = GetTransactionResult<'static>;
 
+// Value of NotifyEvent::TransactionReplaced
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct TransactionReplacedEvent<'a> {
+    // TX added in mempool
+    pub hash: Cow<'a, Hash>,
+    // TX that got replaced
+    pub replaced: Cow<'a, Hash>,
+    // Pending TXs of the same source that were no longer
+    // valid after the replacement and got evicted
+    pub evicted: Vec<Cow<'a, Hash>>,
+    pub source: Address,
+    pub nonce: u64,
+    pub fee_per_kb: u64,
+}
+
 // Value of NotifyEvent::TransactionExecuted
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct TransactionExecutedEvent<'a> {
@@ -1141,6 +1437,14 @@ pub struct NewAssetEvent<'a> {
     pub topoheight: TopoHeight,
 }
 
+// Value of NotifyEvent::AssetUpdate
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct AssetUpdateEvent<'a> {
+    pub asset: Cow<'a, Hash>,
+    pub block_hash: Cow<'a, Hash>,
+    pub topoheight: TopoHeight,
+}
+
 #[derive(Default, Serialize, Deserialize, JsonSchema)]
 pub struct ContractTransfersEntry<'a> {
     // Assets transferred to the key
@@ -1202,6 +1506,17 @@ pub struct ContractEvent<'a> {
     pub data: Cow<'a, ValueCell>
 }
 
+// Event returned by the archival index
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ContractEventEntry<'a> {
+    #[serde(flatten)]
+    pub event: ContractEvent<'a>,
+    // position of the event among the ones with the same id in the block
+    pub index: u32,
+    // how the event was fired
+    pub kind: ContractEventKind
+}
+
 // Value of NotifyEvent::PeerConnected
 pub type PeerConnectedEvent = PeerEntry<'static>;
 
@@ -1244,4 +1559,12 @@ pub struct ContractDeployEvent<'a> {
     pub contract: Cow<'a, Hash>,
     pub block_hash: Cow<'a, Hash>,
     pub topoheight: TopoHeight,
+}
+
+// Value of NotifyEvent::ContractUpgrade
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ContractUpgradeEvent<'a> {
+    pub contract: Cow<'a, Hash>,
+    pub block_hash: Cow<'a, Hash>,
+    pub topoheight: TopoHeight,
 }
\ No newline at end of file
@@ -11,7 +11,7 @@ use bulletproofs::RangeProof;
 use xelis_vm::ValueCell;
 use crate::{
     account::Nonce,
-    contract::{ContractLog, ScheduledExecutionKindLog, ExitError},
+    contract::{ContractAbi, ContractLog, ContractVersion, ScheduledExecutionKindLog, ExitError},
     crypto::{
         elgamal::{CompressedCommitment, CompressedHandle},
         proofs::CiphertextValidityProof,
@@ -305,6 +305,24 @@ pub enum RPCContractLog<'a> {
     Event {
         contract: Cow<'a, Hash>,
         event_id: u64,
+        // Event name from the contract ABI, if registered
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<Cow<'a, str>>,
+    },
+    Upgraded {
+        contract: Cow<'a, Hash>,
+        version: ContractVersion,
+    },
+    AssetUpdated {
+        // Contract owner
+        contract: Cow<'a, Hash>,
+        asset: Cow<'a, Hash>
+    },
+    AssetPaused {
+        // Contract owner
+        contract: Cow<'a, Hash>,
+        asset: Cow<'a, Hash>,
+        paused: bool
     }
 }
 
@@ -357,7 +375,21 @@ impl<'a> RPCContractLog<'a> {
             ContractLog::ExitError(err) => RPCContractLog::ExitError { err: Cow::Owned(err) },
             ContractLog::Event { contract, event_id } => RPCContractLog::Event {
                 contract: Cow::Owned(contract),
-                event_id
+                event_id,
+                name: None
+            },
+            ContractLog::Upgraded { contract, version } => RPCContractLog::Upgraded {
+                contract: Cow::Owned(contract),
+                version
+            },
+            ContractLog::AssetUpdated { contract, asset } => RPCContractLog::AssetUpdated {
+                contract: Cow::Owned(contract),
+                asset: Cow::Owned(asset)
+            },
+            ContractLog::AssetPaused { contract, asset, paused } => RPCContractLog::AssetPaused {
+                contract: Cow::Owned(contract),
+                asset: Cow::Owned(asset),
+                paused
             }
         }
     }
@@ -410,10 +442,40 @@ impl<'a> RPCContractLog<'a> {
             ContractLog::ExitError(err) => RPCContractLog::ExitError { err: Cow::Borrowed(err) },
             ContractLog::Event { contract, event_id } => RPCContractLog::Event {
                 contract: Cow::Borrowed(contract),
-                event_id: *event_id
+                event_id: *event_id,
+                name: None
+            },
+            ContractLog::Upgraded { contract, version } => RPCContractLog::Upgraded {
+                contract: Cow::Borrowed(contract),
+                version: *version
+            },
+            ContractLog::AssetUpdated { contract, asset } => RPCContractLog::AssetUpdated {
+                contract: Cow::Borrowed(contract),
+                asset: Cow::Borrowed(asset)
+            },
+            ContractLog::AssetPaused { contract, asset, paused } => RPCContractLog::AssetPaused {
+                contract: Cow::Borrowed(contract),
+                asset: Cow::Borrowed(asset),
+                paused: *paused
             }
         }
     }
+
+    // Contract of an event log, used to retrieve its ABI
+    pub fn get_event_contract(&self) -> Option<&Hash> {
+        match self {
+            RPCContractLog::Event { contract, .. } => Some(contract),
+            _ => None
+        }
+    }
+
+    // Set the name of an event log using the ABI of its contract
+    pub fn set_event_name(&mut self, abi: &ContractAbi) {
+        if let RPCContractLog::Event { event_id, name, .. } = self {
+            *name = abi.get_event(*event_id)
+                .map(|event| Cow::Owned(event.name.clone()));
+        }
+    }
 }
 impl<'a> From<RPCContractLog<'a>> for ContractLog {
     fn from(output: RPCContractLog<'a>) -> Self {
@@ -465,9 +527,22 @@ impl<'a> From<RPCContractLog<'a>> for ContractLog {
                 payload: payload.into_owned()
             },
             RPCContractLog::ExitError { err } => ContractLog::ExitError(err.into_owned()),
-            RPCContractLog::Event { contract, event_id } => ContractLog::Event {
+            RPCContractLog::Event { contract, event_id, .. } => ContractLog::Event {
                 contract: contract.into_owned(),
                 event_id
+            },
+            RPCContractLog::Upgraded { contract, version } => ContractLog::Upgraded {
+                contract: contract.into_owned(),
+                version
+            },
+            RPCContractLog::AssetUpdated { contract, asset } => ContractLog::AssetUpdated {
+                contract: contract.into_owned(),
+                asset: asset.into_owned()
+            },
+            RPCContractLog::AssetPaused { contract, asset, paused } => ContractLog::AssetPaused {
+                contract: contract.into_owned(),
+                asset: asset.into_owned(),
+                paused
             }
         }
     }
@@ -10,6 +10,7 @@ use crate::{
         elgamal::CompressedCiphertext,
         Address,
         Hash,
+        HumanReadableProof,
         PrivateKey,
         Signature,
     },
@@ -191,6 +192,25 @@ pub enum BaseFeeMode {
     Cap(u64),
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct BumpFeeParams {
+    // Hash of the pending TX to replace
+    pub hash: Hash,
+    // Fee to use for the new TX
+    // It must be high enough to replace the pending TX in the daemon mempool
+    #[serde(default)]
+    pub fee: FeeBuilder,
+    // Base fee configured, by default, fetch from daemon
+    #[serde(default)]
+    pub base_fee: BaseFeeMode,
+    // Maximum fee allowed to pay
+    // If the calculated fee is above this value, an error is returned
+    pub fee_limit: Option<u64>,
+    // Returns the TX in HEX format also
+    #[serde(default = "default_false_value")]
+    pub tx_as_hex: bool,
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct EstimateFeesParams {
     #[serde(flatten)]
@@ -199,6 +219,28 @@ pub struct EstimateFeesParams {
     pub fee: FeeBuilder,
     #[serde(default)]
     pub base_fee: BaseFeeMode,
+    // Simulate the contract call to recommend a max gas
+    // The fees are then estimated with the recommended max gas
+    #[serde(default)]
+    pub recommend_max_gas: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct EstimateFeesResult {
+    // Minimum fees required to be valid on chain
+    pub fee: u64,
+    // Recommended max gas for a contract call
+    // based on its execution against the daemon state
+    pub max_gas: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum EstimateFeesResponse {
+    // Only the fees are returned by default
+    Fee(u64),
+    // Returned when a max gas recommendation is requested
+    WithMaxGas(EstimateFeesResult),
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -328,6 +370,33 @@ pub struct VerifySignedDataParams {
     pub address: Address,
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CreateBalanceProofParams {
+    // Asset to prove, DAPA by default
+    pub asset: Option<Hash>,
+    // Topoheight of the balance to use, latest by default
+    pub topoheight: Option<TopoHeight>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CreateOwnershipProofParams {
+    // Asset to prove, DAPA by default
+    pub asset: Option<Hash>,
+    // Minimum amount owned to prove
+    pub amount: u64,
+    // Topoheight of the balance to use, latest by default
+    pub topoheight: Option<TopoHeight>
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CreateProofResult {
+    // Proof encoded as a bech32 string
+    #[schemars(with = "String")]
+    pub proof: HumanReadableProof,
+    // Topoheight of the balance used
+    pub topoheight: TopoHeight
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct GetValueFromKeyParams {
     pub tree: String,
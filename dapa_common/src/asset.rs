@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use indexmap::IndexMap;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -177,28 +178,51 @@ impl MaxSupplyMode {
     }
 }
 
-impl Serializer for MaxSupplyMode {
-    fn write(&self, writer: &mut Writer) {
+// The high bits of the max supply mode tag are free
+// They are used to store the version of the AssetData encoding
+const ASSET_DATA_VERSION_SHIFT: u8 = 4;
+
+impl MaxSupplyMode {
+    // Write the max supply mode with the AssetData version in its tag
+    fn write_with_version(&self, writer: &mut Writer, version: u8) {
+        let version = version << ASSET_DATA_VERSION_SHIFT;
         match self {
             Self::None => {
-                writer.write_u8(0);
+                writer.write_u8(version);
             },
             Self::Fixed(max) => {
-                writer.write_u8(1);
+                writer.write_u8(version | 1);
                 max.write(writer);
             },
             Self::Mintable(max) => {
-                writer.write_u8(2);
+                writer.write_u8(version | 2);
                 max.write(writer);
             }
         }
     }
 
+    // Read the max supply mode and the AssetData version from its tag
+    fn read_with_version(reader: &mut Reader) -> Result<(Self, u8), ReaderError> {
+        let tag = reader.read_u8()?;
+        let mode = match tag & ((1 << ASSET_DATA_VERSION_SHIFT) - 1) {
+            0 => Self::None,
+            1 => Self::Fixed(reader.read_u64()?),
+            2 => Self::Mintable(reader.read_u64()?),
+            _ => return Err(ReaderError::InvalidValue)
+        };
+
+        Ok((mode, tag >> ASSET_DATA_VERSION_SHIFT))
+    }
+}
+
+impl Serializer for MaxSupplyMode {
+    fn write(&self, writer: &mut Writer) {
+        self.write_with_version(writer, 0);
+    }
+
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        match reader.read_u8()? {
-            0 => Ok(Self::None),
-            1 => Ok(Self::Fixed(reader.read_u64()?)),
-            2 => Ok(Self::Mintable(reader.read_u64()?)),
+        match Self::read_with_version(reader)? {
+            (mode, 0) => Ok(mode),
             _ => Err(ReaderError::InvalidValue)
         }
     }
@@ -216,7 +240,13 @@ pub struct AssetData {
     // The total supply of the asset
     max_supply: MaxSupplyMode,
     // Contract owning this asset
-    owner: AssetOwner
+    owner: AssetOwner,
+    // Transfers of this asset are paused by its owner
+    #[serde(default)]
+    paused: bool,
+    // Extra metadata set by its owner (icon url, description...)
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    metadata: IndexMap<String, String>
 }
 
 impl AssetData {
@@ -226,7 +256,9 @@ impl AssetData {
             name,
             ticker,
             max_supply,
-            owner
+            owner,
+            paused: false,
+            metadata: IndexMap::new()
         }
     }
 
@@ -261,29 +293,83 @@ impl AssetData {
     pub fn get_owner_mut(&mut self) -> &mut AssetOwner {
         &mut self.owner
     }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn get_metadata(&self) -> &IndexMap<String, String> {
+        &self.metadata
+    }
+
+    pub fn get_metadata_mut(&mut self) -> &mut IndexMap<String, String> {
+        &mut self.metadata
+    }
+
+    // Version of the encoding required by this asset
+    // 0: legacy encoding, ending after the owner
+    // 1: the paused flag and the metadata are written after the owner
+    // Assets without any administration data keep the legacy encoding
+    // so they are still readable by older nodes
+    fn get_encoding_version(&self) -> u8 {
+        if self.paused || !self.metadata.is_empty() {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 impl Serializer for AssetData {
     fn write(&self, writer: &mut Writer) {
+        let version = self.get_encoding_version();
         self.decimals.write(writer);
         self.name.write(writer);
         self.ticker.write(writer);
-        self.max_supply.write(writer);
+        self.max_supply.write_with_version(writer, version);
         self.owner.write(writer);
+        if version > 0 {
+            self.paused.write(writer);
+            self.metadata.write(writer);
+        }
     }
 
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
         let decimals = reader.read()?;
         let name = reader.read()?;
         let ticker = reader.read()?;
-        let max_supply = reader.read()?;
+        let (max_supply, version) = MaxSupplyMode::read_with_version(reader)?;
         let owner = reader.read()?;
 
-        Ok(Self::new(decimals, name, ticker, max_supply, owner))
+        let mut data = Self::new(decimals, name, ticker, max_supply, owner);
+        match version {
+            0 => {},
+            1 => {
+                data.paused = reader.read()?;
+                data.metadata = reader.read()?;
+            },
+            _ => return Err(ReaderError::InvalidValue)
+        }
+
+        Ok(data)
     }
 
     fn size(&self) -> usize {
-        self.decimals.size() + self.name.size() + self.max_supply.size()
+        let mut size = self.decimals.size()
+            + self.name.size()
+            + self.ticker.size()
+            + self.max_supply.size()
+            + self.owner.size();
+
+        if self.get_encoding_version() > 0 {
+            size += self.paused.size() + self.metadata.size();
+        }
+
+        size
     }
 }
 
@@ -296,4 +382,51 @@ pub struct RPCAssetData<'a> {
     // Inner data
     #[serde(flatten)]
     pub inner: AssetData
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_data_serialization() {
+        let mut data = AssetData::new(8, "Token".to_owned(), "TKN".to_owned(), MaxSupplyMode::None, AssetOwner::None);
+
+        data.set_paused(true);
+        data.get_metadata_mut().insert("icon".to_owned(), "https://example.com/icon.png".to_owned());
+
+        let bytes = data.to_bytes();
+        assert_eq!(bytes.len(), data.size());
+        let read = AssetData::from_bytes(&bytes).unwrap();
+        assert!(read.is_paused());
+        assert_eq!(read.get_metadata(), data.get_metadata());
+
+        // Assets without administration data keep the legacy encoding
+        let legacy = AssetData::new(8, "Token".to_owned(), "TKN".to_owned(), MaxSupplyMode::Fixed(100), AssetOwner::None);
+        let bytes = legacy.to_bytes();
+        assert_eq!(bytes.len(), legacy.size());
+        assert_eq!(bytes.len(), 1 + legacy.name.size() + legacy.ticker.size() + 9 + 1);
+        let read = AssetData::from_bytes(&bytes).unwrap();
+        assert!(!read.is_paused());
+        assert!(read.get_metadata().is_empty());
+
+        // Both encodings can be followed by other values
+        for data in [&data, &legacy] {
+            let mut bytes = Vec::new();
+            let mut writer = Writer::new(&mut bytes);
+            data.write(&mut writer);
+            writer.write_u64(42);
+
+            let mut reader = Reader::new(&bytes);
+            let read = AssetData::read(&mut reader).unwrap();
+            assert_eq!(read.is_paused(), data.is_paused());
+            assert_eq!(read.get_metadata(), data.get_metadata());
+            assert_eq!(reader.read_u64().unwrap(), 42);
+        }
+
+        // Unknown encoding version
+        let mut bytes = legacy.to_bytes();
+        bytes[1 + legacy.name.size() + legacy.ticker.size()] |= 2 << ASSET_DATA_VERSION_SHIFT;
+        assert!(AssetData::from_bytes(&bytes).is_err());
+    }
+}
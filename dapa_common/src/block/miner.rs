@@ -278,6 +278,11 @@ impl<'a> MinerWork<'a> {
         self.nonce += 1;
     }
 
+    #[inline(always)]
+    pub fn set_nonce(&mut self, nonce: u64) {
+        self.nonce = nonce;
+    }
+
     #[inline(always)]
     pub fn set_miner(&mut self, miner: Cow<'a, PublicKey>) {
         self.miner = Some(miner);
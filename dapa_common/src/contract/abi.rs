@@ -0,0 +1,271 @@
+use anyhow::{anyhow, Error};
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use xelis_vm::{Access, Module};
+use crate::{
+    block::TopoHeight,
+    crypto::{elgamal::PublicKey, Hash, Signature},
+    serializer::*
+};
+use super::CONTRACT_ARGUMENT_TYPES;
+
+// Maximum size in bytes of a name in the ABI
+pub const MAX_ABI_NAME_SIZE: usize = 64;
+// Maximum size in bytes of a serialized ABI
+pub const MAX_ABI_SIZE: usize = 64 * 1024;
+
+// Type used for a parameter that can't be described
+// using one of the contract argument types
+pub const ABI_ANY_TYPE: &str = "any";
+
+// Named and typed value of an entry or event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ContractAbiParam {
+    pub name: String,
+    // One of the contract argument types or "any"
+    #[serde(rename = "type")]
+    pub kind: String
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ContractAbiEntry {
+    pub name: String,
+    #[serde(default)]
+    pub params: Vec<ContractAbiParam>
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ContractAbiEvent {
+    pub name: String,
+    #[serde(default)]
+    pub fields: Vec<ContractAbiParam>
+}
+
+// Human readable description of a contract interface
+// The bytecode only contains chunk ids, so it is provided
+// by the contract deployer and stored by the nodes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ContractAbi {
+    // Entry chunk id => entry
+    pub entries: IndexMap<u16, ContractAbiEntry>,
+    // Event id => event
+    #[serde(default)]
+    pub events: IndexMap<u64, ContractAbiEvent>
+}
+
+impl ContractAbi {
+    pub fn get_entry(&self, id: u16) -> Option<&ContractAbiEntry> {
+        self.entries.get(&id)
+    }
+
+    pub fn get_entry_by_name(&self, name: &str) -> Option<(u16, &ContractAbiEntry)> {
+        self.entries.iter()
+            .find(|(_, entry)| entry.name == name)
+            .map(|(id, entry)| (*id, entry))
+    }
+
+    pub fn get_event(&self, id: u64) -> Option<&ContractAbiEvent> {
+        self.events.get(&id)
+    }
+
+    // Message that must be signed by the contract deployer to register the ABI
+    // It is bound to the module deployed at topoheight and to a version
+    // that must increase for each new registration, so a previous one can't be replayed
+    pub fn get_signing_bytes(&self, contract: &Hash, topoheight: TopoHeight, version: u64) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(contract.size() + topoheight.size() + version.size() + self.size());
+        let mut writer = Writer::new(&mut bytes);
+        contract.write(&mut writer);
+        writer.write_u64(topoheight);
+        writer.write_u64(version);
+        self.write(&mut writer);
+        bytes
+    }
+
+    // Verify that the ABI was signed by the contract deployer
+    // for the module deployed at topoheight and this version
+    pub fn verify_signature(&self, contract: &Hash, topoheight: TopoHeight, version: u64, signature: &Signature, deployer: &PublicKey) -> bool {
+        signature.verify(&self.get_signing_bytes(contract, topoheight, version), deployer)
+    }
+
+    // Verify the ABI against the contract module
+    // Each entry must be a callable chunk and match its declared parameters count
+    pub fn verify(&self, module: &Module) -> Result<(), Error> {
+        let chunks = module.chunks();
+        for (id, entry) in self.entries.iter() {
+            verify_name(&entry.name)?;
+            verify_params(&entry.params)?;
+
+            let chunk = chunks.get(*id as usize)
+                .ok_or_else(|| anyhow!("Entry '{}' references unknown chunk {}", entry.name, id))?;
+
+            match &chunk.access {
+                Access::Entry { parameters } | Access::All { parameters } => {
+                    if let Some(parameters) = parameters {
+                        if parameters.len() != entry.params.len() {
+                            return Err(anyhow!("Entry '{}' expects {} parameters, got {}", entry.name, parameters.len(), entry.params.len()))
+                        }
+                    }
+                },
+                _ => return Err(anyhow!("Chunk {} of entry '{}' is not an entry", id, entry.name))
+            }
+        }
+
+        for event in self.events.values() {
+            verify_name(&event.name)?;
+            verify_params(&event.fields)?;
+        }
+
+        Ok(())
+    }
+}
+
+// ABI registered in a node for a contract module
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RegisteredContractAbi {
+    // Topoheight at which the described module was deployed
+    pub topoheight: TopoHeight,
+    // Version signed by the contract deployer
+    pub version: u64,
+    pub abi: ContractAbi
+}
+
+fn verify_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() || name.len() > MAX_ABI_NAME_SIZE {
+        return Err(anyhow!("Invalid name '{}', expected between 1 and {} bytes", name, MAX_ABI_NAME_SIZE))
+    }
+
+    Ok(())
+}
+
+fn verify_params(params: &[ContractAbiParam]) -> Result<(), Error> {
+    if params.len() > u8::MAX as usize {
+        return Err(anyhow!("Too many parameters: {}", params.len()))
+    }
+
+    for param in params {
+        verify_name(&param.name)?;
+        if param.kind != ABI_ANY_TYPE && !CONTRACT_ARGUMENT_TYPES.contains(&param.kind.as_str()) {
+            return Err(anyhow!("Unknown type '{}' for '{}'", param.kind, param.name))
+        }
+    }
+
+    Ok(())
+}
+
+impl Serializer for ContractAbiParam {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_string(&self.name);
+        writer.write_string(&self.kind);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self {
+            name: reader.read_string()?,
+            kind: reader.read_string()?
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.name.size() + self.kind.size()
+    }
+}
+
+impl Serializer for ContractAbiEntry {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_string(&self.name);
+        self.params.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self {
+            name: reader.read_string()?,
+            params: Vec::read(reader)?
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.name.size() + self.params.size()
+    }
+}
+
+impl Serializer for ContractAbiEvent {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_string(&self.name);
+        self.fields.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self {
+            name: reader.read_string()?,
+            fields: Vec::read(reader)?
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.name.size() + self.fields.size()
+    }
+}
+
+impl Serializer for ContractAbi {
+    fn write(&self, writer: &mut Writer) {
+        self.entries.write(writer);
+        self.events.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self {
+            entries: IndexMap::read(reader)?,
+            events: IndexMap::read(reader)?
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.entries.size() + self.events.size()
+    }
+}
+
+impl Serializer for RegisteredContractAbi {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u64(self.topoheight);
+        writer.write_u64(self.version);
+        self.abi.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self {
+            topoheight: reader.read_u64()?,
+            version: reader.read_u64()?,
+            abi: ContractAbi::read(reader)?
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.topoheight.size() + self.version.size() + self.abi.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(name: &str, kind: &str) -> ContractAbiParam {
+        ContractAbiParam { name: name.to_owned(), kind: kind.to_owned() }
+    }
+
+    #[test]
+    fn test_contract_abi_serde() {
+        let mut abi = ContractAbi::default();
+        abi.entries.insert(1, ContractAbiEntry { name: "transfer".to_owned(), params: vec![param("to", "address"), param("amount", "u64")] });
+        abi.events.insert(0, ContractAbiEvent { name: "Transfer".to_owned(), fields: vec![param("amount", "u64")] });
+
+        assert_eq!(ContractAbi::from_bytes(&abi.to_bytes()).unwrap(), abi);
+
+        let json = serde_json::to_string(&abi).unwrap();
+        assert_eq!(serde_json::from_str::<ContractAbi>(&json).unwrap(), abi);
+
+        assert_eq!(abi.get_entry_by_name("transfer").map(|(id, _)| id), Some(1));
+        assert!(verify_params(&[param("x", "struct")]).is_err());
+        assert!(verify_name("").is_err());
+    }
+}
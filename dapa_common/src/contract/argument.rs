@@ -0,0 +1,71 @@
+use std::str::FromStr;
+use anyhow::{anyhow, Context, Error};
+use xelis_vm::{OpaqueWrapper, Primitive, ValueCell};
+use crate::crypto::{Address, Hash};
+
+// Types supported for a contract argument
+pub const CONTRACT_ARGUMENT_TYPES: [&str; 11] = [
+    "null",
+    "bool",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "string",
+    "bytes",
+    "hash",
+    "address"
+];
+
+// Parse a contract argument from a typed string using the `type:value` format
+// Example: `u64:100`, `bool:true`, `string:hello`, `hash:<hex>`, `address:<address>`
+// Bytes are provided in hexadecimal
+pub fn parse_contract_argument(input: &str) -> Result<ValueCell, Error> {
+    let (kind, value) = match input.split_once(':') {
+        Some((kind, value)) => (kind.trim(), value.trim()),
+        None if input.trim() == "null" => ("null", ""),
+        None => return Err(anyhow!("Invalid argument '{}', expected format is <type>:<value>", input))
+    };
+
+    let primitive = match kind {
+        "null" => Primitive::Null,
+        "bool" => Primitive::Boolean(bool::from_str(value).context("Invalid bool value")?),
+        "u8" => Primitive::U8(u8::from_str(value).context("Invalid u8 value")?),
+        "u16" => Primitive::U16(u16::from_str(value).context("Invalid u16 value")?),
+        "u32" => Primitive::U32(u32::from_str(value).context("Invalid u32 value")?),
+        "u64" => Primitive::U64(u64::from_str(value).context("Invalid u64 value")?),
+        "u128" => Primitive::U128(u128::from_str(value).context("Invalid u128 value")?),
+        "string" => Primitive::String(value.to_owned()),
+        "bytes" => return Ok(ValueCell::Bytes(hex::decode(value).context("Invalid bytes hex value")?)),
+        "hash" => {
+            let hash = Hash::from_str(value).map_err(|e| anyhow!("Invalid hash value: {}", e))?;
+            Primitive::Opaque(OpaqueWrapper::new(hash))
+        },
+        "address" => {
+            let address = Address::from_string(value).context("Invalid address value")?;
+            Primitive::Opaque(OpaqueWrapper::new(address))
+        },
+        _ => return Err(anyhow!("Unknown argument type '{}', supported types: {}", kind, CONTRACT_ARGUMENT_TYPES.join(", ")))
+    };
+
+    Ok(primitive.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_contract_argument() {
+        assert_eq!(parse_contract_argument("u64:100").unwrap(), ValueCell::from(Primitive::U64(100)));
+        assert_eq!(parse_contract_argument("bool:true").unwrap(), ValueCell::from(Primitive::Boolean(true)));
+        assert_eq!(parse_contract_argument("string: hello").unwrap(), ValueCell::from(Primitive::String("hello".to_owned())));
+        assert_eq!(parse_contract_argument("bytes:0a0b").unwrap(), ValueCell::Bytes(vec![10, 11]));
+        assert_eq!(parse_contract_argument("null").unwrap(), ValueCell::from(Primitive::Null));
+
+        assert!(parse_contract_argument("u8:256").is_err());
+        assert!(parse_contract_argument("100").is_err());
+        assert!(parse_contract_argument("i64:1").is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use xelis_vm::ValueCell;
 
 use crate::{
-    contract::{ExitError, ScheduledExecutionKindLog},
+    contract::{ContractVersion, ExitError, ScheduledExecutionKindLog},
     crypto::{Hash, PublicKey},
     serializer::*
 };
@@ -97,6 +97,26 @@ pub enum ContractLog {
         contract: Hash,
         // Event id
         event_id: u64,
+    },
+    // Contract module has been replaced by the contract itself
+    Upgraded {
+        // Contract hash
+        contract: Hash,
+        // Version of the new module
+        version: ContractVersion,
+    },
+    // Asset name, ticker or extra metadata updated by its owner
+    AssetUpdated {
+        // Contract owning the asset
+        contract: Hash,
+        asset: Hash
+    },
+    // Asset transfers paused or resumed by its owner
+    AssetPaused {
+        // Contract owning the asset
+        contract: Hash,
+        asset: Hash,
+        paused: bool
     }
 }
 
@@ -177,6 +197,22 @@ impl Serializer for ContractLog {
                 contract.write(writer);
                 event_id.write(writer);
             },
+            ContractLog::Upgraded { contract, version } => {
+                writer.write_u8(14);
+                contract.write(writer);
+                version.write(writer);
+            },
+            ContractLog::AssetUpdated { contract, asset } => {
+                writer.write_u8(15);
+                contract.write(writer);
+                asset.write(writer);
+            },
+            ContractLog::AssetPaused { contract, asset, paused } => {
+                writer.write_u8(16);
+                contract.write(writer);
+                asset.write(writer);
+                paused.write(writer);
+            },
         }
     }
 
@@ -249,6 +285,22 @@ impl Serializer for ContractLog {
                 let event_id = u64::read(reader)?;
                 ContractLog::Event { contract, event_id }
             },
+            14 => {
+                let contract = Hash::read(reader)?;
+                let version = ContractVersion::read(reader)?;
+                ContractLog::Upgraded { contract, version }
+            },
+            15 => {
+                let contract = Hash::read(reader)?;
+                let asset = Hash::read(reader)?;
+                ContractLog::AssetUpdated { contract, asset }
+            },
+            16 => {
+                let contract = Hash::read(reader)?;
+                let asset = Hash::read(reader)?;
+                let paused = bool::read(reader)?;
+                ContractLog::AssetPaused { contract, asset, paused }
+            },
             _ => return Err(ReaderError::InvalidValue)
         })
     }
@@ -269,6 +321,9 @@ impl Serializer for ContractLog {
             ContractLog::TransferPayload { contract, amount, asset, destination, payload } => contract.size() + amount.size() + asset.size() + destination.size() + payload.size(),
             ContractLog::ExitError(err) => err.size(),
             ContractLog::Event { contract, event_id } => contract.size() + event_id.size(),
+            ContractLog::Upgraded { contract, version } => contract.size() + version.size(),
+            ContractLog::AssetUpdated { contract, asset } => contract.size() + asset.size(),
+            ContractLog::AssetPaused { contract, asset, paused } => contract.size() + asset.size() + paused.size(),
         }
     }
 }
\ No newline at end of file
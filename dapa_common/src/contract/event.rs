@@ -0,0 +1,35 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use crate::serializer::*;
+
+// Syscall used by a contract to fire an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractEventKind {
+    // Fired using `fire_rpc_event` for the RPC clients
+    Rpc,
+    // Emitted using `emit_event` for the contracts listening to it
+    // Its data is the array of the parameters given to the callbacks
+    Callback,
+}
+
+impl Serializer for ContractEventKind {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(match self {
+            Self::Rpc => 0,
+            Self::Callback => 1,
+        });
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        match reader.read_u8()? {
+            0 => Ok(Self::Rpc),
+            1 => Ok(Self::Callback),
+            _ => Err(ReaderError::InvalidValue),
+        }
+    }
+
+    fn size(&self) -> usize {
+        1
+    }
+}
@@ -1,3 +1,4 @@
+mod argument;
 mod opaque;
 mod random;
 mod contract_log;
@@ -11,6 +12,8 @@ mod source;
 mod error;
 mod event_callback;
 mod version;
+mod abi;
+mod event;
 
 #[cfg(test)]
 pub mod tests;
@@ -72,6 +75,7 @@ use crate::{
     versioned_type::VersionedState
 };
 
+pub use argument::*;
 pub use random::DeterministicRandom;
 pub use contract_log::*;
 
@@ -86,6 +90,8 @@ pub use source::*;
 pub use error::*;
 pub use event_callback::*;
 pub use version::*;
+pub use abi::*;
+pub use event::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TransferOutput {
@@ -186,6 +192,8 @@ pub struct ChainStateChanges {
     pub events_listeners: HashMap<(Hash, u64), Vec<(Hash, EventCallbackRegistration)>>,
     // Extra Gas fee accumulated during the execution
     pub extra_gas_fee: u64,
+    // Modules upgraded by their contract during the execution
+    pub modules: HashMap<Hash, ContractModule>,
 }
 
 // ChainState shared across each executions
@@ -236,6 +244,20 @@ pub struct ChainState<'a> {
     // This is reduced from the used gas fee at the end of the execution
     // to prevent double refunding/paying
     pub gas_fee_allowance: u64,
+    // Is the execution a read-only view
+    // If true, any syscall modifying the state is rejected
+    pub read_only: bool,
+}
+
+impl<'a> ChainState<'a> {
+    // Ensure the state can be modified by the current execution
+    pub fn ensure_writable(&self) -> Result<(), anyhow::Error> {
+        if self.read_only {
+            return Err(anyhow::anyhow!("state changes are not allowed in read-only mode"))
+        }
+
+        Ok(())
+    }
 }
 
 // Aggregate all events from all executed contracts to track in one structure
@@ -246,7 +268,11 @@ pub struct ContractEventTracker {
     // All the transfers made by all contracts aggregated per public key
     pub aggregated_transfers: HashMap<PublicKey, HashMap<Hash, u64>>,
     // All assets registered by all contracts
-    pub assets_created: IndexSet<Hash>
+    pub assets_created: IndexSet<Hash>,
+    // All contracts that upgraded their module
+    pub contracts_upgraded: IndexSet<Hash>,
+    // All assets updated by their owner
+    pub assets_updated: IndexSet<Hash>
 }
 
 macro_rules! async_handler {
@@ -2217,6 +2243,149 @@ pub fn build_environment<P: ContractProvider>(version: ContractVersion) -> Envir
         );
     }
 
+    if version >= ContractVersion::V2 {
+        // Replace the module of the current contract
+        // The new module is applied only if the execution is successful
+        env.register_static_function(
+            "upgrade",
+            contract_type.clone(),
+            vec![
+                ("module", Type::Bytes),
+            ],
+            FunctionHandler::Sync(contract_upgrade),
+            50_000,
+            None
+        );
+
+        // Hashes and signatures used by other chains
+        // Hashes have the same base cost as Hash::sha3 and are charged per byte
+        // Signatures are priced against Signature::verify (500), based on their verification time:
+        // ed25519 is close to it but also hashes the message, secp256k1 is ~3x slower and ~4x to recover the key
+        env.register_static_function(
+            "sha256",
+            hash_type.clone(),
+            vec![("input", Type::Bytes)],
+            FunctionHandler::Sync(sha256_fn),
+            7500,
+            Some(hash_type.clone())
+        );
+        env.register_static_function(
+            "keccak256",
+            hash_type.clone(),
+            vec![("input", Type::Bytes)],
+            FunctionHandler::Sync(keccak256_fn),
+            7500,
+            Some(hash_type.clone())
+        );
+        env.register_native_function(
+            "ripemd160",
+            None,
+            vec![("input", Type::Bytes)],
+            FunctionHandler::Sync(ripemd160_fn),
+            7500,
+            Some(Type::Bytes)
+        );
+        env.register_native_function(
+            "secp256k1_verify",
+            None,
+            vec![
+                ("message", hash_type.clone()),
+                ("signature", Type::Bytes),
+                ("public_key", Type::Bytes),
+            ],
+            FunctionHandler::Sync(secp256k1_verify_fn),
+            1500,
+            Some(Type::Bool)
+        );
+        env.register_native_function(
+            "secp256k1_recover",
+            None,
+            vec![
+                ("message", hash_type.clone()),
+                ("signature", Type::Bytes),
+            ],
+            FunctionHandler::Sync(secp256k1_recover_fn),
+            2000,
+            Some(Type::Optional(Box::new(Type::Bytes)))
+        );
+        env.register_native_function(
+            "ed25519_verify",
+            None,
+            vec![
+                ("message", Type::Bytes),
+                ("signature", Type::Bytes),
+                ("public_key", Type::Bytes),
+            ],
+            FunctionHandler::Sync(ed25519_verify_fn),
+            750,
+            Some(Type::Bool)
+        );
+
+        // Asset administration by its owner
+        env.register_native_function(
+            "set_name",
+            Some(asset_type.clone()),
+            vec![("name", Type::String)],
+            FunctionHandler::Sync(asset_set_name),
+            250,
+            Some(Type::Bool)
+        );
+        env.register_native_function(
+            "set_ticker",
+            Some(asset_type.clone()),
+            vec![("ticker", Type::String)],
+            FunctionHandler::Sync(asset_set_ticker),
+            250,
+            Some(Type::Bool)
+        );
+        env.register_native_function(
+            "set_metadata",
+            Some(asset_type.clone()),
+            vec![
+                ("key", Type::String),
+                ("value", Type::Optional(Box::new(Type::String))),
+            ],
+            FunctionHandler::Sync(asset_set_metadata),
+            250,
+            Some(Type::Bool)
+        );
+        env.register_native_function(
+            "get_metadata",
+            Some(asset_type.clone()),
+            vec![("key", Type::String)],
+            FunctionHandler::Sync(asset_get_metadata),
+            5,
+            Some(Type::Optional(Box::new(Type::String)))
+        );
+        env.register_native_function(
+            "set_paused",
+            Some(asset_type.clone()),
+            vec![("paused", Type::Bool)],
+            FunctionHandler::Sync(asset_set_paused),
+            250,
+            Some(Type::Bool)
+        );
+        env.register_native_function(
+            "is_paused",
+            Some(asset_type.clone()),
+            vec![],
+            FunctionHandler::Sync(asset_is_paused),
+            5,
+            Some(Type::Bool)
+        );
+        env.register_native_function(
+            "burn_from",
+            Some(asset_type.clone()),
+            vec![
+                ("contract", hash_type.clone()),
+                ("amount", Type::U64),
+            ],
+            FunctionHandler::Async(async_handler!(asset_burn_from::<P>)),
+            500,
+            Some(Type::Bool)
+        );
+    }
+
     env
 }
 
@@ -2254,6 +2423,23 @@ pub fn from_context<'a, 'ty, 'r, P: ContractProvider>(context: &'a mut VMContext
     Ok((provider, state))
 }
 
+// Same as `state_from_context` but reject the call in read-only mode
+#[inline]
+pub fn state_from_context_mut<'a, 'ty, 'r>(context: &'a mut VMContext<'ty, 'r>) -> Result<&'a mut ChainState<'ty>, anyhow::Error> {
+    let state = state_from_context(context)?;
+    state.ensure_writable()?;
+    Ok(state)
+}
+
+// Same as `from_context` but reject the call in read-only mode
+// Must be used by every syscall modifying the state
+#[inline]
+pub fn from_context_mut<'a, 'ty, 'r, P: ContractProvider>(context: &'a mut VMContext<'ty, 'r>) -> Result<(&'a P, &'a mut ChainState<'ty>), anyhow::Error> {
+    let (provider, state) = from_context::<P>(context)?;
+    state.ensure_writable()?;
+    Ok((provider, state))
+}
+
 // Function helper to get the balance for the given asset
 // This will first check in our current changes, then in the previous execution cache
 pub async fn get_balance_from_cache<'a, 'b: 'a, P: ContractProvider>(provider: &P, state: &'a mut ChainState<'b>, contract: Hash, asset: Hash) -> Result<&'a mut Option<(VersionedState, u64)>, anyhow::Error> {
@@ -2268,6 +2454,8 @@ pub async fn get_balance_from_cache<'a, 'b: 'a, P: ContractProvider>(provider: &
 
 // Function helper to get the mutable balance for the given asset
 pub async fn get_mut_balance_for_contract<'a, 'b: 'a, P: ContractProvider>(provider: &P, state: &'a mut ChainState<'b>, contract: Hash, asset: Hash) -> Result<&'a mut (VersionedState, u64), anyhow::Error> {
+    state.ensure_writable()?;
+
     Ok(match get_cache_for_contract(&mut state.changes.caches, state.global_caches, contract.clone()).balances.entry(asset.clone()) {
         Entry::Occupied(entry) => entry.into_mut()
             .get_or_insert((VersionedState::New, 0)),
@@ -2432,7 +2620,7 @@ pub async fn record_account_balance_credit<'a, 'b>(
 pub fn record_gas_allowance<'ty, 'r>(context: &mut VMContext<'ty, 'r>, amount: u64) -> Result<(), anyhow::Error> {
     context.increase_gas_usage(amount)?;
 
-    let state = state_from_context(context)?;
+    let state = state_from_context_mut(context)?;
     state.gas_fee_allowance = state.gas_fee_allowance.checked_add(amount)
         .context("Overflow while increasing gas allowance")?;
 
@@ -2469,7 +2657,7 @@ fn rpc_event_fn(_: FnInstance, mut params: FnParams, metadata: &ModuleMetadata<'
         return Err(EnvironmentError::Static("Event not serializable"))
     }
 
-    let state = state_from_context(context)?;
+    let state = state_from_context_mut(context)?;
     let entry = get_cache_for_contract(&mut state.changes.caches, state.global_caches, metadata.metadata.contract_executor.clone())
         .events.entry(id)
         .or_insert_with(Vec::new);
@@ -2490,7 +2678,7 @@ fn emit_event_fn(_: FnInstance, mut params: FnParams, metadata: &ModuleMetadata<
     let id = params.remove(0)
         .as_u64()?;
 
-    let state = state_from_context(context)?;
+    let state = state_from_context_mut(context)?;
 
     state.logs.push(ContractLog::Event {
         contract: metadata.metadata.contract_executor.clone(),
@@ -2528,7 +2716,7 @@ async fn listen_event_fn<'a, 'ty, 'r, P: ContractProvider>(zelf: FnInstance<'a>,
     let event_id = params.remove(0)
         .as_u64()?;
 
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
     let listeners = state.changes.events_listeners.entry((contract.clone(), event_id))
         .or_insert_with(Default::default);
 
@@ -2701,14 +2889,14 @@ async fn transfer_internal<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>,
     }
 
     {
-        let (provider, chain_state) = from_context::<P>(context)?;
+        let (provider, chain_state) = from_context_mut::<P>(context)?;
         // verify that the address is well registered, otherwise: pay extra fees
         if !provider.account_exists(destination.get_public_key(), chain_state.topoheight).await? {
             context.increase_gas_usage(FEE_PER_ACCOUNT_CREATION)?;
         }
     }
 
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
     if destination.is_mainnet() != state.mainnet {
         return Ok(false);
     }
@@ -2753,14 +2941,14 @@ async fn transfer_contract<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>,
         .into_owned()
         .into_opaque_type()?;
     {
-        let (provider, chain_state) = from_context::<P>(context)?;
+        let (provider, chain_state) = from_context_mut::<P>(context)?;
         // verify that the contract exists
         if !provider.has_contract(&destination, chain_state.topoheight).await? {
             return Ok(SysCallResult::Return(Primitive::Boolean(false).into()));
         }
     }
 
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
 
     if amount == 0 {
         return Ok(SysCallResult::Return(Primitive::Boolean(false).into()));
@@ -2780,7 +2968,7 @@ async fn transfer_contract<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>,
 }
 
 async fn burn<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>, mut params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext<'ty, 'r>) -> FnReturnType<ContractMetadata> {
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
 
     let asset: Hash = params.remove(1)
         .into_owned()
@@ -2852,7 +3040,7 @@ async fn increase_gas_limit<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>,
         return Ok(SysCallResult::Return(Primitive::Boolean(false).into())); 
     }
 
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
 
     // We have to ensure that before the deposit with current invoke,
     // we currently have ENOUGH coins before any deposit, contract transfer and such
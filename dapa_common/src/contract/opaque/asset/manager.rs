@@ -13,6 +13,7 @@ use crate::{
     config::{COST_PER_ASSET, DAPA_ASSET},
     contract::{
         from_context,
+        from_context_mut,
         has_enough_balance_for_contract,
         get_cache_for_contract,
         record_balance_charge,
@@ -58,10 +59,39 @@ fn is_valid_char_for_asset(c: char, whitespace: bool, uppercase_only: bool) -> b
     }
 }
 
+// Verify the asset name set by a contract
+pub(super) fn verify_asset_name(name: &str) -> Result<(), EnvironmentError> {
+    if name.len() > u8::MAX as usize {
+        return Err(EnvironmentError::Expect("Asset name is too long".to_owned()));
+    }
+
+    // Name can be ASCII only
+    if !is_valid_str_for_asset(name, true, false) {
+        return Err(EnvironmentError::Expect("Asset name must be ASCII only".to_owned()));
+    }
+
+    Ok(())
+}
+
+// Verify the asset ticker set by a contract
+pub(super) fn verify_asset_ticker(ticker: &str) -> Result<(), EnvironmentError> {
+    if ticker.len() > TICKER_LEN {
+        return Err(EnvironmentError::Expect("Asset ticker is too long".to_owned()));
+    }
+
+    // Ticker can be ASCII & upper case only
+    // No whitespace is allowed in it
+    if !is_valid_str_for_asset(ticker, false, true) {
+        return Err(EnvironmentError::Expect("Asset ticker must be ASCII only".to_owned()));
+    }
+
+    Ok(())
+}
+
 // Create a new asset
 // Return None if the asset already exists
 pub async fn asset_create<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>, mut params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext<'ty, 'r>) -> FnReturnType<ContractMetadata> {
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
 
     let (id, values) = params.remove(4).into_owned().to_enum()?;
     let max_supply = match id {
@@ -97,27 +127,12 @@ pub async fn asset_create<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>, m
         .into_owned()
         .into_string()?;
 
-    if ticker.len() > TICKER_LEN {
-        return Err(EnvironmentError::Expect("Asset ticker is too long".to_owned()).into());
-    }
-
-    // Ticker can be ASCII & upper case only
-    // No whitespace is allowed in it
-    if !is_valid_str_for_asset(&ticker, false, true) {
-        return Err(EnvironmentError::Expect("Asset ticker must be ASCII only".to_owned()).into());
-    }
+    verify_asset_ticker(&ticker)?;
 
     let name = params.remove(1)
         .into_owned()
         .into_string()?;
-    if name.len() > u8::MAX as usize {
-        return Err(EnvironmentError::Expect("Asset name is too long".to_owned()).into());
-    }
-
-    // Name can be ASCII only
-    if !is_valid_str_for_asset(&name, true, false) {
-        return Err(EnvironmentError::Expect("Asset name must be ASCII only".to_owned()).into());
-    }
+    verify_asset_name(&name)?;
 
     // Check that we have enough XEL in the balance
     if !has_enough_balance_for_contract(provider, state, metadata.metadata.contract_executor.clone(), DAPA_ASSET, COST_PER_ASSET).await? {
@@ -168,6 +183,7 @@ pub async fn asset_create<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>, m
     }
 
     state.logs.push(ContractLog::NewAsset { contract: metadata.metadata.contract_executor.clone(), asset: asset_hash.clone() });
+    state.changes.tracker.assets_created.insert(asset_hash.clone());
 
     let asset = OpaqueAsset {
         hash: asset_hash
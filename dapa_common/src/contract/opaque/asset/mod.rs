@@ -14,11 +14,18 @@ use xelis_vm::{
     ValueCell
 };
 use crate::{
+    asset::AssetData,
+    config::FEE_PER_BYTE_STORED_CONTRACT,
     contract::{
         from_context,
+        from_context_mut,
         get_asset_changes_for_hash,
         get_asset_changes_for_hash_mut,
+        has_enough_balance_for_contract,
+        record_balance_charge,
         record_balance_credit,
+        record_burned_asset,
+        state_from_context_mut,
         ChainState,
         ContractLog,
         ContractProvider,
@@ -30,6 +37,11 @@ use crate::{
 
 pub use manager::*;
 
+// Maximum extra metadata entries per asset
+pub const MAX_ASSET_METADATA_ENTRIES: usize = 16;
+// Maximum size for an extra metadata key
+pub const ASSET_METADATA_KEY_LEN: usize = 32;
+
 // Represent an Asset type in the opaque context
 // It only holds the asset hash because the AssetData
 // may be updated at any time in the chain state
@@ -224,7 +236,7 @@ pub async fn asset_transfer_ownership<'a, 'ty, 'r, P: ContractProvider>(zelf: Fn
 
     let zelf = zelf?;
     let asset: &OpaqueAsset = zelf.as_opaque_type()?;
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
 
     // Ensure that the contract hash is a valid one
     if !provider.has_contract(&asset.hash, state.topoheight).await? {
@@ -244,7 +256,7 @@ pub async fn asset_transfer_ownership<'a, 'ty, 'r, P: ContractProvider>(zelf: Fn
 pub async fn asset_mint<'a, 'ty, 'r, P: ContractProvider>(zelf: FnInstance<'a>, params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext<'ty, 'r>) -> FnReturnType<ContractMetadata> {
     let mut zelf = zelf?;
     let asset: &mut OpaqueAsset = zelf.as_opaque_type_mut()?;
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
 
     let changes = get_asset_changes_for_hash_mut(state, &asset.hash)?;
     let asset_data = &mut changes.data.1;
@@ -292,6 +304,215 @@ pub async fn asset_mint<'a, 'ty, 'r, P: ContractProvider>(zelf: FnInstance<'a>,
     Ok(SysCallResult::Return(Primitive::Boolean(true).into()))
 }
 
+// Get the asset data to update if the contract is its owner
+// It is marked as updated so a new version is stored
+fn get_asset_data_for_owner<'a>(state: &'a mut ChainState, asset: &'a Hash, contract: &Hash) -> Result<Option<&'a mut AssetData>, anyhow::Error> {
+    let changes = get_asset_changes_for_hash_mut(state, asset)?;
+    if !changes.data.1.get_owner().is_owner(contract) {
+        return Ok(None)
+    }
+
+    changes.data.0.mark_updated();
+    Ok(Some(&mut changes.data.1))
+}
+
+fn record_asset_updated(state: &mut ChainState, contract: Hash, asset: Hash) {
+    state.changes.tracker.assets_updated.insert(asset.clone());
+    state.logs.push(ContractLog::AssetUpdated { contract, asset });
+}
+
+// Update the asset name, only its owner can do it
+pub fn asset_set_name(zelf: FnInstance, mut params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let name = params.remove(0)
+        .into_owned()
+        .into_string()?;
+
+    verify_asset_name(&name)?;
+
+    let zelf = zelf?;
+    let asset: &OpaqueAsset = zelf.as_opaque_type()?;
+    let state = state_from_context_mut(context)?;
+    let contract = &metadata.metadata.contract_executor;
+
+    let Some(data) = get_asset_data_for_owner(state, &asset.hash, contract)? else {
+        return Ok(SysCallResult::Return(Primitive::Boolean(false).into()))
+    };
+    data.set_name(name);
+
+    record_asset_updated(state, contract.clone(), asset.hash.clone());
+    Ok(SysCallResult::Return(Primitive::Boolean(true).into()))
+}
+
+// Update the asset ticker, only its owner can do it
+pub fn asset_set_ticker(zelf: FnInstance, mut params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let ticker = params.remove(0)
+        .into_owned()
+        .into_string()?;
+
+    verify_asset_ticker(&ticker)?;
+
+    let zelf = zelf?;
+    let asset: &OpaqueAsset = zelf.as_opaque_type()?;
+    let state = state_from_context_mut(context)?;
+    let contract = &metadata.metadata.contract_executor;
+
+    let Some(data) = get_asset_data_for_owner(state, &asset.hash, contract)? else {
+        return Ok(SysCallResult::Return(Primitive::Boolean(false).into()))
+    };
+    data.set_ticker(ticker);
+
+    record_asset_updated(state, contract.clone(), asset.hash.clone());
+    Ok(SysCallResult::Return(Primitive::Boolean(true).into()))
+}
+
+// Set or remove (if value is null) an extra metadata entry
+// such as an icon url or a description
+pub fn asset_set_metadata(zelf: FnInstance, mut params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let param = params.remove(1)
+        .into_owned();
+    let value = if !param.is_null() {
+        Some(param.into_string()?)
+    } else {
+        None
+    };
+
+    let key = params.remove(0)
+        .into_owned()
+        .into_string()?;
+
+    if key.is_empty() || key.len() > ASSET_METADATA_KEY_LEN {
+        return Err(EnvironmentError::Expect("Asset metadata key has an invalid size".to_owned()).into());
+    }
+
+    // Keys are lowercase ASCII only
+    if !key.chars().all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_')) {
+        return Err(EnvironmentError::Expect("Asset metadata key must be lowercase ASCII only".to_owned()).into());
+    }
+
+    if value.as_ref().is_some_and(|v| v.len() > u8::MAX as usize) {
+        return Err(EnvironmentError::Expect("Asset metadata value is too long".to_owned()).into());
+    }
+
+    // Each byte is stored in every new version of the asset
+    let size = key.len() + value.as_ref().map_or(0, |v| v.len());
+    context.increase_gas_usage(size as u64 * FEE_PER_BYTE_STORED_CONTRACT)?;
+
+    let zelf = zelf?;
+    let asset: &OpaqueAsset = zelf.as_opaque_type()?;
+    let state = state_from_context_mut(context)?;
+    let contract = &metadata.metadata.contract_executor;
+
+    let Some(data) = get_asset_data_for_owner(state, &asset.hash, contract)? else {
+        return Ok(SysCallResult::Return(Primitive::Boolean(false).into()))
+    };
+
+    let entries = data.get_metadata_mut();
+    match value {
+        Some(value) => {
+            if !entries.contains_key(&key) && entries.len() >= MAX_ASSET_METADATA_ENTRIES {
+                return Err(EnvironmentError::Expect("Asset metadata entries limit reached".to_owned()).into());
+            }
+            entries.insert(key, value);
+        },
+        None => {
+            entries.shift_remove(&key);
+        }
+    }
+
+    record_asset_updated(state, contract.clone(), asset.hash.clone());
+    Ok(SysCallResult::Return(Primitive::Boolean(true).into()))
+}
+
+// Get an extra metadata entry of the asset
+pub fn asset_get_metadata(zelf: FnInstance, params: FnParams, _: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let key = params[0]
+        .as_ref()
+        .as_string()?;
+
+    let zelf = zelf?;
+    let asset: &OpaqueAsset = zelf.as_opaque_type()?;
+    let state: &ChainState = context.get()
+        .context("Chain state not found")?;
+
+    let changes = get_asset_changes_for_hash(state, &asset.hash)?;
+    let value = match changes.data.1.get_metadata().get(key) {
+        Some(v) => Primitive::String(v.clone()),
+        None => Primitive::Null
+    };
+
+    Ok(SysCallResult::Return(value.into()))
+}
+
+// Pause or resume the transfers of the asset in transactions
+// Only its owner can do it
+pub fn asset_set_paused(zelf: FnInstance, params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let paused = params[0]
+        .as_ref()
+        .as_bool()?;
+
+    let zelf = zelf?;
+    let asset: &OpaqueAsset = zelf.as_opaque_type()?;
+    let state = state_from_context_mut(context)?;
+    let contract = &metadata.metadata.contract_executor;
+
+    let Some(data) = get_asset_data_for_owner(state, &asset.hash, contract)? else {
+        return Ok(SysCallResult::Return(Primitive::Boolean(false).into()))
+    };
+    data.set_paused(paused);
+
+    state.changes.tracker.assets_updated.insert(asset.hash.clone());
+    state.logs.push(ContractLog::AssetPaused {
+        contract: contract.clone(),
+        asset: asset.hash.clone(),
+        paused
+    });
+
+    Ok(SysCallResult::Return(Primitive::Boolean(true).into()))
+}
+
+pub fn asset_is_paused(zelf: FnInstance, _: FnParams, _: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let zelf = zelf?;
+    let asset: &OpaqueAsset = zelf.as_opaque_type()?;
+    let state: &ChainState = context.get()
+        .context("Chain state not found")?;
+    let changes = get_asset_changes_for_hash(state, &asset.hash)?;
+    Ok(SysCallResult::Return(Primitive::Boolean(changes.data.1.is_paused()).into()))
+}
+
+// Burn an amount of the asset from the balance of another contract
+// Only the asset owner can do it
+pub async fn asset_burn_from<'a, 'ty, 'r, P: ContractProvider>(zelf: FnInstance<'a>, mut params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext<'ty, 'r>) -> FnReturnType<ContractMetadata> {
+    let amount = params.remove(1)
+        .into_owned()
+        .to_u64()?;
+
+    let from: Hash = params.remove(0)
+        .into_owned()
+        .into_opaque_type()?;
+
+    let zelf = zelf?;
+    let asset: &OpaqueAsset = zelf.as_opaque_type()?;
+    let (provider, state) = from_context_mut::<P>(context)?;
+
+    let is_owner = get_asset_changes_for_hash(state, &asset.hash)?
+        .data.1
+        .get_owner()
+        .is_owner(&metadata.metadata.contract_executor);
+
+    if !is_owner || amount == 0 {
+        return Ok(SysCallResult::Return(Primitive::Boolean(false).into()))
+    }
+
+    if !has_enough_balance_for_contract(provider, state, from.clone(), asset.hash.clone(), amount).await? {
+        return Ok(SysCallResult::Return(Primitive::Boolean(false).into()))
+    }
+
+    record_balance_charge(provider, state, from.clone(), asset.hash.clone(), amount).await?;
+    record_burned_asset(provider, state, from, asset.hash.clone(), amount).await?;
+
+    Ok(SysCallResult::Return(Primitive::Boolean(true).into()))
+}
+
 pub fn max_supply_mode_get_max_supply(zelf: FnInstance, _: FnParams, _: &ModuleMetadata<'_>, _: &mut VMContext) -> FnReturnType<ContractMetadata> {
     let zelf = zelf?;
     let (id, fields) = zelf.as_enum()?;
@@ -8,17 +8,21 @@ use xelis_vm::{
     FnInstance,
     FnParams,
     FnReturnType,
+    ModuleValidator,
     Primitive,
     Reference,
     SysCallResult,
     traits::{JSONHelper, Serializable}
 };
 use crate::{
+    config::FEE_PER_BYTE_STORED_CONTRACT,
     contract::{
         from_context,
         has_enough_balance_for_contract,
         record_balance_charge,
         record_balance_credit,
+        state_from_context_mut,
+        ContractLog,
         ContractProvider,
         ContractMetadata,
         ModuleMetadata,
@@ -26,6 +30,7 @@ use crate::{
     },
     versioned_type::VersionedState,
     crypto::Hash,
+    serializer::Serializer,
     transaction::ContractDeposit
 };
 
@@ -217,4 +222,40 @@ pub fn contract_get_hash<'a>(zelf: FnInstance<'a>, _: FnParams, _: &ModuleMetada
     let opaque: &OpaqueContract = zelf.as_opaque_type()?;
 
     Ok(SysCallResult::Return(opaque.hash.clone().into()))
-}
\ No newline at end of file
+}
+
+// Replace the module of the contract being executed
+// Only the contract itself can upgrade its module, so the upgrade rules live in its code
+// Previous modules are kept as older versions of the contract
+pub fn contract_upgrade(_: FnInstance, mut params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let param = params.remove(0)
+        .into_owned();
+    let bytes = param.as_bytes()?;
+
+    // The new module is stored like any contract data
+    context.increase_gas_usage(FEE_PER_BYTE_STORED_CONTRACT * bytes.len() as u64)?;
+
+    let module = ContractModule::from_bytes(&bytes)
+        .map_err(|_| EnvironmentError::Static("Invalid contract module"))?;
+
+    let state = state_from_context_mut(context)?;
+    let environment = state.environments.get(&module.version)
+        .map(|env| (*env).as_ref())
+        .ok_or(EnvironmentError::Static("Contract environment not found"))?;
+
+    ModuleValidator::new(&module.module, environment)
+        .verify()
+        .map_err(|_| EnvironmentError::Static("Invalid contract module"))?;
+
+    let contract = metadata.metadata.contract_executor.clone();
+    debug!("Upgrading contract {} to a new {} module", contract, module.version);
+
+    state.logs.push(ContractLog::Upgraded {
+        contract: contract.clone(),
+        version: module.version,
+    });
+    state.changes.tracker.contracts_upgraded.insert(contract.clone());
+    state.changes.modules.insert(contract, module);
+
+    Ok(SysCallResult::None)
+}
@@ -0,0 +1,40 @@
+use ed25519_dalek::{Signature, VerifyingKey};
+use xelis_vm::{
+    VMContext,
+    FnInstance,
+    FnParams,
+    FnReturnType,
+    Primitive,
+    SysCallResult
+};
+use crate::contract::{ModuleMetadata, ContractMetadata};
+
+// Verify an ed25519 signature of the message
+// Strict verification is used to reject weak keys and malleable signatures
+pub fn ed25519_verify_fn(_: FnInstance, params: FnParams, _: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let message = params[0]
+        .as_ref()
+        .as_bytes()?;
+
+    let signature = params[1]
+        .as_ref()
+        .as_bytes()?;
+
+    let public_key = params[2]
+        .as_ref()
+        .as_bytes()?;
+
+    // The message is hashed using SHA512
+    // Gas cost is proportional to its size
+    context.increase_gas_usage((message.len() as u64) * 4)?;
+
+    // A signature or a key with a wrong size is simply invalid
+    let valid = match (<[u8; 64]>::try_from(&signature[..]), <[u8; 32]>::try_from(&public_key[..])) {
+        (Ok(signature), Ok(public_key)) => VerifyingKey::from_bytes(&public_key)
+            .map(|key| key.verify_strict(&message, &Signature::from_bytes(&signature)).is_ok())
+            .unwrap_or(false),
+        _ => false
+    };
+
+    Ok(SysCallResult::Return(Primitive::Boolean(valid).into()))
+}
@@ -1,5 +1,7 @@
 use anyhow::Context as AnyhowContext;
-use sha3::{Digest, Sha3_256};
+use ripemd::Ripemd160;
+use sha2::Sha256;
+use sha3::{Digest, Keccak256, Sha3_256};
 use xelis_vm::{
     traits::Serializable,
     VMContext,
@@ -161,3 +163,39 @@ pub fn sha3_fn(_: FnInstance, params: FnParams, _: &ModuleMetadata<'_>, context:
     let hash = Hash::new(Sha3_256::digest(&input).into());
     Ok(SysCallResult::Return(Primitive::Opaque(OpaqueWrapper::new(hash)).into()))
 }
+
+pub fn sha256_fn(_: FnInstance, params: FnParams, _: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let input = params[0]
+        .as_ref()
+        .as_bytes()?;
+
+    // Same cost as SHA3
+    context.increase_gas_usage((input.len() as u64) * 4)?;
+
+    let hash = Hash::new(Sha256::digest(&input).into());
+    Ok(SysCallResult::Return(Primitive::Opaque(OpaqueWrapper::new(hash)).into()))
+}
+
+// Keccak256 is the original Keccak padding used by Ethereum, not the SHA3 standard
+pub fn keccak256_fn(_: FnInstance, params: FnParams, _: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let input = params[0]
+        .as_ref()
+        .as_bytes()?;
+
+    context.increase_gas_usage((input.len() as u64) * 4)?;
+
+    let hash = Hash::new(Keccak256::digest(&input).into());
+    Ok(SysCallResult::Return(Primitive::Opaque(OpaqueWrapper::new(hash)).into()))
+}
+
+// RIPEMD160 produces a 20 bytes digest, it is returned as bytes
+pub fn ripemd160_fn(_: FnInstance, params: FnParams, _: &ModuleMetadata<'_>, context: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let input = params[0]
+        .as_ref()
+        .as_bytes()?;
+
+    context.increase_gas_usage((input.len() as u64) * 4)?;
+
+    let digest = Ripemd160::digest(&input);
+    Ok(SysCallResult::Return(ValueCell::Bytes(digest.to_vec()).into()))
+}
@@ -4,10 +4,14 @@ mod ciphertext;
 mod proofs;
 mod ristretto;
 mod scalar;
+mod secp256k1;
+mod ed25519;
 
 pub use hash::*;
 pub use signature::*;
 pub use ciphertext::*;
 pub use proofs::*;
 pub use ristretto::*;
-pub use scalar::*;
\ No newline at end of file
+pub use scalar::*;
+pub use secp256k1::*;
+pub use ed25519::*;
\ No newline at end of file
@@ -0,0 +1,106 @@
+use k256::ecdsa::{
+    signature::hazmat::PrehashVerifier,
+    RecoveryId,
+    Signature,
+    VerifyingKey
+};
+use xelis_vm::{
+    VMContext,
+    EnvironmentError,
+    FnInstance,
+    FnParams,
+    FnReturnType,
+    Primitive,
+    SysCallResult,
+    ValueCell
+};
+use crate::{
+    contract::{ModuleMetadata, ContractMetadata},
+    crypto::Hash
+};
+
+// Compact signature size (r || s)
+pub const SECP256K1_SIGNATURE_SIZE: usize = 64;
+
+// Parse a compact signature
+// High S values are rejected to prevent signatures malleability
+fn parse_signature(bytes: &[u8]) -> Option<Signature> {
+    let signature = Signature::from_slice(bytes).ok()?;
+    if signature.normalize_s().is_some() {
+        return None;
+    }
+
+    Some(signature)
+}
+
+// Verify a secp256k1 ECDSA signature over an already hashed message
+// The public key can be in compressed or uncompressed SEC1 format
+pub fn secp256k1_verify_fn(_: FnInstance, params: FnParams, _: &ModuleMetadata<'_>, _: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let message: &Hash = params[0]
+        .as_ref()
+        .as_opaque_type()?;
+
+    let signature = params[1]
+        .as_ref()
+        .as_bytes()?;
+
+    let public_key = params[2]
+        .as_ref()
+        .as_bytes()?;
+
+    // A signature that can't be parsed is simply invalid
+    let valid = signature.len() == SECP256K1_SIGNATURE_SIZE && match (parse_signature(&signature), VerifyingKey::from_sec1_bytes(&public_key)) {
+        (Some(signature), Ok(key)) => key.verify_prehash(message.as_bytes(), &signature).is_ok(),
+        _ => false
+    };
+
+    Ok(SysCallResult::Return(Primitive::Boolean(valid).into()))
+}
+
+// Recover the public key that signed the hashed message
+// The signature is expected as r || s || v, with v being 0, 1, 27 or 28
+// Returns the uncompressed SEC1 public key (65 bytes) or null if it can't be recovered
+pub fn secp256k1_recover_fn(_: FnInstance, params: FnParams, _: &ModuleMetadata<'_>, _: &mut VMContext) -> FnReturnType<ContractMetadata> {
+    let message: &Hash = params[0]
+        .as_ref()
+        .as_opaque_type()?;
+
+    let signature = params[1]
+        .as_ref()
+        .as_bytes()?;
+
+    if signature.len() != SECP256K1_SIGNATURE_SIZE + 1 {
+        return Err(EnvironmentError::InvalidParameter);
+    }
+
+    let v = signature[SECP256K1_SIGNATURE_SIZE];
+    let recovery_id = RecoveryId::from_byte(if v >= 27 { v - 27 } else { v });
+
+    let key = match (parse_signature(&signature[..SECP256K1_SIGNATURE_SIZE]), recovery_id) {
+        (Some(signature), Some(recovery_id)) => VerifyingKey::recover_from_prehash(message.as_bytes(), &signature, recovery_id).ok(),
+        _ => None
+    };
+
+    Ok(SysCallResult::Return(match key {
+        Some(key) => ValueCell::Bytes(key.to_encoded_point(false).as_bytes().to_vec()).into(),
+        None => Primitive::Null.into()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use k256::ecdsa::SigningKey;
+    use super::*;
+
+    #[test]
+    fn test_parse_signature_rejects_high_s() {
+        let key = SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let (signature, _) = key.sign_prehash_recoverable(&[2u8; 32]).unwrap();
+        let bytes = signature.to_bytes();
+        assert!(parse_signature(&bytes).is_some());
+
+        // Negate S to get the high S version of the same signature
+        let high = Signature::from_scalars(signature.r(), -signature.s()).unwrap();
+        assert!(parse_signature(&high.to_bytes()).is_none());
+    }
+}
@@ -1152,6 +1152,8 @@ async fn write_storage_value<'ty, P: ContractProvider>(
     key: ValueCell,
     value: Option<ValueCell>,
 ) -> Result<Option<ValueCell>, EnvironmentError> {
+    ctx.state.ensure_writable()?;
+
     let size = data_size_in_bytes(&key) + value.as_ref().map_or(0, |v| data_size_in_bytes(v));
     ctx.charge_write(size)?;
 
@@ -22,6 +22,7 @@ use crate::{
     config::FEE_PER_BYTE_STORED_CONTRACT,
     contract::{
         from_context,
+        from_context_mut,
         get_cache_for_contract,
         ContractProvider,
         ContractMetadata,
@@ -171,7 +172,7 @@ pub async fn storage_store<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>,
     let cost = total_size as u64 * FEE_PER_BYTE_STORED_CONTRACT;
     context.increase_gas_usage(cost)?;
 
-    let (storage, state) = from_context::<P>(context)?;
+    let (storage, state) = from_context_mut::<P>(context)?;
 
     let cache = get_cache_for_contract(&mut state.changes.caches, state.global_caches, metadata.metadata.contract_executor.clone());
 
@@ -200,7 +201,7 @@ pub async fn storage_store<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>,
 }
 
 pub async fn storage_delete<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>, mut params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext<'ty, 'r>) -> FnReturnType<ContractMetadata> {
-    let (storage, state) = from_context::<P>(context)?;
+    let (storage, state) = from_context_mut::<P>(context)?;
 
     // into_owned calls `deep_clone`
     let key = params.remove(0)
@@ -27,7 +27,7 @@ use crate::{
         DAPA_ASSET
     },
     contract::{
-        from_context,
+        from_context_mut,
         has_enough_balance_for_contract,
         record_balance_charge,
         record_burned_asset,
@@ -151,7 +151,7 @@ async fn schedule_execution<'a, 'ty, 'r, P: ContractProvider>(
     metadata: &ModuleMetadata<'_>,
     context: &mut VMContext<'ty, 'r>
 ) -> FnReturnType<ContractMetadata> {
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
 
     match kind {
         ScheduledExecutionKind::TopoHeight(topoheight) => {
@@ -245,7 +245,7 @@ async fn schedule_execution<'a, 'ty, 'r, P: ContractProvider>(
     };
 
     // register it
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
 
     // check that it does not already exist
     if !state.executions.insert(execution) {
@@ -393,7 +393,7 @@ pub async fn scheduled_execution_increase_max_gas<'a, 'ty, 'r, P: ContractProvid
         return Ok(SysCallResult::Return(Primitive::Boolean(false).into()));
     }
 
-    let (provider, state) = from_context::<P>(context)?;
+    let (provider, state) = from_context_mut::<P>(context)?;
 
     if !state.executions.allow_executions {
         return Ok(SysCallResult::Return(Primitive::Boolean(false).into()));
@@ -439,7 +439,7 @@ pub async fn scheduled_execution_increase_max_gas<'a, 'ty, 'r, P: ContractProvid
         source
     };
 
-    let (_, state) = from_context::<P>(context)?;
+    let (_, state) = from_context_mut::<P>(context)?;
 
     let execution = state.executions.get_mut(&scheduled_execution.hash)?;
 
@@ -0,0 +1,91 @@
+use xelis_vm::Access;
+use crate::{
+    contract::{ContractAbi, ContractAbiEntry, ContractAbiEvent, ContractAbiParam},
+    crypto::KeyPair
+};
+
+use super::*;
+
+fn param(name: &str, kind: &str) -> ContractAbiParam {
+    ContractAbiParam { name: name.to_owned(), kind: kind.to_owned() }
+}
+
+fn entry(name: &str, params: Vec<ContractAbiParam>) -> ContractAbiEntry {
+    ContractAbiEntry { name: name.to_owned(), params }
+}
+
+#[test]
+fn test_contract_abi_verify_module() {
+    let code = r#"
+        fn add(a: u64, b: u64) -> u64 {
+            return a + b
+        }
+
+        entry deposit(amount: u64, memo: string) {
+            assert(add(amount, 1) > 0);
+            return 0
+        }
+    "#;
+
+    let state = MockChainState::new();
+    let module = compile_contract(&state.env, code).expect("compile contract");
+
+    let chunks = module.chunks();
+    let entry_id = chunks.iter()
+        .position(|chunk| matches!(chunk.access, Access::Entry { .. }))
+        .expect("entry chunk") as u16;
+    let internal_id = chunks.iter()
+        .position(|chunk| matches!(chunk.access, Access::Internal))
+        .expect("internal chunk") as u16;
+
+    let mut abi = ContractAbi::default();
+    abi.entries.insert(entry_id, entry("deposit", vec![param("amount", "u64"), param("memo", "string")]));
+    abi.events.insert(0, ContractAbiEvent { name: "Deposit".to_owned(), fields: vec![param("amount", "u64")] });
+    assert!(abi.verify(&module).is_ok());
+
+    // Only callable chunks can be described
+    let mut invalid = abi.clone();
+    invalid.entries.insert(internal_id, entry("add", vec![param("a", "u64"), param("b", "u64")]));
+    assert!(invalid.verify(&module).is_err());
+
+    // Chunk must exist in the module
+    let mut invalid = abi.clone();
+    invalid.entries.insert(chunks.len() as u16, entry("withdraw", Vec::new()));
+    assert!(invalid.verify(&module).is_err());
+
+    // Parameters count must match when the module declares them
+    if let Access::Entry { parameters: Some(_) } = &chunks[entry_id as usize].access {
+        let mut invalid = abi.clone();
+        invalid.entries.insert(entry_id, entry("deposit", vec![param("amount", "u64")]));
+        assert!(invalid.verify(&module).is_err());
+    }
+
+    // Events are checked too
+    let mut invalid = abi.clone();
+    invalid.events.insert(1, ContractAbiEvent { name: "Withdraw".to_owned(), fields: vec![param("amount", "struct")] });
+    assert!(invalid.verify(&module).is_err());
+}
+
+#[test]
+fn test_contract_abi_signature() {
+    let deployer = KeyPair::new();
+    let contract = Hash::new([1u8; 32]);
+    let mut abi = ContractAbi::default();
+    abi.entries.insert(0, entry("deposit", vec![param("amount", "u64")]));
+
+    let signature = deployer.sign(&abi.get_signing_bytes(&contract, 10, 2));
+    assert!(abi.verify_signature(&contract, 10, 2, &signature, deployer.get_public_key()));
+
+    // Can't be replayed for another version, module or contract
+    assert!(!abi.verify_signature(&contract, 10, 3, &signature, deployer.get_public_key()));
+    assert!(!abi.verify_signature(&contract, 11, 2, &signature, deployer.get_public_key()));
+    assert!(!abi.verify_signature(&Hash::zero(), 10, 2, &signature, deployer.get_public_key()));
+
+    // Nor used for another ABI
+    let mut other = abi.clone();
+    other.entries.insert(1, entry("withdraw", Vec::new()));
+    assert!(!other.verify_signature(&contract, 10, 2, &signature, deployer.get_public_key()));
+
+    // Only the deployer can sign it
+    assert!(!abi.verify_signature(&contract, 10, 2, &signature, KeyPair::new().get_public_key()));
+}
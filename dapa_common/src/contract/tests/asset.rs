@@ -0,0 +1,189 @@
+use crate::{
+    asset::{AssetData, AssetOwner, MaxSupplyMode},
+    config::{COIN_VALUE, DAPA_ASSET},
+    contract::AssetChanges,
+    crypto::Hashable,
+    transaction::{
+        builder::{
+            ContractDepositBuilder,
+            FeeBuilder,
+            InvokeContractBuilder,
+            TransactionBuilder,
+            TransactionTypeBuilder,
+            TransferBuilder
+        },
+        tests::{Account, AccountStateImpl, MockAccount},
+        verify::{NoZKPCache, VerificationError},
+        Reference,
+        Transaction,
+        TxVersion
+    },
+    versioned_type::VersionedState
+};
+
+use super::*;
+
+// Register an asset owned by the contract in the chain state
+fn create_asset(state: &mut MockChainState, owner: &Hash, supply: u64) -> Hash {
+    let asset = Hash::new(rand::random());
+    let data = AssetData::new(8, "Regulated".to_owned(), "REG".to_owned(), MaxSupplyMode::None, AssetOwner::Creator {
+        contract: owner.clone(),
+        id: 0
+    });
+
+    state.assets.insert(asset.clone(), Some(AssetChanges {
+        data: (VersionedState::New, data),
+        circulating_supply: (VersionedState::New, supply)
+    }));
+
+    asset
+}
+
+fn get_asset_data<'a>(state: &'a MockChainState, asset: &Hash) -> &'a AssetData {
+    &state.assets[asset].as_ref().unwrap().data.1
+}
+
+fn hash_param(hash: &Hash) -> ValueCell {
+    Primitive::Opaque(hash.clone().into()).into()
+}
+
+// Set the account with its current balances and nonce in the chain state
+fn set_account(state: &mut MockChainState, account: &Account) {
+    let balances = account.balances.iter()
+        .map(|(asset, balance)| (asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap()))
+        .collect();
+
+    state.accounts.insert(account.keypair.get_public_key().compress(), MockAccount {
+        balances,
+        nonce: account.nonce,
+    });
+}
+
+fn build_tx(account: &Account, data: TransactionTypeBuilder) -> Transaction {
+    let mut state = AccountStateImpl {
+        balances: account.balances.clone(),
+        nonce: account.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let builder = TransactionBuilder::new(TxVersion::V2, account.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    builder.build(&mut state, &account.keypair).unwrap()
+}
+
+#[tokio::test]
+async fn test_paused_asset_transfers() {
+    let code = r#"
+        entry main(hash: Hash) {
+            let asset = Asset::get_by_hash(hash).expect("asset");
+            assert(asset.set_paused(true));
+            assert(asset.is_paused());
+            return 0
+        }
+    "#;
+
+    let mut state = MockChainState::new();
+    let owner = create_contract(&mut state, code).expect("create contract");
+    let asset = create_asset(&mut state, &owner, 0);
+
+    invoke_first_entry(&mut state, &owner, vec![hash_param(&asset)]).await;
+    assert!(get_asset_data(&state, &asset).is_paused());
+    assert!(state.tracker.assets_updated.contains(&asset));
+
+    let mut alice = Account::new();
+    alice.set_balance(DAPA_ASSET, 100 * COIN_VALUE);
+    alice.set_balance(asset.clone(), 100);
+    let bob = Account::new();
+
+    let tx = build_tx(&alice, TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+        amount: 10,
+        destination: bob.address(),
+        asset: asset.clone(),
+        extra_data: None,
+        encrypt_extra_data: true,
+    }]));
+
+    set_account(&mut state, &alice);
+    let hash = tx.hash();
+    let result = tx.verify(&hash, &mut state, &NoZKPCache).await;
+    assert!(matches!(result, Err(VerificationError::AssetPaused(ref paused)) if *paused == asset), "{:?}", result);
+
+    // Deposits in a contract are paused too
+    let tx = build_tx(&alice, TransactionTypeBuilder::InvokeContract(InvokeContractBuilder {
+        contract: owner.clone(),
+        entry_id: 0,
+        max_gas: 1000,
+        parameters: Vec::new(),
+        deposits: [
+            (asset.clone(), ContractDepositBuilder {
+                amount: 10,
+                private: false
+            })
+        ].into_iter().collect(),
+        permission: Default::default(),
+    }));
+
+    set_account(&mut state, &alice);
+    let hash = tx.hash();
+    let result = tx.verify(&hash, &mut state, &NoZKPCache).await;
+    assert!(matches!(result, Err(VerificationError::AssetPaused(ref paused)) if *paused == asset), "{:?}", result);
+}
+
+#[tokio::test]
+async fn test_asset_administration_not_owner() {
+    let code = r#"
+        entry main(hash: Hash, target: Hash) {
+            let asset = Asset::get_by_hash(hash).expect("asset");
+            assert(!asset.set_name("Renamed"));
+            assert(!asset.set_ticker("RNM"));
+            assert(!asset.set_metadata("icon", null));
+            assert(!asset.set_paused(true));
+            assert(!asset.burn_from(target, 1));
+            return 0
+        }
+    "#;
+
+    let mut state = MockChainState::new();
+    let contract = create_contract(&mut state, code).expect("create contract");
+    let target = Hash::new([1u8; 32]);
+    let asset = create_asset(&mut state, &Hash::new([2u8; 32]), 100);
+    state.set_contract_balance(&target, &asset, 100);
+
+    invoke_first_entry(&mut state, &contract, vec![hash_param(&asset), hash_param(&target)]).await;
+
+    // Nothing has changed
+    let data = get_asset_data(&state, &asset);
+    assert_eq!(data.get_name(), "Regulated");
+    assert_eq!(data.get_ticker(), "REG");
+    assert!(!data.is_paused());
+    assert!(state.tracker.assets_updated.is_empty());
+    assert_eq!(state.get_contract_balance(&target, &asset), 100);
+}
+
+#[tokio::test]
+async fn test_asset_burn_from() {
+    let code = r#"
+        entry main(hash: Hash, target: Hash) {
+            let asset = Asset::get_by_hash(hash).expect("asset");
+            assert(!asset.burn_from(target, 101));
+            assert(!asset.burn_from(target, 0));
+            assert(asset.burn_from(target, 60));
+            assert(!asset.burn_from(target, 41));
+            assert(asset.get_supply() == 40);
+            return 0
+        }
+    "#;
+
+    let mut state = MockChainState::new();
+    let owner = create_contract(&mut state, code).expect("create contract");
+    let target = Hash::new([1u8; 32]);
+    let asset = create_asset(&mut state, &owner, 100);
+    state.set_contract_balance(&target, &asset, 100);
+
+    invoke_first_entry(&mut state, &owner, vec![hash_param(&asset), hash_param(&target)]).await;
+
+    assert_eq!(state.get_contract_balance(&target, &asset), 40);
+    assert_eq!(state.assets[&asset].as_ref().unwrap().circulating_supply.1, 40);
+}
@@ -19,8 +19,13 @@ use crate::{
     transaction::{tests::MockChainState, verify::BlockchainContractState}
 };
 
+mod abi;
+mod asset;
 mod gas;
 mod events;
+mod natives;
+mod upgrade;
+mod view;
 
 /// Compiles the given contract code into a Module
 pub fn compile_contract(environment: &EnvironmentBuilder<ContractMetadata>, code: &str) -> anyhow::Result<Module> {
@@ -95,6 +100,22 @@ pub async fn invoke_contract(
     ).await
 }
 
+/// Invokes the first entry of the contract with enough gas for the natives
+pub async fn invoke_first_entry(state: &mut MockChainState, contract: &Hash, params: Vec<ValueCell>) -> vm::ExecutionResult {
+    vm::invoke_contract(
+        ContractCaller::System,
+        state,
+        Cow::Owned(contract.clone()),
+        None,
+        params.into_iter(),
+        IndexMap::new(),
+        1_000_000,
+        InvokeContract::Entry(0),
+        Cow::Owned(Default::default()),
+        true,
+    ).await.expect("invoke first entry")
+}
+
 #[tokio::test]
 async fn test_execute_simple_contract() {
     // Compile a simple contract that returns 0 (success)
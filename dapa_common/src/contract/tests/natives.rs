@@ -0,0 +1,163 @@
+use super::*;
+
+// Invoke the first entry of the contract and returns the gas used
+async fn invoke_for_gas(state: &mut MockChainState, contract: &Hash, params: Vec<ValueCell>) -> u64 {
+    let execution = invoke_first_entry(state, contract, params).await;
+    assert!(execution.is_success(), "execution failed: {:?}", execution);
+    execution.used_gas
+}
+
+// Gas used by the contract code for 1000 more bytes in input
+async fn gas_per_kb(code: &str) -> u64 {
+    let mut state = MockChainState::new();
+    let contract = create_contract(&mut state, code).expect("create contract");
+
+    let small = invoke_for_gas(&mut state, &contract, vec![ValueCell::Bytes(vec![0u8; 24])]).await;
+    let large = invoke_for_gas(&mut state, &contract, vec![ValueCell::Bytes(vec![0u8; 1024])]).await;
+    large - small
+}
+
+#[tokio::test]
+async fn test_hash_natives_gas_per_byte() {
+    // Same contract without hashing, to remove the cost of the input itself
+    let baseline = gas_per_kb(r#"
+        entry main(input: bytes) {
+            let digest = input;
+            return 0
+        }
+    "#).await;
+
+    for (function, gas_per_byte) in [("Hash::blake3", 1), ("Hash::sha3", 4), ("Hash::sha256", 4), ("Hash::keccak256", 4), ("ripemd160", 4)] {
+        let code = r#"
+            entry main(input: bytes) {
+                let digest = FUNCTION(input);
+                return 0
+            }
+        "#.replace("FUNCTION", function);
+
+        assert_eq!(gas_per_kb(&code).await - baseline, 1000 * gas_per_byte, "{}", function);
+    }
+}
+
+// Gas used by the contract code with the given function
+async fn call_gas(code: &str, function: &str, params: Vec<ValueCell>) -> u64 {
+    let mut state = MockChainState::new();
+    let contract = create_contract(&mut state, &code.replace("FUNCTION", function)).expect("create contract");
+    invoke_for_gas(&mut state, &contract, params).await
+}
+
+#[tokio::test]
+async fn test_natives_base_cost() {
+    use curve25519_dalek::{ristretto::CompressedRistretto, traits::Identity, Scalar};
+    use crate::{contract::OpaqueRistrettoPoint, crypto::Signature};
+
+    // Each native is compared against a native with a known cost and the same arguments count
+    // Inputs are empty or invalid so no per byte cost is added
+    let code = r#"
+        entry main(input: bytes) {
+            let result = FUNCTION(input);
+            return 0
+        }
+    "#;
+    let params = || vec![ValueCell::Bytes(Vec::new())];
+
+    let blake3 = call_gas(code, "Hash::blake3", params()).await;
+    for (function, cost) in [("Hash::sha3", 7500), ("Hash::sha256", 7500), ("Hash::keccak256", 7500), ("ripemd160", 7500)] {
+        assert_eq!(call_gas(code, function, params()).await - blake3, cost - 3000, "{}", function);
+    }
+
+    let code = r#"
+        entry main(message: Hash, input: bytes, signature: Signature, point: RistrettoPoint) {
+            let result = FUNCTION;
+            return 0
+        }
+    "#;
+    let params = |signature: Vec<u8>| vec![
+        Primitive::Opaque(Hash::zero().into()).into(),
+        ValueCell::Bytes(signature),
+        Primitive::Opaque(Signature::new(Scalar::ONE, Scalar::ONE).into()).into(),
+        Primitive::Opaque(OpaqueRistrettoPoint::Compressed(CompressedRistretto::identity()).into()).into(),
+    ];
+
+    // Signature::verify (500) and the signature natives all take three values
+    let verify = call_gas(code, "signature.verify(input, point)", params(Vec::new())).await;
+    for (function, cost) in [("secp256k1_verify(message, input, input)", 1500), ("ed25519_verify(input, input, input)", 750)] {
+        assert_eq!(call_gas(code, function, params(Vec::new())).await - verify, cost - 500, "{}", function);
+    }
+
+    // RistrettoPoint::add (5000) takes two values like secp256k1_recover
+    // The recovery byte is set but the signature can't be parsed
+    let add = call_gas(code, "point.add(point)", params(vec![0u8; 65])).await;
+    assert_eq!(add - call_gas(code, "secp256k1_recover(message, input)", params(vec![0u8; 65])).await, 5000 - 2000);
+}
+
+#[tokio::test]
+async fn test_hash_natives_digest() {
+    let code = r#"
+        entry main(input: bytes, sha256: string, keccak256: string) {
+            assert(Hash::sha256(input).to_hex() == sha256);
+            assert(Hash::keccak256(input).to_hex() == keccak256);
+            return 0
+        }
+    "#;
+
+    let mut state = MockChainState::new();
+    let contract = create_contract(&mut state, code).expect("create contract");
+    invoke_for_gas(&mut state, &contract, vec![
+        ValueCell::Bytes(b"abc".to_vec()),
+        Primitive::String("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_owned()).into(),
+        Primitive::String("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45".to_owned()).into(),
+    ]).await;
+}
+
+#[tokio::test]
+async fn test_signature_natives() {
+    use ed25519_dalek::Signer;
+    use k256::ecdsa::SigningKey;
+
+    let code = r#"
+        entry main(message: Hash, signature: bytes, recoverable: bytes, public_key: bytes, data: bytes, ed_signature: bytes, ed_public_key: bytes) {
+            assert(secp256k1_verify(message, signature, public_key));
+            let recovered = secp256k1_recover(message, recoverable).expect("recovered key");
+            assert(secp256k1_verify(message, signature, recovered));
+            assert(!secp256k1_verify(message, ed_signature, recovered));
+            // A signature with a wrong size is invalid
+            assert(!secp256k1_verify(message, recoverable, recovered));
+            assert(ed25519_verify(data, ed_signature, ed_public_key));
+            // A signature or a key with a wrong size is invalid
+            assert(!ed25519_verify(data, recoverable, ed_public_key));
+            assert(!ed25519_verify(data, ed_signature, public_key));
+            return 0
+        }
+    "#;
+
+    let mut state = MockChainState::new();
+    let contract = create_contract(&mut state, code).expect("create contract");
+
+    let message = Hash::new([2u8; 32]);
+    let key = SigningKey::from_slice(&[1u8; 32]).unwrap();
+    let (signature, recovery_id) = key.sign_prehash_recoverable(message.as_bytes()).unwrap();
+    let mut recoverable = signature.to_bytes().to_vec();
+    recoverable.push(recovery_id.to_byte() + 27);
+
+    let data = b"bridge attestation".to_vec();
+    let ed_key = ed25519_dalek::SigningKey::from_bytes(&[3u8; 32]);
+    let ed_signature = ed_key.sign(&data);
+
+    let params = |recoverable: Vec<u8>| vec![
+        Primitive::Opaque(message.clone().into()).into(),
+        ValueCell::Bytes(signature.to_bytes().to_vec()),
+        ValueCell::Bytes(recoverable),
+        ValueCell::Bytes(key.verifying_key().to_encoded_point(true).as_bytes().to_vec()),
+        ValueCell::Bytes(data.clone()),
+        ValueCell::Bytes(ed_signature.to_bytes().to_vec()),
+        ValueCell::Bytes(ed_key.verifying_key().to_bytes().to_vec()),
+    ];
+
+    // The recovery byte is not part of the compact signature
+    let execution = invoke_contract(&mut state, &contract, InvokeContract::Entry(0), params(signature.to_bytes().to_vec())).await
+        .expect("invoke contract");
+    assert!(!execution.is_success());
+
+    invoke_for_gas(&mut state, &contract, params(recoverable)).await;
+}
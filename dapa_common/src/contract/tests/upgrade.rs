@@ -0,0 +1,100 @@
+use xelis_vm::ValueCell;
+use crate::{contract::ContractLog, serializer::Serializer};
+
+use super::*;
+
+// Invoke the first entry of the contract with the given module bytes
+async fn invoke_upgrade(state: &mut MockChainState, contract: &Hash, module: Vec<u8>) -> vm::ExecutionResult {
+    invoke_first_entry(state, contract, vec![ValueCell::Bytes(module)]).await
+}
+
+fn get_module_bytes(state: &MockChainState, contract: &Hash) -> Vec<u8> {
+    state.contracts.get(contract)
+        .and_then(|v| v.as_ref())
+        .and_then(|(_, module)| module.as_ref())
+        .expect("contract module")
+        .to_bytes()
+}
+
+#[tokio::test]
+async fn test_contract_upgrade() {
+    let code = r#"
+        entry upgrade(module: bytes) {
+            Contract::upgrade(module);
+            return 0
+        }
+    "#;
+
+    let mut chain_state = MockChainState::new();
+    let contract = create_contract(&mut chain_state, code).expect("create contract");
+    let previous = get_module_bytes(&chain_state, &contract);
+
+    // Invalid modules are rejected and the contract is left untouched
+    let execution = invoke_upgrade(&mut chain_state, &contract, vec![0, 1, 2]).await;
+    assert!(!execution.is_success());
+    assert_eq!(get_module_bytes(&chain_state, &contract), previous);
+
+    let new_code = r#"
+        entry upgrade(module: bytes) {
+            return 1
+        }
+    "#;
+    let module = ContractModule {
+        version: Default::default(),
+        module: Arc::new(compile_contract(&chain_state.env, new_code).expect("compile new module")),
+    };
+
+    let execution = invoke_upgrade(&mut chain_state, &contract, module.to_bytes()).await;
+    assert!(execution.is_success(), "upgrade failed {:?}", execution);
+    assert_eq!(get_module_bytes(&chain_state, &contract), module.to_bytes());
+    assert!(chain_state.tracker.contracts_upgraded.contains(&contract));
+
+    let upgraded = chain_state.contract_logs.values()
+        .flatten()
+        .any(|log| matches!(log, ContractLog::Upgraded { contract: hash, .. } if *hash == contract));
+    assert!(upgraded);
+
+    // The new module is now executed, and it can't be upgraded anymore
+    let execution = invoke_upgrade(&mut chain_state, &contract, previous).await;
+    assert!(!execution.is_success());
+    assert_eq!(get_module_bytes(&chain_state, &contract), module.to_bytes());
+}
+
+#[tokio::test]
+async fn test_contract_upgrade_from_cross_contract_call() {
+    let code = r#"
+        entry upgrade(module: bytes) {
+            Contract::upgrade(module);
+            return 0
+        }
+    "#;
+
+    let mut chain_state = MockChainState::new();
+    let target = create_contract(&mut chain_state, code).expect("create target contract");
+
+    // The proxy loads the target module before it gets upgraded by the call
+    let code = r#"
+        entry proxy(module: bytes) {
+            let contract: Contract = Contract::new(Hash::from_hex("CONTRACT_HASH")).expect("load contract");
+            let deposits: map<Hash, u64> = {};
+            contract.call(0, [module], deposits);
+            return 0
+        }
+    "#.replace("CONTRACT_HASH", &target.to_string());
+    let proxy = create_contract(&mut chain_state, &code).expect("create proxy contract");
+
+    let new_code = r#"
+        entry upgrade(module: bytes) {
+            return 1
+        }
+    "#;
+    let module = ContractModule {
+        version: Default::default(),
+        module: Arc::new(compile_contract(&chain_state.env, new_code).expect("compile new module")),
+    };
+
+    let execution = invoke_upgrade(&mut chain_state, &proxy, module.to_bytes()).await;
+    assert!(execution.is_success(), "proxy upgrade failed {:?}", execution);
+    assert_eq!(get_module_bytes(&chain_state, &target), module.to_bytes());
+    assert!(chain_state.tracker.contracts_upgraded.contains(&target));
+}
@@ -0,0 +1,62 @@
+use crate::transaction::verify::BlockchainContractState;
+
+use super::*;
+
+// Call the given chunk of the contract in read-only mode
+async fn call_view(state: &mut MockChainState, contract: &Hash, chunk_id: u16) -> vm::ExitValue {
+    let (environment, mut chain_state) = state.get_contract_environment_for(
+        Cow::Owned(contract.clone()),
+        None,
+        ContractCaller::System,
+        Cow::Owned(Default::default()),
+    ).await.expect("contract environment");
+
+    let (_, exit_value) = vm::call_contract_view(
+        environment,
+        &mut chain_state,
+        Cow::Owned(contract.clone()),
+        chunk_id,
+        std::iter::empty(),
+        10000,
+    ).await.expect("call contract view");
+
+    exit_value
+}
+
+#[tokio::test]
+async fn test_contract_view_read_only() {
+    let code = r#"
+        entry get() {
+            let storage: Storage = Storage::new();
+            if storage.has("counter") {
+                return 1
+            }
+            return 0
+        }
+
+        entry set() {
+            let storage: Storage = Storage::new();
+            storage.store("counter", 1);
+            return 0
+        }
+
+        entry emit() {
+            emit_event(1, "hello");
+            return 0
+        }
+    "#;
+
+    let mut chain_state = MockChainState::new();
+    let contract = create_contract(&mut chain_state, code).expect("create contract");
+
+    assert!(call_view(&mut chain_state, &contract, 0).await.is_success());
+
+    // Any write is rejected at the syscall
+    assert!(!call_view(&mut chain_state, &contract, 1).await.is_success());
+    assert!(!call_view(&mut chain_state, &contract, 2).await.is_success());
+
+    // The same entry is allowed through a regular invoke
+    let execution = invoke_contract(&mut chain_state, &contract, InvokeContract::Entry(2), Vec::new()).await
+        .expect("invoke contract");
+    assert!(execution.is_success(), "emit failed {:?}", execution);
+}
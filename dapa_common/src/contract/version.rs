@@ -12,14 +12,16 @@ pub enum ContractVersion {
     #[default]
     V0,
     V1,
+    V2,
 }
 
 impl ContractVersion {
     #[inline(always)]
-    pub const fn variants() -> [ContractVersion; 2] {
+    pub const fn variants() -> [ContractVersion; 3] {
         [
             ContractVersion::V0,
             ContractVersion::V1,
+            ContractVersion::V2,
         ]
     }
 }
@@ -31,6 +33,7 @@ impl FromStr for ContractVersion {
         match s {
             "v0" | "0" => Ok(ContractVersion::V0),
             "v1" | "1" => Ok(ContractVersion::V1),
+            "v2" | "2" => Ok(ContractVersion::V2),
             _ => Err("Invalid contract version"),
         }
     }
@@ -42,6 +45,7 @@ impl fmt::Display for ContractVersion {
         match self {
             ContractVersion::V0 => write!(f, "v0"),
             ContractVersion::V1 => write!(f, "v1"),
+            ContractVersion::V2 => write!(f, "v2"),
         }
     }
 }
@@ -55,6 +59,7 @@ impl Serializer for ContractVersion {
         match reader.read_u8()? {
             0 => Ok(ContractVersion::V0),
             1 => Ok(ContractVersion::V1),
+            2 => Ok(ContractVersion::V2),
             _ => Err(ReaderError::InvalidValue),
         }
     }
@@ -71,5 +76,6 @@ mod tests {
     #[test]
     fn test_ordering() {
         assert!(ContractVersion::V0 < ContractVersion::V1);
+        assert!(ContractVersion::V1 < ContractVersion::V2);
     }
 }
\ No newline at end of file
@@ -4,6 +4,7 @@ use std::{
     sync::Arc
 };
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use curve25519_dalek::Scalar;
@@ -88,7 +89,7 @@ pub enum ContractError<E> {
     DepositNotFound,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", content = "value")]
 pub enum ExitValue {
     Error(ExitError),
@@ -296,7 +297,7 @@ pub async fn invoke_contract<'a, P: ContractProvider, E, B: BlockchainApplyState
     let mut logs = chain_state.logs;
 
     let gas_injections = chain_state.injected_gas;
-    let modules = chain_state.loaded_modules;
+    let mut modules = chain_state.loaded_modules;
 
     // On success: it is well allocated to either burned coins or scheduled execution
     // On failure: it is included in the gas refund
@@ -333,6 +334,10 @@ pub async fn invoke_contract<'a, P: ContractProvider, E, B: BlockchainApplyState
             refund_extra_gas_injections(state, gas_injections, max_gas, vm_max_gas, &mut logs, &mut changes.caches).await?;
         }
 
+        // A contract called by another one may have upgraded its module,
+        // drop its loaded module so it doesn't overwrite the upgrade in the cache
+        modules.retain(|hash, _| !changes.modules.contains_key(hash));
+
         state.merge_contract_changes(
             changes,
             executions,
@@ -442,6 +447,36 @@ pub async fn invoke_contract<'a, P: ContractProvider, E, B: BlockchainApplyState
     })
 }
 
+// Call a chunk of a contract in read-only mode
+// The chain state is flagged as read-only so any syscall
+// trying to modify the state fails, changes are never merged
+// Returns the used gas and the exit value
+pub async fn call_contract_view<'a, P: ContractProvider>(
+    contract_environment: ContractEnvironment<'a, P>,
+    chain_state: &mut ChainState<'a>,
+    contract: Cow<'a, Hash>,
+    chunk_id: u16,
+    parameters: impl DoubleEndedIterator<Item = ValueCell> + ExactSizeIterator,
+    max_gas: u64,
+) -> Result<(u64, ExitValue), VMError> {
+    debug!("Calling contract {} chunk {} in read-only mode", contract, chunk_id);
+    chain_state.read_only = true;
+
+    let caller = chain_state.caller.clone();
+    let (used_gas, _, exit_value) = run_virtual_machine(
+        contract_environment,
+        chain_state,
+        &caller,
+        InvokeContract::Chunk(chunk_id, false),
+        contract,
+        IndexMap::new(),
+        parameters,
+        max_gas
+    ).await?;
+
+    Ok((used_gas, exit_value))
+}
+
 // We need to refund the extra (unused) gas
 // this is the tx max gas - used gas
 // We want to refund proportionally to the injections made
@@ -1,5 +1,6 @@
 use std::fmt::{self, Display, Formatter};
 use anyhow::Error;
+use merlin::Transcript;
 use serde::de::Error as SerdeError;
 
 use crate::{
@@ -18,7 +19,8 @@ use super::{
         encode,
         Bech32Error
     },
-    proofs::{BalanceProof, OwnershipProof},
+    elgamal::{Ciphertext, PublicKey},
+    proofs::{BalanceProof, OwnershipProof, ProofVerificationError},
     Hash
 };
 
@@ -45,6 +47,39 @@ pub enum HumanReadableProof {
 }
 
 impl HumanReadableProof {
+    // Get the asset of the proof
+    pub fn asset(&self) -> &Hash {
+        match self {
+            HumanReadableProof::Balance { asset, .. } => asset,
+            HumanReadableProof::Ownership { asset, .. } => asset
+        }
+    }
+
+    // Get the topoheight of the balance ciphertext used
+    pub fn topoheight(&self) -> u64 {
+        match self {
+            HumanReadableProof::Balance { topoheight, .. } => *topoheight,
+            HumanReadableProof::Ownership { topoheight, .. } => *topoheight
+        }
+    }
+
+    // Get the amount being proven
+    pub fn amount(&self) -> u64 {
+        match self {
+            HumanReadableProof::Balance { proof, .. } => proof.amount(),
+            HumanReadableProof::Ownership { proof, .. } => proof.amount()
+        }
+    }
+
+    // Verify the proof against the balance ciphertext of the prover at the proof topoheight
+    // Transcripts are the same as the ones used by the default proof constructors
+    pub fn verify(&self, public_key: &PublicKey, ciphertext: Ciphertext) -> Result<(), ProofVerificationError> {
+        match self {
+            HumanReadableProof::Balance { proof, .. } => proof.verify(public_key, ciphertext, &mut Transcript::new(b"balance_proof")),
+            HumanReadableProof::Ownership { proof, .. } => proof.verify(public_key, ciphertext, &mut Transcript::new(b"ownership_proof"))
+        }
+    }
+
     // Transform a shareable proof to a human readable string
     pub fn as_string(&self) -> Result<String, Bech32Error> {
         let bits = convert_bits(&self.to_bytes(), 8, 5, true)?;
@@ -149,8 +184,6 @@ impl Display for HumanReadableProof {
 
 #[cfg(test)]
 mod tests {
-    use merlin::Transcript;
-
     use crate::{
         config::DAPA_ASSET,
         crypto::{
@@ -218,4 +251,26 @@ mod tests {
         assert!(proof.pre_verify(keypair.get_public_key(), ct, &mut transcript, &mut batch_collector).is_ok());
         assert!(batch_collector.verify().is_ok());
     }
+
+    #[test]
+    fn test_hr_proof_verify() {
+        let keypair = KeyPair::new();
+        let balance = 100u64;
+        let ct = keypair.get_public_key().encrypt(balance);
+
+        let proof = BalanceProof::new(&keypair, balance, ct.clone());
+        let shareable = HumanReadableProof::Balance { proof, asset: DAPA_ASSET, topoheight: 5 };
+        assert_eq!(shareable.amount(), balance);
+        assert_eq!(shareable.topoheight(), 5);
+        assert!(shareable.verify(keypair.get_public_key(), ct.clone()).is_ok());
+
+        let proof = OwnershipProof::new(&keypair, balance, 10, ct.clone()).unwrap();
+        let shareable = HumanReadableProof::Ownership { proof, asset: DAPA_ASSET, topoheight: 5 };
+        assert_eq!(shareable.amount(), 10);
+        assert!(shareable.verify(keypair.get_public_key(), ct).is_ok());
+
+        // Another balance must not be accepted
+        let other = keypair.get_public_key().encrypt(balance + 1);
+        assert!(shareable.verify(keypair.get_public_key(), other).is_err());
+    }
 }
\ No newline at end of file
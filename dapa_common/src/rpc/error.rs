@@ -34,6 +34,8 @@ pub enum InternalRpcError {
     InvalidVersion,
     #[error("Method '{}' in request was not found", _0)]
     MethodNotFound(String),
+    #[error("Method '{}' is not allowed", _0)]
+    MethodNotAllowed(String),
     #[error(transparent)]
     AnyError(#[from] AnyError),
     #[error("Websocket client was not found")]
@@ -44,6 +46,8 @@ pub enum InternalRpcError {
     EventAlreadySubscribed,
     #[error("batch limit exceeded")]
     BatchLimitExceeded,
+    #[error("rate limit exceeded, try again later")]
+    RateLimitExceeded,
 }
 
 impl InternalRpcError {
@@ -66,7 +70,9 @@ impl InternalRpcError {
             // 32000 to -32099	Server error (Reserved for implementation-defined server-errors)
             Self::InvalidContext => -32001,
             Self::ClientNotFound => -32002,
+            Self::MethodNotAllowed(_) => -32003,
             Self::AnyError(_) => -32004,
+            Self::RateLimitExceeded => -32005,
             // Events invalid requests
             Self::EventNotSubscribed => -1,
             Self::EventAlreadySubscribed => -2,
@@ -7,7 +7,9 @@ pub mod client;
 mod types;
 mod rpc_handler;
 mod error;
+mod rate_limiter;
 
 pub use types::*;
 pub use error::*;
-pub use rpc_handler::*;
\ No newline at end of file
+pub use rpc_handler::*;
+pub use rate_limiter::*;
\ No newline at end of file
@@ -0,0 +1,206 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Mutex,
+};
+use log::trace;
+use crate::{
+    crypto::hash,
+    rpc::{tid, InternalRpcError},
+    time::Instant
+};
+
+// Maximum count of clients tracked
+// Idle clients are cleaned first, then the least recently seen one is evicted
+const MAX_TRACKED_CLIENTS: usize = 4096;
+
+// Key used to identify a RPC client for the rate limiter
+// This is either its IP or the hash of its API key
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RpcClientKey(pub String);
+
+tid!(RpcClientKey);
+
+impl RpcClientKey {
+    // Build the key from the IP address of the client
+    pub fn from_ip(addr: Option<SocketAddr>) -> Self {
+        Self(addr.map(|addr| addr.ip().to_string()).unwrap_or_default())
+    }
+
+    // Build the key from the API key used by the client
+    // The API key is hashed to never be kept or logged in plaintext
+    pub fn from_api_key(key: &str) -> Self {
+        Self(format!("key:{}", hash(key.as_bytes())))
+    }
+}
+
+// Token bucket of a client
+struct Bucket {
+    tokens: f64,
+    last_update: Instant
+}
+
+// Rate limiter based on a token bucket per client
+// Each method consumes a cost in tokens, 1 by default
+pub struct RateLimiter {
+    // Tokens refilled per second
+    requests_per_second: u64,
+    // Maximum tokens a client can have
+    burst: u64,
+    // Cost in tokens of each method
+    method_costs: HashMap<Cow<'static, str>, u64>,
+    buckets: Mutex<HashMap<RpcClientKey, Bucket>>
+}
+
+impl RateLimiter {
+    // Create a new rate limiter
+    // The burst is at least the requests per second
+    pub fn new(requests_per_second: u64, burst: u64, method_costs: HashMap<Cow<'static, str>, u64>) -> Self {
+        Self {
+            requests_per_second,
+            burst: burst.max(requests_per_second),
+            method_costs,
+            buckets: Mutex::new(HashMap::new())
+        }
+    }
+
+    // Get the cost in tokens of a method
+    pub fn get_method_cost(&self, method: &str) -> u64 {
+        self.method_costs.get(method)
+            .copied()
+            .unwrap_or(1)
+    }
+
+    // Consume the tokens required by the method for this client
+    // A cost above the burst is capped to it to keep the method callable
+    pub fn try_acquire(&self, key: &RpcClientKey, method: &str) -> Result<(), InternalRpcError> {
+        let cost = self.get_method_cost(method).min(self.burst);
+        self.try_acquire_at(key, cost, Instant::now())
+    }
+
+    fn try_acquire_at(&self, key: &RpcClientKey, cost: u64, now: Instant) -> Result<(), InternalRpcError> {
+        let mut buckets = self.buckets.lock()
+            .map_err(|_| InternalRpcError::InternalError("Rate limiter lock is poisoned"))?;
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(key) {
+            trace!("cleaning idle clients from rate limiter");
+            buckets.retain(|_, bucket| self.get_tokens_at(bucket, now) < self.burst as f64);
+
+            // No client is idle, evict the least recently seen one to stay bounded
+            if buckets.len() >= MAX_TRACKED_CLIENTS {
+                let oldest = buckets.iter()
+                    .min_by_key(|(_, bucket)| bucket.last_update)
+                    .map(|(key, _)| key.clone());
+
+                if let Some(oldest) = oldest {
+                    trace!("evicting {:?} from rate limiter", oldest);
+                    buckets.remove(&oldest);
+                }
+            }
+        }
+
+        let bucket = buckets.entry(key.clone())
+            .or_insert_with(|| Bucket {
+                tokens: self.burst as f64,
+                last_update: now
+            });
+
+        let tokens = self.refill(bucket, now);
+        if tokens < cost as f64 {
+            trace!("rate limit reached for {:?}, {} tokens left for a cost of {}", key, tokens, cost);
+            return Err(InternalRpcError::RateLimitExceeded)
+        }
+
+        bucket.tokens = tokens - cost as f64;
+
+        Ok(())
+    }
+
+    // Get the tokens of the bucket refilled based on the elapsed time
+    fn get_tokens_at(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.last_update).as_secs_f64();
+        (bucket.tokens + elapsed * self.requests_per_second as f64).min(self.burst as f64)
+    }
+
+    // Refill the bucket based on the elapsed time and returns its tokens
+    fn refill(&self, bucket: &mut Bucket, now: Instant) -> f64 {
+        bucket.tokens = self.get_tokens_at(bucket, now);
+        bucket.last_update = now;
+        bucket.tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_burst() {
+        let limiter = RateLimiter::new(1, 3, HashMap::new());
+        let key = RpcClientKey("127.0.0.1".to_owned());
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire_at(&key, 1, now).is_ok());
+        }
+        assert!(limiter.try_acquire_at(&key, 1, now).is_err());
+
+        // One token is refilled after one second
+        let now = now + Duration::from_secs(1);
+        assert!(limiter.try_acquire_at(&key, 1, now).is_ok());
+        assert!(limiter.try_acquire_at(&key, 1, now).is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter_method_cost() {
+        let mut costs = HashMap::new();
+        costs.insert(Cow::Borrowed("get_account_history"), 5);
+
+        let limiter = RateLimiter::new(5, 5, costs);
+        assert_eq!(limiter.get_method_cost("get_info"), 1);
+        assert_eq!(limiter.get_method_cost("get_account_history"), 5);
+
+        let now = Instant::now();
+        let key = RpcClientKey("127.0.0.1".to_owned());
+        assert!(limiter.try_acquire_at(&key, 5, now).is_ok());
+        assert!(limiter.try_acquire_at(&key, 1, now).is_err());
+
+        // Another client has its own bucket
+        let other = RpcClientKey("127.0.0.2".to_owned());
+        assert!(limiter.try_acquire_at(&other, 1, now).is_ok());
+    }
+
+    #[test]
+    fn test_rate_limiter_bounded_clients() {
+        let limiter = RateLimiter::new(1, 1, HashMap::new());
+        let now = Instant::now();
+
+        // No client is idle as they all used their token
+        for i in 0..MAX_TRACKED_CLIENTS {
+            let key = RpcClientKey(i.to_string());
+            assert!(limiter.try_acquire_at(&key, 1, now).is_ok());
+        }
+
+        let later = now + Duration::from_millis(100);
+        let key = RpcClientKey("new".to_owned());
+        assert!(limiter.try_acquire_at(&key, 1, later).is_ok());
+        assert!(limiter.try_acquire_at(&key, 1, later).is_err());
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), MAX_TRACKED_CLIENTS);
+        assert!(buckets.contains_key(&key));
+    }
+
+    #[test]
+    fn test_client_key_from_api_key() {
+        let key = RpcClientKey::from_api_key("secret");
+        assert!(!key.0.contains("secret"));
+        assert!(!format!("{:?}", key).contains("secret"));
+
+        // Same API key shares the same bucket
+        assert_eq!(key, RpcClientKey::from_api_key("secret"));
+        assert_ne!(key, RpcClientKey::from_api_key("other"));
+    }
+}
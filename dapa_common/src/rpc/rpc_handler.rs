@@ -17,6 +17,8 @@ use crate::{
     time::Instant,
     rpc::{
         InternalRpcError,
+        RateLimiter,
+        RpcClientKey,
         RpcRequest,
         RpcResponseError,
         JSON_RPC_VERSION
@@ -34,6 +36,10 @@ cfg_if! {
         pub type HandlerParams<P, R> = for<'a> fn(&'a Context, P) -> Pin<Box<dyn Future<Output = Result<R, InternalRpcError>> + 'a>>;
 
         pub type HandlerNoParams<R> = for<'a> fn(&'a Context) -> Pin<Box<dyn Future<Output = Result<R, InternalRpcError>> + 'a>>;
+
+        pub type MethodGuard = Box<dyn Fn(&Context, &str) -> Result<(), InternalRpcError>>;
+
+        pub type ClientKeyResolver = Box<dyn Fn(&Context) -> Option<RpcClientKey>>;
     } else {
         pub type Handler = Box<
             dyn for<'a> Fn(&'a Context, Value) -> Pin<Box<dyn Future<Output = Result<Value, InternalRpcError>> + Send + 'a>>
@@ -43,6 +49,10 @@ cfg_if! {
         pub type HandlerParams<P, R> = for<'a> fn(&'a Context, P) -> Pin<Box<dyn Future<Output = Result<R, InternalRpcError>> + Send + 'a>>;
 
         pub type HandlerNoParams<R> = for<'a> fn(&'a Context) -> Pin<Box<dyn Future<Output = Result<R, InternalRpcError>> + Send + 'a>>;
+
+        pub type MethodGuard = Box<dyn Fn(&Context, &str) -> Result<(), InternalRpcError> + Send + Sync>;
+
+        pub type ClientKeyResolver = Box<dyn Fn(&Context) -> Option<RpcClientKey> + Send + Sync>;
     }
 }
 
@@ -70,7 +80,16 @@ pub struct RPCHandler<T: ShareableTid<'static>> {
     // all RPC methods registered
     methods: HashMap<Cow<'static, str>, MethodHandler>,
     data: T,
-    batch_limit: Option<usize>
+    batch_limit: Option<usize>,
+    // Called before executing any method
+    // it can reject the call based on the context
+    guard: Option<MethodGuard>,
+    // Limit the requests per client
+    // Clients are identified using the key stored in the context
+    rate_limiter: Option<RateLimiter>,
+    // Resolve the client key from the context
+    // it has priority over the key stored in the context
+    client_key_resolver: Option<ClientKeyResolver>
 }
 
 tid! { impl<'a, T: 'static> TidAble<'a> for RPCHandler<T> where T: ShareableTid<'static> }
@@ -84,7 +103,10 @@ where
         let mut handler = Self {
             methods: HashMap::new(),
             data,
-            batch_limit: batch_limit.into()
+            batch_limit: batch_limit.into(),
+            guard: None,
+            rate_limiter: None,
+            client_key_resolver: None
         };
 
         // Internally register the "schema" method to get all registered methods
@@ -132,6 +154,23 @@ where
         })
     }
 
+    // Set the guard called before executing any method
+    // This can be used to restrict the methods available for a client
+    pub fn set_method_guard(&mut self, guard: MethodGuard) {
+        self.guard = Some(guard);
+    }
+
+    // Set the rate limiter applied on each method call
+    pub fn set_rate_limiter(&mut self, rate_limiter: RateLimiter) {
+        self.rate_limiter = Some(rate_limiter);
+    }
+
+    // Set the resolver used to identify a client for the rate limiter
+    // If it returns None, the key stored in the context is used
+    pub fn set_client_key_resolver(&mut self, resolver: ClientKeyResolver) {
+        self.client_key_resolver = Some(resolver);
+    }
+
     pub fn has_method(&self, method_name: &str) -> bool {
         self.methods.contains_key(method_name)
     }
@@ -154,6 +193,21 @@ where
         let handler = self.methods.get(&key)
             .ok_or_else(|| RpcResponseError::new(request.id.clone(), InternalRpcError::MethodNotFound(request.method.clone())))?;
 
+        if let Some(guard) = self.guard.as_ref() {
+            guard(context, &request.method)
+                .map_err(|err| RpcResponseError::new(request.id.clone(), err))?;
+        }
+
+        if let Some(rate_limiter) = self.rate_limiter.as_ref() {
+            let resolved = self.client_key_resolver.as_ref()
+                .and_then(|resolver| resolver(context));
+
+            if let Some(key) = resolved.as_ref().or_else(|| context.get::<RpcClientKey>()) {
+                rate_limiter.try_acquire(key, &request.method)
+                    .map_err(|err| RpcResponseError::new(request.id.clone(), err))?;
+            }
+        }
+
         trace!("executing '{}' RPC method", request.method);
         counter!("dapa_rpc_calls", "method" => request.method.clone()).increment(1);
 
@@ -16,6 +16,7 @@ use crate::{
         ShareableTid,
         RpcResponseError,
         RPCHandler,
+        RpcClientKey,
         RpcResponse,
     }
 };
@@ -97,6 +98,7 @@ where
         context.insert_ref(session);
         context.insert_ref(&self.handler);
         context.insert_ref(&self.events);
+        context.insert(RpcClientKey::from_ip(session.get_request().peer_addr()));
 
         self.handler.handle_request_with_context(context, message).await
     }
@@ -1,6 +1,7 @@
 use actix_web::{dev::RequestHead, http::{Uri, header::HeaderMap}};
 use reqwest::{Method, Version};
 use actix_web::HttpRequest as ActixHttpRequest;
+use std::net::SocketAddr;
 
 // Copy of actix_web::HttpRequest
 // Its done to Copy it & save it in WebSocketSession
@@ -32,6 +33,12 @@ impl HttpRequest {
     self.head().version
     }
 
+    /// Peer socket address.
+    #[inline]
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.head().peer_addr
+    }
+
     #[inline]
     /// Returns request's headers.
     pub fn headers(&self) -> &HeaderMap {
@@ -226,6 +226,15 @@ impl TransactionTypeBuilder {
 
         used_keys
     }
+
+    // Get the max gas of the contract call, if any
+    pub fn max_gas_mut(&mut self) -> Option<&mut u64> {
+        match self {
+            TransactionTypeBuilder::InvokeContract(payload) => Some(&mut payload.max_gas),
+            TransactionTypeBuilder::DeployContract(payload) => payload.invoke.as_mut().map(|invoke| &mut invoke.max_gas),
+            _ => None,
+        }
+    }
 }
 
 impl TransactionBuilder {
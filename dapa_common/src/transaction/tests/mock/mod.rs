@@ -1,4 +1,4 @@
-use std::{borrow::Cow, collections::{HashMap, VecDeque, hash_map::Entry}, sync::Arc};
+use std::{borrow::Cow, collections::{HashMap, VecDeque, hash_map::Entry}, sync::{Arc, OnceLock}};
 use anyhow::Context;
 use async_trait::async_trait;
 use curve25519_dalek::{ristretto::CompressedRistretto, traits::Identity};
@@ -20,6 +20,7 @@ use crate::{
         EventCallbackRegistration,
         CallbackEvent,
         ContractCache,
+        ContractEnvironments,
         ContractLog,
         ContractMetadata,
         ContractModule,
@@ -46,6 +47,19 @@ use crate::{
 mod provider;
 pub use provider::*;
 
+// Environments available to the contracts, built once for all the tests
+// Modules are using the default version but are compiled against the latest environment
+fn mock_environments() -> &'static ContractEnvironments {
+    static ENVIRONMENTS: OnceLock<ContractEnvironments> = OnceLock::new();
+    ENVIRONMENTS.get_or_init(|| {
+        let environment = Arc::new(build_environment::<MockStorageProvider>(ContractVersion::V2).build());
+        ContractVersion::variants()
+            .into_iter()
+            .map(|version| (version, environment.clone()))
+            .collect()
+    })
+}
+
 // Represent an account on chain
 #[derive(Debug, Clone)]
 pub struct MockAccount {
@@ -99,7 +113,7 @@ impl MockChainState {
             burned_coins: HashMap::new(),
             gas_fee: 0,
             burned_fee: 0,
-            env: Arc::new(build_environment::<MockStorageProvider>(ContractVersion::V1)),
+            env: Arc::new(build_environment::<MockStorageProvider>(ContractVersion::V2)),
             provider: MockStorageProvider::default(),
             mainnet: false,
             block_hash: Hash::zero(),
@@ -297,6 +311,15 @@ impl<'a> BlockchainVerificationState<'a, anyhow::Error> for MockChainState {
         Ok(self.multisig.get(account))
     }
 
+    async fn is_asset_paused(
+        &mut self,
+        asset: &'a Hash
+    ) -> Result<bool,  anyhow::Error> {
+        Ok(self.assets.get(asset)
+            .and_then(|changes| changes.as_ref())
+            .is_some_and(|changes| changes.data.1.is_paused()))
+    }
+
     async fn get_environment(&mut self, _: ContractVersion) -> Result<&Environment<ContractMetadata>,  anyhow::Error> {
         Ok(self.env.environment())
     }
@@ -437,7 +460,8 @@ impl<'a> BlockchainContractState<'a, MockStorageProvider,  anyhow::Error> for Mo
             },
             permission,
             gas_fee_allowance: 0,
-            environments: Cow::Owned(HashMap::new()),
+            read_only: false,
+            environments: Cow::Borrowed(mock_environments()),
             loaded_modules: Default::default(),
         };
 
@@ -478,6 +502,16 @@ impl<'a> BlockchainContractState<'a, MockStorageProvider,  anyhow::Error> for Mo
             };
         }
 
+        for (contract, module) in changes.modules {
+            let entry = self.contracts.entry(Cow::Owned(contract)).or_insert(None);
+            if let Some((state, current)) = entry.as_mut() {
+                state.mark_updated();
+                *current = Some(Cow::Owned(module));
+            } else {
+                *entry = Some((VersionedState::New, Some(Cow::Owned(module))));
+            }
+        }
+
         self.assets = changes.assets;
         self.tracker = changes.tracker;
         self.events.extend(changes.events);
@@ -55,6 +55,8 @@ pub enum VerificationError<T> {
     DepositNotFound,
     #[error("Configured max gas is above the network limit")]
     MaxGasReached,
+    #[error("Transfers of asset {} are paused", _0)]
+    AssetPaused(Hash),
     #[error(transparent)]
     Contract(#[from] ContractError<T>),
 }
\ No newline at end of file
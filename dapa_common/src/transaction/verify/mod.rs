@@ -503,6 +503,38 @@ impl Transaction {
         Ok(())
     }
 
+    // Verify that the transfers of the assets moved by this transaction are not paused
+    // Assets sent to an account or deposited in a contract are checked
+    async fn verify_assets_not_paused<'a, E, B: BlockchainVerificationState<'a, E>>(
+        &'a self,
+        state: &mut B,
+    ) -> Result<(), VerificationError<E>> {
+        let assets: Vec<&'a Hash> = match &self.data {
+            TransactionType::Transfers(transfers) => transfers.iter()
+                .map(|transfer| transfer.get_asset())
+                .collect(),
+            TransactionType::InvokeContract(payload) => payload.deposits.keys().collect(),
+            TransactionType::DeployContract(payload) => payload.invoke.iter()
+                .flat_map(|invoke| invoke.deposits.keys())
+                .collect(),
+            TransactionType::Burn(_) | TransactionType::MultiSig(_) => return Ok(())
+        };
+
+        for asset in assets {
+            // Native asset can't be paused
+            if *asset == DAPA_ASSET {
+                continue;
+            }
+
+            if state.is_asset_paused(asset).await.map_err(VerificationError::State)? {
+                debug!("Asset {} transfers are paused", asset);
+                return Err(VerificationError::AssetPaused(asset.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
     async fn verify_dynamic_parts<'a, E, B: BlockchainVerificationState<'a, E>>(
         &'a self,
         tx_hash: &'a Hash,
@@ -534,6 +566,8 @@ impl Transaction {
             .update_account_nonce(&self.source, self.nonce + 1).await
             .map_err(VerificationError::State)?;
 
+        self.verify_assets_not_paused(state).await?;
+
         match &self.data {
             TransactionType::Transfers(transfers) => {
                 for transfer in transfers.iter() {
@@ -688,6 +722,8 @@ impl Transaction {
             return Err(VerificationError::Commitments);
         }
 
+        self.verify_assets_not_paused(state).await?;
+
         let mut transfers_decompressed: Vec<_> = Vec::new();
         let mut deposits_decompressed: HashMap<_, _> = HashMap::new();
         match &self.data {
@@ -1355,6 +1391,8 @@ impl Transaction {
         state: &mut B
     ) -> Result<(), VerificationError<E>> {
         trace!("apply with partial verify");
+        self.verify_assets_not_paused(state).await?;
+
         let mut sigma_batch_collector = BatchCollector::default();
 
         let mut transfers_decompressed = Vec::new();
@@ -106,6 +106,12 @@ pub trait BlockchainVerificationState<'a, E> {
         account: &'a CompressedPublicKey
     ) -> Result<Option<&MultiSigPayload>, E>;
 
+    /// Check if the transfers of the asset are paused by its owner
+    async fn is_asset_paused(
+        &mut self,
+        asset: &'a Hash
+    ) -> Result<bool, E>;
+
     /// Get the environment
     /// Returns an error if the environment is not found or not compatible
     async fn get_environment(&mut self, version: ContractVersion) -> Result<&Environment<ContractMetadata>, E>;
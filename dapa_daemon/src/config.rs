@@ -24,6 +24,56 @@ pub const NETWORK_ID: [u8; NETWORK_ID_SIZE] = [0x44, 0x41, 0x50, 0x41, 0x56, 0x3
 // bind addresses
 pub const DEFAULT_P2P_BIND_ADDRESS: &str = "0.0.0.0:20100";
 pub const DEFAULT_RPC_BIND_ADDRESS: &str = "0.0.0.0:20101";
+pub const DEFAULT_STRATUM_BIND_ADDRESS: &str = "0.0.0.0:20103";
+
+// Stratum server
+// Bytes of the extra nonce set by the server, the remaining ones are left to the miner
+pub const STRATUM_DEFAULT_EXTRA_NONCE_SIZE: usize = 16;
+// Maximum size of a message sent by a Stratum client
+pub const STRATUM_MAX_MESSAGE_SIZE: usize = 4096;
+
+// GetWork pool mode
+// Default difficulty of the shares submitted by the miners
+pub const POOL_DEFAULT_SHARE_DIFFICULTY: u64 = 50_000;
+// Default count of last shares rewarded with PPLNS
+pub const POOL_DEFAULT_PPLNS_WINDOW: usize = 10_000;
+// Shares received in this window are used to estimate the hashrate
+pub const POOL_HASHRATE_WINDOW_MS: u64 = 10 * 60 * 1000;
+// Count of recent shares kept to detect duplicates
+pub const POOL_SHARES_CACHE_SIZE: usize = 16_384;
+// Maximum count of blocks found kept in the pool history
+pub const POOL_MAX_BLOCKS_HISTORY: usize = 1_000;
+// Expected time between two shares of a miner session
+pub const POOL_TARGET_SHARE_TIME_MS: u64 = 10 * 1000;
+// Minimum time between two adjustments of the share difficulty of a session
+pub const POOL_RETARGET_INTERVAL_MS: u64 = 60 * 1000;
+// Lowest share difficulty that can be assigned to a session
+pub const POOL_MIN_SHARE_DIFFICULTY: u64 = 1_000;
+// Interval between each check of the pending pool blocks
+// The ledger is also saved on disk at this interval if it has changed
+pub const POOL_LEDGER_INTERVAL_MS: u64 = 30 * 1000;
+
+// RPC rate limiter
+// Maximum requests a client can burst by default
+pub const RPC_DEFAULT_RATE_LIMIT_BURST: u64 = 50;
+// Default cost of the heaviest RPC methods, others cost 1
+pub const RPC_DEFAULT_METHOD_COSTS: [(&str, u64); 15] = [
+    ("get_account_history", 10),
+    ("get_account_transactions", 5),
+    ("get_contract_events", 5),
+    ("get_contract_data_entries", 10),
+    ("get_accounts", 5),
+    ("get_account_assets", 5),
+    ("get_mempool", 5),
+    ("get_mempool_summary", 5),
+    ("get_transactions", 5),
+    ("get_blocks_range_by_topoheight", 5),
+    ("get_blocks_range_by_height", 5),
+    ("simulate_transaction", 10),
+    ("call_contract_view", 10),
+    ("verify_human_readable_proof", 5),
+    ("register_contract_abi", 10),
+];
 
 // Default cache size for storage DB
 pub const DEFAULT_CACHE_SIZE: usize = 1024;
@@ -139,6 +189,19 @@ pub const CHAIN_SYNC_RESPONSE_MAX_BLOCKS: usize = u16::MAX as _;
 // average block time is calculated on the last N topoheight
 pub const CHAIN_AVERAGE_BLOCK_TIME_N: u64 = 50;
 
+// Mempool rules
+// Minimum fee per KB ratio in percent required by default
+// to replace a pending TX using the same source and nonce
+pub const MEMPOOL_DEFAULT_REPLACE_FEE_RATIO: u64 = 110;
+// Interval in seconds between each save of the mempool journal on disk
+pub const MEMPOOL_DEFAULT_JOURNAL_INTERVAL: u64 = 60;
+// Maximum count of pending TXs in mempool by default
+pub const MEMPOOL_DEFAULT_MAX_TXS: usize = 50_000;
+// Maximum size in bytes of all pending TXs in mempool by default
+pub const MEMPOOL_DEFAULT_MAX_SIZE: usize = 256 * 1024 * 1024;
+// Time to live in seconds of a pending TX by default: 3 hours
+pub const MEMPOOL_DEFAULT_TX_TTL: u64 = 3 * 60 * 60;
+
 // P2p rules
 // time between each ping
 pub const P2P_PING_DELAY: u64 = 10;
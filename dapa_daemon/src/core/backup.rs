@@ -0,0 +1,79 @@
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    path::Path
+};
+use anyhow::Context;
+use log::info;
+use serde::{Deserialize, Serialize};
+use dapa_common::{
+    block::TopoHeight,
+    config::VERSION,
+    crypto::Hash,
+    network::Network,
+    time::{get_current_time_in_millis, TimestampMillis}
+};
+use super::{
+    blockchain::Blockchain,
+    config::StorageBackend,
+    error::BlockchainError,
+    storage::Storage
+};
+
+// File written next to the DB copy describing the backup
+pub const BACKUP_MANIFEST_FILE: &str = "manifest.json";
+
+// Describe the state of the chain stored in a backup
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupManifest {
+    // Daemon version that created the backup
+    pub version: String,
+    pub network: Network,
+    pub backend: StorageBackend,
+    pub topoheight: TopoHeight,
+    pub top_hash: Hash,
+    pub timestamp: TimestampMillis
+}
+
+// Create a consistent copy of the DB in the given directory while the daemon is running
+// The DB is written in a sub directory named after the network,
+// so the backup directory can be used directly as the daemon dir path
+// The storage read lock is kept during the copy so no block can be added meanwhile
+pub async fn backup_blockchain<S: Storage>(blockchain: &Blockchain<S>, dir_path: &str) -> Result<BackupManifest, BlockchainError> {
+    let dir = Path::new(dir_path);
+    if dir.exists() && dir.read_dir()?.next().is_some() {
+        return Err(BlockchainError::BackupTargetNotEmpty(dir_path.to_owned()))
+    }
+    fs::create_dir_all(dir)?;
+
+    let network = *blockchain.get_network();
+    let storage = blockchain.get_storage_read().await;
+    if storage.has_snapshot().await? {
+        return Err(BlockchainError::BackupSnapshotActive)
+    }
+
+    let topoheight = storage.get_top_topoheight().await?;
+    let top_hash = storage.get_hash_at_topo_height(topoheight).await?;
+    info!("Creating backup at topoheight {} ({}) in {}", topoheight, top_hash, dir_path);
+
+    storage.create_backup(&dir.join(network.to_string().to_lowercase())).await?;
+    let backend = storage.get_backend();
+    drop(storage);
+
+    let manifest = BackupManifest {
+        version: VERSION.to_owned(),
+        network,
+        backend,
+        topoheight,
+        top_hash,
+        timestamp: get_current_time_in_millis()
+    };
+
+    let writer = BufWriter::new(File::create(dir.join(BACKUP_MANIFEST_FILE))?);
+    serde_json::to_writer_pretty(writer, &manifest)
+        .context("Error while writing backup manifest")?;
+
+    info!("Backup created in {}", dir_path);
+
+    Ok(manifest)
+}
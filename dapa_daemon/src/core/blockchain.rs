@@ -14,16 +14,23 @@ use dapa_common::{
             StableHeightChangedEvent,
             StableTopoHeightChangedEvent,
             TransactionExecutedEvent,
+            TransactionReplacedEvent,
             GetTransactionResult,
             ContractDeployEvent,
+            ContractUpgradeEvent,
             InvokeContractEvent,
             NewAssetEvent,
+            AssetUpdateEvent,
             ContractTransfersEvent,
             ContractTransfersEntry,
             ContractTransfersEntryKey,
             ContractEvent,
             MempoolTransactionSummary,
             NewTopoHeightEvent,
+            SimulateTransactionResult,
+            SimulatedContractBalance,
+            SimulatedContractEvent,
+            SimulatedTransfer,
         },
         RPCContractLog,
         RPCTransaction,
@@ -66,7 +73,7 @@ use dapa_common::{
         TimestampMillis
     },
     transaction::{
-        verify::BlockchainVerificationState,
+        verify::{BlockchainVerificationState, ContractEnvironment, NoZKPCache},
         Transaction,
         TransactionType
     },
@@ -80,12 +87,23 @@ use dapa_common::{
         is_multi_threads_supported,
         task::spawn_blocking,
         net::lookup_host,
-        sync::{RwLock, RwLockReadGuard, Semaphore}
+        sync::{RwLock, RwLockReadGuard, Semaphore},
+        time::interval
     },
     varuint::VarUint,
-    contract::{ContractMetadata, ContractVersion, build_environment},
+    contract::{
+        vm::{self, ContractCaller, ExitValue},
+        ChainState as ContractChainState,
+        ContractLog,
+        ContractMetadata,
+        ContractVersion,
+        ExecutionsManager,
+        InterContractPermission,
+        build_environment
+    },
+    versioned_type::VersionedState,
 };
-use xelis_vm::{Environment, tid};
+use xelis_vm::{Environment, ValueCell, tid};
 use crate::{
     config::{
         get_genesis_block_hash, get_hex_genesis_block,
@@ -101,7 +119,9 @@ use crate::{
         blockdag,
         difficulty,
         error::BlockchainError,
-        mempool::Mempool,
+        integrity,
+        mempool::{Mempool, SortedTx},
+        mempool_journal::MempoolJournal,
         nonce_checker::NonceChecker,
         simulator::Simulator,
         storage::{
@@ -121,7 +141,8 @@ use crate::{
     rpc::{
         rpc::{
             get_block_type_for_block,
-            get_block_response
+            get_block_response,
+            set_contract_logs_event_names
         },
         DaemonRpcServer,
         SharedDaemonRpcServer
@@ -238,6 +259,10 @@ pub struct Blockchain<S: Storage> {
     concurrency: usize,
     // Cache for mining block header templates
     mining_cache: RwLock<Option<BlockHeader>>,
+    // Index all the executed transactions per account
+    account_transactions_index: bool,
+    // Index all the events fired by the contracts
+    contract_events_index: bool,
 }
 
 tid! { impl<'a, S: 'static> TidAble<'a> for Blockchain<S> where S: Storage }
@@ -307,9 +332,18 @@ impl<S: Storage> Blockchain<S> {
             .map(|version| (version, Arc::new(build_environment::<S>(version).build())))
             .collect();
 
+        // Journal used to keep the pending TXs across restarts
+        let mempool_journal = if !config.mempool.disable_journal {
+            let path = format!("{}mempool-{}.bin", config.dir_path.as_deref().unwrap_or_default(), network.to_string().to_lowercase());
+            Some(MempoolJournal::new(path))
+        } else {
+            None
+        };
+        let mempool_journal_interval = config.mempool.journal_interval;
+
         info!("Initializing chain...");
         let blockchain = Self {
-            mempool: RwLock::new(Mempool::new(network, config.disable_zkp_cache)),
+            mempool: RwLock::new(Mempool::new(network, config.disable_zkp_cache, &config.mempool, mempool_journal)),
             storage: RwLock::new(storage),
             storage_semaphore: Semaphore::new(1),
             pre_verify_block_semaphore: Semaphore::new(config.pre_verify_block_threads_count),
@@ -327,6 +361,8 @@ impl<S: Storage> Blockchain<S> {
             disable_zkp_cache: config.disable_zkp_cache,
             concurrency: config.concurrency,
             mining_cache: RwLock::new(None),
+            account_transactions_index: config.enable_account_transactions_index,
+            contract_events_index: config.enable_contract_events_index,
         };
 
         // include genesis block
@@ -362,12 +398,51 @@ impl<S: Storage> Blockchain<S> {
 
                 info!("Cleaning data above topoheight {} in case of potential DB corruption", topoheight);
                 storage.delete_versioned_data_above_topoheight(topoheight).await?;
+
+                // Cross-check all the indexes once the corrupted data is removed
+                let report = integrity::check_storage_integrity(&mut *storage, config.repair_db_integrity).await?;
+                for entry in report.entries.iter() {
+                    warn!("DB integrity: {}{}", entry.issue, if entry.repaired { " (repaired)" } else { "" });
+                }
+            }
+
+            // Reload the pending TXs saved during the last run
+            if let Err(e) = blockchain.load_mempool_journal(&*storage).await {
+                warn!("Error while loading the mempool journal: {}", e);
             }
         } else {
             warn!("Recovery mode enabled, required pre-computed data have been skipped.");
         }
 
         let arc = Arc::new(blockchain);
+
+        // Save periodically the mempool journal
+        if arc.mempool.read().await.has_journal() && mempool_journal_interval > 0 {
+            let zelf = Arc::downgrade(&arc);
+            spawn_task("mempool-journal", async move {
+                let mut interval = interval(Duration::from_secs(mempool_journal_interval));
+                // First tick is completed immediately
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    let Some(blockchain) = zelf.upgrade() else {
+                        debug!("Blockchain dropped, stopping mempool journal task");
+                        break;
+                    };
+
+                    // Only the serialization is done under the lock
+                    let Some((journal, bytes)) = blockchain.mempool.read().await.serialize_journal() else {
+                        debug!("Mempool journal disabled, stopping mempool journal task");
+                        break;
+                    };
+
+                    if let Err(e) = journal.write(bytes).await {
+                        warn!("Error while saving the mempool journal: {}", e);
+                    }
+                }
+            });
+        }
+
         // create P2P Server
         if !config.p2p.disable {
             let dir_path = config.dir_path;
@@ -421,7 +496,7 @@ impl<S: Storage> Blockchain<S> {
 
             match P2pServer::new(
                 config.concurrency_task_count_limit,
-                dir_path,
+                dir_path.clone(),
                 config.tag,
                 config.max_peers,
                 config.bind_address,
@@ -457,7 +532,8 @@ impl<S: Storage> Blockchain<S> {
                         info!("RPC Server will listen on: {}", rpc_config.bind_address);
                         match DaemonRpcServer::new(
                             Arc::clone(&arc),
-                            rpc_config
+                            rpc_config,
+                            dir_path.as_deref()
                         ).await {
                             Ok(server) => *arc.rpc.write().await = Some(server),
                             Err(e) => error!("Error while starting RPC server: {}", e)
@@ -523,6 +599,18 @@ impl<S: Storage> Blockchain<S> {
         self.simulator.is_some()
     }
 
+    // Is the account transactions archival index enabled
+    #[inline]
+    pub fn is_account_transactions_index_enabled(&self) -> bool {
+        self.account_transactions_index
+    }
+
+    // Is the contract events archival index enabled
+    #[inline]
+    pub fn is_contract_events_index_enabled(&self) -> bool {
+        self.contract_events_index
+    }
+
     // Skip PoW verification flag
     #[inline]
     pub fn skip_pow_verification(&self) -> bool {
@@ -588,8 +676,12 @@ impl<S: Storage> Blockchain<S> {
 
         {
             debug!("stopping mempool module");
-            let mut mempool = self.mempool.write().await;
-            mempool.stop().await;
+            let journal = self.mempool.write().await.stop().await;
+            if let Some((journal, bytes)) = journal {
+                if let Err(e) = journal.write(bytes).await {
+                    warn!("Error while saving the mempool journal: {}", e);
+                }
+            }
         }
 
         info!("All modules are now stopped!");
@@ -632,6 +724,45 @@ impl<S: Storage> Blockchain<S> {
         Ok(())
     }
 
+    // Load the TXs from the mempool journal and add them back in mempool
+    // TXs already executed or no longer valid are dropped
+    async fn load_mempool_journal(&self, storage: &S) -> Result<(), BlockchainError> {
+        let mut mempool = self.mempool.write().await;
+        let entries = mempool.load_journal()?;
+        if entries.is_empty() {
+            return Ok(())
+        }
+
+        info!("Reloading {} TXs from mempool journal", entries.len());
+        let mut transactions = Vec::with_capacity(entries.len());
+        for (tx, first_seen) in entries {
+            let hash = tx.hash();
+            if storage.is_tx_executed_in_a_block(&hash).await? {
+                debug!("TX {} from mempool journal is already executed, skipping it", hash);
+                continue;
+            }
+
+            transactions.push((Arc::new(hash), Arc::new(tx), first_seen));
+        }
+
+        let chain_cache = storage.chain_cache().await;
+        let block_version = get_version_at_height(&self.network, chain_cache.height);
+        let orphaned = mempool.add_back_txs(storage, transactions.into_iter(), &self.environments, chain_cache.stable_topoheight, chain_cache.topoheight, block_version, FEE_PER_KB, chain_cache.stable_height).await?;
+        for (hash, _) in orphaned.iter() {
+            debug!("TX {} from mempool journal has been dropped", hash);
+        }
+
+        // Limits may have been lowered since the last run
+        let evicted = mempool.evict_txs(storage, &self.environments, chain_cache.stable_topoheight, chain_cache.topoheight, FEE_PER_KB, chain_cache.stable_height, block_version).await?;
+        if !evicted.is_empty() {
+            info!("{} TXs from mempool journal have been evicted", evicted.len());
+        }
+
+        info!("{} TXs reloaded in mempool, {} dropped", mempool.size(), orphaned.len());
+
+        Ok(())
+    }
+
     async fn initialize_caches(&self, storage: &mut S) -> Result<(), BlockchainError> {
         debug!("Initializing caches from storage");
 
@@ -1175,6 +1306,44 @@ impl<S: Storage> Blockchain<S> {
         self.add_tx_to_mempool_internal(storage, tx, tx_size, hash, broadcast).await
     }
 
+    // Notify the RPC clients that these TXs have been removed from the mempool
+    // without being executed in a block (replaced, evicted...)
+    async fn notify_txs_removed_from_mempool(&self, txs: Vec<(Arc<Hash>, SortedTx)>) {
+        if txs.is_empty() {
+            return;
+        }
+
+        let Some(rpc) = self.rpc.read().await.clone() else {
+            return;
+        };
+
+        if !rpc.is_event_tracked(&NotifyEvent::TransactionOrphaned).await {
+            return;
+        }
+
+        let mainnet = self.network.is_mainnet();
+        let events = txs.into_iter()
+            .map(|(tx_hash, sorted_tx)| {
+                let data = RPCTransaction::from_tx(&sorted_tx.get_tx(), Cow::Borrowed(&tx_hash), sorted_tx.get_size(), None, mainnet);
+                json!(GetTransactionResult {
+                    blocks: None,
+                    executed_in_block: None,
+                    in_mempool: false,
+                    first_seen: Some(sorted_tx.get_first_seen()),
+                    data,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        spawn_task("rpc-notify-txs-removed", async move {
+            for json in events {
+                if let Err(e) = rpc.notify_clients(&NotifyEvent::TransactionOrphaned, json).await {
+                    debug!("Error while broadcasting event TransactionOrphaned to websocket: {}", e);
+                }
+            }
+        });
+    }
+
     // Add a tx to the mempool with the given hash, it will verify the TX and check that it is not already in mempool or in blockchain
     // and its validity (nonce, balance, etc...)
     pub async fn add_tx_to_mempool_internal(
@@ -1187,7 +1356,7 @@ impl<S: Storage> Blockchain<S> {
     ) -> Result<(), BlockchainError> {
         debug!("add tx to mempool internal {} (broadcast = {})", hash, broadcast);
 
-        let hash = {
+        let (hash, replaced, evicted) = {
             debug!("locking mempool to add tx");
             let mut mempool = self.mempool.write().await;
             debug!("mempool locked to add tx");
@@ -1203,18 +1372,29 @@ impl<S: Storage> Blockchain<S> {
             let current_topoheight = chain_cache.topoheight;
             let height = chain_cache.height;
 
+            let version = get_version_at_height(self.get_network(), height);
+
             // get the highest nonce available
             // if presents, it means we have at least one tx from this owner in mempool
+            let mut replace = None;
             if let Some(cache) = mempool.get_cache_for(tx.get_source()) {
                 // we accept to delete a tx from mempool if the new one has a higher fee
                 if let Some(hash2) = cache.has_tx_with_same_nonce(tx.get_nonce()) {
                     // A TX with the same nonce is already in mempool
                     debug!("TX {} nonce is already used by TX {}", hash, hash2);
-                    return Err(BlockchainError::TxNonceAlreadyUsed(tx.get_nonce(), hash2.as_ref().clone()))
-                }
+                    let replaced = mempool.get_sorted_tx(hash2)?;
+                    let min_fee_per_kb = mempool.get_replacement_min_fee_per_kb(replaced)
+                        .ok_or_else(|| BlockchainError::TxNonceAlreadyUsed(tx.get_nonce(), hash2.as_ref().clone()))?;
+
+                    let (fee_per_kb, _) = estimate_tx_fee_per_kb(storage, stable_topoheight, &tx, tx_size, version).await?;
+                    if fee_per_kb < min_fee_per_kb {
+                        debug!("TX {} fee per kb {} is too low to replace TX {}, expected at least {}", hash, fee_per_kb, hash2, min_fee_per_kb);
+                        return Err(BlockchainError::TxReplacementFeeTooLow(hash2.as_ref().clone(), min_fee_per_kb, fee_per_kb))
+                    }
 
-                // check that the nonce is in the range
-                if !(tx.get_nonce() <= cache.get_max() + 1 && tx.get_nonce() >= cache.get_min()) {
+                    replace = Some(fee_per_kb);
+                } else if !(tx.get_nonce() <= cache.get_max() + 1 && tx.get_nonce() >= cache.get_min()) {
+                    // check that the nonce is in the range
                     debug!("TX {} nonce is not in the range of the pending TXs for this owner, received: {}, expected between {} and {}", hash, tx.get_nonce(), cache.get_min(), cache.get_max());
                     return Err(BlockchainError::InvalidTxNonceMempoolCache(tx.get_nonce(), cache.get_min(), cache.get_max()))
                 }
@@ -1224,21 +1404,78 @@ impl<S: Storage> Blockchain<S> {
             let hash = hash.into_arc();
 
             let start = Instant::now();
-            let version = get_version_at_height(self.get_network(), height);
 
             // NOTE: we do not verify / clean against requested base fee
             // to ensure no TX is orphaned, but only delayed until the chain congestion reduce
-            mempool.add_tx(storage, &self.environments, stable_topoheight, current_topoheight, FEE_PER_KB, stable_height, hash.clone(), tx.clone(), tx_size, version).await?;
+            let replaced = if let Some(fee_per_kb) = replace {
+                let evicted = mempool.replace_tx(storage, &self.environments, stable_topoheight, current_topoheight, FEE_PER_KB, stable_height, hash.clone(), tx.clone(), tx_size, version).await?;
+                debug!("TX {} has replaced {} TX(s) in the mempool", hash, evicted.len());
+                Some((fee_per_kb, evicted))
+            } else {
+                mempool.add_tx(storage, &self.environments, stable_topoheight, current_topoheight, FEE_PER_KB, stable_height, hash.clone(), tx.clone(), tx_size, version).await?;
+                None
+            };
 
             debug!("TX {} has been added to the mempool", hash);
 
+            // Keep the mempool below its limits
+            let mut evicted = mempool.evict_txs(storage, &self.environments, stable_topoheight, current_topoheight, FEE_PER_KB, stable_height, version).await?;
+            if !evicted.is_empty() {
+                counter!("dapa_mempool_txs_evicted").increment(evicted.len() as u64);
+                if let Some(index) = evicted.iter().position(|(tx_hash, _)| *tx_hash == hash) {
+                    debug!("TX {} has been evicted from the mempool", hash);
+                    // Other TXs may have been evicted with it
+                    evicted.swap_remove(index);
+                    self.notify_txs_removed_from_mempool(evicted).await;
+
+                    return Err(BlockchainError::MempoolFull(hash.as_ref().clone()))
+                }
+
+                debug!("TX {} has evicted {} TX(s) from the mempool", hash, evicted.len());
+            }
+
             // Record the time taken to add the transaction to the mempool
             histogram!("dapa_mempool_tx_added_ms").record(start.elapsed().as_millis() as f64);
             counter!("dapa_txs_verified").increment(1u64);
 
-            hash
+            (hash, replaced, evicted)
         };
 
+        self.notify_txs_removed_from_mempool(evicted).await;
+
+        if let Some((fee_per_kb, evicted)) = replaced {
+            counter!("dapa_mempool_txs_replaced").increment(1u64);
+
+            if let Some(rpc) = self.rpc.read().await.as_ref() {
+                if rpc.is_event_tracked(&NotifyEvent::TransactionReplaced).await {
+                    let mut evicted = evicted.iter()
+                        .map(|(tx_hash, _)| Cow::Owned(tx_hash.as_ref().clone()));
+
+                    if let Some(replaced) = evicted.next() {
+                        let data = TransactionReplacedEvent {
+                            hash: Cow::Borrowed(&hash),
+                            replaced,
+                            evicted: evicted.collect(),
+                            source: tx.get_source().as_address(self.network.is_mainnet()),
+                            nonce: tx.get_nonce(),
+                            fee_per_kb,
+                        };
+                        let json = json!(data);
+
+                        let rpc = rpc.clone();
+                        spawn_task("rpc-notify-tx-replaced", async move {
+                            if let Err(e) = rpc.notify_clients(&NotifyEvent::TransactionReplaced, json).await {
+                                debug!("Error while broadcasting event TransactionReplaced to websocket: {}", e);
+                            }
+                        });
+                    }
+                }
+            }
+
+            // The replaced TX and its dependents are no longer in mempool
+            self.notify_txs_removed_from_mempool(evicted).await;
+        }
+
         if broadcast {
             debug!("broadcast new tx {} added in mempool", hash);
             // P2p broadcast to others peers
@@ -1292,6 +1529,238 @@ impl<S: Storage> Blockchain<S> {
         Ok(())
     }
 
+    // Simulate the execution of a TX against the current chain state
+    // TX is verified and executed like in a new block built on top of the current tips,
+    // but all changes are kept in memory and dropped once done:
+    // nothing is written to the storage, and the mempool is not touched
+    // NOTE: pending TXs of the sender in mempool are not taken into account
+    pub async fn simulate_transaction(&self, storage: &S, tx: Arc<Transaction>) -> Result<SimulateTransactionResult<'static>, BlockchainError> {
+        let hash = tx.hash();
+        debug!("simulate tx {}", hash);
+
+        let tx_size = tx.size();
+        if tx_size > MAX_TRANSACTION_SIZE {
+            return Err(BlockchainError::TxTooBig(tx_size, MAX_TRANSACTION_SIZE))
+        }
+
+        if storage.is_tx_executed_in_a_block(&hash).await? {
+            return Err(BlockchainError::TxAlreadyInBlockchain(hash))
+        }
+
+        let chain_cache = storage.chain_cache().await;
+        let stable_topoheight = chain_cache.stable_topoheight;
+        let stable_height = chain_cache.stable_height;
+        let topoheight = chain_cache.topoheight;
+
+        // Build a throwaway block on top of the current tips including our TX
+        let mut header = self.get_block_header_template_for_storage(storage, tx.get_source().clone()).await?;
+        header.txs_hashes.insert(hash.clone());
+
+        let version = header.get_version();
+        let block_hash = header.hash();
+        let block = Block::new(header, vec![tx.clone()]);
+
+        let required_tx_fee = if version >= BlockVersion::V5 {
+            self.get_required_base_fee(storage, block.get_tips().iter()).await?.0
+        } else {
+            FEE_PER_KB
+        };
+
+        let mut result = SimulateTransactionResult {
+            hash: Cow::Owned(hash.clone()),
+            topoheight: topoheight + 1,
+            success: false,
+            error: None,
+            fees_paid: 0,
+            fees_burned: 0,
+            used_gas: 0,
+            refunded_gas: 0,
+            contract_logs: Vec::new(),
+            events: Vec::new(),
+            contract_balances: Vec::new(),
+            transfers: Vec::new(),
+            assets_created: Vec::new(),
+        };
+
+        // Fully verify the TX like the mempool would do
+        // Fee is checked against the base fee required by the block
+        {
+            let mut chain_state = ChainState::new(storage, &self.environments, stable_topoheight, topoheight, version, required_tx_fee, stable_height);
+            if let Err(e) = tx.verify(&hash, &mut chain_state, &NoZKPCache).await {
+                debug!("simulated tx {} is invalid: {}", hash, e);
+                result.error = Some(e.to_string());
+                return Ok(result)
+            }
+        }
+
+        // Execute it like in a new block
+        let mut chain_state = ApplicableChainState::new(
+            storage,
+            &self.environments,
+            stable_topoheight,
+            topoheight + 1,
+            version,
+            &block_hash,
+            &block,
+            required_tx_fee,
+            stable_height
+        );
+
+        chain_state.process_scheduled_executions().await?;
+
+        chain_state.link_tx_to_block(&hash, &block_hash, tx.invoked_contract());
+        if let Err(e) = tx.apply_with_partial_verify(&hash, &mut chain_state).await {
+            debug!("simulated tx {} failed during execution: {}", hash, e);
+            result.error = Some(e.to_string());
+            return Ok(result)
+        }
+        chain_state.mark_tx_as_executed_in_block(&hash, &block_hash)?;
+
+        chain_state.process_executions_at_block_end().await?;
+
+        let is_mainnet = self.network.is_mainnet();
+        let logs = chain_state.get_contract_logs_for_tx(&hash)
+            .cloned()
+            .unwrap_or_default();
+
+        let max_gas = match tx.get_data() {
+            TransactionType::InvokeContract(payload) => payload.max_gas,
+            TransactionType::DeployContract(payload) => payload.invoke.as_ref().map_or(0, |invoke| invoke.max_gas),
+            _ => 0
+        };
+
+        let mut exit_code = None;
+        for log in logs.iter() {
+            match log {
+                ContractLog::RefundGas { amount } => result.refunded_gas += *amount,
+                ContractLog::ExitCode(code) => exit_code = Some(*code),
+                _ => {}
+            }
+        }
+
+        // A contract call is only successful if it exited with the code 0
+        result.success = exit_code.map_or(true, |code| code == Some(0));
+        result.used_gas = max_gas.saturating_sub(result.refunded_gas);
+        result.fees_paid = chain_state.get_total_fees();
+        result.fees_burned = chain_state.get_total_fees_burned();
+        result.contract_logs = logs.into_iter()
+            .map(|log| RPCContractLog::from_owned(log, is_mainnet))
+            .collect();
+        set_contract_logs_event_names(storage, &mut result.contract_logs).await?;
+
+        for (contract, cache) in chain_state.get_contracts_cache() {
+            for (id, elements) in cache.events.iter() {
+                for data in elements {
+                    result.events.push(SimulatedContractEvent {
+                        contract: Cow::Owned(contract.clone()),
+                        event_id: *id,
+                        data: Cow::Owned(data.clone()),
+                    });
+                }
+            }
+
+            for (asset, balance) in cache.balances.iter() {
+                let Some((state, current)) = balance else {
+                    continue
+                };
+
+                if !state.should_be_stored() {
+                    continue
+                }
+
+                let previous = storage.get_contract_balance_at_maximum_topoheight(contract, asset, topoheight).await?
+                    .map_or(0, |(_, balance)| balance.take());
+
+                if previous != *current {
+                    result.contract_balances.push(SimulatedContractBalance {
+                        contract: Cow::Owned(contract.clone()),
+                        asset: Cow::Owned(asset.clone()),
+                        previous,
+                        current: *current,
+                    });
+                }
+            }
+        }
+
+        let tracker = chain_state.get_contract_tracker();
+        for (key, assets) in tracker.aggregated_transfers.iter() {
+            for (asset, amount) in assets {
+                result.transfers.push(SimulatedTransfer {
+                    destination: Cow::Owned(key.as_address(is_mainnet)),
+                    asset: Cow::Owned(asset.clone()),
+                    amount: *amount,
+                });
+            }
+        }
+
+        result.assets_created = tracker.assets_created.iter()
+            .map(|asset| Cow::Owned(asset.clone()))
+            .collect();
+
+        // chain state is dropped here without being finalized
+        Ok(result)
+    }
+
+    // Call a chunk of a deployed contract in read-only mode
+    // It is executed in the context of the block at the requested topoheight
+    // Returns the used gas and the exit value of the call
+    pub async fn call_contract_view(&self, storage: &S, contract: &Hash, chunk_id: u16, parameters: Vec<ValueCell>, max_gas: u64, topoheight: TopoHeight) -> Result<(u64, ExitValue), BlockchainError> {
+        debug!("call contract {} view chunk {} at topoheight {}", contract, chunk_id, topoheight);
+
+        let (contract_topoheight, version) = storage.get_contract_at_maximum_topoheight_for(contract, topoheight).await?
+            .ok_or_else(|| BlockchainError::ContractNotFound(contract.clone()))?;
+        let module = version.take()
+            .ok_or_else(|| BlockchainError::ContractNotFound(contract.clone()))?;
+
+        let environment = self.environments.get(&module.version)
+            .ok_or(BlockchainError::ContractEnvironmentNotFound(module.version))?;
+
+        let block_hash = storage.get_hash_at_topo_height(topoheight).await?;
+        let block = storage.get_block_by_hash(&block_hash).await?;
+
+        let global_modules = [
+            (Cow::Borrowed(contract), Some((VersionedState::FetchedAt(contract_topoheight), Some(Cow::Borrowed(module.as_ref())))))
+        ].into();
+        let global_caches = HashMap::new();
+        let global_executions = HashMap::new();
+
+        let mainnet = self.network.is_mainnet();
+        let mut state = ContractChainState {
+            debug_mode: !mainnet,
+            mainnet,
+            entry_contract: Cow::Borrowed(contract),
+            topoheight,
+            block_hash: &block_hash,
+            block: &block,
+            caller: ContractCaller::System,
+            logs: Vec::new(),
+            changes: Default::default(),
+            global_modules: &global_modules,
+            global_caches: &global_caches,
+            injected_gas: IndexMap::new(),
+            executions: ExecutionsManager {
+                global_executions: &global_executions,
+                changes: Default::default(),
+                allow_executions: false,
+            },
+            permission: Cow::Owned(InterContractPermission::All),
+            gas_fee_allowance: 0,
+            read_only: true,
+            environments: Cow::Borrowed(&self.environments),
+            loaded_modules: Default::default(),
+        };
+
+        let contract_environment = ContractEnvironment {
+            environment,
+            module: &module.module,
+            version: module.version,
+            provider: storage,
+        };
+
+        vm::call_contract_view(contract_environment, &mut state, Cow::Borrowed(contract), chunk_id, parameters.into_iter(), max_gas).await
+            .map_err(|e| BlockchainError::Any(e.into()))
+    }
+
     // Get a block template for the new block work (mining)
     pub async fn get_block_template(&self, address: PublicKey) -> Result<BlockHeader, BlockchainError> {
         debug!("get block template");
@@ -2524,10 +2993,14 @@ impl<S: Storage> Blockchain<S> {
                                     let is_mainnet = self.network.is_mainnet();
 
                                     if let Some(contract_logs) = chain_state.get_contract_logs_for_tx(&tx_hash) {
-                                        let contract_logs = contract_logs.into_iter()
+                                        let mut contract_logs = contract_logs.into_iter()
                                         .map(|log| RPCContractLog::from_log(log, is_mainnet))
                                         .collect::<Vec<_>>();
 
+                                        if let Err(e) = set_contract_logs_event_names(chain_state.get_storage(), &mut contract_logs).await {
+                                            warn!("Error while naming the contract events of {}: {}", tx_hash, e);
+                                        }
+
                                         let value = json!(InvokeContractEvent {
                                             tx_hash: Cow::Borrowed(&tx_hash),
                                             block_hash: Cow::Borrowed(&hash),
@@ -2599,6 +3072,39 @@ impl<S: Storage> Blockchain<S> {
                         }
                     }
 
+                    if should_track_events.contains(&NotifyEvent::AssetUpdate) {
+                        let entry = events.entry(NotifyEvent::AssetUpdate)
+                            .or_insert_with(Vec::new);
+
+                        for asset in contract_tracker.assets_updated.iter() {
+                            let value = json!(AssetUpdateEvent {
+                                asset: Cow::Borrowed(asset),
+                                block_hash: Cow::Borrowed(&hash),
+                                topoheight: highest_topo,
+                            });
+
+                            entry.push(value);
+                        }
+                    }
+
+                    for contract in contract_tracker.contracts_upgraded.iter() {
+                        let event = NotifyEvent::ContractUpgrade {
+                            contract: contract.clone(),
+                        };
+
+                        if should_track_events.contains(&event) {
+                            let value = json!(ContractUpgradeEvent {
+                                contract: Cow::Borrowed(contract),
+                                block_hash: Cow::Borrowed(&hash),
+                                topoheight: highest_topo,
+                            });
+
+                            events.entry(event)
+                                .or_insert_with(Vec::new)
+                                .push(value);
+                        }
+                    }
+
                     let mut aggregated_events = HashMap::new();
                     for ((caller, contract), transfers) in contract_tracker.contracts_transfers.iter() {
                         for (key, assets) in transfers.iter() {
@@ -2686,7 +3192,7 @@ impl<S: Storage> Blockchain<S> {
                 // let mut storage = holder.write().await?;
                 // trace!("Storage write lock acquired to apply changes for block {}", hash);
 
-                finalizer.apply_changes(&mut *storage, past_emitted_supply, block_reward).await?;
+                finalizer.apply_changes(&mut *storage, past_emitted_supply, block_reward, self.account_transactions_index, self.contract_events_index).await?;
 
                 if should_track_events.contains(&NotifyEvent::BlockOrdered) {
                     let value = json!(BlockOrderedEvent {
@@ -2913,7 +3419,17 @@ impl<S: Storage> Blockchain<S> {
 
             let start = Instant::now();
             // NOTE: we don't remove any under-paid TX, they stay in mempool until fixed
-            let res = mempool.clean_up(&*storage, &self.environments, base_topo_height, highest_topo, version, FEE_PER_KB, base_height, dag_is_overwritten).await?;
+            let mut res = mempool.clean_up(&*storage, &self.environments, base_topo_height, highest_topo, version, FEE_PER_KB, base_height, dag_is_overwritten).await?;
+
+            // Drop the TXs pending since too long and keep the mempool below its limits
+            let expired = mempool.expire_txs(&*storage, &self.environments, base_topo_height, highest_topo, FEE_PER_KB, base_height, version).await?;
+            counter!("dapa_mempool_txs_expired").increment(expired.len() as u64);
+            res.extend(expired);
+
+            let evicted = mempool.evict_txs(&*storage, &self.environments, base_topo_height, highest_topo, FEE_PER_KB, base_height, version).await?;
+            counter!("dapa_mempool_txs_evicted").increment(evicted.len() as u64);
+            res.extend(evicted);
+
             debug!("Took {:?} to clean mempool!", start.elapsed());
             histogram!("dapa_mempool_clean_up_ms").record(start.elapsed().as_millis() as f64);
 
@@ -0,0 +1,954 @@
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path
+};
+use futures::StreamExt;
+use indexmap::IndexSet;
+use log::{debug, info, trace, warn};
+use xelis_vm::ValueCell;
+use dapa_common::{
+    account::{Balance, Nonce, VersionedBalance, VersionedNonce},
+    asset::{AssetData, VersionedAssetData},
+    block::{BlockHeader, TopoHeight},
+    contract::ContractModule,
+    crypto::{hash_multiple, Hash, Hashable, PublicKey, HASH_SIZE},
+    immutable::Immutable,
+    network::Network,
+    serializer::{Reader, ReaderError, Serializer, Writer},
+    transaction::{MultiSigPayload, Transaction}
+};
+use crate::{
+    config::{DEV_PUBLIC_KEY, PRUNE_SAFETY_LIMIT},
+    p2p::{BlockMetadata, ScheduledExecutionMetadata, MAX_ITEMS_PER_PAGE}
+};
+use super::{
+    blockchain::Blockchain,
+    config::StorageBackend,
+    error::BlockchainError,
+    storage::{
+        Storage,
+        VersionedContractBalance,
+        VersionedContractData,
+        VersionedContractModule,
+        VersionedMultiSig,
+        VersionedSupply
+    }
+};
+
+#[cfg(feature = "sled")]
+use super::{config::SledConfig, storage::SledStorage};
+
+#[cfg(feature = "rocksdb")]
+use super::{config::RocksDBConfig, storage::RocksStorage};
+
+// Magic bytes at the start of each chain snapshot file
+const SNAPSHOT_MAGIC: &[u8; 8] = b"DAPASNAP";
+// Version of the chain snapshot format
+const SNAPSHOT_VERSION: u8 = 1;
+// Maximum size of a single entry in the file
+// This prevents allocating a huge buffer on a corrupted length
+const MAX_ENTRY_SIZE: usize = 64 * 1024 * 1024;
+
+// Entry stored in a chain snapshot file
+// The file is written as [magic][version] followed by the entries
+// Each entry is prefixed by its size as u32 and the last one is End
+// followed by the checksum of all the entries
+// Entries are written in the same order as a bootstrap sync
+// and are all applied at the stable topoheight of the Header,
+// except the balances and contract modules which keep their versions
+pub enum ChainSnapshotEntry {
+    // Stable point of the snapshot
+    Header {
+        network: Network,
+        topoheight: TopoHeight,
+        height: u64,
+        hash: Hash
+    },
+    Asset {
+        hash: Hash,
+        data: AssetData,
+        supply: Option<u64>
+    },
+    Account {
+        key: PublicKey,
+        nonce: Nonce,
+        multisig: Option<MultiSigPayload>
+    },
+    // A page of spendable balances versions for an account asset
+    // Versions are ordered from the highest to the lowest topoheight
+    Balances {
+        key: PublicKey,
+        asset: Hash,
+        balances: Vec<Balance>
+    },
+    // A version of the module of a contract
+    // Versions of a contract are ordered from the lowest to the highest topoheight
+    // None means the contract has been deleted
+    ContractModule {
+        hash: Hash,
+        topoheight: TopoHeight,
+        module: Option<ContractModule>
+    },
+    ContractBalance {
+        contract: Hash,
+        asset: Hash,
+        amount: u64
+    },
+    ContractData {
+        contract: Hash,
+        key: ValueCell,
+        value: ValueCell
+    },
+    ScheduledExecution(ScheduledExecutionMetadata),
+    // One of the last PRUNE_SAFETY_LIMIT blocks + the stable block
+    // ordered by topoheight
+    Block {
+        metadata: BlockMetadata,
+        header: Immutable<BlockHeader>,
+        txs: Vec<Immutable<Transaction>>
+    },
+    End
+}
+
+impl Serializer for ChainSnapshotEntry {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(match reader.read_u8()? {
+            0 => Self::Header {
+                network: Network::read(reader)?,
+                topoheight: reader.read_u64()?,
+                height: reader.read_u64()?,
+                hash: reader.read_hash()?
+            },
+            1 => Self::Asset {
+                hash: reader.read_hash()?,
+                data: AssetData::read(reader)?,
+                supply: Option::read(reader)?
+            },
+            2 => Self::Account {
+                key: PublicKey::read(reader)?,
+                nonce: reader.read_u64()?,
+                multisig: Option::read(reader)?
+            },
+            3 => Self::Balances {
+                key: PublicKey::read(reader)?,
+                asset: reader.read_hash()?,
+                balances: Vec::read(reader)?
+            },
+            4 => Self::ContractModule {
+                hash: reader.read_hash()?,
+                topoheight: reader.read_u64()?,
+                module: Option::read(reader)?
+            },
+            5 => Self::ContractBalance {
+                contract: reader.read_hash()?,
+                asset: reader.read_hash()?,
+                amount: reader.read_u64()?
+            },
+            6 => Self::ContractData {
+                contract: reader.read_hash()?,
+                key: ValueCell::read(reader)?,
+                value: ValueCell::read(reader)?
+            },
+            7 => Self::ScheduledExecution(ScheduledExecutionMetadata::read(reader)?),
+            8 => {
+                let metadata = BlockMetadata::read(reader)?;
+                let header = Immutable::<BlockHeader>::read(reader)?;
+                // TXs are written in the same order as in the header
+                let mut txs = Vec::with_capacity(header.get_txs_count());
+                for _ in 0..header.get_txs_count() {
+                    txs.push(Immutable::read(reader)?);
+                }
+
+                Self::Block {
+                    metadata,
+                    header,
+                    txs
+                }
+            },
+            9 => Self::End,
+            _ => return Err(ReaderError::InvalidValue)
+        })
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        match self {
+            Self::Header { network, topoheight, height, hash } => {
+                writer.write_u8(0);
+                network.write(writer);
+                writer.write_u64(*topoheight);
+                writer.write_u64(*height);
+                writer.write_hash(hash);
+            },
+            Self::Asset { hash, data, supply } => {
+                writer.write_u8(1);
+                writer.write_hash(hash);
+                data.write(writer);
+                supply.write(writer);
+            },
+            Self::Account { key, nonce, multisig } => {
+                writer.write_u8(2);
+                key.write(writer);
+                writer.write_u64(*nonce);
+                multisig.write(writer);
+            },
+            Self::Balances { key, asset, balances } => {
+                writer.write_u8(3);
+                key.write(writer);
+                writer.write_hash(asset);
+                balances.write(writer);
+            },
+            Self::ContractModule { hash, topoheight, module } => {
+                writer.write_u8(4);
+                writer.write_hash(hash);
+                writer.write_u64(*topoheight);
+                module.write(writer);
+            },
+            Self::ContractBalance { contract, asset, amount } => {
+                writer.write_u8(5);
+                writer.write_hash(contract);
+                writer.write_hash(asset);
+                writer.write_u64(*amount);
+            },
+            Self::ContractData { contract, key, value } => {
+                writer.write_u8(6);
+                writer.write_hash(contract);
+                key.write(writer);
+                value.write(writer);
+            },
+            Self::ScheduledExecution(execution) => {
+                writer.write_u8(7);
+                execution.write(writer);
+            },
+            Self::Block { metadata, header, txs } => {
+                writer.write_u8(8);
+                metadata.write(writer);
+                header.write(writer);
+                for tx in txs {
+                    tx.write(writer);
+                }
+            },
+            Self::End => {
+                writer.write_u8(9);
+            }
+        }
+    }
+}
+
+// Stable point contained in a chain snapshot
+#[derive(Debug)]
+pub struct ChainSnapshotInfo {
+    pub topoheight: TopoHeight,
+    pub height: u64,
+    pub hash: Hash,
+    pub entries: u64
+}
+
+// Write the entries in the file while computing the checksum
+struct SnapshotWriter {
+    writer: BufWriter<File>,
+    checksum: Hash,
+    entries: u64
+}
+
+impl SnapshotWriter {
+    fn new(file: File) -> Result<Self, BlockchainError> {
+        let mut writer = BufWriter::new(file);
+        writer.write_all(SNAPSHOT_MAGIC)?;
+        writer.write_all(&[SNAPSHOT_VERSION])?;
+
+        Ok(Self {
+            writer,
+            checksum: Hash::zero(),
+            entries: 0
+        })
+    }
+
+    fn write_entry(&mut self, entry: &ChainSnapshotEntry) -> Result<(), BlockchainError> {
+        let bytes = entry.to_bytes();
+        if bytes.len() > MAX_ENTRY_SIZE {
+            return Err(BlockchainError::InvalidSnapshot("entry is too big"))
+        }
+
+        self.writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+        self.writer.write_all(&bytes)?;
+        self.checksum = hash_multiple(&[self.checksum.as_bytes(), &bytes]);
+        self.entries += 1;
+
+        Ok(())
+    }
+
+    // Write the End entry followed by the checksum
+    fn finish(mut self) -> Result<u64, BlockchainError> {
+        self.write_entry(&ChainSnapshotEntry::End)?;
+        self.writer.write_all(self.checksum.as_bytes())?;
+        self.writer.flush()?;
+
+        Ok(self.entries)
+    }
+}
+
+// Read the entries from the file while computing the checksum
+// The checksum is verified once the End entry is reached
+struct SnapshotReader {
+    reader: BufReader<File>,
+    checksum: Hash,
+    entries: u64,
+    done: bool
+}
+
+impl SnapshotReader {
+    fn open(path: &str) -> Result<Self, BlockchainError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0u8; SNAPSHOT_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != *SNAPSHOT_MAGIC {
+            return Err(BlockchainError::InvalidSnapshot("not a chain snapshot file"))
+        }
+
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != SNAPSHOT_VERSION {
+            return Err(BlockchainError::UnsupportedSnapshotVersion(version[0]))
+        }
+
+        Ok(Self {
+            reader,
+            checksum: Hash::zero(),
+            entries: 0,
+            done: false
+        })
+    }
+
+    // Read the next entry, returns None once the End entry is reached
+    fn next_entry(&mut self) -> Result<Option<ChainSnapshotEntry>, BlockchainError> {
+        if self.done {
+            return Ok(None)
+        }
+
+        let mut size = [0u8; 4];
+        self.reader.read_exact(&mut size)?;
+        let size = u32::from_be_bytes(size) as usize;
+        if size > MAX_ENTRY_SIZE {
+            return Err(BlockchainError::InvalidSnapshot("entry is too big"))
+        }
+
+        let mut bytes = vec![0u8; size];
+        self.reader.read_exact(&mut bytes)?;
+        self.checksum = hash_multiple(&[self.checksum.as_bytes(), &bytes]);
+        self.entries += 1;
+
+        let mut reader = Reader::new(&bytes);
+        let entry = ChainSnapshotEntry::read(&mut reader)?;
+        if reader.size() != 0 {
+            return Err(BlockchainError::InvalidSnapshot("entry has trailing bytes"))
+        }
+
+        if let ChainSnapshotEntry::End = entry {
+            let mut checksum = [0u8; HASH_SIZE];
+            self.reader.read_exact(&mut checksum)?;
+            let checksum = Hash::new(checksum);
+            if checksum != self.checksum {
+                return Err(BlockchainError::SnapshotChecksumMismatch(checksum, self.checksum.clone()))
+            }
+
+            self.done = true;
+            return Ok(None)
+        }
+
+        Ok(Some(entry))
+    }
+}
+
+// Export the chain state at the stable topoheight into a snapshot file
+// The DB is first copied next to the file while holding the storage read lock
+// and the snapshot is built from that copy, so the chain isn't blocked during the export
+pub async fn export_chain_snapshot<S: Storage>(blockchain: &Blockchain<S>, path: &str) -> Result<ChainSnapshotInfo, BlockchainError> {
+    let network = *blockchain.get_network();
+    let db_dir = format!("{}.db/", path);
+    if Path::new(&db_dir).exists() {
+        return Err(BlockchainError::BackupTargetNotEmpty(db_dir))
+    }
+
+    let storage = blockchain.get_storage_read().await;
+    if storage.has_snapshot().await? {
+        return Err(BlockchainError::BackupSnapshotActive)
+    }
+
+    // Everything exported is read at the stable topoheight,
+    // so the copy can be ahead of it without changing the result
+    let topoheight = storage.chain_cache().await.stable_topoheight;
+    fs::create_dir_all(&db_dir)?;
+    debug!("Copying the DB in {} to export the chain snapshot", db_dir);
+    let backend = match storage.create_backup(&Path::new(&db_dir).join(network.to_string().to_lowercase())).await {
+        Ok(()) => storage.get_backend(),
+        Err(BlockchainError::BackupNotSupported) => {
+            // An in-memory storage can't be copied, export it directly
+            fs::remove_dir_all(&db_dir)?;
+            return export_chain_snapshot_from(&*storage, network, topoheight, path).await
+        },
+        Err(e) => {
+            fs::remove_dir_all(&db_dir)?;
+            return Err(e)
+        }
+    };
+    drop(storage);
+
+    let res = export_chain_snapshot_from_copy(backend, &db_dir, network, topoheight, path).await;
+    fs::remove_dir_all(&db_dir)?;
+
+    res
+}
+
+// Open the DB copy with its backend and export the snapshot from it
+// The storage is dropped before returning so the copy can be deleted
+async fn export_chain_snapshot_from_copy(backend: StorageBackend, db_dir: &str, network: Network, topoheight: TopoHeight, path: &str) -> Result<ChainSnapshotInfo, BlockchainError> {
+    match backend {
+        #[cfg(feature = "sled")]
+        StorageBackend::Sled => {
+            let config = SledConfig::default();
+            let storage = SledStorage::new(db_dir.to_owned(), None, network, config.internal_cache_size, config.internal_db_mode)?;
+            export_chain_snapshot_from(&storage, network, topoheight, path).await
+        },
+        #[cfg(feature = "rocksdb")]
+        StorageBackend::RocksDB => {
+            let storage = RocksStorage::new(db_dir, network, &RocksDBConfig::default());
+            export_chain_snapshot_from(&storage, network, topoheight, path).await
+        },
+        #[cfg(feature = "rocksdb")]
+        StorageBackend::Memory => Err(BlockchainError::BackupNotSupported)
+    }
+}
+
+// Write all the entries of the chain state at the given stable topoheight
+// Each section is read in a single pass over its iterator
+async fn export_chain_snapshot_from<S: Storage>(storage: &S, network: Network, topoheight: TopoHeight, path: &str) -> Result<ChainSnapshotInfo, BlockchainError> {
+    // We need the last PRUNE_SAFETY_LIMIT blocks below the stable one
+    let pruned_topoheight = storage.get_pruned_topoheight().await?;
+    if topoheight < PRUNE_SAFETY_LIMIT || pruned_topoheight.is_some_and(|pruned| topoheight - PRUNE_SAFETY_LIMIT <= pruned) {
+        return Err(BlockchainError::SnapshotChainTooShort)
+    }
+
+    let hash = storage.get_hash_at_topo_height(topoheight).await?;
+    let height = storage.get_block_header_by_hash(&hash).await?.get_height();
+    info!("Exporting chain snapshot at stable topoheight {} ({}) to {}", topoheight, hash, path);
+
+    // Write in a temporary file to not leave an incomplete snapshot behind
+    let tmp = format!("{}.tmp", path);
+    let mut writer = SnapshotWriter::new(File::create(&tmp)?)?;
+    writer.write_entry(&ChainSnapshotEntry::Header {
+        network,
+        topoheight,
+        height,
+        hash: hash.clone()
+    })?;
+
+    debug!("Exporting assets");
+    let mut assets = storage.get_assets_with_data_in_range(None, Some(topoheight)).await?;
+    loop {
+        let chunk = assets.by_ref()
+            .take(MAX_ITEMS_PER_PAGE)
+            .map(|res| res.map(|(hash, _, data)| (hash, data)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for (hash, data) in chunk {
+            let supply = storage.get_circulating_supply_for_asset_at_maximum_topoheight(&hash, topoheight).await?
+                .map(|(_, v)| v.take());
+
+            writer.write_entry(&ChainSnapshotEntry::Asset { hash, data, supply })?;
+        }
+    }
+
+    debug!("Exporting accounts");
+    let mut keys = storage.get_registered_keys(None, Some(topoheight)).await?;
+    loop {
+        let chunk = keys.by_ref()
+            .take(MAX_ITEMS_PER_PAGE)
+            .collect::<Result<Vec<PublicKey>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for key in chunk {
+            let Some((_, nonce)) = storage.get_nonce_at_maximum_topoheight(&key, topoheight).await? else {
+                warn!("No nonce found for {} at topoheight {}, skipping it", key.as_address(network.is_mainnet()), topoheight);
+                continue;
+            };
+
+            let multisig = storage.get_multisig_at_maximum_topoheight_for(&key, topoheight).await?
+                .and_then(|(_, v)| v.take())
+                .map(Cow::into_owned);
+
+            writer.write_entry(&ChainSnapshotEntry::Account {
+                key: key.clone(),
+                nonce: nonce.get_nonce(),
+                multisig
+            })?;
+
+            export_balances(storage, &mut writer, &key, topoheight).await?;
+        }
+    }
+
+    debug!("Exporting contracts");
+    let mut contracts = storage.get_contracts(0, topoheight).await?;
+    loop {
+        let chunk = contracts.by_ref()
+            .take(MAX_ITEMS_PER_PAGE)
+            .collect::<Result<Vec<Hash>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for contract in chunk {
+            export_contract(storage, &mut writer, &contract, topoheight).await?;
+        }
+    }
+
+    debug!("Exporting scheduled executions");
+    let mut executions = storage.get_registered_contract_scheduled_executions_in_range(0, topoheight, Some(topoheight)).await?
+        .boxed();
+
+    while let Some(res) = executions.next().await {
+        let (execution_topoheight, registration_topoheight, execution) = res?;
+        writer.write_entry(&ChainSnapshotEntry::ScheduledExecution(ScheduledExecutionMetadata {
+            execution,
+            execution_topoheight,
+            registration_topoheight
+        }))?;
+    }
+
+    debug!("Exporting last {} blocks", PRUNE_SAFETY_LIMIT + 1);
+    for topoheight in topoheight - PRUNE_SAFETY_LIMIT..=topoheight {
+        let hash = storage.get_hash_at_topo_height(topoheight).await?;
+        let topoheight_metadata = storage.get_metadata_at_topoheight(topoheight).await?;
+        let difficulty = storage.get_difficulty_for_block_hash(&hash).await?;
+        let cumulative_difficulty = storage.get_cumulative_difficulty_for_block_hash(&hash).await?;
+        let p = storage.get_estimated_covariance_for_block_hash(&hash).await?;
+        let size_ema = storage.get_block_size_ema(&hash).await?;
+        let header = storage.get_block_header_by_hash(&hash).await?;
+
+        let mut executed_transactions = IndexSet::new();
+        let mut txs = Vec::with_capacity(header.get_txs_count());
+        for tx_hash in header.get_txs_hashes() {
+            if storage.is_tx_executed_in_block(tx_hash, &hash).await? {
+                executed_transactions.insert(tx_hash.clone());
+            }
+            txs.push(storage.get_transaction(tx_hash).await?);
+        }
+
+        let metadata = BlockMetadata { hash, topoheight_metadata, difficulty, cumulative_difficulty, p, size_ema, executed_transactions };
+        writer.write_entry(&ChainSnapshotEntry::Block { metadata, header, txs })?;
+    }
+
+    let entries = writer.finish()?;
+    fs::rename(&tmp, path)?;
+    info!("Chain snapshot exported to {} with {} entries", path, entries);
+
+    Ok(ChainSnapshotInfo {
+        topoheight,
+        height,
+        hash,
+        entries
+    })
+}
+
+// Export all the balances versions of an account
+async fn export_balances<S: Storage>(storage: &S, writer: &mut SnapshotWriter, key: &PublicKey, topoheight: TopoHeight) -> Result<(), BlockchainError> {
+    let mut assets = storage.get_assets_for(key).await?;
+    loop {
+        let chunk = assets.by_ref()
+            .take(MAX_ITEMS_PER_PAGE)
+            .collect::<Result<Vec<Hash>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for asset in chunk {
+            let Some(summary) = storage.get_account_summary_for(key, &asset, 0, topoheight).await? else {
+                continue;
+            };
+
+            let min_topoheight = summary.output_topoheight.unwrap_or(0);
+            let mut max_topoheight = Some(summary.stable_topoheight);
+            while let Some(max) = max_topoheight {
+                trace!("Exporting balances of asset {} at max topoheight {}", asset, max);
+                let (balances, mut next_max) = storage.get_spendable_balances_for(key, &asset, min_topoheight, max, MAX_ITEMS_PER_PAGE).await?;
+
+                // Same as the bootstrap sync, only one round for the dev public key
+                // as it may be updated at each block
+                if next_max.is_some() && *key == *DEV_PUBLIC_KEY {
+                    next_max = None;
+                }
+
+                if !balances.is_empty() {
+                    writer.write_entry(&ChainSnapshotEntry::Balances {
+                        key: key.clone(),
+                        asset: asset.clone(),
+                        balances
+                    })?;
+                }
+
+                max_topoheight = next_max;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Export the modules, balances and storage of a contract
+// Every module version is kept so the upgrades history is still available
+async fn export_contract<S: Storage>(storage: &S, writer: &mut SnapshotWriter, contract: &Hash, topoheight: TopoHeight) -> Result<(), BlockchainError> {
+    let Some((last_topoheight, mut version)) = storage.get_contract_at_maximum_topoheight_for(contract, topoheight).await? else {
+        warn!("No module found for contract {} at topoheight {}, skipping it", contract, topoheight);
+        return Ok(())
+    };
+
+    // Collect the topoheights first as the versions are linked from the highest one
+    let mut topoheights = vec![last_topoheight];
+    while let Some(previous) = version.get_previous_topoheight() {
+        // Older versions may have been pruned
+        if !storage.has_contract_at_exact_topoheight(contract, previous).await? {
+            break;
+        }
+
+        version = storage.get_contract_at_topoheight_for(contract, previous).await?;
+        topoheights.push(previous);
+    }
+
+    for module_topoheight in topoheights.into_iter().rev() {
+        trace!("Exporting module of contract {} at topoheight {}", contract, module_topoheight);
+        let mut version = storage.get_contract_at_topoheight_for(contract, module_topoheight).await?;
+        writer.write_entry(&ChainSnapshotEntry::ContractModule {
+            hash: contract.clone(),
+            topoheight: module_topoheight,
+            module: version.take().map(Cow::into_owned)
+        })?;
+    }
+
+    let mut assets = storage.get_contract_assets_for(contract).await?;
+    loop {
+        let chunk = assets.by_ref()
+            .take(MAX_ITEMS_PER_PAGE)
+            .collect::<Result<Vec<Hash>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for asset in chunk {
+            if let Some((_, balance)) = storage.get_contract_balance_at_maximum_topoheight(contract, &asset, topoheight).await? {
+                writer.write_entry(&ChainSnapshotEntry::ContractBalance {
+                    contract: contract.clone(),
+                    asset,
+                    amount: balance.take()
+                })?;
+            }
+        }
+    }
+
+    let mut entries = storage.get_contract_data_entries_at_maximum_topoheight(contract, topoheight).await?
+        .boxed();
+
+    while let Some(res) = entries.next().await {
+        let (key, value) = res?;
+        writer.write_entry(&ChainSnapshotEntry::ContractData {
+            contract: contract.clone(),
+            key,
+            value
+        })?;
+    }
+
+    Ok(())
+}
+
+// Read the whole snapshot file to verify its checksum and content
+// This is done before writing anything in the storage
+pub fn verify_chain_snapshot(path: &str, network: &Network) -> Result<ChainSnapshotInfo, BlockchainError> {
+    let mut reader = SnapshotReader::open(path)?;
+    let Some(ChainSnapshotEntry::Header { network: snapshot_network, topoheight, height, hash }) = reader.next_entry()? else {
+        return Err(BlockchainError::InvalidSnapshot("missing header"))
+    };
+
+    if snapshot_network != *network {
+        return Err(BlockchainError::SnapshotNetworkMismatch(snapshot_network, *network))
+    }
+
+    if topoheight < PRUNE_SAFETY_LIMIT {
+        return Err(BlockchainError::InvalidSnapshot("stable topoheight is too low"))
+    }
+
+    let mut last_block = None;
+    let mut last_module: Option<(Hash, TopoHeight)> = None;
+    let mut blocks = 0;
+    while let Some(entry) = reader.next_entry()? {
+        match entry {
+            ChainSnapshotEntry::Header { .. } => return Err(BlockchainError::InvalidSnapshot("duplicated header")),
+            ChainSnapshotEntry::ContractModule { hash, topoheight: module_topoheight, .. } => {
+                if module_topoheight > topoheight {
+                    return Err(BlockchainError::InvalidSnapshot("contract module is above the stable topoheight"))
+                }
+
+                // Versions are linked to the previous entry of the same contract
+                if let Some((last_hash, last_topoheight)) = &last_module {
+                    if *last_hash == hash && module_topoheight <= *last_topoheight {
+                        return Err(BlockchainError::InvalidSnapshot("contract modules are not ordered"))
+                    }
+                }
+
+                last_module = Some((hash, module_topoheight));
+            },
+            ChainSnapshotEntry::Block { metadata, header, txs } => {
+                if header.get_inner().hash() != metadata.hash {
+                    return Err(BlockchainError::InvalidSnapshot("block hash mismatch"))
+                }
+
+                for (tx, tx_hash) in txs.iter().zip(header.get_txs_hashes()) {
+                    if tx.get_inner().hash() != *tx_hash {
+                        return Err(BlockchainError::InvalidSnapshot("transaction hash mismatch"))
+                    }
+                }
+
+                blocks += 1;
+                last_block = Some(metadata.hash);
+            },
+            _ => {}
+        }
+    }
+
+    // Last N blocks + stable block
+    if blocks != PRUNE_SAFETY_LIMIT + 1 {
+        return Err(BlockchainError::InvalidSnapshot("invalid blocks count"))
+    }
+
+    if last_block.as_ref() != Some(&hash) {
+        return Err(BlockchainError::InvalidSnapshot("last block is not the stable block"))
+    }
+
+    Ok(ChainSnapshotInfo {
+        topoheight,
+        height,
+        hash,
+        entries: reader.entries
+    })
+}
+
+// Balances versions of an account asset being imported
+// Each version is stored once the next (lower) one is known
+// to link it to its previous topoheight
+struct PendingBalances {
+    key: PublicKey,
+    asset: Hash,
+    previous_version: Option<(TopoHeight, VersionedBalance)>,
+    highest_topoheight: Option<TopoHeight>
+}
+
+impl PendingBalances {
+    async fn apply<S: Storage>(&mut self, storage: &mut S, balances: Vec<Balance>) -> Result<(), BlockchainError> {
+        for balance in balances {
+            let (topoheight, version) = balance.as_version();
+            if self.highest_topoheight.is_none_or(|v| topoheight > v) {
+                self.highest_topoheight = Some(topoheight);
+            }
+
+            if let Some((prev_topoheight, mut prev)) = self.previous_version.take() {
+                prev.set_previous_topoheight(Some(topoheight));
+                storage.set_balance_at_topoheight(&self.asset, prev_topoheight, &self.key, &prev).await?;
+            }
+
+            self.previous_version = Some((topoheight, version));
+        }
+
+        Ok(())
+    }
+
+    // Store the oldest version and the last topoheight of the balance
+    async fn flush<S: Storage>(self, storage: &mut S) -> Result<(), BlockchainError> {
+        if let Some((topoheight, version)) = self.previous_version {
+            storage.set_balance_at_topoheight(&self.asset, topoheight, &self.key, &version).await?;
+        }
+
+        if let Some(topoheight) = self.highest_topoheight {
+            storage.set_last_topoheight_for_balance(&self.key, &self.asset, topoheight)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Import a chain snapshot on a fresh chain
+// The file is fully verified first, then all its entries are written
+// at the stable topoheight like a bootstrap sync would do
+// Once done, the node can continue to sync from its peers
+pub async fn import_chain_snapshot<S: Storage>(blockchain: &Blockchain<S>, path: &str) -> Result<ChainSnapshotInfo, BlockchainError> {
+    info!("Verifying chain snapshot {}", path);
+    let info = verify_chain_snapshot(path, blockchain.get_network())?;
+    info!("Importing chain snapshot at stable topoheight {} ({}) with {} entries", info.topoheight, info.hash, info.entries);
+
+    {
+        let _permit = blockchain.storage_semaphore().acquire().await?;
+        let mut storage = blockchain.get_storage().write().await;
+        let current_topoheight = storage.chain_cache().await.topoheight;
+        if current_topoheight != 0 {
+            return Err(BlockchainError::SnapshotChainNotEmpty(current_topoheight))
+        }
+
+        let stable_topoheight = info.topoheight;
+        let lowest_topoheight = stable_topoheight - PRUNE_SAFETY_LIMIT;
+        let mut block_topoheight = lowest_topoheight;
+        let mut pending_balances: Option<PendingBalances> = None;
+        let mut last_module: Option<(Hash, TopoHeight)> = None;
+
+        let mut reader = SnapshotReader::open(path)?;
+        while let Some(entry) = reader.next_entry()? {
+            // Balances of an account asset may be split in several entries
+            let entry = match entry {
+                ChainSnapshotEntry::Balances { key, asset, balances } => {
+                    let mut pending = match pending_balances.take() {
+                        Some(pending) if pending.key == key && pending.asset == asset => pending,
+                        previous => {
+                            if let Some(previous) = previous {
+                                previous.flush(&mut *storage).await?;
+                            }
+
+                            PendingBalances {
+                                key,
+                                asset,
+                                previous_version: None,
+                                highest_topoheight: None
+                            }
+                        }
+                    };
+
+                    pending.apply(&mut *storage, balances).await?;
+                    pending_balances = Some(pending);
+                    continue;
+                },
+                entry => entry
+            };
+
+            if let Some(pending) = pending_balances.take() {
+                pending.flush(&mut *storage).await?;
+            }
+
+            match entry {
+                ChainSnapshotEntry::Header { .. } | ChainSnapshotEntry::Balances { .. } | ChainSnapshotEntry::End => {},
+                ChainSnapshotEntry::Asset { hash, data, supply } => {
+                    trace!("Importing asset {}", hash);
+                    storage.add_asset(&hash, stable_topoheight, VersionedAssetData::new(data, None)).await?;
+                    if let Some(supply) = supply {
+                        storage.set_last_circulating_supply_for_asset(&hash, stable_topoheight, &VersionedSupply::new(supply, None)).await?;
+                    }
+                },
+                ChainSnapshotEntry::Account { key, nonce, multisig } => {
+                    trace!("Importing account {}", key.as_address(blockchain.get_network().is_mainnet()));
+                    storage.set_last_nonce_to(&key, stable_topoheight, &VersionedNonce::new(nonce, None)).await?;
+                    storage.set_account_registration_topoheight(&key, stable_topoheight).await?;
+                    if let Some(multisig) = multisig {
+                        storage.set_last_multisig_to(&key, stable_topoheight, VersionedMultiSig::new(Some(Cow::Owned(multisig)), None)).await?;
+                    }
+                },
+                ChainSnapshotEntry::ContractModule { hash, topoheight, module } => {
+                    trace!("Importing contract {} module at topoheight {}", hash, topoheight);
+                    let previous_topoheight = last_module.take()
+                        .filter(|(last_hash, _)| *last_hash == hash)
+                        .map(|(_, last_topoheight)| last_topoheight);
+
+                    // Versions are imported from the lowest one, so the last one stays the latest module
+                    let versioned = VersionedContractModule::new(module.map(Cow::Owned), previous_topoheight);
+                    storage.set_last_contract_to(&hash, topoheight, &versioned).await?;
+                    last_module = Some((hash, topoheight));
+                },
+                ChainSnapshotEntry::ContractBalance { contract, asset, amount } => {
+                    storage.set_last_contract_balance_to(&contract, &asset, stable_topoheight, VersionedContractBalance::new(amount, None)).await?;
+                },
+                ChainSnapshotEntry::ContractData { contract, key, value } => {
+                    storage.set_last_contract_data_to(&contract, &key, stable_topoheight, &VersionedContractData::new(Some(value), None)).await?;
+                },
+                ChainSnapshotEntry::ScheduledExecution(execution) => {
+                    storage.set_contract_scheduled_execution_at_topoheight(&execution.execution.contract, execution.registration_topoheight, &execution.execution, execution.execution_topoheight).await?;
+                },
+                ChainSnapshotEntry::Block { metadata, header, txs } => {
+                    let topoheight = block_topoheight;
+                    block_topoheight += 1;
+
+                    // The genesis block is already in storage
+                    if storage.has_block_with_hash(&metadata.hash).await? {
+                        debug!("Block {} at topoheight {} already in storage, skipping", metadata.hash, topoheight);
+                        continue;
+                    }
+
+                    trace!("Importing block {} at topoheight {}", metadata.hash, topoheight);
+                    for tx_hash in header.get_txs_hashes() {
+                        storage.add_block_linked_to_tx_if_not_present(tx_hash, &metadata.hash).await?;
+                    }
+
+                    storage.set_metadata_at_topoheight(topoheight, metadata.topoheight_metadata).await?;
+                    storage.set_topo_height_for_block(&metadata.hash, topoheight).await?;
+
+                    for tx in metadata.executed_transactions {
+                        if !header.get_txs_hashes().contains(&tx) || storage.is_tx_executed_in_a_block(&tx).await? {
+                            return Err(BlockchainError::InvalidSnapshot("invalid executed transaction in block"))
+                        }
+
+                        storage.mark_tx_as_executed_in_block(&tx, &metadata.hash).await?;
+                    }
+
+                    let txs = txs.into_iter()
+                        .map(Immutable::into_arc)
+                        .collect::<Vec<_>>();
+
+                    storage.save_block(header.into_arc(), &txs, metadata.difficulty, metadata.cumulative_difficulty, metadata.p, metadata.size_ema, Immutable::Owned(metadata.hash)).await?;
+                }
+            }
+        }
+
+        storage.set_pruned_topoheight(Some(lowest_topoheight)).await?;
+        storage.set_top_topoheight(stable_topoheight).await?;
+        storage.set_top_height(info.height).await?;
+        storage.store_tips(&HashSet::from([info.hash.clone()])).await?;
+    }
+
+    info!("Reload caches from disk");
+    blockchain.reload_from_disk().await?;
+    info!("Chain snapshot imported at topoheight {}", info.topoheight);
+
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_entry_serialization() {
+        let entry = ChainSnapshotEntry::Header {
+            network: Network::Testnet,
+            topoheight: 100,
+            height: 90,
+            hash: Hash::max()
+        };
+
+        let bytes = entry.to_bytes();
+        let Ok(ChainSnapshotEntry::Header { network, topoheight, height, hash }) = ChainSnapshotEntry::from_bytes(&bytes) else {
+            panic!("invalid header entry");
+        };
+        assert_eq!(network, Network::Testnet);
+        assert_eq!(topoheight, 100);
+        assert_eq!(height, 90);
+        assert_eq!(hash, Hash::max());
+
+        let bytes = ChainSnapshotEntry::End.to_bytes();
+        assert!(matches!(ChainSnapshotEntry::from_bytes(&bytes), Ok(ChainSnapshotEntry::End)));
+        assert!(ChainSnapshotEntry::from_bytes(&[10]).is_err());
+    }
+}
@@ -1,7 +1,8 @@
-use std::time::Duration;
+use std::{str::FromStr, time::Duration};
 use humantime::Duration as HumanDuration;
 use serde::{Deserialize, Serialize};
 use dapa_common::{
+    api::daemon::PoolPayoutScheme,
     crypto::Hash,
     prompt::LogLevel,
     utils::detect_available_parallelism
@@ -57,6 +58,22 @@ const fn default_getwork_rate_limit_ms() -> u64 {
     500
 }
 
+const fn default_pool_share_difficulty() -> u64 {
+    POOL_DEFAULT_SHARE_DIFFICULTY
+}
+
+const fn default_pool_pplns_window() -> usize {
+    POOL_DEFAULT_PPLNS_WINDOW
+}
+
+fn default_stratum_bind_address() -> String {
+    DEFAULT_STRATUM_BIND_ADDRESS.to_owned()
+}
+
+const fn default_stratum_extra_nonce_size() -> usize {
+    STRATUM_DEFAULT_EXTRA_NONCE_SIZE
+}
+
 fn default_p2p_temp_ban_duration() -> HumanDuration {
     HumanDuration::from(Duration::from_secs(PEER_TEMP_BAN_TIME))
 }
@@ -85,6 +102,71 @@ const fn default_rpc_batch_limit() -> usize {
     20
 }
 
+const fn default_rpc_rate_limit_burst() -> u64 {
+    RPC_DEFAULT_RATE_LIMIT_BURST
+}
+
+const fn default_mempool_replace_fee_ratio() -> u64 {
+    MEMPOOL_DEFAULT_REPLACE_FEE_RATIO
+}
+
+const fn default_mempool_journal_interval() -> u64 {
+    MEMPOOL_DEFAULT_JOURNAL_INTERVAL
+}
+
+const fn default_mempool_max_txs() -> usize {
+    MEMPOOL_DEFAULT_MAX_TXS
+}
+
+const fn default_mempool_max_size() -> usize {
+    MEMPOOL_DEFAULT_MAX_SIZE
+}
+
+const fn default_mempool_tx_ttl() -> u64 {
+    MEMPOOL_DEFAULT_TX_TTL
+}
+
+#[derive(Debug, Clone, clap::Args, Serialize, Deserialize)]
+pub struct PoolConfig {
+    /// Enable the pool mode for the GetWork and Stratum servers.
+    /// Blocks are mined for the pool address and the miners
+    /// are rewarded based on the shares they submit.
+    #[clap(name = "getwork-pool-mode", long)]
+    #[serde(default)]
+    pub enable: bool,
+    /// Address receiving the rewards of the blocks found by the pool.
+    /// Required in pool mode.
+    #[clap(name = "getwork-pool-address", long)]
+    #[serde(default)]
+    pub address: Option<String>,
+    /// Starting difficulty of the shares submitted by each miner session.
+    /// It is then adjusted per session to receive a share every 10 seconds
+    /// and capped to the block difficulty.
+    #[clap(name = "getwork-pool-share-difficulty", long, default_value_t = default_pool_share_difficulty())]
+    #[serde(default = "default_pool_share_difficulty")]
+    pub share_difficulty: u64,
+    /// Payout scheme used to reward the miners.
+    #[clap(name = "getwork-pool-payout-scheme", long, value_enum, default_value_t = PoolPayoutScheme::Pplns)]
+    #[serde(default)]
+    pub payout_scheme: PoolPayoutScheme,
+    /// Count of last shares rewarded when a block is found using PPLNS.
+    #[clap(name = "getwork-pool-pplns-window", long, default_value_t = default_pool_pplns_window())]
+    #[serde(default = "default_pool_pplns_window")]
+    pub pplns_window: usize,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            address: None,
+            share_difficulty: default_pool_share_difficulty(),
+            payout_scheme: PoolPayoutScheme::default(),
+            pplns_window: default_pool_pplns_window(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, clap::Args, Serialize, Deserialize)]
 pub struct GetWorkConfig {
     /// Disable GetWork Server (WebSocket for miners).
@@ -104,6 +186,40 @@ pub struct GetWorkConfig {
     #[clap(name = "getwork-notify-job-concurrency", long, default_value_t = detect_available_parallelism())]
     #[serde(default = "detect_available_parallelism")]
     pub notify_job_concurrency: usize,
+    /// Pool mode configuration
+    /// This is used to accept shares and track the miners rewards.
+    #[clap(flatten)]
+    #[serde(default)]
+    pub pool: PoolConfig,
+}
+
+#[derive(Debug, Clone, clap::Args, Serialize, Deserialize)]
+pub struct StratumConfig {
+    /// Enable the Stratum server (TCP for mining proxies and pools).
+    /// Jobs and blocks are shared with the GetWork server,
+    /// so it requires the GetWork server to be enabled.
+    #[clap(name = "enable-stratum-server", long)]
+    #[serde(default)]
+    pub enable: bool,
+    /// Stratum bind address to listen for miners
+    #[clap(name = "stratum-bind-address", long, default_value_t = default_stratum_bind_address())]
+    #[serde(default = "default_stratum_bind_address")]
+    pub bind_address: String,
+    /// Number of bytes of the extra nonce set by the server for each miner.
+    /// The remaining bytes of the extra nonce are left to the miner.
+    #[clap(name = "stratum-extra-nonce-size", long, default_value_t = default_stratum_extra_nonce_size())]
+    #[serde(default = "default_stratum_extra_nonce_size")]
+    pub extra_nonce_size: usize,
+}
+
+impl Default for StratumConfig {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            bind_address: default_stratum_bind_address(),
+            extra_nonce_size: default_stratum_extra_nonce_size(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, clap::Args, Serialize, Deserialize)]
@@ -119,6 +235,138 @@ pub struct PrometheusConfig {
     pub route: String,
 }
 
+// RPC role with the methods it is allowed to call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RPCRoleConfig {
+    pub name: String,
+    pub methods: Vec<String>
+}
+
+impl FromStr for RPCRoleConfig {
+    type Err = &'static str;
+
+    // Expected format: <name>=<method>,<method>,...
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, methods) = s.split_once('=').ok_or("Invalid role, expected <name>=<methods>")?;
+        if name.is_empty() {
+            return Err("Invalid role name")
+        }
+
+        let methods = methods.split(',')
+            .map(str::trim)
+            .filter(|method| !method.is_empty())
+            .map(String::from)
+            .collect();
+
+        Ok(Self {
+            name: name.to_owned(),
+            methods
+        })
+    }
+}
+
+// RPC API key with the role assigned to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RPCApiKeyConfig {
+    pub key: String,
+    pub role: String
+}
+
+impl FromStr for RPCApiKeyConfig {
+    type Err = &'static str;
+
+    // Expected format: <key>=<role>
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, role) = s.rsplit_once('=').ok_or("Invalid API key, expected <key>=<role>")?;
+        if key.is_empty() || role.is_empty() {
+            return Err("Invalid API key or role")
+        }
+
+        Ok(Self {
+            key: key.to_owned(),
+            role: role.to_owned()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, clap::Args, Serialize, Deserialize)]
+pub struct RPCAuthConfig {
+    /// Define a role that can be assigned to an API key.
+    /// Format is `<name>=<method>,<method>,...`
+    /// A method ending with `*` allows all the methods starting with it,
+    /// `*` alone allows all methods.
+    /// The `admin` role allowing all methods is always available.
+    #[clap(name = "rpc-auth-role", long)]
+    #[serde(default)]
+    pub roles: Vec<RPCRoleConfig>,
+    /// API key allowed to access the RPC server with the given role.
+    /// Format is `<key>=<role>`
+    /// The key must be sent using the `Authorization: Bearer <key>` header
+    /// or as the password of an HTTP basic authentication.
+    /// Miners connecting to the GetWork server are authenticated the same way,
+    /// their role must allow the `submit_block` method.
+    #[clap(name = "rpc-auth-api-key", long)]
+    #[serde(default)]
+    pub api_keys: Vec<RPCApiKeyConfig>,
+    /// Role used by the clients that are not authenticated.
+    /// If not set while API keys are configured, authentication is required.
+    #[clap(name = "rpc-auth-public-role", long)]
+    #[serde(default)]
+    pub public_role: Option<String>,
+}
+
+// Cost of a RPC method for the rate limiter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RPCMethodCostConfig {
+    pub method: String,
+    pub cost: u64
+}
+
+impl FromStr for RPCMethodCostConfig {
+    type Err = &'static str;
+
+    // Expected format: <method>=<cost>
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (method, cost) = s.split_once('=').ok_or("Invalid method cost, expected <method>=<cost>")?;
+        let cost = cost.parse().map_err(|_| "Invalid cost")?;
+
+        Ok(Self {
+            method: method.to_owned(),
+            cost
+        })
+    }
+}
+
+#[derive(Debug, Clone, clap::Args, Serialize, Deserialize)]
+pub struct RPCRateLimitConfig {
+    /// Maximum requests per second allowed per client.
+    /// Authenticated clients are identified by their API key, others by their IP.
+    /// Set to 0 to disable the rate limiter.
+    #[clap(name = "rpc-rate-limit", long, default_value_t = 0)]
+    #[serde(default)]
+    pub requests_per_second: u64,
+    /// Maximum requests a client can send at once before being rate limited.
+    #[clap(name = "rpc-rate-limit-burst", long, default_value_t = default_rpc_rate_limit_burst())]
+    #[serde(default = "default_rpc_rate_limit_burst")]
+    pub burst: u64,
+    /// Cost of a method for the rate limiter.
+    /// Format is `<method>=<cost>`, methods not configured cost 1.
+    /// Heavy methods such as `get_account_history` have a higher cost by default.
+    #[clap(name = "rpc-rate-limit-method-cost", long)]
+    #[serde(default)]
+    pub method_costs: Vec<RPCMethodCostConfig>,
+}
+
+impl Default for RPCRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 0,
+            burst: default_rpc_rate_limit_burst(),
+            method_costs: Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, clap::Args, Serialize, Deserialize)]
 pub struct RPCConfig {
     /// GetWork configuration
@@ -126,10 +374,26 @@ pub struct RPCConfig {
     /// Only available if the RPC is enabled
     #[clap(flatten)]
     pub getwork: GetWorkConfig,
+    /// Stratum configuration
+    /// This is used to configure the Stratum server.
+    /// Only available if the GetWork server is enabled
+    #[clap(flatten)]
+    #[serde(default)]
+    pub stratum: StratumConfig,
     /// Prometheus configuration
     /// This is used to configure the Prometheus metrics server.
     #[clap(flatten)]
     pub prometheus: PrometheusConfig,
+    /// Authentication configuration
+    /// This is used to restrict the RPC methods available per client.
+    #[clap(flatten)]
+    #[serde(default)]
+    pub auth: RPCAuthConfig,
+    /// Rate limiter configuration
+    /// This is used to limit the requests sent by each client.
+    #[clap(flatten)]
+    #[serde(default)]
+    pub rate_limit: RPCRateLimitConfig,
     /// Disable RPC Server
     /// This will also disable the GetWork Server as it is loaded on RPC server.
     #[clap(name = "disable-rpc-server", long)]
@@ -391,7 +655,14 @@ pub enum StorageBackend {
     #[cfg(feature = "rocksdb")]
     #[serde(rename = "rocksdb")]
     #[clap(name = "rocksdb")]
-    RocksDB
+    RocksDB,
+    // Same layout as RocksDB but kept in memory only
+    // It is the `InnerDB::Memory` variant of `RocksStorage`,
+    // so it requires the rocksdb feature
+    // Only used when selected explicitly, all data is lost on exit
+    #[cfg(feature = "rocksdb")]
+    #[serde(rename = "memory")]
+    Memory
 }
 
 impl Default for StorageBackend {
@@ -431,6 +702,17 @@ pub struct SledConfig {
     pub internal_db_mode: StorageMode,
 }
 
+#[cfg(feature = "sled")]
+impl Default for SledConfig {
+    fn default() -> Self {
+        Self {
+            cache_size: default_sled_cache_size(),
+            internal_cache_size: default_db_cache_size(),
+            internal_db_mode: StorageMode::default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, clap::Args, Serialize, Deserialize)]
 pub struct RocksDBConfig {
     /// How many background threads RocksDB should use for parallelism.
@@ -489,6 +771,78 @@ pub struct RocksDBConfig {
     pub write_buffer_shared: bool,
 }
 
+impl Default for RocksDBConfig {
+    fn default() -> Self {
+        Self {
+            parallelism: detect_available_parallelism(),
+            max_background_jobs: detect_available_parallelism(),
+            max_subcompaction_jobs: detect_available_parallelism(),
+            low_priority_background_threads: detect_available_parallelism(),
+            max_open_files: default_max_open_files(),
+            keep_max_log_files: default_keep_max_log_files(),
+            #[cfg(feature = "rocksdb")]
+            compression_mode: CompressionMode::default(),
+            #[cfg(feature = "rocksdb")]
+            cache_mode: CacheMode::default(),
+            cache_size: default_db_cache_size(),
+            write_buffer_size: default_db_cache_size(),
+            write_buffer_shared: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, clap::Args, Serialize, Deserialize)]
+pub struct MempoolConfig {
+    /// Minimum fee per KB ratio in percent required to replace
+    /// a pending TX using the same source and nonce.
+    /// For example, 110 means the new TX must pay at least 10% more per KB.
+    /// Set to 0 to disable the replace-by-fee.
+    #[clap(name = "mempool-replace-fee-ratio", long, default_value_t = default_mempool_replace_fee_ratio())]
+    #[serde(default = "default_mempool_replace_fee_ratio")]
+    pub replace_fee_ratio: u64,
+    /// Disable the mempool journal.
+    /// By default, pending TXs are saved on disk and reloaded at the next start.
+    #[clap(name = "mempool-disable-journal", long)]
+    #[serde(default)]
+    pub disable_journal: bool,
+    /// Interval in seconds between each save of the mempool journal.
+    /// Set to 0 to only save it when the daemon is stopped.
+    #[clap(name = "mempool-journal-interval", long, default_value_t = default_mempool_journal_interval())]
+    #[serde(default = "default_mempool_journal_interval")]
+    pub journal_interval: u64,
+    /// Maximum count of pending TXs in mempool.
+    /// Once reached, TXs with the lowest fee per KB are evicted.
+    /// Set to 0 to disable the limit.
+    #[clap(name = "mempool-max-txs", long, default_value_t = default_mempool_max_txs())]
+    #[serde(default = "default_mempool_max_txs")]
+    pub max_txs: usize,
+    /// Maximum size in bytes of all pending TXs in mempool.
+    /// Once reached, TXs with the lowest fee per KB are evicted.
+    /// Set to 0 to disable the limit.
+    #[clap(name = "mempool-max-size", long, default_value_t = default_mempool_max_size())]
+    #[serde(default = "default_mempool_max_size")]
+    pub max_size: usize,
+    /// Time to live in seconds of a pending TX in mempool.
+    /// TXs not included in a block before it are dropped.
+    /// Set to 0 to keep them until they are included or invalidated.
+    #[clap(name = "mempool-tx-ttl", long, default_value_t = default_mempool_tx_ttl())]
+    #[serde(default = "default_mempool_tx_ttl")]
+    pub tx_ttl: u64,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            replace_fee_ratio: default_mempool_replace_fee_ratio(),
+            disable_journal: false,
+            journal_interval: default_mempool_journal_interval(),
+            max_txs: default_mempool_max_txs(),
+            max_size: default_mempool_max_size(),
+            tx_ttl: default_mempool_tx_ttl(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, clap::Args, Serialize, Deserialize)]
 pub struct Config {
     /// RPC configuration
@@ -504,6 +858,10 @@ pub struct Config {
     /// RocksDB Backend if enabled
     #[clap(flatten)]
     pub rocksdb: RocksDBConfig,
+    /// Mempool configuration
+    #[clap(flatten)]
+    #[serde(default)]
+    pub mempool: MempoolConfig,
     /// Set dir path for blockchain storage.
     /// This will be appended by the network name for the database directory.
     /// It must ends with a slash.
@@ -548,11 +906,18 @@ pub struct Config {
     /// Enable the DB integrity check that happen on chain initialization.
     /// This may take some times on huge DB as it's iterating through all versioned data
     /// to verify that no pointer or version is above our current topoheight.
+    /// All the indexes are then cross-checked and the inconsistencies are reported.
     /// Please note that if the recovery mode is enabled, this will be skipped.
     /// By default, it is disabled.
     #[clap(long)]
     #[serde(default)]
     pub check_db_integrity: bool,
+    /// Rebuild the indexes that can be derived from their source data
+    /// when an inconsistency is found by the DB integrity check.
+    /// It has no effect if `check_db_integrity` is disabled.
+    #[clap(long)]
+    #[serde(default)]
+    pub repair_db_integrity: bool,
     /// Enable the recovery mode of the daemon.
     /// No DB integrity check or pre-computations will occurs
     /// such as difficulty for tips, stable height, etc.
@@ -567,6 +932,8 @@ pub struct Config {
     /// Use a different DB backend from the default.
     /// Note that the data will not be migrated from one to another
     /// and you may lose your data.
+    /// Use `--migrate-storage` to copy the data to another backend first.
+    /// The `memory` backend keeps the chain in memory only, like for a disposable simulator chain.
     #[clap(long, value_enum, default_value_t)]
     #[serde(default)]
     pub use_db_backend: StorageBackend,
@@ -581,6 +948,20 @@ pub struct Config {
     #[clap(long, default_value_t = detect_available_parallelism())]
     #[serde(default = "detect_available_parallelism")]
     pub concurrency: usize,
+    /// Enable the archival index of the transactions per account.
+    /// Each executed transaction is linked to its sender and receivers,
+    /// including the contract transfers targets.
+    /// The index is never pruned and is required by the `get_account_transactions` RPC method.
+    /// Only blocks executed while enabled are indexed.
+    #[clap(long)]
+    #[serde(default)]
+    pub enable_account_transactions_index: bool,
+    /// Enable the archival index of the events fired and emitted by the contracts.
+    /// The index is never pruned and is required by the `get_contract_events` RPC method.
+    /// Only blocks executed while enabled are indexed.
+    #[clap(long)]
+    #[serde(default)]
+    pub enable_contract_events_index: bool,
 }
 
 mod humantime_serde {
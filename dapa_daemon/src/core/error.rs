@@ -17,6 +17,7 @@ use dapa_common::{
     account::Nonce,
     block::TopoHeight,
     difficulty::DifficultyError,
+    network::Network,
     prompt::PromptError,
     rpc::InternalRpcError,
     serializer::ReaderError,
@@ -149,6 +150,8 @@ pub enum DiskContext {
     VersionedBalance,
     #[error("contract event callback")]
     ContractEventCallback,
+    #[error("get contract event")]
+    ContractEvent,
 }
 
 #[derive(Error, Debug, EnumDiscriminants)]
@@ -211,6 +214,12 @@ pub enum BlockchainError {
     InvalidDifficulty,
     #[error("Tx nonce {} already used by Tx {}", _0, _1)]
     TxNonceAlreadyUsed(Nonce, Hash),
+    #[error("No pending tx with nonce {} to replace", _0)]
+    NoTxToReplace(Nonce),
+    #[error("Tx {} can't be replaced, fee per kb must be at least {} but got {}", _0, _1, _2)]
+    TxReplacementFeeTooLow(Hash, u64, u64),
+    #[error("Mempool is full, fee per kb of tx {} is too low to be kept", _0)]
+    MempoolFull(Hash),
     #[error("Invalid hash, expected {}, got {}", _0, _1)]
     InvalidHash(Hash, Hash),
     #[error("Invalid previous block hash, expected {}, got {}", _0, _1)]
@@ -349,6 +358,36 @@ pub enum BlockchainError {
     PruneLowerThanLastPruned,
     #[error("Auto prune mode is misconfigured")]
     AutoPruneMode,
+    #[error("Invalid chain snapshot: {}", _0)]
+    InvalidSnapshot(&'static str),
+    #[error("Unsupported chain snapshot version {}", _0)]
+    UnsupportedSnapshotVersion(u8),
+    #[error("Chain snapshot is for network {}, expected {}", _0, _1)]
+    SnapshotNetworkMismatch(Network, Network),
+    #[error("Invalid chain snapshot checksum, expected {}, got {}", _0, _1)]
+    SnapshotChecksumMismatch(Hash, Hash),
+    #[error("Chain snapshot can only be imported on a fresh chain, current topoheight is {}", _0)]
+    SnapshotChainNotEmpty(TopoHeight),
+    #[error("Not enough stable blocks to export a chain snapshot")]
+    SnapshotChainTooShort,
+    #[error("No storage found at {}", _0)]
+    StorageNotFound(String),
+    #[error("Target storage of the migration is not empty")]
+    MigrationTargetNotEmpty,
+    #[error("Storage migration mismatch on {}: source has {}, target has {}", _0, _1, _2)]
+    MigrationMismatch(&'static str, u64, u64),
+    #[error("Storage migration top block mismatch: source has {}, target has {}", _0, _1)]
+    MigrationTopHashMismatch(Hash, Hash),
+    #[error("Account transactions index is not enabled")]
+    AccountTransactionsIndexDisabled,
+    #[error("Contract events index is not enabled")]
+    ContractEventsIndexDisabled,
+    #[error("Backup target directory {} is not empty", _0)]
+    BackupTargetNotEmpty(String),
+    #[error("Backup is not supported by the in-memory storage")]
+    BackupNotSupported,
+    #[error("Backup is not possible while a storage snapshot is active")]
+    BackupSnapshotActive,
     #[error(transparent)]
     TryFromSliceError(#[from] std::array::TryFromSliceError),
     #[error("Invalid ciphertext")]
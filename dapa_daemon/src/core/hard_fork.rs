@@ -113,8 +113,8 @@ pub const fn is_contract_version_allowed_in_block_version(
     match block_version {
         BlockVersion::V0 | BlockVersion::V1 | BlockVersion::V2 => false,
         BlockVersion::V3 | BlockVersion::V4 | BlockVersion::V5 => matches!(contract_version, ContractVersion::V0),
-        // Starting from V6, we enable V1 contracts
-        BlockVersion::V6 => matches!(contract_version, ContractVersion::V0 | ContractVersion::V1),
+        // Starting from V6, we enable V1 and V2 (upgradable) contracts
+        BlockVersion::V6 => matches!(contract_version, ContractVersion::V0 | ContractVersion::V1 | ContractVersion::V2),
     }
 }
 
@@ -276,4 +276,14 @@ mod tests {
         assert_eq!(get_block_time_target_for_version(BlockVersion::V4), 5 * MILLIS_PER_SECOND);
         assert_eq!(get_block_time_target_for_version(BlockVersion::V5), 5 * MILLIS_PER_SECOND);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_is_contract_version_allowed_in_block_version() {
+        assert!(!is_contract_version_allowed_in_block_version(ContractVersion::V0, BlockVersion::V2));
+        assert!(is_contract_version_allowed_in_block_version(ContractVersion::V0, BlockVersion::V5));
+        assert!(!is_contract_version_allowed_in_block_version(ContractVersion::V1, BlockVersion::V5));
+        assert!(!is_contract_version_allowed_in_block_version(ContractVersion::V2, BlockVersion::V5));
+        assert!(is_contract_version_allowed_in_block_version(ContractVersion::V1, BlockVersion::V6));
+        assert!(is_contract_version_allowed_in_block_version(ContractVersion::V2, BlockVersion::V6));
+    }
+}
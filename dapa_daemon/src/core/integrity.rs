@@ -0,0 +1,136 @@
+use std::collections::BTreeSet;
+use log::{debug, info, warn};
+use dapa_common::block::TopoHeight;
+use crate::config::DAPA_ASSET;
+use super::{
+    error::BlockchainError,
+    storage::{
+        types::{IntegrityIssue, IntegrityReport},
+        Storage
+    }
+};
+
+// Scan the whole storage to find inconsistencies between the indexes
+// Chain indexes (topoheight, height) are verified here, the backend
+// specific indexes are verified by the storage itself
+// If repair is set, derivable indexes are rebuilt from their source
+pub async fn check_storage_integrity<S: Storage>(storage: &mut S, repair: bool) -> Result<IntegrityReport, BlockchainError> {
+    let mut report = IntegrityReport::default();
+
+    let top_topoheight = storage.get_top_topoheight().await?;
+    let pruned_topoheight = storage.get_pruned_topoheight().await?.unwrap_or(0);
+    info!("Checking storage integrity from topoheight {} to {}{}", pruned_topoheight, top_topoheight, if repair { " with repair" } else { "" });
+
+    let heights = check_topoheights(storage, pruned_topoheight, top_topoheight, repair, &mut report).await?;
+    check_blocks_at_height(storage, heights, repair, &mut report).await?;
+    check_supply(storage, top_topoheight, &mut report).await?;
+
+    storage.check_indexes_integrity(repair, &mut report).await?;
+
+    let repaired = report.count_repaired();
+    if repaired > 0 {
+        // Cached entries may be built on the invalid indexes
+        storage.clear_objects_cache().await?;
+        storage.flush().await?;
+    }
+
+    if report.is_clean() {
+        info!("No inconsistency found in storage");
+    } else {
+        warn!("{} inconsistencies found in storage, {} repaired", report.entries.len(), repaired);
+    }
+
+    Ok(report)
+}
+
+// Verify that each topoheight references a known block which points back to it
+// and that the block is registered at its height
+// Returns the range of heights of the ordered blocks
+async fn check_topoheights<S: Storage>(storage: &mut S, start: TopoHeight, end: TopoHeight, repair: bool, report: &mut IntegrityReport) -> Result<Option<(u64, u64)>, BlockchainError> {
+    debug!("Checking topoheights {} to {}", start, end);
+
+    let mut heights: Option<(u64, u64)> = None;
+    for topoheight in start..=end {
+        if !storage.has_hash_at_topoheight(topoheight).await? {
+            report.add(IntegrityIssue::MissingHashAtTopo { topoheight }, false);
+            continue;
+        }
+
+        let hash = storage.get_hash_at_topo_height(topoheight).await?;
+        if !storage.has_block_with_hash(&hash).await? {
+            report.add(IntegrityIssue::UnknownBlockAtTopo { topoheight, hash }, false);
+            continue;
+        }
+
+        let ordered = storage.is_block_topological_ordered(&hash).await?
+            && storage.get_topo_height_for_hash(&hash).await? == topoheight;
+        if !ordered {
+            if repair {
+                storage.set_topo_height_for_block(&hash, topoheight).await?;
+            }
+            report.add(IntegrityIssue::InvalidTopoByHash { hash: hash.clone(), topoheight }, repair);
+        }
+
+        let height = storage.get_block_header_by_hash(&hash).await?.get_height();
+        if !storage.get_blocks_at_height(height).await?.contains(&hash) {
+            if repair {
+                storage.add_block_hash_at_height(&hash, height).await?;
+            }
+            report.add(IntegrityIssue::MissingBlockAtHeight { hash, height }, repair);
+        }
+
+        heights = Some(match heights {
+            Some((min, max)) => (min.min(height), max.max(height)),
+            None => (height, height)
+        });
+    }
+
+    Ok(heights)
+}
+
+// Verify that all the blocks registered at each height exist with the same height
+async fn check_blocks_at_height<S: Storage>(storage: &mut S, heights: Option<(u64, u64)>, repair: bool, report: &mut IntegrityReport) -> Result<(), BlockchainError> {
+    let Some((min, max)) = heights else {
+        return Ok(())
+    };
+
+    // Side blocks can be above the highest ordered block
+    let max = max.max(storage.get_top_height().await?);
+    debug!("Checking blocks at heights {} to {}", min, max);
+
+    for height in min..=max {
+        let mut invalid = BTreeSet::new();
+        for hash in storage.get_blocks_at_height(height).await? {
+            let valid = storage.has_block_with_hash(&hash).await?
+                && storage.get_block_header_by_hash(&hash).await?.get_height() == height;
+
+            if !valid {
+                invalid.insert(hash);
+            }
+        }
+
+        for hash in invalid {
+            if repair {
+                storage.remove_block_hash_at_height(&hash, height).await?;
+            }
+            report.add(IntegrityIssue::InvalidBlockAtHeight { hash, height }, repair);
+        }
+    }
+
+    Ok(())
+}
+
+// The circulating supply of the native asset can't be above the emitted supply
+async fn check_supply<S: Storage>(storage: &S, topoheight: TopoHeight, report: &mut IntegrityReport) -> Result<(), BlockchainError> {
+    debug!("Checking native asset supply at topoheight {}", topoheight);
+
+    let emitted = storage.get_emitted_supply_at_topo_height(topoheight).await?;
+    if let Some((_, supply)) = storage.get_circulating_supply_for_asset_at_maximum_topoheight(&DAPA_ASSET, topoheight).await? {
+        let circulating = *supply.get();
+        if circulating > emitted {
+            report.add(IntegrityIssue::InvalidSupply { topoheight, circulating, emitted }, false);
+        }
+    }
+
+    Ok(())
+}
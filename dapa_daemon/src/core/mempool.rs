@@ -1,12 +1,15 @@
 use super::{
+    config::MempoolConfig,
     error::BlockchainError,
     state::MempoolState,
     storage::Storage,
     TxCache,
     blockchain::{ContractEnvironments, estimate_tx_fee_per_kb},
+    mempool_journal::MempoolJournal,
 };
 use std::{
-    collections::HashMap,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
     sync::Arc,
     mem,
 };
@@ -81,19 +84,128 @@ pub struct Mempool {
     // store all sender's nonce for faster finding
     caches: HashMap<PublicKey, AccountCache>,
     disable_zkp_cache: bool,
+    // Minimum fee per KB ratio in percent to replace a pending TX
+    // 0 means the replace-by-fee is disabled
+    replace_fee_ratio: u64,
+    // Maximum count of TXs allowed, 0 means no limit
+    max_txs: usize,
+    // Maximum size in bytes of all TXs, 0 means no limit
+    max_size: usize,
+    // Time to live in seconds for a pending TX, 0 means no expiration
+    tx_ttl: u64,
+    // Total size in bytes of all TXs
+    total_size: usize,
+    // Journal used to persist the pending TXs across restarts
+    // None if the journal is disabled
+    journal: Option<MempoolJournal>,
 }
 
 impl Mempool {
     // Create a new empty mempool
-    pub fn new(network: Network, disable_zkp_cache: bool) -> Self {
+    pub fn new(network: Network, disable_zkp_cache: bool, config: &MempoolConfig, journal: Option<MempoolJournal>) -> Self {
         Mempool {
             mainnet: network.is_mainnet(),
             txs: LinkedHashMap::new(),
             caches: HashMap::new(),
             disable_zkp_cache,
+            replace_fee_ratio: config.replace_fee_ratio,
+            max_txs: config.max_txs,
+            max_size: config.max_size,
+            tx_ttl: config.tx_ttl,
+            total_size: 0,
+            journal,
         }
     }
 
+    // Maximum count of TXs allowed, 0 means no limit
+    pub fn get_max_txs(&self) -> usize {
+        self.max_txs
+    }
+
+    // Maximum size in bytes of all TXs, 0 means no limit
+    pub fn get_max_size(&self) -> usize {
+        self.max_size
+    }
+
+    // Time to live in seconds for a pending TX, 0 means no expiration
+    pub fn get_tx_ttl(&self) -> u64 {
+        self.tx_ttl
+    }
+
+    // Total size in bytes of all TXs in mempool
+    pub fn get_total_size(&self) -> usize {
+        self.total_size
+    }
+
+    // Check if the mempool is above one of its limits
+    pub fn is_above_limits(&self) -> bool {
+        (self.max_txs > 0 && self.txs.len() > self.max_txs)
+            || (self.max_size > 0 && self.total_size > self.max_size)
+    }
+
+    // Insert a TX and track its size
+    fn insert_sorted_tx(&mut self, hash: Arc<Hash>, sorted_tx: SortedTx) {
+        self.total_size += sorted_tx.size;
+        if let Some(previous) = self.txs.insert(hash, sorted_tx) {
+            self.total_size -= previous.size;
+        }
+    }
+
+    // Remove a TX and untrack its size
+    fn take_sorted_tx(&mut self, hash: &Hash) -> Result<SortedTx, BlockchainError> {
+        let sorted_tx = self.txs.remove(hash)
+            .ok_or_else(|| BlockchainError::TxNotFound(hash.clone()))?;
+        self.total_size -= sorted_tx.size;
+
+        Ok(sorted_tx)
+    }
+
+    // Is the mempool journal enabled
+    pub fn has_journal(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    // Serialize all the pending TXs for the journal
+    // The returned bytes must be written once the mempool lock is released
+    // Returns None if the journal is disabled
+    pub fn serialize_journal(&self) -> Option<(MempoolJournal, Vec<u8>)> {
+        self.journal.as_ref().map(|journal| {
+            let (bytes, count) = MempoolJournal::serialize(self.txs.values().map(|sorted_tx| (&sorted_tx.tx, sorted_tx.first_seen)));
+            debug!("{} TXs serialized for mempool journal {}", count, journal.get_path());
+            (journal.clone(), bytes)
+        })
+    }
+
+    // Load all the TXs saved in the journal with their first seen timestamp
+    pub fn load_journal(&self) -> Result<Vec<(Transaction, TimestampSeconds)>, BlockchainError> {
+        match self.journal.as_ref() {
+            Some(journal) => journal.load(),
+            None => Ok(Vec::new())
+        }
+    }
+
+    // Minimum fee per KB ratio in percent to replace a pending TX
+    pub fn get_replace_fee_ratio(&self) -> u64 {
+        self.replace_fee_ratio
+    }
+
+    // Compute the minimum fee per KB required to replace the given pending TX
+    // Returns None if the replace-by-fee is disabled
+    pub fn get_replacement_min_fee_per_kb(&self, replaced: &SortedTx) -> Option<u64> {
+        Self::internal_replacement_min_fee_per_kb(replaced.get_fee_per_kb(), self.replace_fee_ratio)
+    }
+
+    fn internal_replacement_min_fee_per_kb(fee_per_kb: u64, replace_fee_ratio: u64) -> Option<u64> {
+        if replace_fee_ratio == 0 {
+            return None
+        }
+
+        let required = fee_per_kb as u128 * replace_fee_ratio as u128 / 100;
+        let required = u64::try_from(required).unwrap_or(u64::MAX);
+        // A replacement must always pay strictly more than the original
+        Some(required.max(fee_per_kb.saturating_add(1)))
+    }
+
     fn internal_estimate_fee_rates(mut fee_rates: Vec<u64>, base_fee: u64) -> FeeRatesEstimated {
         let len = fee_rates.len();
         // Top 30%
@@ -145,6 +257,11 @@ impl Mempool {
 
     // All checks are made in Blockchain before calling this function
     pub async fn add_tx<S: Storage>(&mut self, storage: &S, environments: &ContractEnvironments, stable_topoheight: TopoHeight, topoheight: TopoHeight, tx_base_fee: u64, base_height: u64, hash: Arc<Hash>, tx: Arc<Transaction>, size: usize, block_version: BlockVersion) -> Result<(), BlockchainError> {
+        self.add_tx_internal(storage, environments, stable_topoheight, topoheight, tx_base_fee, base_height, hash, tx, size, block_version, get_current_time_in_seconds()).await
+    }
+
+    // Verify and add a TX in mempool while keeping the timestamp when it was first seen
+    async fn add_tx_internal<S: Storage>(&mut self, storage: &S, environments: &ContractEnvironments, stable_topoheight: TopoHeight, topoheight: TopoHeight, tx_base_fee: u64, base_height: u64, hash: Arc<Hash>, tx: Arc<Transaction>, size: usize, block_version: BlockVersion, first_seen: TimestampSeconds) -> Result<(), BlockchainError> {
         let mut state = MempoolState::new(&self, storage, environments, stable_topoheight, topoheight, block_version, self.mainnet, tx_base_fee, base_height);
         let tx_cache = TxCache::new(storage, self, self.disable_zkp_cache);
         tx.verify(&hash, &mut state, &tx_cache).await?;
@@ -186,23 +303,219 @@ impl Mempool {
 
         let sorted_tx = SortedTx {
             size,
-            first_seen: get_current_time_in_seconds(),
+            first_seen,
             fee_per_kb,
             fee_limit_per_kb,
             tx,
         };
 
         // insert in map
-        self.txs.insert(hash, sorted_tx);
+        self.insert_sorted_tx(hash, sorted_tx);
 
         Ok(())
     }
 
+    // Replace a pending TX by a new one using the same source and nonce
+    // Fee checks are made in Blockchain before calling this function
+    // All the pending TXs of the source are re-added in nonce order:
+    // the ones depending on the replaced TX are re-verified and evicted if no longer valid
+    // If the new TX is invalid, the previous TXs are restored and the error is returned
+    // Returns the replaced TX followed by the evicted ones
+    pub async fn replace_tx<S: Storage>(&mut self, storage: &S, environments: &ContractEnvironments, stable_topoheight: TopoHeight, topoheight: TopoHeight, tx_base_fee: u64, base_height: u64, hash: Arc<Hash>, tx: Arc<Transaction>, size: usize, block_version: BlockVersion) -> Result<Vec<(Arc<Hash>, SortedTx)>, BlockchainError> {
+        let source = tx.get_source();
+        let nonce = tx.get_nonce();
+
+        let replaced_hash = self.caches.get(source)
+            .and_then(|cache| cache.has_tx_with_same_nonce(nonce))
+            .cloned()
+            .ok_or(BlockchainError::NoTxToReplace(nonce))?;
+
+        // Take all the pending TXs of this source out of the mempool
+        let mut pending = self.take_source_txs(source)?;
+
+        let index = pending.iter()
+            .position(|(tx_hash, _)| *tx_hash == replaced_hash)
+            .ok_or_else(|| BlockchainError::TxNotFound(replaced_hash.as_ref().clone()))?;
+
+        let dependents = pending.split_off(index + 1);
+        let (replaced_hash, replaced) = pending.remove(index);
+
+        let mut evicted = Vec::new();
+
+        // Previous TXs are not impacted by the replacement
+        for (tx_hash, sorted_tx) in pending {
+            if let Err(e) = self.add_tx_internal(storage, environments, stable_topoheight, topoheight, tx_base_fee, base_height, tx_hash.clone(), sorted_tx.tx.clone(), sorted_tx.size, block_version, sorted_tx.first_seen).await {
+                warn!("Error while adding back TX {} for {}: {}", tx_hash, source.as_address(self.mainnet), e);
+                evicted.push((tx_hash, sorted_tx));
+            }
+        }
+
+        if let Err(e) = self.add_tx(storage, environments, stable_topoheight, topoheight, tx_base_fee, base_height, hash.clone(), tx.clone(), size, block_version).await {
+            debug!("Replacement TX {} is invalid, restoring TX {}: {}", hash, replaced_hash, e);
+            for (tx_hash, sorted_tx) in std::iter::once((replaced_hash, replaced)).chain(dependents) {
+                if let Err(e) = self.add_tx_internal(storage, environments, stable_topoheight, topoheight, tx_base_fee, base_height, tx_hash.clone(), sorted_tx.tx.clone(), sorted_tx.size, block_version, sorted_tx.first_seen).await {
+                    warn!("Error while restoring TX {} for {}: {}", tx_hash, source.as_address(self.mainnet), e);
+                }
+            }
+
+            return Err(e)
+        }
+
+        evicted.insert(0, (replaced_hash, replaced));
+
+        // TXs with a higher nonce were built on top of the replaced TX
+        for (tx_hash, sorted_tx) in dependents {
+            if let Err(e) = self.add_tx_internal(storage, environments, stable_topoheight, topoheight, tx_base_fee, base_height, tx_hash.clone(), sorted_tx.tx.clone(), sorted_tx.size, block_version, sorted_tx.first_seen).await {
+                debug!("Evicting TX {} for {} after replacement: {}", tx_hash, source.as_address(self.mainnet), e);
+                evicted.push((tx_hash, sorted_tx));
+            }
+        }
+
+        Ok(evicted)
+    }
+
+    // Take all the pending TXs of a source out of the mempool, ordered by nonce
+    fn take_source_txs(&mut self, source: &PublicKey) -> Result<Vec<(Arc<Hash>, SortedTx)>, BlockchainError> {
+        let cache = self.caches.remove(source)
+            .ok_or_else(|| BlockchainError::AccountNotFound(source.as_address(self.mainnet)))?;
+
+        let mut txs = Vec::with_capacity(cache.txs.len());
+        for tx_hash in cache.txs {
+            let sorted_tx = self.take_sorted_tx(&tx_hash)?;
+            txs.push((tx_hash, sorted_tx));
+        }
+        txs.sort_by_key(|(_, sorted_tx)| sorted_tx.get_tx().get_nonce());
+
+        Ok(txs)
+    }
+
+    // Add back TXs of the same source in nonce order while keeping their first seen timestamp
+    // This is required to recompute the expected balances of the source
+    // Returns the TXs that couldn't be added back
+    async fn add_back_sorted_txs<S: Storage>(&mut self, storage: &S, environments: &ContractEnvironments, stable_topoheight: TopoHeight, topoheight: TopoHeight, tx_base_fee: u64, base_height: u64, block_version: BlockVersion, txs: Vec<(Arc<Hash>, SortedTx)>) -> Vec<(Arc<Hash>, SortedTx)> {
+        let mut dropped = Vec::new();
+        for (tx_hash, sorted_tx) in txs {
+            if let Err(e) = self.add_tx_internal(storage, environments, stable_topoheight, topoheight, tx_base_fee, base_height, tx_hash.clone(), sorted_tx.tx.clone(), sorted_tx.size, block_version, sorted_tx.first_seen).await {
+                debug!("Error while adding back TX {} for {}: {}", tx_hash, sorted_tx.get_tx().get_source().as_address(self.mainnet), e);
+                dropped.push((tx_hash, sorted_tx));
+            }
+        }
+
+        dropped
+    }
+
+    // Evict the TXs with the lowest fee per KB until the mempool is below its limits
+    // Only the last TX of a source can be evicted, so we never keep a TX
+    // whose previous nonce is no longer in mempool
+    // Returns all the evicted TXs
+    pub async fn evict_txs<S: Storage>(&mut self, storage: &S, environments: &ContractEnvironments, stable_topoheight: TopoHeight, topoheight: TopoHeight, tx_base_fee: u64, base_height: u64, block_version: BlockVersion) -> Result<Vec<(Arc<Hash>, SortedTx)>, BlockchainError> {
+        if !self.is_above_limits() {
+            return Ok(Vec::new())
+        }
+
+        // First, select the TXs to evict without modifying the mempool
+        let evictions = self.select_evictions()?;
+
+        let mut evicted = Vec::new();
+        for (source, count) in evictions {
+            let mut txs = self.take_source_txs(&source)?;
+            let tail = txs.split_off(txs.len() - count);
+            debug!("Evicting {} TX(s) from {} as the mempool is full", tail.len(), source.as_address(self.mainnet));
+
+            let dropped = self.add_back_sorted_txs(storage, environments, stable_topoheight, topoheight, tx_base_fee, base_height, block_version, txs).await;
+            evicted.extend(dropped);
+            evicted.extend(tail);
+        }
+
+        Ok(evicted)
+    }
+
+    // Select the TXs with the lowest fee per KB to evict until the mempool is below its limits
+    // Only the last TX of a source can be selected, then its previous one
+    // Returns the count of TXs to evict from the end of each source
+    fn select_evictions(&self) -> Result<Vec<(PublicKey, usize)>, BlockchainError> {
+        let sources: Vec<_> = self.caches.iter().collect();
+        // Count of TXs evicted from the end of each source
+        let mut counts = vec![0usize; sources.len()];
+
+        // Min-heap on the fee per KB of the last TX still kept for each source
+        let mut heap = BinaryHeap::with_capacity(sources.len());
+        for (index, (_, cache)) in sources.iter().enumerate() {
+            if let Some(hash) = cache.txs.last() {
+                heap.push(Reverse((self.get_sorted_tx(hash)?.get_fee_per_kb(), index, cache.txs.len() - 1)));
+            }
+        }
+
+        let mut count = self.txs.len();
+        let mut size = self.total_size;
+        while (self.max_txs > 0 && count > self.max_txs) || (self.max_size > 0 && size > self.max_size) {
+            let Some(Reverse((_, index, position))) = heap.pop() else {
+                break;
+            };
+
+            let cache = sources[index].1;
+            count -= 1;
+            size -= self.get_sorted_tx(&cache.txs[position])?.get_size();
+            counts[index] += 1;
+
+            // The previous TX of this source can now be evicted
+            if position > 0 {
+                let previous = &cache.txs[position - 1];
+                heap.push(Reverse((self.get_sorted_tx(previous)?.get_fee_per_kb(), index, position - 1)));
+            }
+        }
+
+        let evictions = sources.into_iter()
+            .zip(counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|((key, _), count)| (key.clone(), count))
+            .collect();
+
+        Ok(evictions)
+    }
+
+    // Delete all the TXs that are in mempool since more than the configured time to live
+    // All the TXs with a higher nonce from the same source are deleted with them
+    // Returns all the expired TXs
+    pub async fn expire_txs<S: Storage>(&mut self, storage: &S, environments: &ContractEnvironments, stable_topoheight: TopoHeight, topoheight: TopoHeight, tx_base_fee: u64, base_height: u64, block_version: BlockVersion) -> Result<Vec<(Arc<Hash>, SortedTx)>, BlockchainError> {
+        if self.tx_ttl == 0 {
+            return Ok(Vec::new())
+        }
+
+        let expirations = self.select_expirations(get_current_time_in_seconds());
+
+        let mut expired = Vec::new();
+        for (source, position) in expirations {
+            let mut txs = self.take_source_txs(&source)?;
+            let tail = txs.split_off(position);
+            debug!("{} TX(s) from {} have expired", tail.len(), source.as_address(self.mainnet));
+
+            let dropped = self.add_back_sorted_txs(storage, environments, stable_topoheight, topoheight, tx_base_fee, base_height, block_version, txs).await;
+            expired.extend(dropped);
+            expired.extend(tail);
+        }
+
+        Ok(expired)
+    }
+
+    // Select the first TX of each source that is in mempool since more than the time to live
+    // Returns the position from which the TXs of each source are expired
+    fn select_expirations(&self, now: TimestampSeconds) -> Vec<(PublicKey, usize)> {
+        self.caches.iter()
+            .filter_map(|(key, cache)| {
+                cache.txs.iter()
+                    .position(|hash| self.txs.get(hash)
+                        .is_some_and(|sorted_tx| sorted_tx.get_first_seen().saturating_add(self.tx_ttl) <= now)
+                    )
+                    .map(|position| (key.clone(), position))
+            })
+            .collect()
+    }
+
     // Remove a TX using its hash from mempool
     // This will recalculate the cache bounds
     pub fn remove_tx(&mut self, hash: &Hash) -> Result<(), BlockchainError> {
-        let tx = self.txs.remove(hash)
-            .ok_or_else(|| BlockchainError::TxNotFound(hash.clone()))?;
+        let tx = self.take_sorted_tx(hash)?;
         // remove the tx hash from sorted txs
         let key = tx.get_tx()
             .get_source();
@@ -320,6 +633,7 @@ impl Mempool {
     pub fn clear(&mut self) {
         self.txs.clear();
         self.caches.clear();
+        self.total_size = 0;
     }
 
     // Drain all txs from mempool
@@ -330,6 +644,7 @@ impl Mempool {
         }
 
         self.caches.clear();
+        self.total_size = 0;
 
         txs
     }
@@ -347,15 +662,40 @@ impl Mempool {
     ) -> Result<Vec<(Arc<Hash>, Arc<Transaction>)>, BlockchainError> {
         trace!("try add back txs");
 
-        // Group the TXs per source
-        let mut grouped = HashMap::new();
+        let now = get_current_time_in_seconds();
+        let mut entries = Vec::new();
         for hash in transactions {
             let tx = storage.get_transaction(&hash).await?
                 .into_arc();
 
+            entries.push((Arc::new(hash), tx, now));
+        }
+
+        self.add_back_txs(storage, entries.into_iter(), environments, stable_topoheight, topoheight, block_version, tx_base_fee, base_height).await
+    }
+
+    // Add back the given TXs in the mempool while keeping their first seen timestamp
+    // All TXs already in mempool from the same sources are re-verified with them
+    // Returns the TXs that couldn't be added back
+    pub async fn add_back_txs<S: Storage>(
+        &mut self,
+        storage: &S,
+        transactions: impl Iterator<Item = (Arc<Hash>, Arc<Transaction>, TimestampSeconds)>,
+        environments: &ContractEnvironments,
+        stable_topoheight: TopoHeight,
+        topoheight: TopoHeight,
+        block_version: BlockVersion,
+        tx_base_fee: u64,
+        base_height: u64,
+    ) -> Result<Vec<(Arc<Hash>, Arc<Transaction>)>, BlockchainError> {
+        trace!("add back txs");
+
+        // Group the TXs per source
+        let mut grouped = HashMap::new();
+        for (hash, tx, first_seen) in transactions {
             grouped.entry(tx.get_source().clone())
                 .or_insert_with(Vec::new)
-                .push((Arc::new(hash), tx.size(), tx));
+                .push((hash, tx.size(), tx, first_seen));
         }
 
         let mut orphaned = Vec::new();
@@ -365,23 +705,24 @@ impl Mempool {
             // append TXs that were previously in the cache
             if let Some(cache) = cache {
                 for hash in cache.txs.into_iter() {
-                    let tx = self.txs.remove(&hash)
-                        .ok_or_else(|| BlockchainError::TxNotFound(hash.as_ref().clone()))?;
-
-                    txs.push((hash, tx.size, tx.tx));
+                    let tx = self.take_sorted_tx(&hash)?;
+                    txs.push((hash, tx.size, tx.tx, tx.first_seen));
                 }
             }
 
+            // TXs must be verified in nonce order
+            txs.sort_by_key(|(_, _, tx, _)| tx.get_nonce());
+
             // Process TXs normally
             // TODO: maybe we can batch it for faster results
             // We can also mark them as verified in ZKP Cache to only check
             // dynamic
-            for (hash, size, transaction) in txs {
+            for (hash, size, transaction, first_seen) in txs {
                 if self.contains_tx(&hash) {
                     continue;
                 }
 
-                if let Err(e) = self.add_tx(storage, environments, stable_topoheight, topoheight, tx_base_fee, base_height, hash.clone(), transaction.clone(), size, block_version).await {
+                if let Err(e) = self.add_tx_internal(storage, environments, stable_topoheight, topoheight, tx_base_fee, base_height, hash.clone(), transaction.clone(), size, block_version, first_seen).await {
                     warn!("Error while adding back TX {} for {}: {}", hash, source.as_address(self.mainnet), e);
                     orphaned.push((hash, transaction));
                 }
@@ -431,8 +772,7 @@ impl Mempool {
 
                     // Delete all txs from this cache
                     for tx in cache.txs {
-                        let sorted_tx = self.txs.remove(&tx)
-                            .ok_or_else(|| BlockchainError::TxNotFound(tx.as_ref().clone()))?;
+                        let sorted_tx = self.take_sorted_tx(&tx)?;
 
                         deleted_transactions.push((tx, sorted_tx));
                     }
@@ -452,8 +792,7 @@ impl Mempool {
 
                 // Don't let ghost TXs in mempool
                 for tx in cache.txs.drain(..) {
-                    let sorted_tx = self.txs.remove(&tx)
-                        .ok_or_else(|| BlockchainError::TxNotFound(tx.as_ref().clone()))?;
+                    let sorted_tx = self.take_sorted_tx(&tx)?;
 
                     deleted_transactions.push((tx, sorted_tx));
                 }
@@ -557,8 +896,7 @@ impl Mempool {
 
                 // now delete all necessary txs
                 for tx in deleted_txs_hashes {
-                    let sorted_tx = self.txs.remove(&tx)
-                        .ok_or_else(|| BlockchainError::TxNotFound(tx.as_ref().clone()))?;
+                    let sorted_tx = self.take_sorted_tx(&tx)?;
                     debug!("Deleted TX {} for source {} with nonce {}, txs left: {}", tx, key.as_address(self.mainnet), sorted_tx.get_tx().get_nonce(), cache.txs.len());
 
                     deleted_transactions.push((tx, sorted_tx));
@@ -577,9 +915,16 @@ impl Mempool {
         Ok(deleted_transactions)
     }
 
-    pub async fn stop(&mut self) {
+    // Stop the mempool and returns the journal to write, if enabled
+    pub async fn stop(&mut self) -> Option<(MempoolJournal, Vec<u8>)> {
         info!("Stopping mempool...");
+        let journal = self.serialize_journal();
+
+        // Disable the journal to not override it with an empty mempool
+        self.journal = None;
         self.clear();
+
+        journal
     }
 }
 
@@ -702,8 +1047,149 @@ impl AccountCache {
 
 #[cfg(test)]
 mod tests {
+    use dapa_common::crypto::Hashable;
+    use super::super::mempool_journal::tests::create_txs;
     use super::*;
 
+    fn create_mempool(max_txs: usize, max_size: usize, tx_ttl: u64) -> Mempool {
+        let config = MempoolConfig {
+            max_txs,
+            max_size,
+            tx_ttl,
+            ..Default::default()
+        };
+        Mempool::new(Network::Devnet, false, &config, None)
+    }
+
+    // Insert the TXs of a source in nonce order without verifying them
+    fn insert_txs(mempool: &mut Mempool, txs: &[Arc<Transaction>], fee_per_kb: &[u64], size: usize, first_seen: &[TimestampSeconds]) -> Vec<Arc<Hash>> {
+        let mut hashes = Vec::new();
+        for ((tx, fee_per_kb), first_seen) in txs.iter().zip(fee_per_kb).zip(first_seen) {
+            let hash = Arc::new(tx.hash());
+            let nonce = tx.get_nonce();
+            match mempool.caches.get_mut(tx.get_source()) {
+                Some(cache) => cache.update(nonce, hash.clone()),
+                None => {
+                    mempool.caches.insert(tx.get_source().clone(), AccountCache {
+                        min: nonce,
+                        max: nonce,
+                        txs: IndexSet::from([hash.clone()]),
+                        balances: HashMap::new(),
+                        multisig: None
+                    });
+                }
+            };
+
+            mempool.insert_sorted_tx(hash.clone(), SortedTx {
+                tx: tx.clone(),
+                first_seen: *first_seen,
+                size,
+                fee_per_kb: *fee_per_kb,
+                fee_limit_per_kb: *fee_per_kb,
+            });
+            hashes.push(hash);
+        }
+
+        hashes
+    }
+
+    fn sorted_by_source(mut selected: Vec<(PublicKey, usize)>, first: &Arc<Transaction>) -> Vec<usize> {
+        selected.sort_by_key(|(key, _)| key != first.get_source());
+        selected.into_iter().map(|(_, value)| value).collect()
+    }
+
+    #[test]
+    fn test_select_evictions_below_limits() {
+        let mut mempool = create_mempool(3, 0, 0);
+        let txs = create_txs(3);
+        insert_txs(&mut mempool, &txs, &[FEE_PER_KB; 3], 100, &[0; 3]);
+
+        assert!(!mempool.is_above_limits());
+        assert!(mempool.select_evictions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_select_evictions_max_txs() {
+        let mut mempool = create_mempool(3, 0, 0);
+        let alice = create_txs(3);
+        let bob = create_txs(2);
+        insert_txs(&mut mempool, &alice, &[FEE_PER_KB * 5, FEE_PER_KB * 4, FEE_PER_KB], 100, &[0; 3]);
+        insert_txs(&mut mempool, &bob, &[FEE_PER_KB * 3, FEE_PER_KB * 2], 100, &[0; 2]);
+        assert!(mempool.is_above_limits());
+
+        // Lowest fees are evicted first, but only from the end of each source
+        let evictions = mempool.select_evictions().unwrap();
+        assert_eq!(sorted_by_source(evictions, &alice[0]), vec![1, 1]);
+    }
+
+    #[test]
+    fn test_select_evictions_keep_nonce_order() {
+        let mut mempool = create_mempool(3, 0, 0);
+        let alice = create_txs(3);
+        let bob = create_txs(1);
+        // Alice first TXs are the cheapest but are required by her last TX
+        insert_txs(&mut mempool, &alice, &[FEE_PER_KB, FEE_PER_KB, FEE_PER_KB * 10], 100, &[0; 3]);
+        insert_txs(&mut mempool, &bob, &[FEE_PER_KB * 5], 100, &[0]);
+
+        let evictions = mempool.select_evictions().unwrap();
+        assert_eq!(evictions.len(), 1);
+        assert_eq!(evictions[0].0, *bob[0].get_source());
+        assert_eq!(evictions[0].1, 1);
+
+        // Once her last TX is evicted, the previous ones can be too
+        mempool.max_txs = 1;
+        let evictions = mempool.select_evictions().unwrap();
+        assert_eq!(sorted_by_source(evictions, &alice[0]), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_select_evictions_max_size() {
+        let mut mempool = create_mempool(0, 1000, 0);
+        let alice = create_txs(2);
+        let bob = create_txs(2);
+        insert_txs(&mut mempool, &alice, &[FEE_PER_KB * 2, FEE_PER_KB * 2], 300, &[0; 2]);
+        insert_txs(&mut mempool, &bob, &[FEE_PER_KB * 3, FEE_PER_KB], 300, &[0; 2]);
+        assert_eq!(mempool.get_total_size(), 1200);
+        assert!(mempool.is_above_limits());
+
+        // Evicting the cheapest TX is enough to go below the size limit
+        let evictions = mempool.select_evictions().unwrap();
+        assert_eq!(evictions.len(), 1);
+        assert_eq!(evictions[0].0, *bob[0].get_source());
+        assert_eq!(evictions[0].1, 1);
+    }
+
+    #[test]
+    fn test_select_expirations() {
+        let mut mempool = create_mempool(0, 0, 60);
+        let alice = create_txs(3);
+        let bob = create_txs(2);
+        let charlie = create_txs(1);
+        insert_txs(&mut mempool, &alice, &[FEE_PER_KB; 3], 100, &[100, 150, 200]);
+        insert_txs(&mut mempool, &bob, &[FEE_PER_KB; 2], 100, &[140, 100]);
+        insert_txs(&mut mempool, &charlie, &[FEE_PER_KB], 100, &[200]);
+
+        // Nothing has expired yet
+        assert!(mempool.select_expirations(159).is_empty());
+
+        // TTL is reached for the first TX of alice and the second TX of bob
+        // All the next TXs of alice are expired with it
+        let expirations = mempool.select_expirations(160);
+        assert_eq!(expirations.len(), 2);
+        for (key, position) in expirations {
+            if key == *alice[0].get_source() {
+                assert_eq!(position, 0);
+            } else {
+                assert_eq!(key, *bob[0].get_source());
+                assert_eq!(position, 1);
+            }
+        }
+
+        // Expiration is disabled with a TTL of 0
+        mempool.tx_ttl = 0;
+        assert!(mempool.select_expirations(u64::MAX).is_empty());
+    }
+
     #[test]
     fn test_estimated_fee_rates() {
         // Let say we have the following TXs:
@@ -741,4 +1227,40 @@ mod tests {
         assert_eq!(estimated.low, FEE_PER_KB * 2);
         assert_eq!(estimated.default, FEE_PER_KB);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_replacement_min_fee_per_kb() {
+        // Replace-by-fee is disabled
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(FEE_PER_KB, 0), None);
+
+        // Default ratio requires a 10% bump
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(10000, 110), Some(11000));
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(FEE_PER_KB, 150), Some(FEE_PER_KB * 3 / 2));
+    }
+
+    #[test]
+    fn test_replacement_min_fee_per_kb_bump_threshold() {
+        // Below the threshold, the 10% bump rounds down to nothing
+        // and the replacement must still pay strictly more than the original
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(0, 110), Some(1));
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(5, 110), Some(6));
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(9, 110), Some(10));
+
+        // At the threshold, both rules give the same value
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(10, 110), Some(11));
+
+        // Above the threshold, the ratio is used
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(20, 110), Some(22));
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(1000, 110), Some(1100));
+
+        // A ratio without any bump still requires a higher fee
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(10000, 100), Some(10001));
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(10000, 50), Some(10001));
+    }
+
+    #[test]
+    fn test_replacement_min_fee_per_kb_overflow() {
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(u64::MAX, 110), Some(u64::MAX));
+        assert_eq!(super::Mempool::internal_replacement_min_fee_per_kb(u64::MAX / 2, 300), Some(u64::MAX));
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    fs,
+    path::Path,
+    sync::Arc
+};
+use log::{debug, trace, warn};
+use tokio::task::spawn_blocking;
+use dapa_common::{
+    crypto::{hash, Hash},
+    serializer::{Reader, ReaderError, Serializer, Writer},
+    time::TimestampSeconds,
+    transaction::Transaction
+};
+use super::error::BlockchainError;
+
+// Magic bytes at the start of the journal
+const JOURNAL_MAGIC: &[u8; 4] = b"DMPJ";
+// Version of the journal format
+const JOURNAL_VERSION: u8 = 1;
+
+// Journal used to persist the pending TXs of the mempool on disk
+// so they are not lost across a daemon restart
+// The journal starts with [magic][version u8]
+// Each entry is then written as [size u32][checksum][first_seen u64][transaction]
+// with the checksum being the hash of the [first_seen u64][transaction] bytes
+#[derive(Clone)]
+pub struct MempoolJournal {
+    path: String
+}
+
+impl MempoolJournal {
+    pub fn new(path: String) -> Self {
+        Self {
+            path
+        }
+    }
+
+    // Path of the journal file
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    // Serialize all the entries in the journal format
+    // Returns the bytes to write with the count of entries
+    pub fn serialize<'a>(entries: impl Iterator<Item = (&'a Arc<Transaction>, TimestampSeconds)>) -> (Vec<u8>, usize) {
+        let mut bytes = Vec::new();
+        let mut writer = Writer::new(&mut bytes);
+        writer.write_bytes(JOURNAL_MAGIC);
+        writer.write_u8(JOURNAL_VERSION);
+
+        let mut count = 0;
+        for (tx, first_seen) in entries {
+            let mut entry = Vec::new();
+            let mut entry_writer = Writer::new(&mut entry);
+            entry_writer.write_u64(first_seen);
+            tx.write(&mut entry_writer);
+
+            writer.write_u32(entry.len() as u32);
+            writer.write_hash(&hash(&entry));
+            writer.write_bytes(&entry);
+            count += 1;
+        }
+
+        (bytes, count)
+    }
+
+    // Write the serialized entries to the journal
+    // Entries are first written in a temporary file that is renamed
+    // to not corrupt the previous journal in case of a crash during the write
+    pub async fn write(&self, bytes: Vec<u8>) -> Result<(), BlockchainError> {
+        trace!("saving mempool journal to {}", self.path);
+        let path = self.path.clone();
+        spawn_blocking(move || {
+            let tmp = format!("{}.tmp", path);
+            fs::write(&tmp, &bytes)?;
+            fs::rename(&tmp, &path)
+        }).await??;
+
+        Ok(())
+    }
+
+    // Read all the entries from the journal
+    // Returns an empty list if no journal was found or if its format is unknown
+    // Corrupted entries are skipped
+    pub fn load(&self) -> Result<Vec<(Transaction, TimestampSeconds)>, BlockchainError> {
+        trace!("loading mempool journal from {}", self.path);
+        if !Path::new(&self.path).exists() {
+            debug!("No mempool journal found at {}", self.path);
+            return Ok(Vec::new())
+        }
+
+        let bytes = fs::read(&self.path)?;
+        Ok(Self::deserialize(&bytes, &self.path))
+    }
+
+    // Read all the valid entries from the journal bytes
+    fn deserialize(bytes: &[u8], path: &str) -> Vec<(Transaction, TimestampSeconds)> {
+        let mut reader = Reader::new(bytes);
+        let magic: Result<[u8; 4], _> = reader.read_bytes(JOURNAL_MAGIC.len());
+        if !magic.is_ok_and(|magic| magic == *JOURNAL_MAGIC) {
+            warn!("Mempool journal {} has an unknown format, ignoring it", path);
+            return Vec::new()
+        }
+
+        match reader.read_u8() {
+            Ok(JOURNAL_VERSION) => {},
+            Ok(version) => {
+                warn!("Mempool journal {} has an unsupported version {}, ignoring it", path, version);
+                return Vec::new()
+            },
+            Err(e) => {
+                warn!("Error while reading the mempool journal {} version: {}", path, e);
+                return Vec::new()
+            }
+        };
+
+        let mut entries = Vec::new();
+        while reader.size() > 0 {
+            let (size, checksum) = match Self::read_entry_header(&mut reader) {
+                Ok(header) => header,
+                Err(e) => {
+                    warn!("Mempool journal {} is truncated, stopping at entry #{}: {}", path, entries.len(), e);
+                    break;
+                }
+            };
+
+            let Ok(entry) = reader.read_bytes_ref(size) else {
+                warn!("Mempool journal {} is truncated, stopping at entry #{}", path, entries.len());
+                break;
+            };
+
+            if hash(entry) != checksum {
+                warn!("Invalid checksum for an entry of mempool journal {}, skipping it", path);
+                continue;
+            }
+
+            match Self::read_entry(entry) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => warn!("Invalid entry in mempool journal {}, skipping it: {}", path, e)
+            };
+        }
+
+        entries
+    }
+
+    fn read_entry_header(reader: &mut Reader) -> Result<(usize, Hash), ReaderError> {
+        let size = reader.read_u32()? as usize;
+        let checksum = reader.read_hash()?;
+        Ok((size, checksum))
+    }
+
+    fn read_entry(bytes: &[u8]) -> Result<(Transaction, TimestampSeconds), ReaderError> {
+        let mut reader = Reader::new(bytes);
+        let first_seen = reader.read_u64()?;
+        let tx = Transaction::read(&mut reader)?;
+        if reader.size() > 0 {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        Ok((tx, first_seen))
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::collections::HashMap;
+    use tempdir::TempDir;
+    use dapa_common::{
+        account::{CiphertextCache, Nonce},
+        config::{COIN_VALUE, DAPA_ASSET},
+        crypto::{elgamal::Ciphertext, Hashable, KeyPair, PublicKey},
+        transaction::{
+            builder::{AccountState, FeeBuilder, FeeHelper, TransactionBuilder, TransactionTypeBuilder},
+            BurnPayload,
+            Reference,
+            TxVersion
+        }
+    };
+    use super::*;
+
+    struct State {
+        keypair: KeyPair,
+        balances: HashMap<Hash, (u64, CiphertextCache)>,
+        nonce: Nonce
+    }
+
+    impl FeeHelper for State {
+        type Error = anyhow::Error;
+
+        fn account_exists(&self, _: &PublicKey) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+    }
+
+    impl AccountState for State {
+        fn is_mainnet(&self) -> bool {
+            false
+        }
+
+        fn get_account_balance(&self, asset: &Hash) -> Result<u64, Self::Error> {
+            Ok(self.balances[asset].0)
+        }
+
+        fn get_account_ciphertext(&self, asset: &Hash) -> Result<CiphertextCache, Self::Error> {
+            Ok(self.balances[asset].1.clone())
+        }
+
+        fn get_reference(&self) -> Reference {
+            Reference {
+                topoheight: 0,
+                hash: Hash::zero()
+            }
+        }
+
+        fn update_account_balance(&mut self, asset: &Hash, balance: u64, ciphertext: Ciphertext) -> Result<(), Self::Error> {
+            self.balances.insert(asset.clone(), (balance, CiphertextCache::Decompressed(None, ciphertext)));
+            Ok(())
+        }
+
+        fn get_nonce(&self) -> Result<Nonce, Self::Error> {
+            Ok(self.nonce)
+        }
+
+        fn update_nonce(&mut self, new_nonce: Nonce) -> Result<(), Self::Error> {
+            self.nonce = new_nonce;
+            Ok(())
+        }
+    }
+
+    // Build burn TXs with increasing nonces from a new account
+    pub(in crate::core) fn create_txs(count: u64) -> Vec<Arc<Transaction>> {
+        let keypair = KeyPair::new();
+        let balance = 100 * COIN_VALUE;
+        let ciphertext = keypair.get_public_key().encrypt(balance);
+        let mut state = State {
+            balances: HashMap::from([(DAPA_ASSET, (balance, CiphertextCache::Decompressed(None, ciphertext)))]),
+            keypair,
+            nonce: 0
+        };
+
+        (0..count).map(|_| {
+            let data = TransactionTypeBuilder::Burn(BurnPayload {
+                asset: DAPA_ASSET,
+                amount: 1
+            });
+            let builder = TransactionBuilder::new(TxVersion::V2, state.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+            let keypair = state.keypair.clone();
+            Arc::new(builder.build(&mut state, &keypair).unwrap())
+        }).collect()
+    }
+
+    #[tokio::test]
+    async fn test_journal_save_load() {
+        let dir = TempDir::new("mempool-journal").unwrap();
+        let journal = MempoolJournal::new(dir.path().join("mempool.bin").to_string_lossy().into_owned());
+
+        // No journal yet
+        assert!(journal.load().unwrap().is_empty());
+
+        let txs = create_txs(3);
+        let (bytes, count) = MempoolJournal::serialize(txs.iter().zip(100..).map(|(tx, first_seen)| (tx, first_seen)));
+        assert_eq!(count, 3);
+        journal.write(bytes).await.unwrap();
+
+        let entries = journal.load().unwrap();
+        assert_eq!(entries.len(), 3);
+        for ((tx, first_seen), (expected, expected_first_seen)) in entries.iter().zip(txs.iter().zip(100..)) {
+            assert_eq!(tx.hash(), expected.hash());
+            assert_eq!(*first_seen, expected_first_seen);
+        }
+    }
+
+    #[test]
+    fn test_journal_skip_corrupted_entries() {
+        let txs = create_txs(3);
+        let (mut bytes, _) = MempoolJournal::serialize(txs.iter().map(|tx| (tx, 0)));
+
+        // Corrupt the last byte of the first entry
+        let header = JOURNAL_MAGIC.len() + 1;
+        let size = u32::from_be_bytes(bytes[header..header + 4].try_into().unwrap()) as usize;
+        let end = header + 4 + 32 + size;
+        bytes[end - 1] ^= 0xFF;
+
+        let entries = MempoolJournal::deserialize(&bytes, "test");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0.hash(), txs[1].hash());
+        assert_eq!(entries[1].0.hash(), txs[2].hash());
+
+        // A truncated entry is dropped
+        let entries = MempoolJournal::deserialize(&bytes[..bytes.len() - 1], "test");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0.hash(), txs[1].hash());
+    }
+
+    #[test]
+    fn test_journal_unknown_format() {
+        let txs = create_txs(1);
+        let (mut bytes, _) = MempoolJournal::serialize(txs.iter().map(|tx| (tx, 0)));
+
+        // Unsupported version
+        bytes[JOURNAL_MAGIC.len()] = JOURNAL_VERSION + 1;
+        assert!(MempoolJournal::deserialize(&bytes, "test").is_empty());
+
+        // Previous format without any header
+        assert!(MempoolJournal::deserialize(&bytes[JOURNAL_MAGIC.len() + 1..], "test").is_empty());
+        assert!(MempoolJournal::deserialize(&[], "test").is_empty());
+    }
+}
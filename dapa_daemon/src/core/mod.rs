@@ -4,6 +4,7 @@ mod block_size_ema;
 pub mod config;
 pub mod blockchain;
 pub mod mempool;
+pub mod mempool_journal;
 pub mod error;
 pub mod blockdag;
 pub mod storage;
@@ -13,6 +14,9 @@ pub mod nonce_checker;
 pub mod tx_selector;
 pub mod state;
 pub mod merkle;
+pub mod chain_snapshot;
+pub mod backup;
+pub mod integrity;
 
 pub mod hard_fork;
 
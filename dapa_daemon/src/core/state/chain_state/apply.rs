@@ -19,6 +19,7 @@ use dapa_common::{
         EventCallbackRegistration,
         ChainStateChanges,
         ContractCache,
+        ContractEventKind,
         ContractEventTracker,
         ContractLog,
         ContractMetadata,
@@ -38,6 +39,7 @@ use dapa_common::{
         MultiSigPayload,
         Reference,
         Transaction,
+        TransactionType,
         verify::{
             BlockchainApplyState,
             BlockchainContractState,
@@ -54,7 +56,7 @@ use crate::core::{
     state::{chain_state::Account, verify_fee},
     error::BlockchainError,
     storage::{
-        types::TopoHeightMetadata,
+        types::{AccountTransactionEntry, TopoHeightMetadata},
         Storage,
         VersionedContractModule,
         VersionedContractBalance,
@@ -78,6 +80,8 @@ struct ContractManager<'b> {
     executions: ExecutionsChanges,
     // All events callback to process
     events: VecDeque<CallbackEvent>,
+    // All events emitted in this block, kept for the archival index
+    emitted_events: Vec<CallbackEvent>,
     // all events registrations that must be stored
     events_listeners: HashMap<(Hash, u64), Vec<(Hash, EventCallbackRegistration)>>,
     // all events already processed from storage
@@ -106,6 +110,8 @@ pub struct FinalizedChainState<'b> {
     contract_manager: ContractManager<'b>,
     // current block hash
     block_hash: &'b Hash,
+    // current block
+    block: &'b Block,
     // Transactions links to store: tx hash -> (blocks linked, executed in, contract)
     transactions_links: HashMap<&'b Hash, (IndexSet<&'b Hash>, Option<&'b Hash>, Option<&'b Hash>)>,
     // Balances of the receiver accounts
@@ -127,9 +133,18 @@ impl<'a> FinalizedChainState<'a> {
         storage: &mut S,
         past_emitted_supply: u64,
         block_reward: u64,
+        index_account_transactions: bool,
+        index_contract_events: bool,
     ) -> Result<(), BlockchainError> {
         trace!("apply finalized changes");
 
+        // Build the archival index entries before consuming the changes
+        let account_transactions = if index_account_transactions {
+            self.collect_account_transactions()
+        } else {
+            HashMap::new()
+        };
+
         // Set the topoheight for the block
         storage.set_topo_height_for_block(&self.block_hash, self.topoheight).await?;
 
@@ -289,6 +304,8 @@ impl<'a> FinalizedChainState<'a> {
         }
 
         debug!("Storing contract storage changes");
+        // Next index to use per contract event id for the archival index
+        let mut events_indexes: HashMap<(Hash, u64), u32> = HashMap::new();
         // Apply all the contract storage changes
         for (contract, cache) in self.contract_manager.caches {
             // Apply all storage changes
@@ -309,6 +326,27 @@ impl<'a> FinalizedChainState<'a> {
                     }
                 }
             }
+
+            if index_contract_events {
+                for (event_id, events) in cache.events.iter() {
+                    trace!("indexing {} events {} of contract {} at topoheight {}", events.len(), event_id, contract, self.topoheight);
+                    let index = events_indexes.entry((contract.clone(), *event_id)).or_insert(0);
+                    for data in events.iter() {
+                        storage.add_contract_event(&contract, *event_id, self.topoheight, *index, ContractEventKind::Rpc, data).await?;
+                        *index += 1;
+                    }
+                }
+            }
+        }
+
+        if index_contract_events {
+            for event in self.contract_manager.emitted_events {
+                trace!("indexing emitted event {} of contract {} at topoheight {}", event.event_id, event.contract, self.topoheight);
+                let index = events_indexes.entry((event.contract.clone(), event.event_id)).or_insert(0);
+                let data = ValueCell::Object(event.params);
+                storage.add_contract_event(&event.contract, event.event_id, self.topoheight, *index, ContractEventKind::Callback, &data).await?;
+                *index += 1;
+            }
         }
 
         debug!("applying external transfers");
@@ -421,6 +459,14 @@ impl<'a> FinalizedChainState<'a> {
             }
         }
 
+        // Index the transactions per account
+        // All accounts are registered at this point
+        debug!("indexing {} account transactions", account_transactions.len());
+        for ((key, tx_hash), entry) in account_transactions {
+            trace!("indexing tx {} for {} at topoheight {}", tx_hash, key.as_address(storage.is_mainnet()), self.topoheight);
+            storage.add_account_transaction(&key, self.topoheight, &tx_hash, &entry).await?;
+        }
+
         // Finally, update the topoheight metadata
         debug!("updating topoheight metadata to {}", self.topoheight);
         let emitted_supply = past_emitted_supply + block_reward;
@@ -435,6 +481,77 @@ impl<'a> FinalizedChainState<'a> {
 
         Ok(())
     }
+
+    // Collect all the accounts touched by each transaction executed in this block
+    // as sender, transfer receiver or contract transfer target
+    fn collect_account_transactions(&self) -> HashMap<(PublicKey, Hash), AccountTransactionEntry> {
+        let mut entries: HashMap<(PublicKey, Hash), AccountTransactionEntry> = HashMap::new();
+        let mut add_entry = |key: &PublicKey, tx_hash: &Hash, entry: AccountTransactionEntry| {
+            match entries.entry((key.clone(), tx_hash.clone())) {
+                Entry::Occupied(mut o) => o.get_mut().merge(entry),
+                Entry::Vacant(e) => {
+                    e.insert(entry);
+                }
+            }
+        };
+
+        for (tx, tx_hash) in self.block.get_transactions().iter().zip(self.block.get_txs_hashes()) {
+            // Only index the TXs executed in this block
+            let executed = self.transactions_links.get(tx_hash)
+                .is_some_and(|(_, executed_in, _)| executed_in.is_some());
+            if !executed {
+                continue;
+            }
+
+            let assets = match tx.get_data() {
+                TransactionType::Transfers(transfers) => {
+                    for transfer in transfers {
+                        add_entry(transfer.get_destination(), tx_hash, AccountTransactionEntry {
+                            outgoing: false,
+                            incoming: true,
+                            assets: IndexSet::from([transfer.get_asset().clone()]),
+                        });
+                    }
+
+                    transfers.iter()
+                        .map(|transfer| transfer.get_asset().clone())
+                        .collect()
+                },
+                TransactionType::Burn(payload) => IndexSet::from([payload.asset.clone()]),
+                TransactionType::MultiSig(_) => IndexSet::new(),
+                TransactionType::InvokeContract(payload) => payload.deposits.keys().cloned().collect(),
+                TransactionType::DeployContract(payload) => payload.invoke.as_ref()
+                    .map(|invoke| invoke.deposits.keys().cloned().collect())
+                    .unwrap_or_default(),
+            };
+
+            add_entry(tx.get_source(), tx_hash, AccountTransactionEntry {
+                outgoing: true,
+                incoming: false,
+                assets,
+            });
+        }
+
+        // Contract transfers made during a TX call
+        // Scheduled executions are not linked to a TX and are skipped
+        for ((caller, _), transfers) in self.contract_manager.tracker.contracts_transfers.iter() {
+            let executed = self.transactions_links.get(caller)
+                .is_some_and(|(_, executed_in, _)| executed_in.is_some());
+            if !executed {
+                continue;
+            }
+
+            for (key, assets) in transfers {
+                add_entry(key, caller, AccountTransactionEntry {
+                    outgoing: false,
+                    incoming: true,
+                    assets: assets.keys().cloned().collect(),
+                });
+            }
+        }
+
+        entries
+    }
 }
 
 #[async_trait]
@@ -547,6 +664,18 @@ impl<'s, 'b, S: Storage> BlockchainVerificationState<'b, BlockchainError> for Ap
         self.inner.get_multisig_state(account).await
     }
 
+    async fn is_asset_paused(
+        &mut self,
+        asset: &'b Hash
+    ) -> Result<bool, BlockchainError> {
+        // Asset may have been updated by a contract in this block
+        if let Some(changes) = self.contract_manager.assets.get(asset) {
+            return Ok(changes.as_ref().is_some_and(|changes| changes.data.1.is_paused()))
+        }
+
+        self.inner.is_asset_paused(asset).await
+    }
+
     async fn get_environment(&mut self, version: ContractVersion) -> Result<&Environment<ContractMetadata>, BlockchainError> {
         self.inner.get_environment(version).await
     }
@@ -718,6 +847,7 @@ impl<'s, 'b, S: Storage> BlockchainContractState<'b, S, BlockchainError> for App
             },
             permission,
             gas_fee_allowance: 0,
+            read_only: false,
             environments: Cow::Borrowed(self.inner.environments),
             loaded_modules: Default::default(),
         };
@@ -798,6 +928,17 @@ impl<'s, 'b, S: Storage> BlockchainContractState<'b, S, BlockchainError> for App
             };
         }
 
+        // Replace the modules upgraded, previous ones are kept as older versions
+        for (contract, module) in changes.modules {
+            debug!("Upgrading module of contract {}", contract);
+            let (state, current) = self.inner.internal_get_versioned_contract(Cow::Owned(contract.clone())).await?
+                .as_mut()
+                .ok_or_else(|| BlockchainError::ContractNotFound(contract))?;
+
+            state.mark_updated();
+            *current = Some(Cow::Owned(module));
+        }
+
         self.contract_manager.tracker = changes.tracker;
         self.contract_manager.assets = changes.assets;
 
@@ -808,6 +949,7 @@ impl<'s, 'b, S: Storage> BlockchainContractState<'b, S, BlockchainError> for App
         self.contract_manager.executions.at_topoheight.append(&mut executions_changes.at_topoheight);
         self.contract_manager.executions.block_end.append(&mut executions_changes.block_end);
 
+        self.contract_manager.emitted_events.extend(changes.events.iter().cloned());
         self.contract_manager.events.extend(changes.events);
 
         for (key, mut listeners) in changes.events_listeners {
@@ -937,6 +1079,12 @@ impl<'s, 'b, S: Storage> ApplicableChainState<'s, 'b, S> {
         self.total_fees
     }
 
+    // total fees burned
+    #[inline]
+    pub fn get_total_fees_burned(&self) -> u64 {
+        self.total_fees_burned
+    }
+
     // Load the asset changes for supply changes
     pub async fn get_asset_changes_for(&mut self, asset: &Hash, default: bool) -> Result<&mut AssetChanges, BlockchainError> {
         match self.contract_manager.assets.entry(asset.clone()) {
@@ -1170,6 +1318,7 @@ impl<'s, 'b, S: Storage> ApplicableChainState<'s, 'b, S> {
 
         Ok(FinalizedChainState {
             block_hash: self.block_hash,
+            block: self.block,
             contract_manager: self.contract_manager,
             total_fees: self.total_fees + self.inner.gas_fee,
             total_fees_burned: self.total_fees_burned,
@@ -1182,3 +1331,49 @@ impl<'s, 'b, S: Storage> ApplicableChainState<'s, 'b, S> {
         })
     }
 }
+
+#[cfg(all(test, feature = "rocksdb"))]
+mod tests {
+    use dapa_common::{
+        asset::{AssetData, AssetOwner, MaxSupplyMode},
+        block::{BlockHeader, EXTRA_NONCE_SIZE},
+        crypto::KeyPair,
+        network::Network
+    };
+    use crate::core::storage::{AssetProvider, RocksStorage};
+    use super::*;
+
+    #[tokio::test]
+    async fn test_asset_paused_earlier_in_block() {
+        let asset = Hash::new([1u8; 32]);
+        let data = AssetData::new(8, "Regulated".to_owned(), "REG".to_owned(), MaxSupplyMode::None, AssetOwner::Creator {
+            contract: Hash::new([2u8; 32]),
+            id: 0
+        });
+
+        let mut storage = RocksStorage::new_in_memory(Network::Devnet);
+        storage.add_asset(&asset, 0, VersionedAssetData::new(data.clone(), None)).await.unwrap();
+
+        let environments = ContractEnvironments::new();
+        let header = BlockHeader::new(BlockVersion::V3, 1, 0, IndexSet::new(), [0u8; EXTRA_NONCE_SIZE], KeyPair::new().get_public_key().compress(), IndexSet::new());
+        let block = Block::new(header, Vec::new());
+        let block_hash = Hash::zero();
+
+        let mut state = ApplicableChainState::new(&storage, &environments, 0, 1, BlockVersion::V3, &block_hash, &block, FEE_PER_KB, 0);
+        assert!(!state.is_asset_paused(&asset).await.unwrap());
+
+        // A contract paused it in a previous TX of the block
+        let mut paused = data;
+        paused.set_paused(true);
+
+        let mut changes = ChainStateChanges::default();
+        changes.assets.insert(asset.clone(), Some(AssetChanges {
+            data: (VersionedState::Updated(0), paused),
+            circulating_supply: (VersionedState::FetchedAt(0), 0)
+        }));
+        state.merge_contract_changes(changes, ExecutionsChanges::default()).await.unwrap();
+
+        // Stored version is not paused but the block changes are used first
+        assert!(state.is_asset_paused(&asset).await.unwrap());
+    }
+}
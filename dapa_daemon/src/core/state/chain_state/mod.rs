@@ -417,6 +417,15 @@ impl<'s, 'b, S: Storage> BlockchainVerificationState<'b, BlockchainError> for Ch
         Ok(account.multisig.as_ref().and_then(|(_, multisig)| multisig.as_ref()))
     }
 
+    /// Check if the transfers of the asset are paused by its owner
+    async fn is_asset_paused(
+        &mut self,
+        asset: &'b Hash
+    ) -> Result<bool, BlockchainError> {
+        Ok(self.storage.get_asset_at_maximum_topoheight(asset, self.topoheight).await?
+            .is_some_and(|(_, data)| data.get().is_paused()))
+    }
+
     /// Get the contract environments
     async fn get_environment(&mut self, version: ContractVersion) -> Result<&Environment<ContractMetadata>, BlockchainError> {
         self.environments.get(&version)
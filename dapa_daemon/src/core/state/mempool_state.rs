@@ -305,6 +305,15 @@ impl<'a, S: Storage> BlockchainVerificationState<'a, BlockchainError> for Mempoo
             .ok_or_else(|| BlockchainError::AccountNotFound(account.as_address(self.storage.is_mainnet())))
     }
 
+    /// Check if the transfers of the asset are paused by its owner
+    async fn is_asset_paused(
+        &mut self,
+        asset: &'a Hash
+    ) -> Result<bool, BlockchainError> {
+        Ok(self.storage.get_asset_at_maximum_topoheight(asset, self.topoheight).await?
+            .is_some_and(|(_, data)| data.get().is_paused()))
+    }
+
     /// Get the contract environments
     async fn get_environment(&mut self, version: ContractVersion) -> Result<&Environment<ContractMetadata>, BlockchainError> {
         self.environments.get(&version)
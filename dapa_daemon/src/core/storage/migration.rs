@@ -0,0 +1,491 @@
+use futures::{StreamExt, TryStreamExt};
+use log::{debug, info, trace};
+use dapa_common::{
+    block::TopoHeight,
+    crypto::{Hash, PublicKey}
+};
+use crate::core::error::BlockchainError;
+use super::Storage;
+
+// Flush the target storage every N blocks migrated
+const FLUSH_EVERY_N_BLOCKS: u64 = 1000;
+// Items read at once from the source iterators
+const MIGRATION_CHUNK_SIZE: usize = 1024;
+
+// Copy all the entries of a versioned column from the source into the target
+// Each chunk is collected before writing as the target can't be
+// borrowed mutably while the source iterator is alive in the same expression
+macro_rules! migrate_versioned {
+    ($name: expr, $iter: expr, |$entry: pat_param| $set: expr) => {{
+        info!("Migrating versioned {}", $name);
+        let mut versions = $iter.await?;
+        let mut count = 0;
+        loop {
+            let chunk = versions.by_ref()
+                .take(MIGRATION_CHUNK_SIZE)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if chunk.is_empty() {
+                break;
+            }
+
+            count += chunk.len();
+            for $entry in chunk {
+                $set.await?;
+            }
+        }
+        debug!("{} versioned {} migrated", count, $name);
+    }};
+}
+
+// Summary of a storage migration
+#[derive(Debug)]
+pub struct StorageMigrationStats {
+    pub top_topoheight: TopoHeight,
+    pub top_hash: Hash,
+    pub blocks: u64,
+    pub transactions: u64,
+    pub accounts: u64,
+    pub assets: u64,
+    pub contracts: u64
+}
+
+// Copy the whole chain from a storage to another one, even if they are from a different backend
+// The source is never written and the target must be empty
+// Versioned data is copied column by column from the raw entries stored in the source,
+// so every version is kept, including the deleted values and the event callbacks
+// The target is verified against the source once done
+pub async fn migrate_storage<S: Storage, T: Storage>(source: &S, target: &mut T) -> Result<StorageMigrationStats, BlockchainError> {
+    if target.has_blocks().await? {
+        return Err(BlockchainError::MigrationTargetNotEmpty)
+    }
+
+    if !target.has_network()? {
+        target.set_network(&source.get_network()?)?;
+    }
+
+    let top_topoheight = source.get_top_topoheight().await?;
+    let top_height = source.get_top_height().await?;
+    let pruned_topoheight = source.get_pruned_topoheight().await?;
+    info!("Migrating storage until topoheight {} (height {}, pruned topoheight: {:?})", top_topoheight, top_height, pruned_topoheight);
+
+    migrate_assets(source, target).await?;
+    migrate_accounts(source, target, top_topoheight).await?;
+    migrate_contracts(source, target, top_topoheight).await?;
+    migrate_blocks(source, target, top_height).await?;
+    migrate_dag_order(source, target, pruned_topoheight.unwrap_or(0), top_topoheight).await?;
+
+    target.set_pruned_topoheight(pruned_topoheight).await?;
+    target.set_top_topoheight(top_topoheight).await?;
+    target.set_top_height(top_height).await?;
+    target.store_tips(&source.get_tips().await?).await?;
+    target.flush().await?;
+
+    verify_migration(source, target).await
+}
+
+// Assets are migrated first as the balances are linked to them
+async fn migrate_assets<S: Storage, T: Storage>(source: &S, target: &mut T) -> Result<(), BlockchainError> {
+    migrate_versioned!("assets", source.iter_versioned_assets(), |(topoheight, asset, version)| {
+        target.add_asset(&asset, topoheight, version)
+    });
+
+    migrate_versioned!("assets supply", source.iter_versioned_assets_supply(), |(topoheight, asset, version)| {
+        target.set_last_circulating_supply_for_asset(&asset, topoheight, &version)
+    });
+
+    Ok(())
+}
+
+async fn migrate_accounts<S: Storage, T: Storage>(source: &S, target: &mut T, top_topoheight: TopoHeight) -> Result<(), BlockchainError> {
+    info!("Migrating accounts");
+    let mut keys = source.get_registered_keys(None, None).await?;
+    let mut count = 0;
+    loop {
+        let chunk = keys.by_ref()
+            .take(MIGRATION_CHUNK_SIZE)
+            .collect::<Result<Vec<PublicKey>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for key in chunk.iter() {
+            migrate_account(source, target, key, top_topoheight).await?;
+        }
+
+        count += chunk.len();
+        debug!("{} accounts migrated", count);
+    }
+
+    // Accounts are registered, their versions can be linked to them
+    migrate_versioned!("nonces", source.iter_versioned_nonces(), |(topoheight, key, version)| {
+        target.set_last_nonce_to(&key, topoheight, &version)
+    });
+
+    migrate_versioned!("multisigs", source.iter_versioned_multisigs(), |(topoheight, key, version)| {
+        target.set_last_multisig_to(&key, topoheight, version)
+    });
+
+    migrate_versioned!("balances", source.iter_versioned_balances(), |(topoheight, key, asset, version)| {
+        async {
+            target.set_balance_at_topoheight(&asset, topoheight, &key, &version).await?;
+            // Versions are ordered by topoheight for the same balance
+            // so the pointer ends on the latest one
+            target.set_last_topoheight_for_balance(&key, &asset, topoheight)
+        }
+    });
+
+    Ok(())
+}
+
+// Migrate the registration and the transactions index of an account
+async fn migrate_account<S: Storage, T: Storage>(source: &S, target: &mut T, key: &PublicKey, top_topoheight: TopoHeight) -> Result<(), BlockchainError> {
+    let registration_topoheight = source.get_account_registration_topoheight(key).await?;
+    target.set_account_registration_topoheight(key, registration_topoheight).await?;
+
+    // Archival index of the transactions, empty if it was never enabled
+    let mut transactions = source.get_account_transactions(key, 0, top_topoheight, None).await?;
+    loop {
+        let chunk = transactions.by_ref()
+            .take(MIGRATION_CHUNK_SIZE)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for (topoheight, tx_hash, entry) in chunk {
+            target.add_account_transaction(key, topoheight, &tx_hash, &entry).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn migrate_contracts<S: Storage, T: Storage>(source: &S, target: &mut T, top_topoheight: TopoHeight) -> Result<(), BlockchainError> {
+    // Modules are migrated first as they register the contracts
+    migrate_versioned!("contracts", source.iter_versioned_contracts(), |(topoheight, contract, version)| {
+        target.set_last_contract_to(&contract, topoheight, &version)
+    });
+
+    info!("Migrating contracts");
+    let mut contracts = source.get_contracts(0, top_topoheight).await?;
+    loop {
+        let chunk = contracts.by_ref()
+            .take(MIGRATION_CHUNK_SIZE)
+            .collect::<Result<Vec<Hash>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for contract in chunk.iter() {
+            migrate_contract(source, target, contract, top_topoheight).await?;
+        }
+    }
+
+    migrate_versioned!("contracts balances", source.iter_versioned_contracts_balances(), |(topoheight, contract, asset, version)| {
+        target.set_last_contract_balance_to(&contract, &asset, topoheight, version)
+    });
+
+    migrate_versioned!("contracts data", source.iter_versioned_contracts_data(), |(topoheight, contract, key, version)| {
+        target.set_last_contract_data_to(&contract, &key, topoheight, &version)
+    });
+
+    migrate_versioned!("contract event callbacks", source.iter_versioned_contract_event_callbacks(), |(topoheight, contract, event_id, listener, version)| {
+        target.set_last_contract_event_callback(&contract, event_id, &listener, version, topoheight)
+    });
+
+    info!("Migrating scheduled executions");
+    let executions: Vec<_> = source.get_registered_contract_scheduled_executions_in_range(0, top_topoheight, None).await?
+        .boxed()
+        .try_collect()
+        .await?;
+
+    for (execution_topoheight, registration_topoheight, execution) in executions {
+        target.set_contract_scheduled_execution_at_topoheight(&execution.contract, registration_topoheight, &execution, execution_topoheight).await?;
+    }
+
+    Ok(())
+}
+
+// Migrate the ABI, transactions and events index of a contract
+async fn migrate_contract<S: Storage, T: Storage>(source: &S, target: &mut T, contract: &Hash, top_topoheight: TopoHeight) -> Result<(), BlockchainError> {
+    trace!("Migrating contract {}", contract);
+    if let Some(abi) = source.get_contract_abi(contract).await? {
+        target.set_contract_abi(contract, &abi).await?;
+    }
+
+    let mut txs = source.get_contract_transactions(contract).await?;
+    loop {
+        let chunk = txs.by_ref()
+            .take(MIGRATION_CHUNK_SIZE)
+            .collect::<Result<Vec<Hash>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for tx in chunk {
+            target.add_tx_for_contract(contract, &tx).await?;
+        }
+    }
+
+    // Archival index of the events, empty if it was never enabled
+    let mut events = source.get_contract_events(contract, None, 0, top_topoheight, None).await?;
+    loop {
+        let chunk = events.by_ref()
+            .take(MIGRATION_CHUNK_SIZE)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for event in chunk {
+            target.add_contract_event(contract, event.event_id, event.topoheight, event.index, event.kind, &event.data).await?;
+        }
+    }
+
+    Ok(())
+}
+
+// Migrate all the blocks stored, including the orphaned ones
+// with their transactions and execution state
+async fn migrate_blocks<S: Storage, T: Storage>(source: &S, target: &mut T, top_height: u64) -> Result<(), BlockchainError> {
+    info!("Migrating blocks until height {}", top_height);
+    let mut count = 0;
+    for height in 0..=top_height {
+        if !source.has_blocks_at_height(height).await? {
+            continue;
+        }
+
+        for hash in source.get_blocks_at_height(height).await? {
+            // Blocks below the pruned topoheight are not available anymore
+            if !source.has_block_with_hash(&hash).await? {
+                continue;
+            }
+
+            trace!("Migrating block {} at height {}", hash, height);
+            let header = source.get_block_header_by_hash(&hash).await?;
+            let mut txs = Vec::with_capacity(header.get_txs_count());
+            for tx_hash in header.get_txs_hashes() {
+                txs.push(source.get_transaction(tx_hash).await?.into_arc());
+            }
+
+            let difficulty = source.get_difficulty_for_block_hash(&hash).await?;
+            let cumulative_difficulty = source.get_cumulative_difficulty_for_block_hash(&hash).await?;
+            let p = source.get_estimated_covariance_for_block_hash(&hash).await?;
+            let size_ema = source.get_block_size_ema(&hash).await?;
+            target.save_block(header.as_arc(), &txs, difficulty, cumulative_difficulty, p, size_ema, hash.clone().into()).await?;
+
+            for tx_hash in header.get_txs_hashes() {
+                target.set_blocks_for_tx(tx_hash, &source.get_blocks_for_tx(tx_hash).await?).await?;
+                if source.is_tx_executed_in_block(tx_hash, &hash).await? {
+                    target.mark_tx_as_executed_in_block(tx_hash, &hash).await?;
+                }
+
+                if source.has_contract_logs_for_caller(tx_hash).await? {
+                    target.set_contract_logs_for_caller(tx_hash, &source.get_contract_logs_for_caller(tx_hash).await?).await?;
+                }
+            }
+
+            count += 1;
+            if count % FLUSH_EVERY_N_BLOCKS == 0 {
+                debug!("{} blocks migrated", count);
+                target.flush().await?;
+            }
+        }
+    }
+
+    info!("{} blocks migrated", count);
+
+    Ok(())
+}
+
+// Migrate the DAG order with the metadata of each topoheight
+// and the blocks execution order
+async fn migrate_dag_order<S: Storage, T: Storage>(source: &S, target: &mut T, lowest_topoheight: TopoHeight, top_topoheight: TopoHeight) -> Result<(), BlockchainError> {
+    info!("Migrating DAG order from topoheight {} to {}", lowest_topoheight, top_topoheight);
+    for topoheight in lowest_topoheight..=top_topoheight {
+        if !source.has_hash_at_topoheight(topoheight).await? {
+            continue;
+        }
+
+        let hash = source.get_hash_at_topo_height(topoheight).await?;
+        target.set_topo_height_for_block(&hash, topoheight).await?;
+        target.set_metadata_at_topoheight(topoheight, source.get_metadata_at_topoheight(topoheight).await?).await?;
+
+        // Merkle hash is only available since its hard fork
+        if let Ok(merkle_hash) = source.get_balances_merkle_hash_at_topoheight(topoheight).await {
+            target.set_balances_merkle_hash_at_topoheight(topoheight, &merkle_hash).await?;
+        }
+    }
+
+    let mut order = source.get_blocks_execution_order().await?;
+    loop {
+        let chunk = order.by_ref()
+            .take(MIGRATION_CHUNK_SIZE)
+            .collect::<Result<Vec<Hash>, _>>()?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        for hash in chunk {
+            target.add_block_execution_to_order(&hash).await?;
+        }
+    }
+
+    Ok(())
+}
+
+// Compare the counters and the top block of both storages
+async fn verify_migration<S: Storage, T: Storage>(source: &S, target: &T) -> Result<StorageMigrationStats, BlockchainError> {
+    info!("Verifying storage migration");
+    let blocks = verify_count("blocks", source.count_blocks().await?, target.count_blocks().await?)?;
+    let transactions = verify_count("transactions", source.count_transactions().await?, target.count_transactions().await?)?;
+    let accounts = verify_count("accounts", source.count_accounts().await?, target.count_accounts().await?)?;
+    let assets = verify_count("assets", source.count_assets().await?, target.count_assets().await?)?;
+    let contracts = verify_count("contracts", source.count_contracts().await?, target.count_contracts().await?)?;
+    verify_count("blocks execution order", source.get_blocks_execution_count().await?, target.get_blocks_execution_count().await?)?;
+
+    let top_topoheight = target.get_top_topoheight().await?;
+    let top_hash = source.get_top_block_hash().await?;
+    let target_top_hash = target.get_top_block_hash().await?;
+    if top_hash != target_top_hash {
+        return Err(BlockchainError::MigrationTopHashMismatch(top_hash, target_top_hash))
+    }
+
+    Ok(StorageMigrationStats {
+        top_topoheight,
+        top_hash,
+        blocks,
+        transactions,
+        accounts,
+        assets,
+        contracts
+    })
+}
+
+fn verify_count(name: &'static str, source: u64, target: u64) -> Result<u64, BlockchainError> {
+    if source != target {
+        return Err(BlockchainError::MigrationMismatch(name, source, target))
+    }
+
+    Ok(source)
+}
+
+#[cfg(all(test, feature = "rocksdb"))]
+mod tests {
+    use std::sync::Arc;
+    use indexmap::IndexSet;
+    use dapa_common::{
+        account::VersionedNonce,
+        asset::{AssetData, AssetOwner, MaxSupplyMode, VersionedAssetData},
+        block::{BlockHeader, EXTRA_NONCE_SIZE},
+        crypto::{Hashable, KeyPair},
+        network::Network,
+        varuint::VarUint
+    };
+    use crate::core::{
+        hard_fork::get_version_at_height,
+        storage::{
+            types::TopoHeightMetadata,
+            AccountProvider,
+            AssetProvider,
+            BlockDagProvider,
+            BlockExecutionOrderProvider,
+            BlockProvider,
+            DagOrderProvider,
+            DifficultyProvider,
+            NonceProvider,
+            RocksStorage,
+            StateProvider,
+            Tips,
+            TipsProvider
+        }
+    };
+    use super::*;
+
+    fn create_block(height: u64, miner: &PublicKey) -> Arc<BlockHeader> {
+        let version = get_version_at_height(&Network::Devnet, height);
+        Arc::new(BlockHeader::new(version, height, height, IndexSet::new(), [0u8; EXTRA_NONCE_SIZE], miner.clone(), IndexSet::new()))
+    }
+
+    // Store a genesis block mined by a registered account, with an asset
+    async fn create_source() -> RocksStorage {
+        let mut storage = RocksStorage::new_in_memory(Network::Devnet);
+        let data = AssetData::new(8, "DAPA".to_owned(), "DAH".to_owned(), MaxSupplyMode::None, AssetOwner::None);
+        storage.add_asset(&Hash::zero(), 0, VersionedAssetData::new(data, None)).await.unwrap();
+
+        let miner = KeyPair::new().get_public_key().compress();
+        storage.set_account_registration_topoheight(&miner, 0).await.unwrap();
+        storage.set_last_nonce_to(&miner, 0, &VersionedNonce::new(1, None)).await.unwrap();
+
+        let block = create_block(0, &miner);
+        let hash = block.hash();
+        storage.save_block(block, &[], VarUint::from(1u64), VarUint::from(1u64), VarUint::zero(), 0, hash.clone().into()).await.unwrap();
+        storage.set_topo_height_for_block(&hash, 0).await.unwrap();
+        storage.set_metadata_at_topoheight(0, TopoHeightMetadata {
+            block_reward: 10,
+            emitted_supply: 10,
+            total_fees: 0,
+            total_fees_burned: 0
+        }).await.unwrap();
+        storage.add_block_execution_to_order(&hash).await.unwrap();
+
+        storage.set_top_topoheight(0).await.unwrap();
+        storage.set_top_height(0).await.unwrap();
+        storage.store_tips(&Tips::from([hash])).await.unwrap();
+
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_migrate_storage() {
+        let source = create_source().await;
+        let mut target = RocksStorage::new_in_memory(Network::Devnet);
+
+        let stats = migrate_storage(&source, &mut target).await.unwrap();
+        assert_eq!(stats.top_topoheight, 0);
+        assert_eq!(stats.top_hash, source.get_top_block_hash().await.unwrap());
+        assert_eq!(stats.blocks, 1);
+        assert_eq!(stats.transactions, 0);
+        assert_eq!(stats.accounts, 1);
+        assert_eq!(stats.assets, 1);
+        assert_eq!(stats.contracts, 0);
+
+        let miner = source.get_block_header_by_hash(&stats.top_hash).await.unwrap().get_miner().clone();
+        assert_eq!(target.get_account_registration_topoheight(&miner).await.unwrap(), 0);
+        assert_eq!(target.get_last_nonce(&miner).await.unwrap().1.get_nonce(), 1);
+        assert_eq!(target.get_tips().await.unwrap(), source.get_tips().await.unwrap());
+        assert_eq!(target.get_metadata_at_topoheight(0).await.unwrap().emitted_supply, 10);
+
+        // The target must be empty
+        let mut target = create_source().await;
+        assert!(matches!(migrate_storage(&source, &mut target).await, Err(BlockchainError::MigrationTargetNotEmpty)));
+    }
+
+    #[tokio::test]
+    async fn test_verify_migration() {
+        let source = create_source().await;
+        let mut target = RocksStorage::new_in_memory(Network::Devnet);
+        migrate_storage(&source, &mut target).await.unwrap();
+        assert!(verify_migration(&source, &target).await.is_ok());
+
+        // An extra block in the target is detected
+        let miner = KeyPair::new().get_public_key().compress();
+        let block = create_block(1, &miner);
+        let hash = block.hash();
+        target.save_block(block, &[], VarUint::from(1u64), VarUint::from(2u64), VarUint::zero(), 0, hash.clone().into()).await.unwrap();
+        assert!(matches!(verify_migration(&source, &target).await, Err(BlockchainError::MigrationMismatch("blocks", 1, 2))));
+
+        // So is a different top block
+        let mut target = RocksStorage::new_in_memory(Network::Devnet);
+        migrate_storage(&source, &mut target).await.unwrap();
+        target.set_topo_height_for_block(&hash, 0).await.unwrap();
+        assert!(matches!(verify_migration(&source, &target).await, Err(BlockchainError::MigrationTopHashMismatch(_, _))));
+    }
+}
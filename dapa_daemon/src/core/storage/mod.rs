@@ -10,6 +10,7 @@ pub mod sled;
 pub mod rocksdb;
 
 pub mod snapshot;
+pub mod migration;
 
 pub use self::providers::*;
 pub use cache::*;
@@ -20,7 +21,7 @@ pub use rocksdb::RocksStorage;
 #[cfg(feature = "sled")]
 pub use sled::SledStorage;
 
-use std::collections::HashSet;
+use std::{collections::HashSet, path::Path};
 use async_trait::async_trait;
 use log::{debug, trace, warn};
 use dapa_common::{
@@ -32,7 +33,10 @@ use dapa_common::{
     immutable::Immutable,
     transaction::Transaction
 };
-use crate::{config::PRUNE_SAFETY_LIMIT, core::error::BlockchainError};
+use crate::{
+    config::PRUNE_SAFETY_LIMIT,
+    core::{config::StorageBackend, error::BlockchainError}
+};
 
 // Represents the tips of the chain or of a block
 pub type Tips = HashSet<Hash>;
@@ -43,7 +47,8 @@ pub trait Storage:
     + NonceProvider + AccountProvider + ClientProtocolProvider + BlockDagProvider
     + MerkleHashProvider + NetworkProvider + MultiSigProvider + TipsProvider
     + SnapshotProvider + ContractProvider + VersionedProvider + AssetCirculatingSupplyProvider
-    + CacheProvider + StateProvider
+    + CacheProvider + StateProvider + AccountTransactionsProvider + IntegrityProvider
+    + RawVersionedProvider
     + Sync + Send + 'static {
     // delete block at topoheight, and all pointers (hash_at_topo, topo_by_hash, reward, supply, diff, cumulative diff...)
     async fn delete_block_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(Hash, Immutable<BlockHeader>, Vec<(Hash, Immutable<Transaction>)>), BlockchainError>;
@@ -188,4 +193,11 @@ pub trait Storage:
 
     // Flush the inner DB after a block being written
     async fn flush(&mut self) -> Result<(), BlockchainError>;
+
+    // Backend used by this storage
+    fn get_backend(&self) -> StorageBackend;
+
+    // Create a point-in-time copy of the DB in the given directory
+    // Caller must prevent any write to the storage until it's done
+    async fn create_backup(&self, path: &Path) -> Result<(), BlockchainError>;
 }
\ No newline at end of file
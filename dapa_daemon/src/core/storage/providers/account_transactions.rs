@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use dapa_common::{
+    block::TopoHeight,
+    crypto::{Hash, PublicKey}
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::types::AccountTransactionEntry
+};
+
+// Archival index of all the transactions that touched an account
+// It is only populated if enabled in the daemon config
+// and is never pruned
+#[async_trait]
+pub trait AccountTransactionsProvider {
+    // Link a transaction to an account at the topoheight it got executed
+    async fn add_account_transaction(&mut self, key: &PublicKey, topoheight: TopoHeight, tx: &Hash, entry: &AccountTransactionEntry) -> Result<(), BlockchainError>;
+
+    // Retrieve all the transactions linked to an account in the topoheight range (both included)
+    // Newest transactions are returned first, ordered by hash for the same topoheight
+    // after is the (topoheight, tx hash) of the last transaction already read by a previous call
+    async fn get_account_transactions<'a>(&'a self, key: &PublicKey, minimum_topoheight: TopoHeight, maximum_topoheight: TopoHeight, after: Option<(TopoHeight, Hash)>) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, AccountTransactionEntry), BlockchainError>> + 'a, BlockchainError>;
+}
@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use dapa_common::{
+    contract::RegisteredContractAbi,
+    crypto::Hash
+};
+use crate::core::error::BlockchainError;
+
+// Node-local registry of the contracts ABI
+// It is not part of the consensus and is never pruned
+#[async_trait]
+pub trait ContractAbiProvider {
+    // Retrieve the ABI registered for a contract
+    async fn get_contract_abi(&self, contract: &Hash) -> Result<Option<RegisteredContractAbi>, BlockchainError>;
+
+    // Register the ABI of a contract, replacing the previous one
+    async fn set_contract_abi(&mut self, contract: &Hash, abi: &RegisteredContractAbi) -> Result<(), BlockchainError>;
+}
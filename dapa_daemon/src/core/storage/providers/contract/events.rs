@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use dapa_common::{
+    block::TopoHeight,
+    contract::ContractEventKind,
+    crypto::Hash
+};
+use xelis_vm::ValueCell;
+use crate::core::error::BlockchainError;
+
+// Event stored in the archival index
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedContractEvent {
+    pub topoheight: TopoHeight,
+    pub event_id: u64,
+    // Position of the event among the ones with the same id in the block
+    pub index: u32,
+    pub kind: ContractEventKind,
+    pub data: ValueCell
+}
+
+// Archival index of the events fired by the contracts for the RPC
+// It is only populated if enabled in the daemon config
+// and is never pruned
+#[async_trait]
+pub trait ContractEventsProvider {
+    // Store an event fired by a contract in the block executed at topoheight
+    // index is the position of the event among the ones with the same id in this block
+    async fn add_contract_event(&mut self, contract: &Hash, event_id: u64, topoheight: TopoHeight, index: u32, kind: ContractEventKind, data: &ValueCell) -> Result<(), BlockchainError>;
+
+    // Retrieve the events fired by a contract in the topoheight range (both included)
+    // Oldest events are returned first, ordered by event id and index for the same topoheight
+    // If an event id is set, only its events are read using the index by id
+    // after is the (topoheight, event id, index) of the last event already read by a previous call
+    async fn get_contract_events<'a>(&'a self, contract: &Hash, event_id: Option<u64>, minimum_topoheight: TopoHeight, maximum_topoheight: TopoHeight, after: Option<(TopoHeight, u64, u32)>) -> Result<impl Iterator<Item = Result<IndexedContractEvent, BlockchainError>> + 'a, BlockchainError>;
+}
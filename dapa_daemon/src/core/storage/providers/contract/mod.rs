@@ -3,6 +3,8 @@ mod contract_logs;
 mod balance;
 mod scheduled_execution;
 mod event_callback;
+mod events;
+mod abi;
 
 use std::borrow::Cow;
 
@@ -23,12 +25,14 @@ pub use contract_logs::*;
 pub use balance::*;
 pub use scheduled_execution::*;
 pub use event_callback::*;
+pub use events::*;
+pub use abi::*;
 
 // A versioned contract is a contract that can be updated or deleted
 pub type VersionedContractModule<'a> = Versioned<Option<Cow<'a, ContractModule>>>;
 
 #[async_trait]
-pub trait ContractProvider: ContractDataProvider + ContractLogsProvider + ContractInfoProvider + ContractBalanceProvider + ContractScheduledExecutionProvider + ContractEventCallbackProvider {
+pub trait ContractProvider: ContractDataProvider + ContractLogsProvider + ContractInfoProvider + ContractBalanceProvider + ContractScheduledExecutionProvider + ContractEventCallbackProvider + ContractEventsProvider + ContractAbiProvider {
     // Deploy a contract
     async fn set_last_contract_to<'a>(&mut self, hash: &Hash, topoheight: TopoHeight, contract: &VersionedContractModule<'a>) -> Result<(), BlockchainError>;
 
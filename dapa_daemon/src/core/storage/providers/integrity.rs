@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+use crate::core::{
+    error::BlockchainError,
+    storage::types::IntegrityReport
+};
+
+// Backend specific integrity checks
+#[async_trait]
+pub trait IntegrityProvider {
+    // Verify the reverse indexes and the pointers to the latest versions
+    // Inconsistencies are added to the report and rebuilt from
+    // their source entries if repair is set
+    async fn check_indexes_integrity(&mut self, repair: bool, report: &mut IntegrityReport) -> Result<(), BlockchainError>;
+}
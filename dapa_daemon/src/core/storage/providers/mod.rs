@@ -21,6 +21,9 @@ mod versioned;
 mod cache;
 mod state;
 mod asset_supply;
+mod account_transactions;
+mod integrity;
+mod raw_versioned;
 
 pub use asset::*;
 pub use blocks_at_height::*;
@@ -44,4 +47,7 @@ pub use contract::*;
 pub use versioned::*;
 pub use cache::*;
 pub use state::*;
-pub use asset_supply::*;
\ No newline at end of file
+pub use asset_supply::*;
+pub use account_transactions::*;
+pub use integrity::*;
+pub use raw_versioned::*;
\ No newline at end of file
@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use xelis_vm::ValueCell;
+use dapa_common::{
+    account::{VersionedBalance, VersionedNonce},
+    asset::VersionedAssetData,
+    block::TopoHeight,
+    crypto::{Hash, PublicKey}
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        VersionedContractBalance,
+        VersionedContractData,
+        VersionedContractModule,
+        VersionedEventCallbackRegistration,
+        VersionedMultiSig,
+        VersionedSupply
+    }
+};
+
+// Iterate over all the entries stored in the versioned columns
+// Versions are read as they are stored on disk without following their previous topoheight,
+// so deleted values and versions that are no longer linked are also returned
+// For the same key, versions are always returned in increasing topoheight order
+// This is used to copy a storage into another backend without losing any version
+#[async_trait]
+pub trait RawVersionedProvider {
+    // (topoheight, account, nonce)
+    async fn iter_versioned_nonces<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, PublicKey, VersionedNonce), BlockchainError>> + 'a, BlockchainError>;
+
+    // (topoheight, account, multisig)
+    async fn iter_versioned_multisigs<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, PublicKey, VersionedMultiSig<'static>), BlockchainError>> + 'a, BlockchainError>;
+
+    // (topoheight, account, asset, balance)
+    async fn iter_versioned_balances<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, PublicKey, Hash, VersionedBalance), BlockchainError>> + 'a, BlockchainError>;
+
+    // (topoheight, asset, data)
+    async fn iter_versioned_assets<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, VersionedAssetData), BlockchainError>> + 'a, BlockchainError>;
+
+    // (topoheight, asset, circulating supply)
+    async fn iter_versioned_assets_supply<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, VersionedSupply), BlockchainError>> + 'a, BlockchainError>;
+
+    // (topoheight, contract, module)
+    async fn iter_versioned_contracts<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, VersionedContractModule<'static>), BlockchainError>> + 'a, BlockchainError>;
+
+    // (topoheight, contract, key, value)
+    async fn iter_versioned_contracts_data<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, ValueCell, VersionedContractData), BlockchainError>> + 'a, BlockchainError>;
+
+    // (topoheight, contract, asset, balance)
+    async fn iter_versioned_contracts_balances<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, Hash, VersionedContractBalance), BlockchainError>> + 'a, BlockchainError>;
+
+    // (topoheight, contract, event id, listener contract, registration)
+    async fn iter_versioned_contract_event_callbacks<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, u64, Hash, VersionedEventCallbackRegistration), BlockchainError>> + 'a, BlockchainError>;
+}
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+use dapa_common::block::TopoHeight;
+use crate::core::error::BlockchainError;
+
+#[async_trait]
+pub trait VersionedAccountTransactionsProvider {
+    // delete the account transactions indexed at topoheight
+    async fn delete_versioned_account_transactions_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError>;
+
+    // delete the account transactions indexed above topoheight
+    async fn delete_versioned_account_transactions_above_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError>;
+}
@@ -0,0 +1,13 @@
+use async_trait::async_trait;
+use dapa_common::block::TopoHeight;
+
+use crate::core::error::BlockchainError;
+
+#[async_trait]
+pub trait VersionedContractEventsProvider {
+    // delete the contract events indexed at topoheight
+    async fn delete_versioned_contract_events_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError>;
+
+    // delete the contract events indexed above topoheight
+    async fn delete_versioned_contract_events_above_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError>;
+}
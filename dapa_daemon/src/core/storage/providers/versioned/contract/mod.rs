@@ -2,6 +2,7 @@ mod data;
 mod balance;
 mod scheduled_execution;
 mod event_callback;
+mod events;
 
 use async_trait::async_trait;
 use dapa_common::block::TopoHeight;
@@ -11,9 +12,10 @@ pub use data::*;
 pub use balance::*;
 pub use scheduled_execution::VersionedScheduledExecutionsProvider;
 pub use event_callback::*;
+pub use events::*;
 
 #[async_trait]
-pub trait VersionedContractProvider: VersionedContractDataProvider + VersionedContractBalanceProvider + VersionedScheduledExecutionsProvider + VersionedContractEventCallbackProvider {
+pub trait VersionedContractProvider: VersionedContractDataProvider + VersionedContractBalanceProvider + VersionedScheduledExecutionsProvider + VersionedContractEventCallbackProvider + VersionedContractEventsProvider {
     // delete versioned contracts at topoheight
     async fn delete_versioned_contracts_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError>;
 
@@ -7,6 +7,7 @@ mod asset;
 mod cache;
 mod dag_order;
 mod asset_supply;
+mod account_transactions;
 
 use async_trait::async_trait;
 use log::debug;
@@ -22,6 +23,7 @@ pub use asset::*;
 pub use cache::*;
 pub use dag_order::*;
 pub use asset_supply::*;
+pub use account_transactions::*;
 
 // Every versioned key should start with the topoheight in order to be able to delete them easily
 #[async_trait]
@@ -31,6 +33,7 @@ pub trait VersionedProvider:
     + VersionedMultiSigProvider
     + VersionedContractProvider
     + VersionedRegistrationsProvider
+    + VersionedAccountTransactionsProvider
     + VersionedAssetProvider
     + VersionedAssetsCirculatingSupplyProvider
     + VersionedCacheProvider
@@ -43,11 +46,13 @@ pub trait VersionedProvider:
         self.delete_versioned_nonces_at_topoheight(topoheight).await?;
         self.delete_versioned_multisigs_at_topoheight(topoheight).await?;
         self.delete_versioned_registrations_at_topoheight(topoheight).await?;
+        self.delete_versioned_account_transactions_at_topoheight(topoheight).await?;
 
         self.delete_versioned_contracts_at_topoheight(topoheight).await?;
         self.delete_versioned_contract_data_at_topoheight(topoheight).await?;
         self.delete_versioned_contract_balances_at_topoheight(topoheight).await?;
         self.delete_versioned_contract_event_callbacks_at_topoheight(topoheight).await?;
+        self.delete_versioned_contract_events_at_topoheight(topoheight).await?;
         self.delete_scheduled_executions_at_topoheight(topoheight).await?;
 
         self.delete_versioned_assets_supply_at_topoheight(topoheight).await?;
@@ -73,6 +78,7 @@ pub trait VersionedProvider:
         self.delete_versioned_nonces_below_topoheight(topoheight, keep_last).await?;
         self.delete_versioned_multisigs_below_topoheight(topoheight, keep_last).await?;
         // no deletions of registrations below topoheight, we keep them marked
+        // same for the account transactions and contract events archival indexes
 
         self.delete_versioned_contracts_below_topoheight(topoheight, keep_last).await?;
         self.delete_versioned_contract_data_below_topoheight(topoheight, keep_last).await?;
@@ -93,11 +99,13 @@ pub trait VersionedProvider:
         self.delete_versioned_nonces_above_topoheight(topoheight).await?;
         self.delete_versioned_multisigs_above_topoheight(topoheight).await?;
         self.delete_versioned_registrations_above_topoheight(topoheight).await?;
+        self.delete_versioned_account_transactions_above_topoheight(topoheight).await?;
 
         self.delete_versioned_contracts_above_topoheight(topoheight).await?;
         self.delete_versioned_contract_data_above_topoheight(topoheight).await?;
         self.delete_versioned_contract_balances_above_topoheight(topoheight).await?;
         self.delete_versioned_contract_event_callbacks_above_topoheight(topoheight).await?;
+        self.delete_versioned_contract_events_above_topoheight(topoheight).await?;
         self.delete_scheduled_executions_above_topoheight(topoheight).await?;
 
         self.delete_versioned_assets_supply_above_topoheight(topoheight).await?;
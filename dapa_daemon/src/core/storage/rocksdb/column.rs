@@ -122,7 +122,33 @@ pub enum Column {
     ContractsTransactions,
 
     // {topoheight}{asset_id} => {version}
-    VersionedAssetsSupply
+    VersionedAssetsSupply,
+
+    // Archival index of the transactions per account
+    // Only populated if enabled in the config
+    // {account_id}{topoheight}{tx_hash} => {entry}
+    AccountTransactions,
+    // Column used as a "versioned" to delete
+    // the index entries easily per topoheight
+    // {topoheight}{account_id}{tx_hash} => {}
+    PrefixedAccountTransactions,
+
+    // Archival index of the events fired by the contracts
+    // Only populated if enabled in the config
+    // {contract_id}{topoheight}{event_id}{index} => {kind}{data}
+    ContractEvents,
+    // Index of the events per id
+    // {contract_id}{event_id}{topoheight}{index} => {}
+    ContractEventsById,
+    // Column used as a "versioned" to delete
+    // the index entries easily per topoheight
+    // {topoheight}{contract_id}{event_id}{index} => {}
+    PrefixedContractEvents,
+
+    // ABI registered for a contract
+    // Node-local, it is never pruned
+    // {contract_hash} => {topoheight}{version}{abi}
+    ContractsAbi,
 }
 
 impl Column {
@@ -139,12 +165,17 @@ impl Column {
             | VersionedContractsBalances
             | VersionedContractsData
             | PrefixedRegistrations
+            | PrefixedAccountTransactions
+            | PrefixedContractEvents
             // Special case: prefixed with topoheight too
             | DelayedExecution => Some(PREFIX_TOPOHEIGHT_LEN),
 
             ContractsBalances
             | ContractsData
-            | Balances => Some(PREFIX_ID_LEN),
+            | Balances
+            | AccountTransactions
+            | ContractEvents
+            | ContractEventsById => Some(PREFIX_ID_LEN),
 
             _ => None,
         }
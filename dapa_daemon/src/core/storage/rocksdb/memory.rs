@@ -0,0 +1,200 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Bound,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard}
+};
+use bytes::Bytes;
+use crate::core::storage::snapshot::{Direction, IteratorMode};
+use super::Column;
+
+type Tree = BTreeMap<Bytes, Bytes>;
+
+// In-memory replacement of RocksDB
+// Each column is an ordered map to keep the same iteration order as on disk
+// Nothing is persisted: all the data is lost once dropped
+#[derive(Default)]
+pub struct MemoryDB {
+    columns: RwLock<HashMap<Column, Tree>>
+}
+
+impl MemoryDB {
+    // A poisoned lock can't leave a map in a partial state, so we can keep using it
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<Column, Tree>> {
+        self.columns.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<Column, Tree>> {
+        self.columns.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn get(&self, column: Column, key: &[u8]) -> Option<Bytes> {
+        self.read()
+            .get(&column)
+            .and_then(|tree| tree.get(key))
+            .cloned()
+    }
+
+    pub fn put(&self, column: Column, key: &[u8], value: Vec<u8>) {
+        self.write()
+            .entry(column)
+            .or_default()
+            .insert(Bytes::copy_from_slice(key), value.into());
+    }
+
+    pub fn delete(&self, column: Column, key: &[u8]) {
+        if let Some(tree) = self.write().get_mut(&column) {
+            tree.remove(key);
+        }
+    }
+
+    // Total size of all the keys and values stored
+    pub fn size(&self) -> u64 {
+        self.read()
+            .values()
+            .flat_map(|tree| tree.iter())
+            .map(|(k, v)| (k.len() + v.len()) as u64)
+            .sum()
+    }
+
+    // Iterate over a column using the same semantics as RocksDB
+    // The iterator only keeps the bounds of the remaining range,
+    // so no lock is held while iterating and the DB can be updated during the iteration
+    pub fn iter(&self, column: Column, mode: IteratorMode) -> MemoryIterator<'_> {
+        let (lower, upper, direction) = match mode {
+            IteratorMode::Start => (Bound::Unbounded, Bound::Unbounded, Direction::Forward),
+            IteratorMode::End => (Bound::Unbounded, Bound::Unbounded, Direction::Reverse),
+            IteratorMode::From(start, direction) => {
+                let start = Bound::Included(Bytes::copy_from_slice(start));
+                match direction {
+                    Direction::Forward => (start, Bound::Unbounded, direction),
+                    Direction::Reverse => (Bound::Unbounded, start, direction),
+                }
+            },
+            IteratorMode::WithPrefix(prefix, direction) => {
+                (Bound::Included(Bytes::copy_from_slice(prefix)), prefix_upper_bound(prefix), direction)
+            },
+            IteratorMode::Range { lower_bound, upper_bound, direction } => {
+                (Bound::Included(Bytes::copy_from_slice(lower_bound)), Bound::Excluded(Bytes::copy_from_slice(upper_bound)), direction)
+            }
+        };
+
+        MemoryIterator {
+            db: self,
+            column,
+            lower,
+            upper,
+            direction
+        }
+    }
+}
+
+// First key that is greater than all the keys starting with the prefix
+fn prefix_upper_bound(prefix: &[u8]) -> Bound<Bytes> {
+    let mut bound = prefix.to_vec();
+    while let Some(last) = bound.pop() {
+        if last < u8::MAX {
+            bound.push(last + 1);
+            return Bound::Excluded(bound.into())
+        }
+    }
+
+    Bound::Unbounded
+}
+
+// Lazy iterator over a column of the in-memory DB
+// Each step looks up the next entry in the remaining range
+pub struct MemoryIterator<'a> {
+    db: &'a MemoryDB,
+    column: Column,
+    lower: Bound<Bytes>,
+    upper: Bound<Bytes>,
+    direction: Direction
+}
+
+impl MemoryIterator<'_> {
+    // BTreeMap panics on an inverted range
+    fn is_empty_range(&self) -> bool {
+        match (&self.lower, &self.upper) {
+            (Bound::Included(lower), Bound::Included(upper)) => lower > upper,
+            (Bound::Included(lower) | Bound::Excluded(lower), Bound::Included(upper) | Bound::Excluded(upper)) => lower >= upper,
+            _ => false
+        }
+    }
+}
+
+impl Iterator for MemoryIterator<'_> {
+    type Item = (Bytes, Bytes);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_empty_range() {
+            return None
+        }
+
+        let (key, value) = {
+            let columns = self.db.read();
+            let mut range = columns.get(&self.column)?
+                .range((self.lower.clone(), self.upper.clone()));
+
+            let entry = match self.direction {
+                Direction::Forward => range.next(),
+                Direction::Reverse => range.next_back(),
+            };
+
+            entry.map(|(k, v)| (k.clone(), v.clone()))?
+        };
+
+        // Shrink the remaining range to exclude the returned key
+        match self.direction {
+            Direction::Forward => self.lower = Bound::Excluded(key.clone()),
+            Direction::Reverse => self.upper = Bound::Excluded(key.clone()),
+        }
+
+        Some((key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dapa_common::network::Network;
+    use crate::core::storage::{RocksStorage, SnapshotProvider, StateProvider};
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_storage() {
+        let mut storage = RocksStorage::new_in_memory(Network::Devnet);
+        storage.set_top_topoheight(10).await.unwrap();
+        assert_eq!(storage.get_top_topoheight().await.unwrap(), 10);
+
+        // Snapshot changes must be dropped if not applied
+        storage.start_snapshot().await.unwrap();
+        storage.set_top_topoheight(20).await.unwrap();
+        assert_eq!(storage.get_top_topoheight().await.unwrap(), 20);
+        storage.end_snapshot(false).unwrap();
+        assert_eq!(storage.get_top_topoheight().await.unwrap(), 10);
+
+        for (prefix, suffix) in [(0u64, 1u64), (1, 1), (1, 2), (2, 1)] {
+            let mut key = prefix.to_be_bytes().to_vec();
+            key.extend_from_slice(&suffix.to_be_bytes());
+            storage.insert_into_disk(Column::VersionedNonces, key, &()).unwrap();
+        }
+
+        // Prefix iteration must stop at the prefix boundary
+        let keys = storage.iter_keys::<(u64, u64)>(Column::VersionedNonces, IteratorMode::WithPrefix(&1u64.to_be_bytes(), Direction::Forward))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec![(1, 1), (1, 2)]);
+
+        let keys = storage.iter_keys::<(u64, u64)>(Column::VersionedNonces, IteratorMode::WithPrefix(&1u64.to_be_bytes(), Direction::Reverse))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec![(1, 2), (1, 1)]);
+
+        let keys = storage.iter_keys::<(u64, u64)>(Column::VersionedNonces, IteratorMode::From(&1u64.to_be_bytes(), Direction::Reverse))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(keys, vec![(0, 1)]);
+    }
+}
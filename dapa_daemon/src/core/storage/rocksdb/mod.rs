@@ -1,14 +1,16 @@
 mod column;
 mod types;
 mod providers;
+mod memory;
 
-use std::sync::Arc;
+use std::{convert::Infallible, path::Path, sync::Arc};
 
 use anyhow::Context;
 use async_trait::async_trait;
 use itertools::Either;
 use log::{debug, info, trace};
 use rocksdb::{
+    checkpoint::Checkpoint,
     BlockBasedOptions,
     Cache,
     ColumnFamilyDescriptor,
@@ -35,7 +37,7 @@ use dapa_common::{
     transaction::Transaction,
 };
 use crate::core::{
-    config::RocksDBConfig,
+    config::{RocksDBConfig, StorageBackend},
     error::{BlockchainError, DiskContext},
     storage::{
         snapshot::{
@@ -55,6 +57,7 @@ use crate::core::{
 
 pub use column::*;
 pub use types::*;
+pub use memory::MemoryDB;
 
 use super::Storage;
 
@@ -73,7 +76,28 @@ pub(super) const TOP_TOPO_HEIGHT: &[u8; 4] = b"TOPO";
 pub(super) const TOP_HEIGHT: &[u8; 4] = b"TOPH";
 pub(super) const TIPS: &[u8; 4] = b"TIPS";
 
-type InnerDB = DBWithThreadMode<MultiThreaded>;
+// Database engine used by the storage
+// Memory is made for tests and the simulator: nothing is written on disk
+pub enum InnerDB {
+    RocksDB(DBWithThreadMode<MultiThreaded>),
+    Memory(MemoryDB)
+}
+
+impl InnerDB {
+    // Read a value from the DB and map it without copying it
+    fn read<R>(&self, column: Column, key: &[u8], f: impl FnOnce(Option<&[u8]>) -> R) -> Result<R, BlockchainError> {
+        match self {
+            Self::RocksDB(db) => {
+                let cf = cf_handle!(db, column);
+                let value = db.get_pinned_cf(&cf, key)
+                    .with_context(|| format!("Internal error while reading column {:?}", column))?;
+
+                Ok(f(value.as_deref()))
+            },
+            Self::Memory(db) => Ok(f(db.get(column, key).as_deref()))
+        }
+    }
+}
 
 pub type Snapshot = InternalSnapshot<Column>;
 
@@ -156,11 +180,32 @@ pub struct RocksStorage {
     network: Network,
     snapshot: Option<Snapshot>,
     cache: StorageCache,
+    // Opened in read-only mode, nothing is compacted nor flushed
+    read_only: bool,
 }
 
 impl RocksStorage {
     pub fn new(dir: &str, network: Network, config: &RocksDBConfig) -> Self {
+        Self::open(dir, network, config, false)
+    }
+
+    // Open an existing DB in read-only mode
+    // Only the columns already present in the DB are opened
+    pub fn new_read_only(dir: &str, network: Network, config: &RocksDBConfig) -> Self {
+        Self::open(dir, network, config, true)
+    }
+
+    fn open(dir: &str, network: Network, config: &RocksDBConfig, read_only: bool) -> Self {
+        let path = format!("{}{}", dir, network.to_string().to_lowercase());
+        let existing_columns = if read_only {
+            DBWithThreadMode::<MultiThreaded>::list_cf(&Options::default(), &path)
+                .expect("Failed to list RocksDB columns")
+        } else {
+            Vec::new()
+        };
+
         let cfs = Column::iter()
+            .filter(|column| !read_only || existing_columns.contains(&column.to_string()))
             .map(|column| {
                 let name = column.to_string();
                 let prefix = column.prefix();
@@ -211,14 +256,30 @@ impl RocksStorage {
             opts.set_write_buffer_size(config.write_buffer_size as _);
         }
 
-        let db  = DBWithThreadMode::<MultiThreaded>::open_cf_descriptors(&opts, format!("{}{}", dir, network.to_string().to_lowercase()), cfs)
-            .expect("Failed to open RocksDB");
+        let db = if read_only {
+            DBWithThreadMode::<MultiThreaded>::open_cf_descriptors_read_only(&opts, path, cfs, false)
+        } else {
+            DBWithThreadMode::<MultiThreaded>::open_cf_descriptors(&opts, path, cfs)
+        }.expect("Failed to open RocksDB");
 
         Self {
-            db: Arc::new(db),
+            db: Arc::new(InnerDB::RocksDB(db)),
             network,
             snapshot: None,
-            cache: StorageCache::new(None)
+            cache: StorageCache::new(None),
+            read_only
+        }
+    }
+
+    // Create a storage kept entirely in memory
+    // All the data is lost once the storage is dropped
+    pub fn new_in_memory(network: Network) -> Self {
+        Self {
+            db: Arc::new(InnerDB::Memory(MemoryDB::default())),
+            network,
+            snapshot: None,
+            cache: StorageCache::new(None),
+            read_only: false
         }
     }
 
@@ -260,39 +321,63 @@ impl RocksStorage {
             }
         }
 
-        let cf = cf_handle!(self.db, column);
-        let value = self.db.get_pinned_cf(&cf, key_bytes)
-            .with_context(|| format!("Error while checking if key exists in column {:?}", column))?;
-
-        Ok(value.is_some())
+        self.db.read(column, key_bytes, |value| value.is_some())
     }
 
     // Check if its empty by checking the snapshot cache first, and then the raw DB
     pub fn is_empty(&self, column: Column) -> Result<bool, BlockchainError> {
         trace!("is empty {:?}", column);
 
-        let cf = cf_handle!(self.db, column);
-        let mut iterator = self.db.iterator_cf(&cf, InternalIteratorMode::Start);
+        match self.db.as_ref() {
+            InnerDB::RocksDB(db) => {
+                let cf = cf_handle!(db, column);
+                let mut iterator = db.iterator_cf(&cf, InternalIteratorMode::Start);
 
-        if let Some(snapshot) = self.snapshot.as_ref() {
-            return Ok(snapshot.is_empty(column, iterator))
-        }
+                if let Some(snapshot) = self.snapshot.as_ref() {
+                    return Ok(snapshot.is_empty(column, iterator))
+                }
+
+                Ok(iterator.next().is_none())
+            },
+            InnerDB::Memory(db) => {
+                let mut iterator = db.iter(column, IteratorMode::Start)
+                    .map(Ok::<_, Infallible>);
+
+                if let Some(snapshot) = self.snapshot.as_ref() {
+                    return Ok(snapshot.is_empty(column, iterator))
+                }
 
-        Ok(iterator.next().is_none())
+                Ok(iterator.next().is_none())
+            }
+        }
     }
 
     // Count how many entries we have stored in a column
     pub fn count_entries(&self, column: Column) -> Result<usize, BlockchainError> {
         trace!("count entries {:?}", column);
 
-        let cf = cf_handle!(self.db, column);
-        let iterator = self.db.iterator_cf(&cf, InternalIteratorMode::Start);
+        match self.db.as_ref() {
+            InnerDB::RocksDB(db) => {
+                let cf = cf_handle!(db, column);
+                let iterator = db.iterator_cf(&cf, InternalIteratorMode::Start);
 
-        if let Some(snapshot) = self.snapshot.as_ref() {
-            return Ok(snapshot.count_entries(column, iterator))
-        }
+                if let Some(snapshot) = self.snapshot.as_ref() {
+                    return Ok(snapshot.count_entries(column, iterator))
+                }
+
+                Ok(iterator.count())
+            },
+            InnerDB::Memory(db) => {
+                let iterator = db.iter(column, IteratorMode::Start)
+                    .map(Ok::<_, Infallible>);
 
-        Ok(iterator.count())
+                if let Some(snapshot) = self.snapshot.as_ref() {
+                    return Ok(snapshot.count_entries(column, iterator))
+                }
+
+                Ok(iterator.count())
+            }
+        }
     }
 
     pub fn load_optional_from_disk<K: AsRef<[u8]> + ?Sized, V: Serializer>(&self, column: Column, key: &K) -> Result<Option<V>, BlockchainError> {
@@ -317,12 +402,8 @@ impl RocksStorage {
             }
         }
 
-        let cf = cf_handle!(self.db, column);
-        match self.db.get_pinned_cf(&cf, key.as_ref())
-            .with_context(|| format!("Internal error while reading {:?}", column))? {
-            Some(bytes) => Ok(bytes.len()),
-            None => Err(BlockchainError::NotFoundOnDisk(DiskContext::DataLen))
-        }
+        self.db.read(column, key.as_ref(), |value| value.map(|bytes| bytes.len()))?
+            .ok_or(BlockchainError::NotFoundOnDisk(DiskContext::DataLen))
     }
 
     // Internal functions for better borrow checking
@@ -338,12 +419,8 @@ impl RocksStorage {
             }
         }
 
-        let cf = cf_handle!(db, column);
-        match db.get_pinned_cf(&cf, key.as_ref())
-            .with_context(|| format!("Internal error while reading column {:?}", column))? {
-            Some(bytes) => Ok(Some(V::from_bytes(&bytes)?)),
-            None => Ok(None)
-        }
+        let value = db.read(column, key.as_ref(), |value| value.map(V::from_bytes).transpose())??;
+        Ok(value)
     }
 
     pub(super) fn insert_into_disk_internal<K: AsRef<[u8]>, V: Serializer>(db: &InnerDB, snapshot: Option<&mut Snapshot>, column: Column, key: K, value: &V) -> Result<(), BlockchainError> {
@@ -353,10 +430,13 @@ impl RocksStorage {
             Some(snapshot) => {
                 snapshot.put(column, key.as_ref().to_vec(), value.to_bytes());
             },
-            None => {
-                let cf = cf_handle!(db, column);
-                db.put_cf(&cf, key.as_ref(), value.to_bytes())
-                    .map_err(|e| anyhow::anyhow!("Error while inserting into disk column {:?}: {}", column, e))?;
+            None => match db {
+                InnerDB::RocksDB(db) => {
+                    let cf = cf_handle!(db, column);
+                    db.put_cf(&cf, key.as_ref(), value.to_bytes())
+                        .map_err(|e| anyhow::anyhow!("Error while inserting into disk column {:?}: {}", column, e))?;
+                },
+                InnerDB::Memory(db) => db.put(column, key.as_ref(), value.to_bytes())
             }
         };
 
@@ -371,10 +451,13 @@ impl RocksStorage {
             Some(snapshot) => {
                 snapshot.delete(column, bytes.to_vec());
             },
-            None => {
-                let cf = cf_handle!(db, column);
-                db.delete_cf(&cf, bytes)
-                    .with_context(|| format!("Error while removing from disk column {:?}", column))?;
+            None => match db {
+                InnerDB::RocksDB(db) => {
+                    let cf = cf_handle!(db, column);
+                    db.delete_cf(&cf, bytes)
+                        .with_context(|| format!("Error while removing from disk column {:?}", column))?;
+                },
+                InnerDB::Memory(db) => db.delete(column, bytes)
             }
         };
 
@@ -401,18 +484,30 @@ impl RocksStorage {
     pub fn iter_raw_internal<'a>(db: &'a InnerDB, snapshot: Option<&'a Snapshot>, mode: IteratorMode, column: Column) -> Result<impl Iterator<Item = Result<(BytesView<'a>, BytesView<'a>), BlockchainError>> + 'a, BlockchainError> {
         trace!("iter raw {:?}", column);
 
-        let cf = cf_handle!(db, column);
-        let (m, opts) = mode.convert();
-        let iterator = db.iterator_cf_opt(&cf, opts, m);
+        match db {
+            InnerDB::RocksDB(db) => {
+                let cf = cf_handle!(db, column);
+                let (m, opts) = mode.convert();
+                let iterator = db.iterator_cf_opt(&cf, opts, m);
+
+                match snapshot {
+                    Some(snapshot) => Ok(Either::Left(Either::Left(snapshot.lazy_iter_raw(column, mode, iterator)))),
+                    None => {
+                        Ok(Either::Left(Either::Right(iterator.map(|res| {
+                            let (key, value) = res.context("Internal read error in iter raw")?;
+                            Ok((key.into(), value.into()))
+                        }))))
+                    }
+                }
+            },
+            InnerDB::Memory(db) => {
+                let iterator = db.iter(column, mode);
 
-        match snapshot {
-            Some(snapshot) => Ok(Either::Left(snapshot.lazy_iter_raw(column, mode, iterator))),
-            None => {
-                Ok(Either::Right(iterator.map(|res| {
-                    let (key, value) = res.context("Internal read error in iter raw")?;
-                    Ok((key.into(), value.into()))
-                })))
-            } 
+                match snapshot {
+                    Some(snapshot) => Ok(Either::Right(Either::Left(snapshot.lazy_iter_raw(column, mode, iterator.map(Ok::<_, Infallible>))))),
+                    None => Ok(Either::Right(Either::Right(iterator.map(|(key, value)| Ok((key.into(), value.into()))))))
+                }
+            }
         }
     }
 
@@ -498,6 +593,11 @@ impl Storage for RocksStorage {
     async fn get_size_on_disk(&self) -> Result<u64, BlockchainError> {
         let db = Arc::clone(&self.db);
         tokio::task::spawn_blocking(move || {
+            let db = match db.as_ref() {
+                InnerDB::RocksDB(db) => db,
+                InnerDB::Memory(db) => return Ok(db.size())
+            };
+
             let mut size = 0;
             for column in Column::iter() {
                 let cf = cf_handle!(db, column);
@@ -527,11 +627,20 @@ impl Storage for RocksStorage {
 
     // Stop the storage and wait for it to finish
     async fn stop(&mut self) -> Result<(), BlockchainError> {
+        if self.read_only {
+            return Ok(())
+        }
+
         let db = Arc::clone(&self.db);
         // To prevent starving the current async worker,
         // We execute the following on a blocking thread
         // and simply await its result 
         tokio::task::spawn_blocking(move || {
+            let db = match db.as_ref() {
+                InnerDB::RocksDB(db) => db,
+                InnerDB::Memory(_) => return Ok(())
+            };
+
             info!("compacting DB");            
             db.compact_range::<&[u8], &[u8]>(None, None);
             info!("wait for compact");
@@ -550,11 +659,47 @@ impl Storage for RocksStorage {
     // Flush the inner DB after a block being written
     async fn flush(&mut self) -> Result<(), BlockchainError> {
         trace!("flush DB");
-        self.db.flush().context("Error while flushing DB")?;
-        debug!("DB flushed successfully");
+        if self.read_only {
+            return Ok(())
+        }
+
+        if let InnerDB::RocksDB(db) = self.db.as_ref() {
+            db.flush().context("Error while flushing DB")?;
+            debug!("DB flushed successfully");
+        }
 
         Ok(())
     }
+
+    fn get_backend(&self) -> StorageBackend {
+        match self.db.as_ref() {
+            InnerDB::RocksDB(_) => StorageBackend::RocksDB,
+            InnerDB::Memory(_) => StorageBackend::Memory
+        }
+    }
+
+    // Create a RocksDB checkpoint of the DB
+    async fn create_backup(&self, path: &Path) -> Result<(), BlockchainError> {
+        let db = Arc::clone(&self.db);
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let db = match db.as_ref() {
+                InnerDB::RocksDB(db) => db,
+                InnerDB::Memory(_) => return Err(BlockchainError::BackupNotSupported)
+            };
+
+            // The memtables are flushed first and the SST files
+            // are hard linked if the path is on the same filesystem
+            info!("Creating checkpoint at {}", path.display());
+            Checkpoint::new(db)
+                .context("Error while preparing checkpoint")?
+                .create_checkpoint(&path)
+                .context("Error while creating checkpoint")?;
+
+            Ok(())
+        }).await
+            .context("Creating checkpoint")?
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+use itertools::Either;
+use log::trace;
+use dapa_common::{
+    block::TopoHeight,
+    crypto::{Hash, PublicKey},
+    serializer::Skip
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        rocksdb::{
+            AccountId,
+            Column,
+            IteratorMode
+        },
+        snapshot::Direction,
+        types::AccountTransactionEntry,
+        AccountTransactionsProvider,
+        NetworkProvider,
+        RocksStorage
+    }
+};
+
+#[async_trait]
+impl AccountTransactionsProvider for RocksStorage {
+    async fn add_account_transaction(&mut self, key: &PublicKey, topoheight: TopoHeight, tx: &Hash, entry: &AccountTransactionEntry) -> Result<(), BlockchainError> {
+        trace!("add account transaction {} for {} at topoheight {}", tx, key.as_address(self.is_mainnet()), topoheight);
+        let account_id = self.get_account_id(key)?;
+
+        let key = Self::get_account_transaction_key(account_id, topoheight, tx);
+        self.insert_into_disk(Column::AccountTransactions, &key, entry)?;
+
+        let key = Self::get_prefixed_account_transaction_key(account_id, topoheight, tx);
+        self.insert_into_disk(Column::PrefixedAccountTransactions, &key, &())
+    }
+
+    async fn get_account_transactions<'a>(&'a self, key: &PublicKey, minimum_topoheight: TopoHeight, maximum_topoheight: TopoHeight, after: Option<(TopoHeight, Hash)>) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, AccountTransactionEntry), BlockchainError>> + 'a, BlockchainError> {
+        trace!("get account transactions for {} from {} to {} after {:?}", key.as_address(self.is_mainnet()), minimum_topoheight, maximum_topoheight, after);
+        let Some(account_id) = self.get_optional_account_id(key)? else {
+            return Ok(Either::Left(std::iter::empty()))
+        };
+
+        let mut min = account_id.to_be_bytes().to_vec();
+        min.extend_from_slice(&minimum_topoheight.to_be_bytes());
+
+        let mut max = account_id.to_be_bytes().to_vec();
+        max.extend_from_slice(&maximum_topoheight.saturating_add(1).to_be_bytes());
+
+        // Keys are ordered like the cursor, stop right before it
+        let cursor = after.as_ref().map(|(topoheight, tx)| Self::get_account_transaction_key(account_id, *topoheight, tx).to_vec());
+        let upper_bound = match cursor {
+            Some(cursor) if cursor < max => cursor,
+            _ => max
+        };
+
+        let iter = self.iter::<Skip<8, (TopoHeight, Hash)>, AccountTransactionEntry>(Column::AccountTransactions, IteratorMode::Range {
+            lower_bound: &min,
+            upper_bound: &upper_bound,
+            direction: Direction::Reverse
+        })?
+            .map(|res| res.map(|(key, entry)| (key.0.0, key.0.1, entry)))
+            .filter(move |res| res.as_ref().map_or(true, |(topoheight, tx, _)| {
+                *topoheight >= minimum_topoheight
+                    && *topoheight <= maximum_topoheight
+                    && after.as_ref().map_or(true, |(after_topoheight, after_tx)| (topoheight, tx) < (after_topoheight, after_tx))
+            }));
+
+        Ok(Either::Right(iter))
+    }
+}
+
+impl RocksStorage {
+    // Generate the key used by the account transactions index
+    pub(super) fn get_account_transaction_key(account_id: AccountId, topoheight: TopoHeight, tx: &Hash) -> [u8; 48] {
+        let mut buf = [0; 48];
+        buf[0..8].copy_from_slice(&account_id.to_be_bytes());
+        buf[8..16].copy_from_slice(&topoheight.to_be_bytes());
+        buf[16..48].copy_from_slice(tx.as_bytes());
+        buf
+    }
+
+    // Generate the reverse key prefixed by the topoheight
+    pub(super) fn get_prefixed_account_transaction_key(account_id: AccountId, topoheight: TopoHeight, tx: &Hash) -> [u8; 48] {
+        let mut buf = [0; 48];
+        buf[0..8].copy_from_slice(&topoheight.to_be_bytes());
+        buf[8..16].copy_from_slice(&account_id.to_be_bytes());
+        buf[16..48].copy_from_slice(tx.as_bytes());
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexSet;
+    use dapa_common::{crypto::KeyPair, network::Network};
+    use crate::core::storage::{AccountProvider, VersionedAccountTransactionsProvider};
+    use super::*;
+
+    #[tokio::test]
+    async fn test_account_transactions_index() {
+        let mut storage = RocksStorage::new_in_memory(Network::Devnet);
+        let key = KeyPair::new().get_public_key().compress();
+        storage.set_account_registration_topoheight(&key, 0).await.unwrap();
+
+        let entry = AccountTransactionEntry {
+            outgoing: true,
+            incoming: false,
+            assets: IndexSet::from([Hash::zero()]),
+        };
+        for topoheight in 1..=5u64 {
+            let tx = Hash::new([topoheight as u8; 32]);
+            storage.add_account_transaction(&key, topoheight, &tx, &entry).await.unwrap();
+        }
+        // Two TXs at the same topoheight
+        storage.add_account_transaction(&key, 3, &Hash::new([10; 32]), &entry).await.unwrap();
+
+        // Newest first and bounded by the range
+        let topoheights = storage.get_account_transactions(&key, 2, 4, None).await.unwrap()
+            .map(|res| res.map(|(topoheight, _, _)| topoheight))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(topoheights, vec![4, 3, 3, 2]);
+
+        // Pages continue right after the cursor, even inside the same topoheight
+        let mut pages = Vec::new();
+        let mut after = None;
+        loop {
+            let page = storage.get_account_transactions(&key, 2, 4, after.clone()).await.unwrap()
+                .take(2)
+                .map(|res| res.map(|(topoheight, tx, _)| (topoheight, tx)))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            let Some(last) = page.last().cloned() else {
+                break;
+            };
+            after = Some(last);
+            pages.push(page);
+        }
+        assert_eq!(pages, vec![
+            vec![(4, Hash::new([4; 32])), (3, Hash::new([10; 32]))],
+            vec![(3, Hash::new([3; 32])), (2, Hash::new([2; 32]))],
+        ]);
+
+        // Rewinding must clean the index
+        storage.delete_versioned_account_transactions_above_topoheight(3).await.unwrap();
+        storage.delete_versioned_account_transactions_at_topoheight(1).await.unwrap();
+        let transactions = storage.get_account_transactions(&key, 0, TopoHeight::MAX, None).await.unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(transactions.len(), 3);
+        assert_eq!(transactions[0].1, Hash::new([10; 32]));
+        assert!(transactions.iter().all(|(_, _, entry)| entry.outgoing && entry.assets.contains(&Hash::zero())));
+    }
+}
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+use log::trace;
+use dapa_common::{
+    contract::RegisteredContractAbi,
+    crypto::Hash
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::{rocksdb::Column, ContractAbiProvider, RocksStorage}
+};
+
+#[async_trait]
+impl ContractAbiProvider for RocksStorage {
+    async fn get_contract_abi(&self, contract: &Hash) -> Result<Option<RegisteredContractAbi>, BlockchainError> {
+        trace!("get contract abi {}", contract);
+        self.load_optional_from_disk(Column::ContractsAbi, contract)
+    }
+
+    async fn set_contract_abi(&mut self, contract: &Hash, abi: &RegisteredContractAbi) -> Result<(), BlockchainError> {
+        trace!("set contract abi {}", contract);
+        self.insert_into_disk(Column::ContractsAbi, contract, abi)
+    }
+}
@@ -0,0 +1,211 @@
+use async_trait::async_trait;
+use itertools::Either;
+use log::trace;
+use dapa_common::{
+    block::TopoHeight,
+    contract::ContractEventKind,
+    crypto::Hash,
+    serializer::Skip
+};
+use xelis_vm::ValueCell;
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        rocksdb::{
+            Column,
+            ContractId,
+            IteratorMode
+        },
+        snapshot::Direction,
+        ContractEventsProvider,
+        IndexedContractEvent,
+        RocksStorage
+    }
+};
+
+#[async_trait]
+impl ContractEventsProvider for RocksStorage {
+    async fn add_contract_event(&mut self, contract: &Hash, event_id: u64, topoheight: TopoHeight, index: u32, kind: ContractEventKind, data: &ValueCell) -> Result<(), BlockchainError> {
+        trace!("add contract {} event {} #{} at topoheight {}", contract, event_id, index, topoheight);
+        let contract_id = self.get_contract_id(contract)?;
+
+        let key = Self::get_contract_event_key(contract_id, topoheight, event_id, index);
+        self.insert_into_disk(Column::ContractEvents, &key, &(kind, data.clone()))?;
+
+        let key = Self::get_contract_event_by_id_key(contract_id, event_id, topoheight, index);
+        self.insert_into_disk(Column::ContractEventsById, &key, &())?;
+
+        let key = Self::get_prefixed_contract_event_key(contract_id, topoheight, event_id, index);
+        self.insert_into_disk(Column::PrefixedContractEvents, &key, &())
+    }
+
+    async fn get_contract_events<'a>(&'a self, contract: &Hash, event_id: Option<u64>, minimum_topoheight: TopoHeight, maximum_topoheight: TopoHeight, after: Option<(TopoHeight, u64, u32)>) -> Result<impl Iterator<Item = Result<IndexedContractEvent, BlockchainError>> + 'a, BlockchainError> {
+        trace!("get contract {} events {:?} from {} to {} after {:?}", contract, event_id, minimum_topoheight, maximum_topoheight, after);
+        let Some(contract_id) = self.get_optional_contract_id(contract)? else {
+            return Ok(Either::Left(std::iter::empty()))
+        };
+
+        // Both keys layouts start with the contract id, followed by the event id when filtering by id
+        let mut prefix = contract_id.to_be_bytes().to_vec();
+        if let Some(id) = event_id {
+            prefix.extend_from_slice(&id.to_be_bytes());
+        }
+
+        let mut min = prefix.clone();
+        min.extend_from_slice(&minimum_topoheight.to_be_bytes());
+
+        let mut max = prefix;
+        max.extend_from_slice(&maximum_topoheight.saturating_add(1).to_be_bytes());
+
+        let filter = move |event: &IndexedContractEvent| {
+            event.topoheight >= minimum_topoheight
+                && event.topoheight <= maximum_topoheight
+                && after.map_or(true, |after| (event.topoheight, event.event_id, event.index) > after)
+        };
+
+        let iter = match event_id {
+            Some(id) => {
+                // Start directly from the topoheight of the cursor, the remaining events are filtered
+                if let Some((topoheight, _, _)) = after.filter(|(topoheight, _, _)| *topoheight > minimum_topoheight) {
+                    min.truncate(16);
+                    min.extend_from_slice(&topoheight.to_be_bytes());
+                }
+
+                let iter = self.iter_keys::<Skip<16, (TopoHeight, u32)>>(Column::ContractEventsById, IteratorMode::Range {
+                    lower_bound: &min,
+                    upper_bound: &max,
+                    direction: Direction::Forward
+                })?
+                    .map(move |res| {
+                        let (topoheight, index) = res?.0;
+                        let key = Self::get_contract_event_key(contract_id, topoheight, id, index);
+                        let (kind, data): (ContractEventKind, ValueCell) = self.load_from_disk(Column::ContractEvents, &key)?;
+                        Ok(IndexedContractEvent { topoheight, event_id: id, index, kind, data })
+                    });
+
+                Either::Left(iter)
+            },
+            None => {
+                // Keys are ordered like the cursor, start right after it
+                let cursor = after.map(|(topoheight, event_id, index)| {
+                    let mut key = Self::get_contract_event_key(contract_id, topoheight, event_id, index).to_vec();
+                    key.push(0);
+                    key
+                });
+                let lower_bound = match cursor {
+                    Some(cursor) if cursor > min => cursor,
+                    _ => min
+                };
+
+                let iter = self.iter::<Skip<8, (TopoHeight, u64, u32)>, (ContractEventKind, ValueCell)>(Column::ContractEvents, IteratorMode::Range {
+                    lower_bound: &lower_bound,
+                    upper_bound: &max,
+                    direction: Direction::Forward
+                })?
+                    .map(|res| res.map(|(key, (kind, data))| {
+                        let (topoheight, event_id, index) = key.0;
+                        IndexedContractEvent { topoheight, event_id, index, kind, data }
+                    }));
+
+                Either::Right(iter)
+            }
+        };
+
+        Ok(Either::Right(iter.filter(move |res| res.as_ref().map_or(true, filter))))
+    }
+}
+
+impl RocksStorage {
+    // Generate the key used by the contract events index
+    pub(super) fn get_contract_event_key(contract_id: ContractId, topoheight: TopoHeight, event_id: u64, index: u32) -> [u8; 28] {
+        let mut buf = [0; 28];
+        buf[0..8].copy_from_slice(&contract_id.to_be_bytes());
+        buf[8..16].copy_from_slice(&topoheight.to_be_bytes());
+        buf[16..24].copy_from_slice(&event_id.to_be_bytes());
+        buf[24..28].copy_from_slice(&index.to_be_bytes());
+        buf
+    }
+
+    // Generate the key used by the index of the events per id
+    pub(super) fn get_contract_event_by_id_key(contract_id: ContractId, event_id: u64, topoheight: TopoHeight, index: u32) -> [u8; 28] {
+        let mut buf = [0; 28];
+        buf[0..8].copy_from_slice(&contract_id.to_be_bytes());
+        buf[8..16].copy_from_slice(&event_id.to_be_bytes());
+        buf[16..24].copy_from_slice(&topoheight.to_be_bytes());
+        buf[24..28].copy_from_slice(&index.to_be_bytes());
+        buf
+    }
+
+    // Generate the reverse key prefixed by the topoheight
+    pub(super) fn get_prefixed_contract_event_key(contract_id: ContractId, topoheight: TopoHeight, event_id: u64, index: u32) -> [u8; 28] {
+        let mut buf = [0; 28];
+        buf[0..8].copy_from_slice(&topoheight.to_be_bytes());
+        buf[8..16].copy_from_slice(&contract_id.to_be_bytes());
+        buf[16..24].copy_from_slice(&event_id.to_be_bytes());
+        buf[24..28].copy_from_slice(&index.to_be_bytes());
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_vm::Primitive;
+    use dapa_common::network::Network;
+    use crate::core::storage::{ContractProvider, VersionedContractEventsProvider, VersionedContractModule};
+    use super::*;
+
+    #[tokio::test]
+    async fn test_contract_events_index() {
+        let mut storage = RocksStorage::new_in_memory(Network::Devnet);
+        let contract = Hash::new([1u8; 32]);
+        storage.set_last_contract_to(&contract, 0, &VersionedContractModule::new(None, None)).await.unwrap();
+
+        for topoheight in 1..=5u64 {
+            for index in 0..2u32 {
+                let data = ValueCell::from(Primitive::U64(topoheight * 10 + index as u64));
+                storage.add_contract_event(&contract, topoheight % 2, topoheight, index, ContractEventKind::Rpc, &data).await.unwrap();
+            }
+        }
+
+        // Oldest first and bounded by the range
+        let events = storage.get_contract_events(&contract, None, 2, 3, None).await.unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0], IndexedContractEvent { topoheight: 2, event_id: 0, index: 0, kind: ContractEventKind::Rpc, data: ValueCell::from(Primitive::U64(20)) });
+        assert_eq!(events[1].data, ValueCell::from(Primitive::U64(21)));
+        assert_eq!((events[3].topoheight, events[3].event_id, events[3].index), (3, 1, 1));
+
+        // Resume after the second event
+        let events = storage.get_contract_events(&contract, None, 2, 3, Some((2, 0, 1))).await.unwrap()
+            .map(|res| res.map(|event| (event.topoheight, event.index)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(events, vec![(3, 0), (3, 1)]);
+
+        // Filter by id using the index
+        let events = storage.get_contract_events(&contract, Some(1), 0, TopoHeight::MAX, Some((3, 1, 0))).await.unwrap()
+            .map(|res| res.map(|event| (event.topoheight, event.event_id, event.index)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(events, vec![(3, 1, 1), (5, 1, 0), (5, 1, 1)]);
+
+        // Unknown contract has no events
+        assert_eq!(storage.get_contract_events(&Hash::zero(), None, 0, TopoHeight::MAX, None).await.unwrap().count(), 0);
+
+        // Rewinding must clean the index
+        storage.delete_versioned_contract_events_above_topoheight(3).await.unwrap();
+        storage.delete_versioned_contract_events_at_topoheight(1).await.unwrap();
+        let topoheights = storage.get_contract_events(&contract, None, 0, TopoHeight::MAX, None).await.unwrap()
+            .map(|res| res.map(|event| event.topoheight))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(topoheights, vec![2, 2, 3, 3]);
+
+        let topoheights = storage.get_contract_events(&contract, Some(1), 0, TopoHeight::MAX, None).await.unwrap()
+            .map(|res| res.map(|event| event.topoheight))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(topoheights, vec![3, 3]);
+    }
+}
@@ -4,6 +4,8 @@ mod balance;
 mod r#impl;
 mod scheduled_execution;
 mod event_callback;
+mod events;
+mod abi;
 
 use anyhow::Context;
 use async_trait::async_trait;
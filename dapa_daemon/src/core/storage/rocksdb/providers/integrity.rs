@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use async_trait::async_trait;
+use log::{debug, trace};
+use dapa_common::{
+    block::TopoHeight,
+    crypto::{Hash, PublicKey},
+    serializer::Serializer
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        rocksdb::{Account, Asset, Column, Contract, IteratorMode},
+        types::{IntegrityIssue, IntegrityReport},
+        IntegrityProvider,
+        RocksStorage
+    }
+};
+
+#[async_trait]
+impl IntegrityProvider for RocksStorage {
+    async fn check_indexes_integrity(&mut self, repair: bool, report: &mut IntegrityReport) -> Result<(), BlockchainError> {
+        trace!("check indexes integrity");
+
+        self.check_reverse_index::<PublicKey, Account>(Column::Account, Column::AccountById, |account| account.id, repair, report)?;
+        self.check_reverse_index::<Hash, Asset>(Column::Assets, Column::AssetById, |asset| asset.id, repair, report)?;
+        self.check_reverse_index::<Hash, Contract>(Column::Contracts, Column::ContractById, |contract| contract.id, repair, report)?;
+
+        self.check_inner_pointers(report)?;
+
+        for (pointers, versions) in [
+            (Column::Balances, Column::VersionedBalances),
+            (Column::ContractsBalances, Column::VersionedContractsBalances),
+            (Column::ContractsData, Column::VersionedContractsData),
+            (Column::ContractEventCallbacks, Column::VersionedContractEventCallbacks)
+        ] {
+            self.check_versions_pointers(pointers, versions, repair, report)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl RocksStorage {
+    // Verify a reverse index {id} => {key} against its source column {key} => {value}
+    // Invalid entries are deleted and missing ones are rebuilt from the source column
+    fn check_reverse_index<K, V>(&mut self, source: Column, index: Column, id_of: fn(&V) -> u64, repair: bool, report: &mut IntegrityReport) -> Result<(), BlockchainError>
+    where
+        K: Serializer + PartialEq + 'static,
+        V: Serializer + 'static
+    {
+        debug!("Checking {:?} index against {:?}", index, source);
+
+        let mut invalid = Vec::new();
+        for res in self.iter::<u64, K>(index, IteratorMode::Start)? {
+            let (id, key) = res?;
+            let valid = self.load_optional_from_disk::<_, V>(source, &key.to_bytes())?
+                .is_some_and(|value| id_of(&value) == id);
+
+            if !valid {
+                invalid.push(id);
+            }
+        }
+
+        for id in invalid {
+            if repair {
+                self.remove_from_disk(index, id.to_be_bytes())?;
+            }
+            report.add(IntegrityIssue::InvalidIndex { index: format!("{:?}", index), key: id.to_string() }, repair);
+        }
+
+        let mut missing = Vec::new();
+        for res in self.iter::<K, V>(source, IteratorMode::Start)? {
+            let (key, value) = res?;
+            let id = id_of(&value);
+            let indexed = self.load_optional_from_disk::<_, K>(index, &id.to_be_bytes())?
+                .is_some_and(|indexed| indexed == key);
+
+            if !indexed {
+                missing.push((id, key));
+            }
+        }
+
+        for (id, key) in missing {
+            if repair {
+                self.insert_into_disk(index, id.to_be_bytes(), &key)?;
+            }
+            report.add(IntegrityIssue::MissingIndex { index: format!("{:?}", index), key: hex::encode(key.to_bytes()) }, repair);
+        }
+
+        Ok(())
+    }
+
+    // Verify the pointers stored inside the accounts, assets and contracts entries
+    // They can't be rebuilt safely, so they are only reported
+    fn check_inner_pointers(&self, report: &mut IntegrityReport) -> Result<(), BlockchainError> {
+        debug!("Checking accounts, assets and contracts pointers");
+
+        for res in self.iter::<PublicKey, Account>(Column::Account, IteratorMode::Start)? {
+            let (key, account) = res?;
+            for (versions, pointer) in [(Column::VersionedNonces, account.nonce_pointer), (Column::VersionedMultisig, account.multisig_pointer)] {
+                if let Some(topoheight) = pointer {
+                    if !self.contains_data(versions, &Self::get_versioned_account_key(account.id, topoheight))? {
+                        report.add(IntegrityIssue::DanglingPointer { index: format!("{:?}", versions), key: hex::encode(key.as_bytes()), topoheight }, false);
+                    }
+                }
+            }
+        }
+
+        for res in self.iter::<Hash, Asset>(Column::Assets, IteratorMode::Start)? {
+            let (hash, asset) = res?;
+            for (versions, pointer) in [(Column::VersionedAssets, asset.data_pointer), (Column::VersionedAssetsSupply, asset.supply_pointer)] {
+                if let Some(topoheight) = pointer {
+                    if !self.contains_data(versions, &Self::get_asset_versioned_key(topoheight, asset.id))? {
+                        report.add(IntegrityIssue::DanglingPointer { index: format!("{:?}", versions), key: hash.to_hex(), topoheight }, false);
+                    }
+                }
+            }
+        }
+
+        for res in self.iter::<Hash, Contract>(Column::Contracts, IteratorMode::Start)? {
+            let (hash, contract) = res?;
+            if let Some(topoheight) = contract.module_pointer {
+                if !self.contains_data(Column::VersionedContracts, &Self::get_versioned_contract_key(contract.id, topoheight))? {
+                    report.add(IntegrityIssue::DanglingPointer { index: format!("{:?}", Column::VersionedContracts), key: hash.to_hex(), topoheight }, false);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Verify that each pointer {key} => {topoheight} references an existing {topoheight}{key} version
+    // Dangling pointers are moved to the highest version available or deleted if there is none
+    fn check_versions_pointers(&mut self, pointers: Column, versions: Column, repair: bool, report: &mut IntegrityReport) -> Result<(), BlockchainError> {
+        debug!("Checking {:?} pointers against {:?}", pointers, versions);
+
+        let mut dangling = BTreeMap::new();
+        for res in Self::iter_raw_internal(&self.db, self.snapshot.as_ref(), IteratorMode::Start, pointers)? {
+            let (key, value) = res?;
+            let topoheight = TopoHeight::from_bytes(&value)?;
+
+            let mut versioned_key = topoheight.to_be_bytes().to_vec();
+            versioned_key.extend_from_slice(&key);
+            if !self.contains_data(versions, &versioned_key)? {
+                dangling.insert(key.to_vec(), topoheight);
+            }
+        }
+
+        if dangling.is_empty() {
+            return Ok(())
+        }
+
+        // Search the highest version of each dangling pointer
+        let mut latest: BTreeMap<Vec<u8>, TopoHeight> = BTreeMap::new();
+        if repair {
+            for res in Self::iter_raw_internal(&self.db, self.snapshot.as_ref(), IteratorMode::Start, versions)? {
+                let (key, _) = res?;
+                let topoheight = TopoHeight::from_bytes(&key[0..8])?;
+                let pointer_key = &key[8..];
+                if dangling.contains_key(pointer_key) {
+                    let entry = latest.entry(pointer_key.to_vec()).or_default();
+                    *entry = topoheight.max(*entry);
+                }
+            }
+        }
+
+        for (key, topoheight) in dangling {
+            if repair {
+                match latest.get(&key) {
+                    Some(last) => self.insert_into_disk(pointers, &key, last)?,
+                    None => self.remove_from_disk(pointers, &key)?
+                };
+            }
+            report.add(IntegrityIssue::DanglingPointer { index: format!("{:?}", pointers), key: hex::encode(&key), topoheight }, repair);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dapa_common::{crypto::KeyPair, network::Network};
+    use crate::core::storage::AccountProvider;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_indexes_integrity() {
+        let mut storage = RocksStorage::new_in_memory(Network::Devnet);
+        let key = KeyPair::new().get_public_key().compress();
+        storage.set_account_registration_topoheight(&key, 0).await.unwrap();
+        let account_id = storage.get_account_id(&key).unwrap();
+
+        // Missing reverse index, a pointer without any version
+        // and a pointer above its last version
+        storage.remove_from_disk(Column::AccountById, account_id.to_be_bytes()).unwrap();
+        storage.insert_into_disk(Column::Balances, [0u8; 16], &5u64).unwrap();
+        storage.insert_into_disk(Column::Balances, [1u8; 16], &5u64).unwrap();
+        let mut versioned_key = 3u64.to_be_bytes().to_vec();
+        versioned_key.extend_from_slice(&[1u8; 16]);
+        storage.insert_into_disk(Column::VersionedBalances, versioned_key, &()).unwrap();
+
+        let mut report = IntegrityReport::default();
+        storage.check_indexes_integrity(true, &mut report).await.unwrap();
+        assert_eq!(report.entries.len(), 3);
+        assert_eq!(report.count_repaired(), 3);
+
+        assert!(storage.load_from_disk::<_, PublicKey>(Column::AccountById, &account_id.to_be_bytes()).unwrap() == key);
+        assert!(!storage.contains_data(Column::Balances, &[0u8; 16]).unwrap());
+        assert_eq!(storage.load_from_disk::<_, TopoHeight>(Column::Balances, &[1u8; 16]).unwrap(), 3);
+
+        // Everything must be consistent once repaired
+        let mut report = IntegrityReport::default();
+        storage.check_indexes_integrity(false, &mut report).await.unwrap();
+        assert!(report.is_clean());
+    }
+}
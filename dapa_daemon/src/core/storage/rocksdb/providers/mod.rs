@@ -20,4 +20,7 @@ mod nonce;
 mod state;
 mod multisig;
 mod contract;
-mod versioned;
\ No newline at end of file
+mod versioned;
+mod account_transactions;
+mod integrity;
+mod raw_versioned;
\ No newline at end of file
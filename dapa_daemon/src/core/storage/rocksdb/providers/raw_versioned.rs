@@ -0,0 +1,110 @@
+use async_trait::async_trait;
+use log::trace;
+use xelis_vm::ValueCell;
+use dapa_common::{
+    account::{VersionedBalance, VersionedNonce},
+    asset::VersionedAssetData,
+    block::TopoHeight,
+    crypto::{Hash, PublicKey}
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        rocksdb::{AccountId, AssetId, Column, ContractDataId, ContractId, IteratorMode},
+        RawVersionedProvider,
+        RocksStorage,
+        VersionedContractBalance,
+        VersionedContractData,
+        VersionedContractModule,
+        VersionedEventCallbackRegistration,
+        VersionedMultiSig,
+        VersionedSupply
+    }
+};
+
+// Versioned columns are keyed by {topoheight}{ids}
+// The ids are resolved back to their hashes and keys
+#[async_trait]
+impl RawVersionedProvider for RocksStorage {
+    async fn iter_versioned_nonces<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, PublicKey, VersionedNonce), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned nonces");
+        Ok(self.iter::<(TopoHeight, AccountId), VersionedNonce>(Column::VersionedNonces, IteratorMode::Start)?
+            .map(move |res| {
+                let ((topoheight, account_id), version) = res?;
+                Ok((topoheight, self.get_account_key_from_id(account_id)?, version))
+            }))
+    }
+
+    async fn iter_versioned_multisigs<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, PublicKey, VersionedMultiSig<'static>), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned multisigs");
+        Ok(self.iter::<(TopoHeight, AccountId), VersionedMultiSig<'static>>(Column::VersionedMultisig, IteratorMode::Start)?
+            .map(move |res| {
+                let ((topoheight, account_id), version) = res?;
+                Ok((topoheight, self.get_account_key_from_id(account_id)?, version))
+            }))
+    }
+
+    async fn iter_versioned_balances<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, PublicKey, Hash, VersionedBalance), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned balances");
+        Ok(self.iter::<(TopoHeight, AccountId, AssetId), VersionedBalance>(Column::VersionedBalances, IteratorMode::Start)?
+            .map(move |res| {
+                let ((topoheight, account_id, asset_id), version) = res?;
+                Ok((topoheight, self.get_account_key_from_id(account_id)?, self.get_asset_hash_from_id(asset_id)?, version))
+            }))
+    }
+
+    async fn iter_versioned_assets<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, VersionedAssetData), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned assets");
+        Ok(self.iter::<(TopoHeight, AssetId), VersionedAssetData>(Column::VersionedAssets, IteratorMode::Start)?
+            .map(move |res| {
+                let ((topoheight, asset_id), version) = res?;
+                Ok((topoheight, self.get_asset_hash_from_id(asset_id)?, version))
+            }))
+    }
+
+    async fn iter_versioned_assets_supply<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, VersionedSupply), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned assets supply");
+        Ok(self.iter::<(TopoHeight, AssetId), VersionedSupply>(Column::VersionedAssetsSupply, IteratorMode::Start)?
+            .map(move |res| {
+                let ((topoheight, asset_id), version) = res?;
+                Ok((topoheight, self.get_asset_hash_from_id(asset_id)?, version))
+            }))
+    }
+
+    async fn iter_versioned_contracts<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, VersionedContractModule<'static>), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned contracts");
+        Ok(self.iter::<(TopoHeight, ContractId), VersionedContractModule<'static>>(Column::VersionedContracts, IteratorMode::Start)?
+            .map(move |res| {
+                let ((topoheight, contract_id), version) = res?;
+                Ok((topoheight, self.get_contract_from_id(contract_id)?, version))
+            }))
+    }
+
+    async fn iter_versioned_contracts_data<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, ValueCell, VersionedContractData), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned contracts data");
+        Ok(self.iter::<(TopoHeight, ContractId, ContractDataId), VersionedContractData>(Column::VersionedContractsData, IteratorMode::Start)?
+            .map(move |res| {
+                let ((topoheight, contract_id, data_id), version) = res?;
+                let key = self.load_from_disk(Column::ContractDataTableById, &data_id.to_be_bytes())?;
+                Ok((topoheight, self.get_contract_from_id(contract_id)?, key, version))
+            }))
+    }
+
+    async fn iter_versioned_contracts_balances<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, Hash, VersionedContractBalance), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned contracts balances");
+        Ok(self.iter::<(TopoHeight, ContractId, AssetId), VersionedContractBalance>(Column::VersionedContractsBalances, IteratorMode::Start)?
+            .map(move |res| {
+                let ((topoheight, contract_id, asset_id), version) = res?;
+                Ok((topoheight, self.get_contract_from_id(contract_id)?, self.get_asset_hash_from_id(asset_id)?, version))
+            }))
+    }
+
+    async fn iter_versioned_contract_event_callbacks<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, u64, Hash, VersionedEventCallbackRegistration), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned contract event callbacks");
+        Ok(self.iter::<(TopoHeight, ContractId, u64, ContractId), VersionedEventCallbackRegistration>(Column::VersionedContractEventCallbacks, IteratorMode::Start)?
+            .map(move |res| {
+                let ((topoheight, contract_id, event_id, listener_id), version) = res?;
+                Ok((topoheight, self.get_contract_from_id(contract_id)?, event_id, self.get_contract_from_id(listener_id)?, version))
+            }))
+    }
+}
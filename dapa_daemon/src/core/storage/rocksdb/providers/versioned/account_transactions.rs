@@ -0,0 +1,48 @@
+use async_trait::async_trait;
+use log::trace;
+use dapa_common::block::TopoHeight;
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        rocksdb::{Column, IteratorMode},
+        snapshot::Direction,
+        RocksStorage,
+        VersionedAccountTransactionsProvider
+    }
+};
+
+#[async_trait]
+impl VersionedAccountTransactionsProvider for RocksStorage {
+    // delete the account transactions indexed at topoheight
+    async fn delete_versioned_account_transactions_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
+        trace!("delete versioned account transactions at topoheight {}", topoheight);
+        let prefix = topoheight.to_be_bytes();
+        self.delete_account_transactions(IteratorMode::WithPrefix(&prefix, Direction::Forward))
+    }
+
+    // delete the account transactions indexed above topoheight
+    async fn delete_versioned_account_transactions_above_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
+        trace!("delete versioned account transactions above topoheight {}", topoheight);
+        let prefix = (topoheight + 1).to_be_bytes();
+        self.delete_account_transactions(IteratorMode::From(&prefix, Direction::Forward))
+    }
+}
+
+impl RocksStorage {
+    fn delete_account_transactions(&mut self, mode: IteratorMode) -> Result<(), BlockchainError> {
+        let snapshot = self.snapshot.clone();
+        for res in Self::iter_raw_internal(&self.db, snapshot.as_ref(), mode, Column::PrefixedAccountTransactions)? {
+            let (key, _) = res?;
+            Self::remove_from_disk_internal(&self.db, self.snapshot.as_mut(), Column::PrefixedAccountTransactions, &key)?;
+
+            // Swap the topoheight and the account id to build the index key
+            let mut index_key = [0; 48];
+            index_key[0..8].copy_from_slice(&key[8..16]);
+            index_key[8..16].copy_from_slice(&key[0..8]);
+            index_key[16..48].copy_from_slice(&key[16..48]);
+            Self::remove_from_disk_internal(&self.db, self.snapshot.as_mut(), Column::AccountTransactions, &index_key)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use log::trace;
+use dapa_common::block::TopoHeight;
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        rocksdb::{Column, IteratorMode},
+        snapshot::Direction,
+        RocksStorage,
+        VersionedContractEventsProvider
+    }
+};
+
+#[async_trait]
+impl VersionedContractEventsProvider for RocksStorage {
+    // delete the contract events indexed at topoheight
+    async fn delete_versioned_contract_events_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
+        trace!("delete versioned contract events at topoheight {}", topoheight);
+        let prefix = topoheight.to_be_bytes();
+        self.delete_contract_events(IteratorMode::WithPrefix(&prefix, Direction::Forward))
+    }
+
+    // delete the contract events indexed above topoheight
+    async fn delete_versioned_contract_events_above_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
+        trace!("delete versioned contract events above topoheight {}", topoheight);
+        let prefix = (topoheight + 1).to_be_bytes();
+        self.delete_contract_events(IteratorMode::From(&prefix, Direction::Forward))
+    }
+}
+
+impl RocksStorage {
+    fn delete_contract_events(&mut self, mode: IteratorMode) -> Result<(), BlockchainError> {
+        let snapshot = self.snapshot.clone();
+        for res in Self::iter_raw_internal(&self.db, snapshot.as_ref(), mode, Column::PrefixedContractEvents)? {
+            let (key, _) = res?;
+            Self::remove_from_disk_internal(&self.db, self.snapshot.as_mut(), Column::PrefixedContractEvents, &key)?;
+
+            // Swap the topoheight and the contract id to build the index key
+            let mut index_key = [0; 28];
+            index_key[0..8].copy_from_slice(&key[8..16]);
+            index_key[8..16].copy_from_slice(&key[0..8]);
+            index_key[16..28].copy_from_slice(&key[16..28]);
+            Self::remove_from_disk_internal(&self.db, self.snapshot.as_mut(), Column::ContractEvents, &index_key)?;
+
+            // Same for the index by id
+            let mut by_id_key = [0; 28];
+            by_id_key[0..8].copy_from_slice(&key[8..16]);
+            by_id_key[8..16].copy_from_slice(&key[16..24]);
+            by_id_key[16..24].copy_from_slice(&key[0..8]);
+            by_id_key[24..28].copy_from_slice(&key[24..28]);
+            Self::remove_from_disk_internal(&self.db, self.snapshot.as_mut(), Column::ContractEventsById, &by_id_key)?;
+        }
+
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ mod data;
 mod balance;
 mod scheduled_execution;
 mod event_callback;
+mod events;
 
 use log::trace;
 use async_trait::async_trait;
@@ -23,6 +23,7 @@ mod asset;
 mod cache;
 mod dag_order;
 mod asset_supply;
+mod account_transactions;
 
 impl VersionedProvider for RocksStorage {}
 
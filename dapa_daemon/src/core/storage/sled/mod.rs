@@ -4,6 +4,7 @@ mod providers;
 use async_trait::async_trait;
 use itertools::Either;
 use crate::core::{
+    config::StorageBackend,
     error::{BlockchainError, DiskContext},
     storage::snapshot::BytesView
 };
@@ -14,11 +15,12 @@ use dapa_common::{
     network::Network,
     serializer::Serializer,
     transaction::Transaction,
-    tokio::sync::Mutex
+    tokio::{sync::Mutex, task::spawn_blocking}
 };
 use std::{
     hash::Hash as StdHash,
     ops::Deref,
+    path::Path,
     str::FromStr,
     sync::Arc
 };
@@ -188,6 +190,22 @@ pub struct SledStorage {
     // Transactions per contract
     // {contract_hash}{tx_hash} => [empty]
     pub(super) contracts_transactions: Tree,
+    // Archival index of the transactions per account
+    // {account_key}{topoheight}{tx_hash} => {entry}
+    pub(super) account_transactions: Tree,
+    // {topoheight}{account_key}{tx_hash} => [empty]
+    pub(super) account_transactions_prefixed: Tree,
+    // Archival index of the events fired by the contracts
+    // {contract_hash}{topoheight}{event_id}{index} => {kind}{data}
+    pub(super) contract_events: Tree,
+    // Index of the events per id
+    // {contract_hash}{event_id}{topoheight}{index} => [empty]
+    pub(super) contract_events_by_id: Tree,
+    // {topoheight}{contract_hash}{event_id}{index} => [empty]
+    pub(super) contract_events_prefixed: Tree,
+    // ABI registered for a contract
+    // {contract_hash} => {topoheight}{version}{abi}
+    pub(super) contracts_abi: Tree,
 
     // opened DB used for assets to create dynamic assets
     pub(super) db: sled::Db,
@@ -197,6 +215,9 @@ pub struct SledStorage {
 
     // If we have a snapshot, we can use it to rollback
     pub(super) snapshot: Option<Snapshot>,
+
+    // Opened in read-only mode, nothing is migrated nor flushed
+    read_only: bool,
 }
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum, Serialize, Deserialize)]
@@ -235,13 +256,33 @@ impl Into<sled::Mode> for StorageMode {
 
 impl SledStorage {
     pub fn new(dir_path: String, cache_size: Option<usize>, network: Network, internal_cache_size: u64, mode: StorageMode) -> Result<Self, BlockchainError> {
+        Self::open(dir_path, cache_size, network, internal_cache_size, mode, false)
+    }
+
+    // Open an existing DB without writing to it
+    // Sled has no read-only mode: the missing trees are still created,
+    // but the network, the DB version and the migrations are never written
+    // and the DB is never flushed
+    pub fn new_read_only(dir_path: String, cache_size: Option<usize>, network: Network, internal_cache_size: u64, mode: StorageMode) -> Result<Self, BlockchainError> {
+        Self::open(dir_path, cache_size, network, internal_cache_size, mode, true)
+    }
+
+    fn open(dir_path: String, cache_size: Option<usize>, network: Network, internal_cache_size: u64, mode: StorageMode, read_only: bool) -> Result<Self, BlockchainError> {
         let path = format!("{}{}", dir_path, network.to_string().to_lowercase());
-        let config = sled::Config::new()
+        if read_only && !Path::new(&path).exists() {
+            return Err(BlockchainError::StorageNotFound(path))
+        }
+
+        let mut config = sled::Config::new()
             .temporary(false)
             .path(path)
             .cache_capacity(internal_cache_size)
             .mode(mode.into());
 
+        if read_only {
+            config = config.flush_every_ms(None);
+        }
+
         let sled = config.open()?;
 
         let mut storage = Self {
@@ -285,9 +326,16 @@ impl SledStorage {
             versioned_contracts_event_callbacks: sled.open_tree("versioned_contracts_event_callbacks")?,
             versioned_assets_supply: sled.open_tree("versioned_assets_supply")?,
             contracts_transactions: sled.open_tree("contracts_transactions")?,
+            account_transactions: sled.open_tree("account_transactions")?,
+            account_transactions_prefixed: sled.open_tree("account_transactions_prefixed")?,
+            contract_events: sled.open_tree("contract_events")?,
+            contract_events_by_id: sled.open_tree("contract_events_by_id")?,
+            contract_events_prefixed: sled.open_tree("contract_events_prefixed")?,
+            contracts_abi: sled.open_tree("contracts_abi")?,
             db: sled,
             cache: StorageCache::new(cache_size),
             snapshot: None,
+            read_only,
         };
 
         // Verify that we are opening a DB on same network
@@ -297,12 +345,16 @@ impl SledStorage {
             if storage_network != network {
                 return Err(BlockchainError::InvalidNetwork);
             }
+        } else if read_only {
+            return Err(BlockchainError::InvalidNetwork);
         } else {
             storage.set_network(&network)?;
         }
 
-        if let Err(e) = storage.handle_migrations() {
-            error!("Error while migrating database: {}", e);
+        if !read_only {
+            if let Err(e) = storage.handle_migrations() {
+                error!("Error while migrating database: {}", e);
+            }
         }
 
         storage.load_cache_from_disk();
@@ -434,6 +486,32 @@ impl SledStorage {
         }
     }
 
+    // Iter raw over a range of keys
+    // Upper bound is never included
+    pub(super) fn range_raw<'a>(snapshot: Option<&'a Snapshot>, tree: &Tree, lower_bound: &[u8], upper_bound: &[u8]) -> impl Iterator<Item = Result<(BytesView<'a>, BytesView<'a>), BlockchainError>> + 'a {
+        let iter = tree.range(lower_bound..upper_bound);
+        match snapshot {
+            Some(snapshot) => Either::Left(snapshot.lazy_iter_raw(tree.into(), IteratorMode::Range { lower_bound, upper_bound, direction: Direction::Forward }, iter)),
+            None => Either::Right(iter.map(|res| {
+                let (k, v) = res?;
+                Ok((k.into(), v.into()))
+            }))
+        }
+    }
+
+    // Iter raw over a range of keys in reverse order
+    // Upper bound is never included
+    pub(super) fn range_raw_reverse<'a>(snapshot: Option<&'a Snapshot>, tree: &Tree, lower_bound: &[u8], upper_bound: &[u8]) -> impl Iterator<Item = Result<(BytesView<'a>, BytesView<'a>), BlockchainError>> + 'a {
+        let iter = tree.range(lower_bound..upper_bound).rev();
+        match snapshot {
+            Some(snapshot) => Either::Left(snapshot.lazy_iter_raw(tree.into(), IteratorMode::Range { lower_bound, upper_bound, direction: Direction::Reverse }, iter)),
+            None => Either::Right(iter.map(|res| {
+                let (k, v) = res?;
+                Ok((k.into(), v.into()))
+            }))
+        }
+    }
+
     // Scan prefix
     pub(super) fn scan_prefix<'a, K: Serializer + 'a, V: Serializer + 'a>(snapshot: Option<&'a Snapshot>, tree: &Tree, prefix: &[u8]) -> impl Iterator<Item = Result<(K, V), BlockchainError>> + 'a {
         Self::scan_prefix_raw(snapshot, tree, prefix).map(|res| {
@@ -783,6 +861,10 @@ impl Storage for SledStorage {
 
     async fn stop(&mut self) -> Result<(), BlockchainError> {
         info!("Stopping Storage...");
+        if self.read_only {
+            return Ok(())
+        }
+
         info!("Flushing Sled database");
         self.db.flush_async().await?;
         info!("Sled database flushed");
@@ -807,8 +889,41 @@ impl Storage for SledStorage {
 
     async fn flush(&mut self) -> Result<(), BlockchainError> {
         trace!("flush sled");
+        if self.read_only {
+            return Ok(())
+        }
+
         let n = self.db.flush_async().await?;
         debug!("Flushed {} bytes", n);
         Ok(())
     }
+
+    fn get_backend(&self) -> StorageBackend {
+        StorageBackend::Sled
+    }
+
+    // Copy all the trees into a new sled DB
+    async fn create_backup(&self, path: &Path) -> Result<(), BlockchainError> {
+        let db = self.db.clone();
+        let path = path.to_path_buf();
+        spawn_blocking(move || {
+            info!("Copying sled database to {}", path.display());
+            let backup = sled::Config::new()
+                .path(&path)
+                .open()?;
+
+            for name in db.tree_names() {
+                debug!("Copying tree {}", String::from_utf8_lossy(&name));
+                let source = db.open_tree(&name)?;
+                let target = backup.open_tree(&name)?;
+                for el in source.iter() {
+                    let (key, value) = el?;
+                    target.insert(key, value)?;
+                }
+            }
+
+            backup.flush()?;
+            Ok(())
+        }).await?
+    }
 }
\ No newline at end of file
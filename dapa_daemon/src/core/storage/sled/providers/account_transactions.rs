@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use log::trace;
+use dapa_common::{
+    block::TopoHeight,
+    crypto::{Hash, PublicKey, HASH_SIZE},
+    serializer::{Serializer, Skip}
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        types::AccountTransactionEntry,
+        AccountTransactionsProvider,
+        NetworkProvider,
+        SledStorage
+    }
+};
+
+#[async_trait]
+impl AccountTransactionsProvider for SledStorage {
+    async fn add_account_transaction(&mut self, key: &PublicKey, topoheight: TopoHeight, tx: &Hash, entry: &AccountTransactionEntry) -> Result<(), BlockchainError> {
+        trace!("add account transaction {} for {} at topoheight {}", tx, key.as_address(self.is_mainnet()), topoheight);
+        let index_key = Self::get_account_transaction_key(key, topoheight, tx);
+        Self::insert_into_disk(self.snapshot.as_mut(), &self.account_transactions, &index_key, entry.to_bytes())?;
+
+        let prefixed_key = Self::get_prefixed_account_transaction_key(key, topoheight, tx);
+        Self::insert_into_disk(self.snapshot.as_mut(), &self.account_transactions_prefixed, &prefixed_key, &[])?;
+
+        Ok(())
+    }
+
+    async fn get_account_transactions<'a>(&'a self, key: &PublicKey, minimum_topoheight: TopoHeight, maximum_topoheight: TopoHeight, after: Option<(TopoHeight, Hash)>) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, AccountTransactionEntry), BlockchainError>> + 'a, BlockchainError> {
+        trace!("get account transactions for {} from {} to {} after {:?}", key.as_address(self.is_mainnet()), minimum_topoheight, maximum_topoheight, after);
+        let mut min = key.as_bytes().to_vec();
+        min.extend_from_slice(&minimum_topoheight.to_be_bytes());
+
+        let mut max = key.as_bytes().to_vec();
+        max.extend_from_slice(&maximum_topoheight.saturating_add(1).to_be_bytes());
+
+        // Keys are ordered like the cursor, stop right before it
+        let cursor = after.as_ref().map(|(topoheight, tx)| Self::get_account_transaction_key(key, *topoheight, tx).to_vec());
+        let upper_bound = match cursor {
+            Some(cursor) if cursor < max => cursor,
+            _ => max
+        };
+
+        Ok(Self::range_raw_reverse(self.snapshot.as_ref(), &self.account_transactions, &min, &upper_bound)
+            .map(|res| {
+                let (key, value) = res?;
+                let Skip((topoheight, tx)) = Skip::<HASH_SIZE, (TopoHeight, Hash)>::from_bytes(&key)?;
+                let entry = AccountTransactionEntry::from_bytes(&value)?;
+
+                Ok((topoheight, tx, entry))
+            })
+            .filter(move |res| res.as_ref().map_or(true, |(topoheight, tx, _)| {
+                *topoheight >= minimum_topoheight
+                    && *topoheight <= maximum_topoheight
+                    && after.as_ref().map_or(true, |(after_topoheight, after_tx)| (topoheight, tx) < (after_topoheight, after_tx))
+            }))
+        )
+    }
+}
+
+impl SledStorage {
+    // Generate the key used by the account transactions index
+    pub(super) fn get_account_transaction_key(key: &PublicKey, topoheight: TopoHeight, tx: &Hash) -> [u8; 72] {
+        let mut buf = [0; 72];
+        buf[0..32].copy_from_slice(key.as_bytes());
+        buf[32..40].copy_from_slice(&topoheight.to_be_bytes());
+        buf[40..72].copy_from_slice(tx.as_bytes());
+        buf
+    }
+
+    // Generate the reverse key prefixed by the topoheight
+    pub(super) fn get_prefixed_account_transaction_key(key: &PublicKey, topoheight: TopoHeight, tx: &Hash) -> [u8; 72] {
+        let mut buf = [0; 72];
+        buf[0..8].copy_from_slice(&topoheight.to_be_bytes());
+        buf[8..40].copy_from_slice(key.as_bytes());
+        buf[40..72].copy_from_slice(tx.as_bytes());
+        buf
+    }
+}
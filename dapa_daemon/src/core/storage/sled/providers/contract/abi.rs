@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+use log::trace;
+use dapa_common::{
+    contract::RegisteredContractAbi,
+    crypto::Hash,
+    serializer::Serializer
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::{ContractAbiProvider, SledStorage}
+};
+
+#[async_trait]
+impl ContractAbiProvider for SledStorage {
+    async fn get_contract_abi(&self, contract: &Hash) -> Result<Option<RegisteredContractAbi>, BlockchainError> {
+        trace!("get contract abi {}", contract);
+        self.load_optional_from_disk(&self.contracts_abi, contract.as_bytes())
+    }
+
+    async fn set_contract_abi(&mut self, contract: &Hash, abi: &RegisteredContractAbi) -> Result<(), BlockchainError> {
+        trace!("set contract abi {}", contract);
+        Self::insert_into_disk(self.snapshot.as_mut(), &self.contracts_abi, contract.as_bytes(), abi.to_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use async_trait::async_trait;
+use itertools::Either;
+use log::trace;
+use dapa_common::{
+    block::TopoHeight,
+    contract::ContractEventKind,
+    crypto::{Hash, HASH_SIZE},
+    serializer::{Serializer, Skip}
+};
+use xelis_vm::ValueCell;
+use crate::core::{
+    error::{BlockchainError, DiskContext},
+    storage::{
+        ContractEventsProvider,
+        IndexedContractEvent,
+        SledStorage
+    }
+};
+
+#[async_trait]
+impl ContractEventsProvider for SledStorage {
+    async fn add_contract_event(&mut self, contract: &Hash, event_id: u64, topoheight: TopoHeight, index: u32, kind: ContractEventKind, data: &ValueCell) -> Result<(), BlockchainError> {
+        trace!("add contract {} event {} #{} at topoheight {}", contract, event_id, index, topoheight);
+        let key = Self::get_contract_event_key(contract, topoheight, event_id, index);
+        Self::insert_into_disk(self.snapshot.as_mut(), &self.contract_events, &key, (kind, data.clone()).to_bytes())?;
+
+        let by_id_key = Self::get_contract_event_by_id_key(contract, event_id, topoheight, index);
+        Self::insert_into_disk(self.snapshot.as_mut(), &self.contract_events_by_id, &by_id_key, &[])?;
+
+        let prefixed_key = Self::get_prefixed_contract_event_key(contract, topoheight, event_id, index);
+        Self::insert_into_disk(self.snapshot.as_mut(), &self.contract_events_prefixed, &prefixed_key, &[])?;
+
+        Ok(())
+    }
+
+    async fn get_contract_events<'a>(&'a self, contract: &Hash, event_id: Option<u64>, minimum_topoheight: TopoHeight, maximum_topoheight: TopoHeight, after: Option<(TopoHeight, u64, u32)>) -> Result<impl Iterator<Item = Result<IndexedContractEvent, BlockchainError>> + 'a, BlockchainError> {
+        trace!("get contract {} events {:?} from {} to {} after {:?}", contract, event_id, minimum_topoheight, maximum_topoheight, after);
+        // Both keys layouts start with the contract hash, followed by the event id when filtering by id
+        let mut prefix = contract.as_bytes().to_vec();
+        if let Some(id) = event_id {
+            prefix.extend_from_slice(&id.to_be_bytes());
+        }
+
+        let mut min = prefix.clone();
+        min.extend_from_slice(&minimum_topoheight.to_be_bytes());
+
+        let mut max = prefix;
+        max.extend_from_slice(&maximum_topoheight.saturating_add(1).to_be_bytes());
+
+        let filter = move |event: &IndexedContractEvent| {
+            event.topoheight >= minimum_topoheight
+                && event.topoheight <= maximum_topoheight
+                && after.map_or(true, |after| (event.topoheight, event.event_id, event.index) > after)
+        };
+
+        let contract = contract.clone();
+        let iter = match event_id {
+            Some(id) => {
+                // Start directly from the topoheight of the cursor, the remaining events are filtered
+                if let Some((topoheight, _, _)) = after.filter(|(topoheight, _, _)| *topoheight > minimum_topoheight) {
+                    min.truncate(HASH_SIZE + 8);
+                    min.extend_from_slice(&topoheight.to_be_bytes());
+                }
+
+                let iter = Self::range_raw(self.snapshot.as_ref(), &self.contract_events_by_id, &min, &max)
+                    .map(move |res| {
+                        let (key, _) = res?;
+                        let Skip((topoheight, index)) = Skip::<{ HASH_SIZE + 8 }, (TopoHeight, u32)>::from_bytes(&key)?;
+                        let key = Self::get_contract_event_key(&contract, topoheight, id, index);
+                        let (kind, data): (ContractEventKind, ValueCell) = self.load_from_disk(&self.contract_events, &key, DiskContext::ContractEvent)?;
+
+                        Ok(IndexedContractEvent { topoheight, event_id: id, index, kind, data })
+                    });
+
+                Either::Left(iter)
+            },
+            None => {
+                // Keys are ordered like the cursor, start right after it
+                let cursor = after.map(|(topoheight, event_id, index)| {
+                    let mut key = Self::get_contract_event_key(&contract, topoheight, event_id, index).to_vec();
+                    key.push(0);
+                    key
+                });
+                let lower_bound = match cursor {
+                    Some(cursor) if cursor > min => cursor,
+                    _ => min
+                };
+
+                let iter = Self::range_raw(self.snapshot.as_ref(), &self.contract_events, &lower_bound, &max)
+                    .map(|res| {
+                        let (key, value) = res?;
+                        let Skip((topoheight, event_id, index)) = Skip::<HASH_SIZE, (TopoHeight, u64, u32)>::from_bytes(&key)?;
+                        let (kind, data) = <(ContractEventKind, ValueCell)>::from_bytes(&value)?;
+
+                        Ok(IndexedContractEvent { topoheight, event_id, index, kind, data })
+                    });
+
+                Either::Right(iter)
+            }
+        };
+
+        Ok(iter.filter(move |res| res.as_ref().map_or(true, filter)))
+    }
+}
+
+impl SledStorage {
+    // Generate the key used by the contract events index
+    pub(super) fn get_contract_event_key(contract: &Hash, topoheight: TopoHeight, event_id: u64, index: u32) -> [u8; 52] {
+        let mut buf = [0; 52];
+        buf[0..32].copy_from_slice(contract.as_bytes());
+        buf[32..40].copy_from_slice(&topoheight.to_be_bytes());
+        buf[40..48].copy_from_slice(&event_id.to_be_bytes());
+        buf[48..52].copy_from_slice(&index.to_be_bytes());
+        buf
+    }
+
+    // Generate the key used by the index of the events per id
+    pub(super) fn get_contract_event_by_id_key(contract: &Hash, event_id: u64, topoheight: TopoHeight, index: u32) -> [u8; 52] {
+        let mut buf = [0; 52];
+        buf[0..32].copy_from_slice(contract.as_bytes());
+        buf[32..40].copy_from_slice(&event_id.to_be_bytes());
+        buf[40..48].copy_from_slice(&topoheight.to_be_bytes());
+        buf[48..52].copy_from_slice(&index.to_be_bytes());
+        buf
+    }
+
+    // Generate the reverse key prefixed by the topoheight
+    pub(super) fn get_prefixed_contract_event_key(contract: &Hash, topoheight: TopoHeight, event_id: u64, index: u32) -> [u8; 52] {
+        let mut buf = [0; 52];
+        buf[0..8].copy_from_slice(&topoheight.to_be_bytes());
+        buf[8..40].copy_from_slice(contract.as_bytes());
+        buf[40..48].copy_from_slice(&event_id.to_be_bytes());
+        buf[48..52].copy_from_slice(&index.to_be_bytes());
+        buf
+    }
+}
@@ -4,6 +4,8 @@ mod provider;
 mod balance;
 mod scheduled_execution;
 mod event_callback;
+mod events;
+mod abi;
 
 use async_trait::async_trait;
 use dapa_common::{
@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+use async_trait::async_trait;
+use log::{debug, trace};
+use sled::Tree;
+use dapa_common::{block::TopoHeight, serializer::Serializer};
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        types::{IntegrityIssue, IntegrityReport},
+        IntegrityProvider,
+        SledStorage
+    }
+};
+
+#[async_trait]
+impl IntegrityProvider for SledStorage {
+    // Sled has no reverse index, only the versions pointers are checked
+    async fn check_indexes_integrity(&mut self, repair: bool, report: &mut IntegrityReport) -> Result<(), BlockchainError> {
+        trace!("check indexes integrity");
+
+        for (pointers, versions) in [
+            (self.balances.clone(), self.versioned_balances.clone()),
+            (self.nonces.clone(), self.versioned_nonces.clone()),
+            (self.assets_supply.clone(), self.versioned_assets_supply.clone()),
+            (self.contracts.clone(), self.versioned_contracts.clone()),
+            (self.contracts_balances.clone(), self.versioned_contracts_balances.clone())
+        ] {
+            self.check_versions_pointers(&pointers, &versions, repair, report)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SledStorage {
+    fn contains_raw_data(&self, tree: &Tree, key: &[u8]) -> Result<bool, BlockchainError> {
+        if let Some(snapshot) = self.snapshot.as_ref() {
+            if let Some(v) = snapshot.contains(tree.into(), key) {
+                return Ok(v);
+            }
+        }
+
+        Ok(tree.contains_key(key)?)
+    }
+
+    // Verify that each pointer {key} => {topoheight} references an existing {topoheight}{key} version
+    // Dangling pointers are moved to the highest version available or deleted if there is none
+    fn check_versions_pointers(&mut self, pointers: &Tree, versions: &Tree, repair: bool, report: &mut IntegrityReport) -> Result<(), BlockchainError> {
+        let index = String::from_utf8_lossy(&pointers.name()).into_owned();
+        debug!("Checking {} pointers", index);
+
+        let mut dangling = BTreeMap::new();
+        for res in Self::iter_raw(self.snapshot.as_ref(), pointers) {
+            let (key, value) = res?;
+            let topoheight = TopoHeight::from_bytes(&value)?;
+
+            let versioned_key = Self::get_versioned_key(&key, topoheight);
+            if !self.contains_raw_data(versions, &versioned_key)? {
+                dangling.insert(key.to_vec(), topoheight);
+            }
+        }
+
+        if dangling.is_empty() {
+            return Ok(())
+        }
+
+        // Search the highest version of each dangling pointer
+        let mut latest: BTreeMap<Vec<u8>, TopoHeight> = BTreeMap::new();
+        if repair {
+            for res in Self::iter_raw(self.snapshot.as_ref(), versions) {
+                let (key, _) = res?;
+                let topoheight = TopoHeight::from_bytes(&key[0..8])?;
+                let pointer_key = &key[8..];
+                if dangling.contains_key(pointer_key) {
+                    let entry = latest.entry(pointer_key.to_vec()).or_default();
+                    *entry = topoheight.max(*entry);
+                }
+            }
+        }
+
+        for (key, topoheight) in dangling {
+            if repair {
+                match latest.get(&key) {
+                    Some(last) => {
+                        Self::insert_into_disk(self.snapshot.as_mut(), pointers, &key, &last.to_be_bytes())?;
+                    },
+                    None => {
+                        Self::remove_from_disk_without_reading(self.snapshot.as_mut(), pointers, &key)?;
+                    }
+                };
+            }
+            report.add(IntegrityIssue::DanglingPointer { index: index.clone(), key: hex::encode(&key), topoheight }, repair);
+        }
+
+        Ok(())
+    }
+}
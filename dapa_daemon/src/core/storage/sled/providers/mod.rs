@@ -20,4 +20,7 @@ mod tips_provider;
 mod contract;
 mod versioned;
 mod cache;
-mod state;
\ No newline at end of file
+mod state;
+mod account_transactions;
+mod integrity;
+mod raw_versioned;
\ No newline at end of file
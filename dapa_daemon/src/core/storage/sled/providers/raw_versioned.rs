@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use log::trace;
+use xelis_vm::ValueCell;
+use dapa_common::{
+    account::{VersionedBalance, VersionedNonce},
+    asset::VersionedAssetData,
+    block::TopoHeight,
+    crypto::{Hash, PublicKey}
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        RawVersionedProvider,
+        SledStorage,
+        VersionedContractBalance,
+        VersionedContractData,
+        VersionedContractModule,
+        VersionedEventCallbackRegistration,
+        VersionedMultiSig,
+        VersionedSupply
+    }
+};
+
+// Versioned trees are keyed by {topoheight}{key}
+// except the multisigs one which is keyed by {account}{topoheight}
+#[async_trait]
+impl RawVersionedProvider for SledStorage {
+    async fn iter_versioned_nonces<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, PublicKey, VersionedNonce), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned nonces");
+        Ok(Self::iter::<(TopoHeight, PublicKey), VersionedNonce>(self.snapshot.as_ref(), &self.versioned_nonces)
+            .map(|res| res.map(|((topoheight, key), version)| (topoheight, key, version))))
+    }
+
+    async fn iter_versioned_multisigs<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, PublicKey, VersionedMultiSig<'static>), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned multisigs");
+        Ok(Self::iter::<(PublicKey, TopoHeight), VersionedMultiSig<'static>>(self.snapshot.as_ref(), &self.versioned_multisigs)
+            .map(|res| res.map(|((key, topoheight), version)| (topoheight, key, version))))
+    }
+
+    async fn iter_versioned_balances<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, PublicKey, Hash, VersionedBalance), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned balances");
+        Ok(Self::iter::<(TopoHeight, PublicKey, Hash), VersionedBalance>(self.snapshot.as_ref(), &self.versioned_balances)
+            .map(|res| res.map(|((topoheight, key, asset), version)| (topoheight, key, asset, version))))
+    }
+
+    async fn iter_versioned_assets<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, VersionedAssetData), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned assets");
+        Ok(Self::iter::<(TopoHeight, Hash), VersionedAssetData>(self.snapshot.as_ref(), &self.versioned_assets)
+            .map(|res| res.map(|((topoheight, asset), version)| (topoheight, asset, version))))
+    }
+
+    async fn iter_versioned_assets_supply<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, VersionedSupply), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned assets supply");
+        Ok(Self::iter::<(TopoHeight, Hash), VersionedSupply>(self.snapshot.as_ref(), &self.versioned_assets_supply)
+            .map(|res| res.map(|((topoheight, asset), version)| (topoheight, asset, version))))
+    }
+
+    async fn iter_versioned_contracts<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, VersionedContractModule<'static>), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned contracts");
+        Ok(Self::iter::<(TopoHeight, Hash), VersionedContractModule<'static>>(self.snapshot.as_ref(), &self.versioned_contracts)
+            .map(|res| res.map(|((topoheight, contract), version)| (topoheight, contract, version))))
+    }
+
+    async fn iter_versioned_contracts_data<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, ValueCell, VersionedContractData), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned contracts data");
+        Ok(Self::iter::<(TopoHeight, Hash, ValueCell), VersionedContractData>(self.snapshot.as_ref(), &self.versioned_contracts_data)
+            .map(|res| res.map(|((topoheight, contract, key), version)| (topoheight, contract, key, version))))
+    }
+
+    async fn iter_versioned_contracts_balances<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, Hash, VersionedContractBalance), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned contracts balances");
+        Ok(Self::iter::<(TopoHeight, Hash, Hash), VersionedContractBalance>(self.snapshot.as_ref(), &self.versioned_contracts_balances)
+            .map(|res| res.map(|((topoheight, contract, asset), version)| (topoheight, contract, asset, version))))
+    }
+
+    async fn iter_versioned_contract_event_callbacks<'a>(&'a self) -> Result<impl Iterator<Item = Result<(TopoHeight, Hash, u64, Hash, VersionedEventCallbackRegistration), BlockchainError>> + 'a, BlockchainError> {
+        trace!("iter versioned contract event callbacks");
+        Ok(Self::iter::<(TopoHeight, Hash, u64, Hash), VersionedEventCallbackRegistration>(self.snapshot.as_ref(), &self.versioned_contracts_event_callbacks)
+            .map(|res| res.map(|((topoheight, contract, event_id, listener), version)| (topoheight, contract, event_id, listener, version))))
+    }
+}
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use log::trace;
+use dapa_common::{
+    block::TopoHeight,
+    serializer::Serializer
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::{SledStorage, VersionedAccountTransactionsProvider}
+};
+
+#[async_trait]
+impl VersionedAccountTransactionsProvider for SledStorage {
+    async fn delete_versioned_account_transactions_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
+        trace!("delete versioned account transactions at topoheight {}", topoheight);
+
+        let snapshot = self.snapshot.clone();
+        for el in Self::scan_prefix_raw(snapshot.as_ref(), &self.account_transactions_prefixed, &topoheight.to_be_bytes()) {
+            let (key, _) = el?;
+            self.delete_account_transaction_from_prefixed_key(&key)?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete_versioned_account_transactions_above_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
+        trace!("delete versioned account transactions above topoheight {}", topoheight);
+
+        let snapshot = self.snapshot.clone();
+        for el in Self::iter_raw(snapshot.as_ref(), &self.account_transactions_prefixed) {
+            let (key, _) = el?;
+            let topo = TopoHeight::from_bytes(&key[0..8])?;
+            if topo > topoheight {
+                self.delete_account_transaction_from_prefixed_key(&key)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SledStorage {
+    fn delete_account_transaction_from_prefixed_key(&mut self, key: &[u8]) -> Result<(), BlockchainError> {
+        Self::remove_from_disk_without_reading(self.snapshot.as_mut(), &self.account_transactions_prefixed, key)?;
+
+        // Swap the topoheight and the account key to build the index key
+        let mut index_key = [0; 72];
+        index_key[0..32].copy_from_slice(&key[8..40]);
+        index_key[32..40].copy_from_slice(&key[0..8]);
+        index_key[40..72].copy_from_slice(&key[40..72]);
+        Self::remove_from_disk_without_reading(self.snapshot.as_mut(), &self.account_transactions, &index_key)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use log::trace;
+use dapa_common::block::TopoHeight;
+use crate::core::{
+    error::BlockchainError,
+    storage::{SledStorage, VersionedContractEventsProvider}
+};
+
+#[async_trait]
+impl VersionedContractEventsProvider for SledStorage {
+    async fn delete_versioned_contract_events_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
+        trace!("delete versioned contract events at topoheight {}", topoheight);
+
+        let snapshot = self.snapshot.clone();
+        for el in Self::scan_prefix_raw(snapshot.as_ref(), &self.contract_events_prefixed, &topoheight.to_be_bytes()) {
+            let (key, _) = el?;
+            self.delete_contract_event_from_prefixed_key(&key)?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete_versioned_contract_events_above_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
+        trace!("delete versioned contract events above topoheight {}", topoheight);
+
+        let snapshot = self.snapshot.clone();
+        for el in Self::range_raw(snapshot.as_ref(), &self.contract_events_prefixed, &(topoheight + 1).to_be_bytes(), &TopoHeight::MAX.to_be_bytes()) {
+            let (key, _) = el?;
+            self.delete_contract_event_from_prefixed_key(&key)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SledStorage {
+    fn delete_contract_event_from_prefixed_key(&mut self, key: &[u8]) -> Result<(), BlockchainError> {
+        Self::remove_from_disk_without_reading(self.snapshot.as_mut(), &self.contract_events_prefixed, key)?;
+
+        // Swap the topoheight and the contract hash to build the index key
+        let mut index_key = [0; 52];
+        index_key[0..32].copy_from_slice(&key[8..40]);
+        index_key[32..40].copy_from_slice(&key[0..8]);
+        index_key[40..52].copy_from_slice(&key[40..52]);
+        Self::remove_from_disk_without_reading(self.snapshot.as_mut(), &self.contract_events, &index_key)?;
+
+        // Same for the index by id
+        let mut by_id_key = [0; 52];
+        by_id_key[0..32].copy_from_slice(&key[8..40]);
+        by_id_key[32..40].copy_from_slice(&key[40..48]);
+        by_id_key[40..48].copy_from_slice(&key[0..8]);
+        by_id_key[48..52].copy_from_slice(&key[48..52]);
+        Self::remove_from_disk_without_reading(self.snapshot.as_mut(), &self.contract_events_by_id, &by_id_key)?;
+
+        Ok(())
+    }
+}
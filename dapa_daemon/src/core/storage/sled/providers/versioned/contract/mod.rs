@@ -2,6 +2,7 @@ mod data;
 mod balance;
 mod scheduled_execution;
 mod event_callback;
+mod events;
 
 use async_trait::async_trait;
 use log::trace;
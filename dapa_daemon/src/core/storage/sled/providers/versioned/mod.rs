@@ -7,6 +7,7 @@ mod asset;
 mod cache;
 mod dag_order;
 mod asset_supply;
+mod account_transactions;
 
 use log::{debug, trace};
 use sled::Tree;
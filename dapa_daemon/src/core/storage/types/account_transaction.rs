@@ -0,0 +1,52 @@
+use indexmap::IndexSet;
+use dapa_common::{
+    crypto::Hash,
+    serializer::*
+};
+
+// Archival index entry linking an account to a transaction
+#[derive(Debug, Clone, Default)]
+pub struct AccountTransactionEntry {
+    // Account is the source of the transaction
+    pub outgoing: bool,
+    // Account received funds from the transaction
+    // either from a transfer or from a contract
+    pub incoming: bool,
+    // All the assets involved for this account
+    pub assets: IndexSet<Hash>,
+}
+
+impl AccountTransactionEntry {
+    // Merge another entry of the same account and transaction
+    pub fn merge(&mut self, other: AccountTransactionEntry) {
+        self.outgoing |= other.outgoing;
+        self.incoming |= other.incoming;
+        self.assets.extend(other.assets);
+    }
+}
+
+impl Serializer for AccountTransactionEntry {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let outgoing = reader.read_bool()?;
+        let incoming = reader.read_bool()?;
+        let assets = IndexSet::read(reader)?;
+
+        Ok(Self {
+            outgoing,
+            incoming,
+            assets,
+        })
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        self.outgoing.write(writer);
+        self.incoming.write(writer);
+        self.assets.write(writer);
+    }
+
+    fn size(&self) -> usize {
+        self.outgoing.size()
+        + self.incoming.size()
+        + self.assets.size()
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt;
+use serde::Serialize;
+use dapa_common::{block::TopoHeight, crypto::Hash};
+
+// Inconsistency found while scanning the storage
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum IntegrityIssue {
+    // No block hash is stored at this topoheight
+    MissingHashAtTopo {
+        topoheight: TopoHeight
+    },
+    // The hash stored at this topoheight references an unknown block
+    UnknownBlockAtTopo {
+        topoheight: TopoHeight,
+        hash: Hash
+    },
+    // The topoheight stored for the block doesn't point back to it
+    InvalidTopoByHash {
+        hash: Hash,
+        topoheight: TopoHeight
+    },
+    // The block is not registered at its height
+    MissingBlockAtHeight {
+        hash: Hash,
+        height: u64
+    },
+    // The block registered at this height is unknown or has another height
+    InvalidBlockAtHeight {
+        hash: Hash,
+        height: u64
+    },
+    // The circulating supply of the native asset is above the emitted supply
+    InvalidSupply {
+        topoheight: TopoHeight,
+        circulating: u64,
+        emitted: u64
+    },
+    // A reverse index entry doesn't match its source entry
+    InvalidIndex {
+        index: String,
+        key: String
+    },
+    // A source entry is not referenced by its reverse index
+    MissingIndex {
+        index: String,
+        key: String
+    },
+    // A pointer references a version that doesn't exist
+    DanglingPointer {
+        index: String,
+        key: String,
+        topoheight: TopoHeight
+    }
+}
+
+impl fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHashAtTopo { topoheight } => write!(f, "no block hash at topoheight {}", topoheight),
+            Self::UnknownBlockAtTopo { topoheight, hash } => write!(f, "unknown block {} at topoheight {}", hash, topoheight),
+            Self::InvalidTopoByHash { hash, topoheight } => write!(f, "topoheight of block {} doesn't match {}", hash, topoheight),
+            Self::MissingBlockAtHeight { hash, height } => write!(f, "block {} is not registered at height {}", hash, height),
+            Self::InvalidBlockAtHeight { hash, height } => write!(f, "invalid block {} registered at height {}", hash, height),
+            Self::InvalidSupply { topoheight, circulating, emitted } => write!(f, "circulating supply {} is above emitted supply {} at topoheight {}", circulating, emitted, topoheight),
+            Self::InvalidIndex { index, key } => write!(f, "invalid {} entry {}", index, key),
+            Self::MissingIndex { index, key } => write!(f, "missing {} entry for {}", index, key),
+            Self::DanglingPointer { index, key, topoheight } => write!(f, "{} entry {} points to missing version at topoheight {}", index, key, topoheight)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReportEntry {
+    #[serde(flatten)]
+    pub issue: IntegrityIssue,
+    // Set if the index has been rebuilt
+    pub repaired: bool
+}
+
+// Result of a storage integrity check
+#[derive(Debug, Default, Serialize)]
+pub struct IntegrityReport {
+    pub entries: Vec<IntegrityReportEntry>
+}
+
+impl IntegrityReport {
+    pub fn add(&mut self, issue: IntegrityIssue, repaired: bool) {
+        self.entries.push(IntegrityReportEntry { issue, repaired });
+    }
+
+    // No inconsistency was found
+    pub fn is_clean(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn count_repaired(&self) -> usize {
+        self.entries.iter()
+            .filter(|entry| entry.repaired)
+            .count()
+    }
+}
@@ -1,3 +1,7 @@
 mod topoheight_metadata;
+mod account_transaction;
+mod integrity;
 
-pub use topoheight_metadata::TopoHeightMetadata;
\ No newline at end of file
+pub use topoheight_metadata::TopoHeightMetadata;
+pub use account_transaction::AccountTransactionEntry;
+pub use integrity::{IntegrityIssue, IntegrityReport, IntegrityReportEntry};
@@ -62,13 +62,16 @@ use core::{
         PreVerifyBlock,
     },
     blockdag,
+    backup,
+    chain_snapshot,
+    integrity,
     config::{Config as InnerConfig, StorageBackend},
     hard_fork::{
         get_block_time_target_for_version,
         get_pow_algorithm_for_version,
         get_version_at_height
     },
-    storage::Storage
+    storage::{migration, Storage}
 };
 
 #[cfg(feature = "rocksdb")]
@@ -78,7 +81,7 @@ use core::storage::rocksdb::RocksStorage;
 use core::storage::sled::SledStorage;
 
 use std::{
-    fs::File,
+    fs::{self, File},
     io::Write,
     net::{IpAddr, SocketAddr},
     path::Path,
@@ -187,6 +190,18 @@ pub struct CliConfig {
     #[serde(skip)]
     #[serde(default)]
     generate_config_template: bool,
+    /// Migrate the storage to the selected DB backend and exit.
+    /// The current storage is opened read-only and kept as is.
+    #[clap(long, value_enum, requires = "migrate_storage_dir_path")]
+    #[serde(skip)]
+    #[serde(default)]
+    migrate_storage: Option<StorageBackend>,
+    /// Set dir path for the migrated storage.
+    /// It must be different from the current dir path and ends with a slash.
+    #[clap(long)]
+    #[serde(skip)]
+    #[serde(default)]
+    migrate_storage_dir_path: Option<String>,
 }
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 8)]
@@ -217,8 +232,14 @@ async fn main() -> Result<()> {
 
         let file = File::open(path)
             .context("Error while opening config file")?;
+        let migrate_storage = config.migrate_storage.take();
+        let migrate_storage_dir_path = config.migrate_storage_dir_path.take();
         config = serde_json::from_reader(file)
             .context("Error while reading config file")?;
+
+        // Migration is a one-shot action that can't be set from the config file
+        config.migrate_storage = migrate_storage;
+        config.migrate_storage_dir_path = migrate_storage_dir_path;
     } else if config.generate_config_template {
         eprintln!("Provided config file path is required to generate the template with --config-file");
         return Ok(());
@@ -263,24 +284,129 @@ async fn main() -> Result<()> {
     let dir_path = blockchain_config.dir_path.as_deref()
         .unwrap_or_default();
 
+    if let Some(path) = config.migrate_storage_dir_path.as_deref() {
+        if !(path.ends_with("/") || path.ends_with("\\")) {
+            return Err(anyhow::anyhow!("Migration path must ends with / or \\"));
+        }
+
+        if path == dir_path {
+            return Err(anyhow::anyhow!("Migration path must be different from the current dir path"));
+        }
+    }
+
+    // The source of a migration is never written
+    let read_only = config.migrate_storage.is_some();
     match blockchain_config.use_db_backend {
         #[cfg(feature = "sled")]
         StorageBackend::Sled => {
-            let use_cache = if blockchain_config.sled.cache_size > 0 {
-                Some(blockchain_config.sled.cache_size)
+            let storage = open_sled_storage(dir_path, blockchain_config, config.network, read_only)?;
+            start_storage(prompt, storage, config).await
+        },
+        #[cfg(feature = "rocksdb")]
+        StorageBackend::RocksDB => {
+            let storage = if read_only {
+                RocksStorage::new_read_only(dir_path, config.network, &blockchain_config.rocksdb)
             } else {
-                None
+                RocksStorage::new(dir_path, config.network, &blockchain_config.rocksdb)
             };
+            start_storage(prompt, storage, config).await
+        },
+        #[cfg(feature = "rocksdb")]
+        StorageBackend::Memory => {
+            warn!("Using an in-memory storage, all the chain data will be lost on exit");
+            let storage = RocksStorage::new_in_memory(config.network);
+            start_storage(prompt, storage, config).await
+        }
+    }
+}
 
-            let storage = SledStorage::new(dir_path.to_owned(), use_cache, config.network, blockchain_config.sled.internal_cache_size, blockchain_config.sled.internal_db_mode)?;
-            start_chain(prompt, storage, config).await
+#[cfg(feature = "sled")]
+fn open_sled_storage(dir_path: &str, config: &InnerConfig, network: Network, read_only: bool) -> Result<SledStorage> {
+    let use_cache = if config.sled.cache_size > 0 {
+        Some(config.sled.cache_size)
+    } else {
+        None
+    };
+
+    let storage = if read_only {
+        SledStorage::new_read_only(dir_path.to_owned(), use_cache, network, config.sled.internal_cache_size, config.sled.internal_db_mode)?
+    } else {
+        SledStorage::new(dir_path.to_owned(), use_cache, network, config.sled.internal_cache_size, config.sled.internal_db_mode)?
+    };
+    Ok(storage)
+}
+
+// Either start the chain or migrate the storage if requested
+async fn start_storage<S: Storage>(prompt: ShareablePrompt, storage: S, config: CliConfig) -> Result<()> {
+    let Some(backend) = config.migrate_storage else {
+        return start_chain(prompt, storage, config).await
+    };
+
+    let dir_path = config.core.dir_path.as_deref().unwrap_or_default();
+    let target_dir_path = config.migrate_storage_dir_path.as_deref().unwrap_or_default();
+    match backend {
+        #[cfg(feature = "sled")]
+        StorageBackend::Sled => {
+            let target = open_sled_storage(target_dir_path, &config.core, config.network, false)?;
+            run_storage_migration(storage, target, dir_path, target_dir_path, config.network).await
         },
         #[cfg(feature = "rocksdb")]
         StorageBackend::RocksDB => {
-            let storage = RocksStorage::new(&dir_path, config.network, &blockchain_config.rocksdb);
-            start_chain(prompt, storage, config).await
+            let target = RocksStorage::new(target_dir_path, config.network, &config.core.rocksdb);
+            run_storage_migration(storage, target, dir_path, target_dir_path, config.network).await
+        },
+        #[cfg(feature = "rocksdb")]
+        StorageBackend::Memory => Err(anyhow::anyhow!("Storage can't be migrated to the memory backend"))
+    }
+}
+
+async fn run_storage_migration<S: Storage, T: Storage>(mut source: S, mut target: T, dir_path: &str, target_dir_path: &str, network: Network) -> Result<()> {
+    info!("Migrating storage from '{}' to '{}', this may take a while...", dir_path, target_dir_path);
+    let start = Instant::now();
+    let res = migration::migrate_storage(&source, &mut target).await;
+
+    source.stop().await?;
+    target.stop().await?;
+
+    let stats = res.context("Error while migrating storage")?;
+    info!(
+        "Storage migrated in {} until topoheight {} ({}): {} blocks, {} transactions, {} accounts, {} assets, {} contracts",
+        format_duration(Duration::from_secs(start.elapsed().as_secs())),
+        stats.top_topoheight,
+        stats.top_hash,
+        stats.blocks,
+        stats.transactions,
+        stats.accounts,
+        stats.assets,
+        stats.contracts
+    );
+
+    // Peerlist is stored in its own DB independently of the backend
+    let peerlist = format!("peerlist-{}", network.to_string().to_lowercase());
+    let source_peerlist = Path::new(dir_path).join(&peerlist);
+    let target_peerlist = Path::new(target_dir_path).join(&peerlist);
+    if source_peerlist.exists() && !target_peerlist.exists() {
+        copy_dir_all(&source_peerlist, &target_peerlist)
+            .context("Error while copying peerlist")?;
+        info!("Peerlist copied to {}", target_peerlist.display());
+    }
+
+    Ok(())
+}
+
+fn copy_dir_all(source: &Path, target: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let path = target.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &path)?;
+        } else {
+            fs::copy(entry.path(), path)?;
         }
     }
+
+    Ok(())
 }
 
 async fn start_chain<S: Storage>(prompt: ShareablePrompt, storage: S, config: CliConfig) -> Result<()> {
@@ -322,6 +448,7 @@ async fn run_prompt<S: Storage>(prompt: ShareablePrompt, blockchain: Arc<Blockch
     command_manager.add_command(Command::with_optional_arguments("blacklist", "View blacklist or add a peer ip in it", vec![Arg::new("ip", ArgType::String)], CommandHandler::Async(async_handler!(blacklist::<S>))))?;
     command_manager.add_command(Command::with_optional_arguments("whitelist", "View whitelist or add a peer ip in it", vec![Arg::new("ip", ArgType::String)], CommandHandler::Async(async_handler!(whitelist::<S>))))?;
     command_manager.add_command(Command::with_optional_arguments("verify_chain", "Check chain supply", vec![Arg::new("topoheight", ArgType::Number)], CommandHandler::Async(async_handler!(verify_chain::<S>))))?;
+    command_manager.add_command(Command::with_optional_arguments("check_db_integrity", "Cross-check all the storage indexes and optionally repair them", vec![Arg::new("repair", ArgType::Bool)], CommandHandler::Async(async_handler!(check_db_integrity::<S>))))?;
     command_manager.add_command(Command::with_required_arguments("kick_peer", "Kick a peer using its ip:port", vec![Arg::new("address", ArgType::String)], CommandHandler::Async(async_handler!(kick_peer::<S>))))?;
     command_manager.add_command(Command::with_required_arguments("temp_ban_address", "Temporarily ban an IP address with its formatted duration (ex: 1h)", vec![Arg::new("address", ArgType::String), Arg::new("duration", ArgType::String)], CommandHandler::Async(async_handler!(temp_ban_address::<S>))))?;
     command_manager.add_command(Command::new("clear_caches", "Clear storage caches", CommandHandler::Async(async_handler!(clear_caches::<S>))))?;
@@ -348,6 +475,9 @@ async fn run_prompt<S: Storage>(prompt: ShareablePrompt, blockchain: Arc<Blockch
     command_manager.add_command(Command::new("show_mempool", "Show all transactions in mempool", CommandHandler::Async(async_handler!(show_mempool::<S>))))?;
     command_manager.add_command(Command::with_optional_arguments("import_block", "Import a block in hexadecimal format", vec![Arg::new("hex", ArgType::String)], CommandHandler::Async(async_handler!(import_block::<S>))))?;
     command_manager.add_command(Command::with_optional_arguments("show_emitted_supply_at_topoheight", "Show emitted supply at a specific topoheight", vec![Arg::new("topoheight", ArgType::Number)], CommandHandler::Async(async_handler!(show_emitted_supply_at_topoheight::<S>))))?;
+    command_manager.add_command(Command::with_optional_arguments("export_snapshot", "Export the chain state at the stable topoheight into a snapshot file", vec![Arg::new("filename", ArgType::String)], CommandHandler::Async(async_handler!(export_snapshot::<S>))))?;
+    command_manager.add_command(Command::with_required_arguments("import_snapshot", "Import a chain snapshot file on a fresh chain", vec![Arg::new("filename", ArgType::String)], CommandHandler::Async(async_handler!(import_snapshot::<S>))))?;
+    command_manager.add_command(Command::with_required_arguments("backup_db", "Create a backup of the database in an empty directory", vec![Arg::new("path", ArgType::String)], CommandHandler::Async(async_handler!(backup_db::<S>))))?;
 
     // Don't keep the lock for ever
     let p2p = {
@@ -1464,6 +1594,50 @@ async fn prune_chain<S: Storage>(manager: &CommandManager, mut arguments: Argume
     Ok(())
 }
 
+async fn export_snapshot<S: Storage>(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let filename = if arguments.has_argument("filename") {
+        arguments.get_value("filename")?.to_string_value()?
+    } else {
+        "chain_snapshot.bin".to_owned()
+    };
+
+    let context = manager.get_context().lock()?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    manager.message(format!("Exporting chain snapshot to {}...", filename));
+    match chain_snapshot::export_chain_snapshot(blockchain, &filename).await {
+        Ok(info) => manager.message(format!("Chain snapshot exported at topoheight {} ({}) with {} entries", info.topoheight, info.hash, info.entries)),
+        Err(e) => manager.error(format!("Error while exporting chain snapshot: {}", e))
+    };
+
+    Ok(())
+}
+
+async fn import_snapshot<S: Storage>(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let filename = arguments.get_value("filename")?.to_string_value()?;
+    let context = manager.get_context().lock()?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    manager.message(format!("Importing chain snapshot from {}...", filename));
+    match chain_snapshot::import_chain_snapshot(blockchain, &filename).await {
+        Ok(info) => manager.message(format!("Chain snapshot imported at topoheight {} ({}), syncing the rest of the chain from peers", info.topoheight, info.hash)),
+        Err(e) => manager.error(format!("Error while importing chain snapshot: {}", e))
+    };
+
+    Ok(())
+}
+
+async fn backup_db<S: Storage>(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let path = arguments.get_value("path")?.to_string_value()?;
+    let context = manager.get_context().lock()?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    manager.message(format!("Creating database backup in {}...", path));
+    match backup::backup_blockchain(blockchain, &path).await {
+        Ok(manifest) => manager.message(format!("Database backup created at topoheight {} ({})", manifest.topoheight, manifest.top_hash)),
+        Err(e) => manager.error(format!("Error while creating database backup: {}", e))
+    };
+
+    Ok(())
+}
+
 async fn status<S: Storage>(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -1631,6 +1805,38 @@ async fn clear_p2p_peerlist<S: Storage>(manager: &CommandManager, _: ArgumentMan
     Ok(())
 }
 
+async fn check_db_integrity<S: Storage>(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let repair = if arguments.has_argument("repair") {
+        arguments.get_value("repair")?.to_bool()?
+    } else {
+        false
+    };
+
+    let context = manager.get_context().lock()?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let mut storage = blockchain.get_storage().write().await;
+
+    manager.message("Checking storage integrity, this may take a while...");
+    let report = integrity::check_storage_integrity(&mut *storage, repair).await
+        .context("Error while checking storage integrity")?;
+
+    for entry in report.entries.iter() {
+        if entry.repaired {
+            manager.message(format!("Repaired: {}", entry.issue));
+        } else {
+            manager.warn(entry.issue.to_string());
+        }
+    }
+
+    if report.is_clean() {
+        manager.message("No inconsistency found");
+    } else {
+        manager.message(format!("{} inconsistencies found, {} repaired", report.entries.len(), report.count_repaired()));
+    }
+
+    Ok(())
+}
+
 async fn clear_caches<S: Storage>(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -0,0 +1,274 @@
+use std::{collections::HashMap, sync::Arc};
+use actix_web::http::header::{self, HeaderMap};
+use actix_web_httpauth::headers::authorization::{Basic, Bearer, Scheme};
+use log::{debug, info};
+use dapa_common::rpc::tid;
+use crate::core::config::RPCAuthConfig;
+
+// Role always available allowing all methods
+pub const ADMIN_ROLE: &str = "admin";
+// Method a role must allow to mine through the GetWork or Stratum servers
+pub const MINING_METHOD: &str = "submit_block";
+
+#[derive(Debug, thiserror::Error)]
+pub enum RpcAuthError {
+    #[error("unknown role '{}'", _0)]
+    UnknownRole(String),
+    #[error("authentication is required")]
+    MissingAuthorization,
+    #[error("invalid authorization header")]
+    InvalidAuthorization,
+    #[error("invalid API key")]
+    InvalidApiKey,
+}
+
+// Role assigned to a RPC client
+// It contains the methods it is allowed to call
+pub struct RpcRole {
+    name: String,
+    methods: Vec<String>
+}
+
+tid!(RpcRole);
+
+impl RpcRole {
+    pub fn new(name: String, methods: Vec<String>) -> Self {
+        Self {
+            name,
+            methods
+        }
+    }
+
+    // Get the role name
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    // Check if the method can be called with this role
+    // A method ending with '*' is used as a prefix
+    pub fn is_method_allowed(&self, method: &str) -> bool {
+        self.methods.iter().any(|allowed| match allowed.strip_suffix('*') {
+            Some(prefix) => method.starts_with(prefix),
+            None => allowed == method
+        })
+    }
+
+    // Check if the role can mine through the GetWork or Stratum servers
+    pub fn is_mining_allowed(&self) -> bool {
+        self.is_method_allowed(MINING_METHOD)
+    }
+}
+
+// Authentication of the RPC clients
+// Each API key is mapped to a role
+// API keys are compared in constant time
+pub struct RpcAuth {
+    keys: HashMap<String, Arc<RpcRole>>,
+    // Role used when no authorization header is provided
+    public: Option<Arc<RpcRole>>
+}
+
+impl RpcAuth {
+    // Build the authentication from the config
+    // Returns None if no API key and no public role are configured
+    pub fn new(config: &RPCAuthConfig) -> Result<Option<Self>, RpcAuthError> {
+        if config.api_keys.is_empty() && config.public_role.is_none() {
+            return Ok(None)
+        }
+
+        let mut roles = HashMap::new();
+        roles.insert(ADMIN_ROLE.to_owned(), Arc::new(RpcRole::new(ADMIN_ROLE.to_owned(), vec!["*".to_owned()])));
+        for role in config.roles.iter() {
+            debug!("Registering RPC role {} with {} methods", role.name, role.methods.len());
+            roles.insert(role.name.clone(), Arc::new(RpcRole::new(role.name.clone(), role.methods.clone())));
+        }
+
+        let get_role = |name: &str| roles.get(name)
+            .cloned()
+            .ok_or_else(|| RpcAuthError::UnknownRole(name.to_owned()));
+
+        let mut keys = HashMap::with_capacity(config.api_keys.len());
+        for api_key in config.api_keys.iter() {
+            keys.insert(api_key.key.clone(), get_role(&api_key.role)?);
+        }
+
+        let public = config.public_role.as_deref()
+            .map(get_role)
+            .transpose()?;
+
+        info!("RPC authentication enabled with {} API keys, public role: {}", keys.len(), public.as_ref().map(|role| role.get_name()).unwrap_or("none"));
+
+        Ok(Some(Self {
+            keys,
+            public
+        }))
+    }
+
+    // Find the role of a client based on its request headers
+    // The API key is read from a Bearer token or from the password of a basic authentication
+    // Returns the API key used, None if the client is not authenticated
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<(Option<&str>, &Arc<RpcRole>), RpcAuthError> {
+        let Some(value) = headers.get(header::AUTHORIZATION) else {
+            return self.authenticate_api_key(None)
+        };
+
+        let key = if let Ok(bearer) = Bearer::parse(value) {
+            bearer.token().to_string()
+        } else if let Ok(basic) = Basic::parse(value) {
+            basic.password()
+                .ok_or(RpcAuthError::InvalidAuthorization)?
+                .to_string()
+        } else {
+            return Err(RpcAuthError::InvalidAuthorization)
+        };
+
+        self.authenticate_api_key(Some(&key))
+    }
+
+    // Find the role of a client based on its API key
+    // The public role is used if no API key is provided
+    pub fn authenticate_api_key(&self, key: Option<&str>) -> Result<(Option<&str>, &Arc<RpcRole>), RpcAuthError> {
+        let Some(key) = key else {
+            let role = self.public.as_ref().ok_or(RpcAuthError::MissingAuthorization)?;
+            return Ok((None, role))
+        };
+
+        // Compare against every key to not leak which one is matching
+        let mut found = None;
+        for (api_key, role) in self.keys.iter() {
+            if constant_time_eq(api_key.as_bytes(), key.as_bytes()) && found.is_none() {
+                found = Some((Some(api_key.as_str()), role));
+            }
+        }
+
+        found.ok_or(RpcAuthError::InvalidApiKey)
+    }
+}
+
+// Compare two byte slices in constant time
+// Only the length of the slices may be leaked
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false
+    }
+
+    let diff = a.iter()
+        .zip(b)
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y));
+
+    std::hint::black_box(diff) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use actix_web::http::header::HeaderValue;
+    use crate::core::config::{RPCApiKeyConfig, RPCRoleConfig};
+    use super::*;
+
+    fn create_role(methods: &[&str]) -> RpcRole {
+        RpcRole::new("test".to_owned(), methods.iter().map(|method| method.to_string()).collect())
+    }
+
+    #[test]
+    fn test_role_method_allowed() {
+        let role = create_role(&["get_info", "get_block_*"]);
+        assert!(role.is_method_allowed("get_info"));
+        assert!(role.is_method_allowed("get_block_at_topoheight"));
+        assert!(role.is_method_allowed("get_block_"));
+
+        // Exact methods are not prefixes
+        assert!(!role.is_method_allowed("get_info_extra"));
+        assert!(!role.is_method_allowed("get_inf"));
+        assert!(!role.is_method_allowed("get_blocks_range_by_height"));
+        assert!(!role.is_method_allowed("submit_block"));
+        assert!(!role.is_mining_allowed());
+    }
+
+    #[test]
+    fn test_role_wildcard() {
+        let role = create_role(&["*"]);
+        assert!(role.is_method_allowed("get_info"));
+        assert!(role.is_method_allowed(""));
+        assert!(role.is_mining_allowed());
+
+        let role = create_role(&[]);
+        assert!(!role.is_method_allowed("get_info"));
+        assert!(!role.is_mining_allowed());
+    }
+
+    #[test]
+    fn test_role_config_from_str() {
+        let config = RPCRoleConfig::from_str("miner=submit_block, get_block_template,,get_info").unwrap();
+        assert_eq!(config.name, "miner");
+        assert_eq!(config.methods, vec!["submit_block", "get_block_template", "get_info"]);
+
+        let config = RPCRoleConfig::from_str("reader=get_*").unwrap();
+        assert_eq!(config.name, "reader");
+        assert_eq!(config.methods, vec!["get_*"]);
+
+        // A role without any method is allowed
+        let config = RPCRoleConfig::from_str("none=").unwrap();
+        assert!(config.methods.is_empty());
+
+        assert!(RPCRoleConfig::from_str("miner").is_err());
+        assert!(RPCRoleConfig::from_str("=get_info").is_err());
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let config = RPCAuthConfig {
+            roles: vec![RPCRoleConfig::from_str("miner=submit_block,get_block_template").unwrap()],
+            api_keys: vec![
+                RPCApiKeyConfig::from_str("secret=admin").unwrap(),
+                RPCApiKeyConfig::from_str("mining=miner").unwrap(),
+            ],
+            public_role: None
+        };
+        let auth = RpcAuth::new(&config).unwrap().unwrap();
+
+        let mut headers = HeaderMap::new();
+        assert!(matches!(auth.authenticate(&headers), Err(RpcAuthError::MissingAuthorization)));
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer mining"));
+        let (key, role) = auth.authenticate(&headers).unwrap();
+        assert_eq!(key, Some("mining"));
+        assert_eq!(role.get_name(), "miner");
+        assert!(role.is_mining_allowed());
+        assert!(!role.is_method_allowed("get_info"));
+
+        // Basic authentication uses the password as API key
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic dXNlcjpzZWNyZXQ="));
+        let (key, role) = auth.authenticate(&headers).unwrap();
+        assert_eq!(key, Some("secret"));
+        assert_eq!(role.get_name(), ADMIN_ROLE);
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer invalid"));
+        assert!(matches!(auth.authenticate(&headers), Err(RpcAuthError::InvalidApiKey)));
+    }
+
+    #[test]
+    fn test_auth_config() {
+        // No authentication configured
+        assert!(RpcAuth::new(&RPCAuthConfig::default()).unwrap().is_none());
+
+        // Unknown role
+        let config = RPCAuthConfig {
+            roles: Vec::new(),
+            api_keys: vec![RPCApiKeyConfig::from_str("secret=miner").unwrap()],
+            public_role: None
+        };
+        assert!(matches!(RpcAuth::new(&config), Err(RpcAuthError::UnknownRole(_))));
+
+        // Public role is used without authorization header
+        let config = RPCAuthConfig {
+            roles: vec![RPCRoleConfig::from_str("public=get_info").unwrap()],
+            api_keys: Vec::new(),
+            public_role: Some("public".to_owned())
+        };
+        let auth = RpcAuth::new(&config).unwrap().unwrap();
+        let (key, role) = auth.authenticate(&HeaderMap::new()).unwrap();
+        assert!(key.is_none());
+        assert_eq!(role.get_name(), "public");
+    }
+}
@@ -5,6 +5,7 @@ use dapa_common::{
     crypto::{Hash, PublicKey},
     time::{get_current_time_in_millis, TimestampMillis}
 };
+use super::SessionDifficulty;
 
 pub struct Miner {
     // Used to display correctly its address
@@ -20,11 +21,13 @@ pub struct Miner {
     // blocks rejected since he is connected
     blocks_rejected: usize,
     // timestamp of the last invalid block received
-    last_invalid_block: TimestampMillis
+    last_invalid_block: TimestampMillis,
+    // share difficulty of the session in pool mode
+    share_difficulty: Option<SessionDifficulty>
 }
 
 impl Miner {
-    pub fn new(mainnet: bool, key: PublicKey, name: String) -> Self {
+    pub fn new(mainnet: bool, key: PublicKey, name: String, share_difficulty: Option<SessionDifficulty>) -> Self {
         Self {
             mainnet,
             first_seen: get_current_time_in_millis(),
@@ -32,10 +35,19 @@ impl Miner {
             name,
             blocks_accepted: IndexSet::new(),
             blocks_rejected: 0,
-            last_invalid_block: 0
+            last_invalid_block: 0,
+            share_difficulty
         }
     }
 
+    pub fn get_share_difficulty(&self) -> Option<&SessionDifficulty> {
+        self.share_difficulty.as_ref()
+    }
+
+    pub fn get_share_difficulty_mut(&mut self) -> Option<&mut SessionDifficulty> {
+        self.share_difficulty.as_mut()
+    }
+
     pub fn first_seen(&self) -> u64 {
         self.first_seen
     }
@@ -1,4 +1,5 @@
 mod miner;
+mod pool;
 
 use std::{
     borrow::Cow,
@@ -21,21 +22,22 @@ use log::{debug, error, trace, warn};
 use lru::LruCache;
 use serde::Serialize;
 use dapa_common::{
-    tokio::{sync::Mutex, time::sleep},
+    tokio::{sync::{broadcast, Mutex}, task::spawn_blocking, time::sleep},
     api::daemon::{
         GetBlockTemplateResult,
         GetMinerWorkResult,
         NotifyEvent,
+        PoolPayoutScheme,
         SubmitMinerWorkParams
     },
-    block::{BlockHeader, MinerWork},
+    block::{Algorithm, BlockHeader, MinerWork, TopoHeight},
     crypto::{
         Address,
         Hash,
         Hashable,
         PublicKey
     },
-    difficulty::Difficulty,
+    difficulty::{check_difficulty, Difficulty},
     immutable::Immutable,
     rpc::{
         server::websocket::{WebSocketHandler, WebSocketSessionShared},
@@ -46,33 +48,55 @@ use dapa_common::{
     tokio::spawn_task
 };
 use crate::{
-    config::DEV_PUBLIC_KEY,
+    config::{DEV_PUBLIC_KEY, POOL_LEDGER_INTERVAL_MS},
     core::{
         blockchain::{
+            get_block_dev_fee,
+            get_block_reward,
             Blockchain,
             BroadcastOption,
             PreVerifyBlock,
         },
-        hard_fork::get_pow_algorithm_for_version,
+        error::BlockchainError,
+        hard_fork::{get_block_time_target_for_version, get_pow_algorithm_for_version},
         storage::Storage
     }
 };
 
 pub use miner::*;
+pub use pool::*;
 
 #[derive(Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")] 
 pub enum Response {
     NewJob(GetMinerWorkResult),
     BlockAccepted,
+    ShareAccepted,
     BlockRejected(String)
 }
 
 pub enum BlockResult {
     Accepted(Arc<Hash>),
+    // Valid share in pool mode, its PoW is not enough for a block
+    ShareAccepted,
     Rejected(anyhow::Error)
 }
 
+// Capacity of the jobs channel, lagging subscribers only keep the latest jobs
+const JOBS_CHANNEL_CAPACITY: usize = 16;
+
+// Mining job shared with the other mining servers (Stratum)
+// The miner key and extra nonce are set by each server
+#[derive(Debug, Clone)]
+pub struct MiningJob {
+    pub header_work_hash: Hash,
+    pub timestamp: TimestampMillis,
+    pub algorithm: Algorithm,
+    pub height: u64,
+    pub topoheight: TopoHeight,
+    pub difficulty: Difficulty
+}
+
 pub type SharedGetWorkServer<S> = Arc<GetWorkServer<S>>;
 
 pub struct GetWorkServer<S: Storage> {
@@ -97,10 +121,14 @@ pub struct GetWorkServer<S: Storage> {
     notify_rate_limit_ms: TimestampMillis,
     // Current limit for the number of miners to notify at the same time
     notify_job_concurrency: usize,
+    // Broadcast each new job to the subscribed mining servers
+    jobs: broadcast::Sender<MiningJob>,
+    // Shares accounting if the pool mode is enabled
+    pool: Option<Pool>,
 }
 
 impl<S: Storage> GetWorkServer<S> {
-    pub fn new(blockchain: Arc<Blockchain<S>>, notify_rate_limit_ms: TimestampMillis, notify_job_concurrency: usize) -> Arc<Self> {
+    pub fn new(blockchain: Arc<Blockchain<S>>, notify_rate_limit_ms: TimestampMillis, notify_job_concurrency: usize, pool: Option<Pool>) -> Arc<Self> {
         let server = Arc::new(Self {
             miners: Mutex::new(HashMap::new()),
             blockchain,
@@ -109,7 +137,9 @@ impl<S: Storage> GetWorkServer<S> {
             last_notify: AtomicU64::new(0),
             is_job_dirty: AtomicBool::new(false),
             notify_rate_limit_ms,
-            notify_job_concurrency
+            notify_job_concurrency,
+            jobs: broadcast::channel(JOBS_CHANNEL_CAPACITY).0,
+            pool
         });
 
         if notify_rate_limit_ms > 0 {
@@ -117,6 +147,11 @@ impl<S: Storage> GetWorkServer<S> {
             spawn_task("getwork-notifier", zelf.task_notifier());
         }
 
+        if server.pool.is_some() {
+            let zelf = Arc::clone(&server);
+            spawn_task("getwork-pool", zelf.task_pool());
+        }
+
         server
     }
 
@@ -157,6 +192,51 @@ impl<S: Storage> GetWorkServer<S> {
         }
     }
 
+    // Credit the pool blocks once they are stable
+    // and save the pool ledger if it has changed since the last save
+    async fn task_pool(self: Arc<Self>) {
+        let Some(pool) = self.pool.as_ref() else {
+            return
+        };
+
+        loop {
+            sleep(Duration::from_millis(POOL_LEDGER_INTERVAL_MS)).await;
+            if let Err(e) = self.settle_pool_blocks(pool).await {
+                error!("Error while settling the pool blocks: {}", e);
+            }
+
+            if let Err(e) = pool.flush_ledger().await {
+                error!("Error while saving the pool ledger: {}", e);
+            }
+        }
+    }
+
+    // A pending block below the stable height can't be reorganized anymore:
+    // it is either ordered in the DAG with its final reward or orphaned
+    async fn settle_pool_blocks(&self, pool: &Pool) -> Result<(), BlockchainError> {
+        let storage = self.blockchain.get_storage_read().await;
+        let stable_height = storage.chain_cache().await.stable_height;
+        let pending = pool.get_stable_pending_blocks(stable_height).await;
+        if pending.is_empty() {
+            return Ok(())
+        }
+
+        let mut results = Vec::with_capacity(pending.len());
+        for (hash, height) in pending {
+            let reward = if storage.is_block_topological_ordered(&hash).await? {
+                let topoheight = storage.get_topo_height_for_hash(&hash).await?;
+                let reward = storage.get_block_reward_at_topo_height(topoheight).await?;
+                Some(reward - reward * get_block_dev_fee(height) / 100)
+            } else {
+                None
+            };
+
+            results.push((hash, reward));
+        }
+
+        pool.settle_blocks(results).await
+    }
+
     // Returns the number of miners connected to the getwork server
     pub async fn count_miners(&self) -> usize {
         trace!("count miners");
@@ -169,11 +249,42 @@ impl<S: Storage> GetWorkServer<S> {
         &self.miners
     }
 
-    // retrieve last mining job and set random extra nonce and miner public key
-    // then, send it
-    async fn send_new_job(&self, session: &WebSocketSessionShared<Self>, key: &PublicKey) -> Result<(), anyhow::Error> {
-        debug!("Sending new job to miner");
-        let (version, mut job, height, difficulty) = {
+    // Returns the pool if the pool mode is enabled
+    pub fn get_pool(&self) -> Option<&Pool> {
+        self.pool.as_ref()
+    }
+
+    // In pool mode, blocks are mined for the pool key
+    pub fn get_job_miner<'a>(&'a self, key: &'a PublicKey) -> &'a PublicKey {
+        match self.pool.as_ref() {
+            Some(pool) => pool.get_key(),
+            None => key
+        }
+    }
+
+    // Share difficulty of a new miner session if the pool mode is enabled
+    pub fn new_session_difficulty(&self) -> Option<SessionDifficulty> {
+        self.pool.as_ref().map(Pool::new_session_difficulty)
+    }
+
+    // In pool mode, miners are working on the share difficulty of their session
+    // Shares can't be harder than the block itself
+    pub fn get_job_difficulty(&self, difficulty: &Difficulty, share_difficulty: Option<&SessionDifficulty>) -> Difficulty {
+        match share_difficulty.filter(|_| self.pool.is_some()) {
+            Some(share_difficulty) => cap_share_difficulty(difficulty, share_difficulty.get_current()),
+            None => *difficulty
+        }
+    }
+
+    // Subscribe to the new jobs notified to the miners
+    pub fn subscribe_jobs(&self) -> broadcast::Receiver<MiningJob> {
+        self.jobs.subscribe()
+    }
+
+    // retrieve last mining job from cache if we are rate limited
+    // otherwise, generate a new one
+    pub async fn get_current_job(&self) -> Result<MiningJob, anyhow::Error> {
+        let (version, job, height, difficulty) = {
             debug!("locking last header hashfor new job");
             let mut hash = self.last_header_hash.lock().await;
 
@@ -222,13 +333,25 @@ impl<S: Storage> GetWorkServer<S> {
             }
         };
 
-        // set miner key and random extra nonce
-        job.set_miner(Cow::Borrowed(key));
-        OsRng.fill_bytes(job.get_extra_nonce());
-
         // get the algorithm for the current version
         let algorithm = get_pow_algorithm_for_version(version);
         let topoheight = self.blockchain.get_topo_height().await;
+        let (header_work_hash, timestamp, _, _, _) = job.take();
+
+        Ok(MiningJob { header_work_hash, timestamp, algorithm, height, topoheight, difficulty })
+    }
+
+    // retrieve last mining job and set random extra nonce and miner public key
+    // then, send it
+    async fn send_new_job(&self, session: &WebSocketSessionShared<Self>, key: &PublicKey, share_difficulty: Option<&SessionDifficulty>) -> Result<(), anyhow::Error> {
+        debug!("Sending new job to miner");
+        let MiningJob { header_work_hash, timestamp, algorithm, height, topoheight, difficulty } = self.get_current_job().await?;
+
+        // set miner key and random extra nonce
+        let mut job = MinerWork::new(header_work_hash, timestamp);
+        job.set_miner(Cow::Borrowed(self.get_job_miner(key)));
+        OsRng.fill_bytes(job.get_extra_nonce());
+        let difficulty = self.get_job_difficulty(&difficulty, share_difficulty);
 
         debug!("Sending job to new miner");
         session.send_json(Response::NewJob(GetMinerWorkResult { algorithm, miner_work: job.to_hex(), height, topoheight, difficulty })).await
@@ -268,7 +391,7 @@ impl<S: Storage> GetWorkServer<S> {
         let miners_empty = {
             let miners = self.miners.lock().await;
             let empty =  miners.is_empty();
-            if empty && !is_event_tracked {
+            if empty && !is_event_tracked && self.jobs.receiver_count() == 0 {
                 debug!("No miners connected, no need to notify them");
                 return Ok(());
             }
@@ -335,19 +458,38 @@ impl<S: Storage> GetWorkServer<S> {
             mining_jobs.put(header_work_hash.clone(), (header, difficulty));
         }
 
+        // a send error only means that there is no subscriber
+        let _ = self.jobs.send(MiningJob {
+            header_work_hash: header_work_hash.clone(),
+            timestamp: job.get_timestamp(),
+            algorithm,
+            height,
+            topoheight,
+            difficulty
+        });
+
         if !miners_empty {
             // now let's send the job to every miner
-            let miners = self.miners.lock().await;
+            let mut miners = self.miners.lock().await;
+
+            // Sessions without any share are adjusted here
+            let now = get_current_time_in_millis();
+            for miner in miners.values_mut() {
+                if let Some(share_difficulty) = miner.get_share_difficulty_mut() {
+                    share_difficulty.retarget(now);
+                }
+            }
 
             debug!("Notifying {} miners for new job", miners.len());
             stream::iter(miners.iter())
                 .for_each_concurrent(self.notify_job_concurrency, |(addr, miner)| {
                     let mut job = job.clone();
+                    let difficulty = self.get_job_difficulty(&difficulty, miner.get_share_difficulty());
                     async move {
                         debug!("Notifying {} for new job", miner);
                         let addr = addr.clone();
 
-                        job.set_miner(Cow::Borrowed(miner.get_public_key()));
+                        job.set_miner(Cow::Borrowed(self.get_job_miner(miner.get_public_key())));
                         OsRng.fill_bytes(job.get_extra_nonce());
                         let template = job.to_hex();
 
@@ -363,33 +505,37 @@ impl<S: Storage> GetWorkServer<S> {
         Ok(())
     }
 
-    // this function is called when a miner send a new block
-    // we retrieve the block header saved in cache using the mining job "header_work_hash"
+    // retrieve the block header saved in cache using the mining job "header_work_hash"
     // its used to check that the job come from our server
     // when it's found, we merge the miner job inside the block header
-    async fn accept_miner_job(&self, job: MinerWork<'_>) -> Result<BlockResult, InternalRpcError> {
-        trace!("accept miner job");
+    async fn get_miner_header(&self, job: MinerWork<'_>) -> Result<(BlockHeader, Difficulty), InternalRpcError> {
+        let mining_jobs = self.mining_jobs.lock().await;
+        if let Some((header, difficulty)) = mining_jobs.peek(job.get_header_work_hash()) {
+            // job is found in cache, clone it and put miner data inside
+            let mut miner_header = header.clone();
+            if !miner_header.apply_miner_work(job) {
+                return Err(InternalRpcError::InvalidJSONRequest);
+            }
 
-        let mut miner_header;
-        {
-            let mining_jobs = self.mining_jobs.lock().await;
-            if let Some((header, _)) = mining_jobs.peek(job.get_header_work_hash()) {
-                // job is found in cache, clone it and put miner data inside
-                miner_header = header.clone();
-                if !miner_header.apply_miner_work(job) {
-                    return Err(InternalRpcError::InvalidJSONRequest);
-                }
-            } else {
-                // really old job, or miner send invalid job
-                debug!("Job {} was not found in cache", job.get_header_work_hash());
-                return Err(InternalRpcError::InvalidParams("Job was not found in cache"))
-            };
+            Ok((miner_header, *difficulty))
+        } else {
+            // really old job, or miner send invalid job
+            debug!("Job {} was not found in cache", job.get_header_work_hash());
+            Err(InternalRpcError::InvalidParams("Job was not found in cache"))
         }
+    }
 
-        let block = self.blockchain.build_block_from_header(miner_header).await?;
+    // build the block from the miner header and add it to the chain
+    // the PoW hash can be provided if it was already computed
+    async fn add_miner_block(&self, header: BlockHeader, pow_hash: Option<Hash>) -> Result<BlockResult, InternalRpcError> {
+        let block = self.blockchain.build_block_from_header(header).await?;
         let block_hash = Arc::new(block.hash());
+        let pre_verify = match pow_hash {
+            Some(pow_hash) => PreVerifyBlock::Partial { block_hash: Immutable::Arc(block_hash.clone()), pow_hash },
+            None => PreVerifyBlock::Hash(Immutable::Arc(block_hash.clone()))
+        };
 
-        Ok(match self.blockchain.add_new_block(block, PreVerifyBlock::Hash(Immutable::Arc(block_hash.clone())), BroadcastOption::All, true).await {
+        Ok(match self.blockchain.add_new_block(block, pre_verify, BroadcastOption::All, true).await {
             Ok(_) => BlockResult::Accepted(block_hash),
             Err(e) => {
                 debug!("Error while accepting miner block: {}", e);
@@ -398,13 +544,111 @@ impl<S: Storage> GetWorkServer<S> {
         })
     }
 
+    // this function is called when a miner send a new block
+    pub async fn accept_miner_job(&self, job: MinerWork<'_>) -> Result<BlockResult, InternalRpcError> {
+        trace!("accept miner job");
+
+        let (miner_header, _) = self.get_miner_header(job).await?;
+        self.add_miner_block(miner_header, None).await
+    }
+
+    // Miner reward of the block based on the current emitted supply
+    async fn get_miner_reward(&self, header: &BlockHeader) -> Result<u64, BlockchainError> {
+        let storage = self.blockchain.get_storage_read().await;
+        let topoheight = storage.chain_cache().await.topoheight;
+        let supply = storage.get_emitted_supply_at_topo_height(topoheight).await?;
+
+        let reward = get_block_reward(supply, get_block_time_target_for_version(header.get_version()));
+        Ok(reward - reward * get_block_dev_fee(header.get_height()) / 100)
+    }
+
+    // verify the share PoW against the share difficulty and register it
+    // if it's also valid for the block difficulty, the block is added to the chain
+    async fn accept_pool_share(&self, pool: &Pool, job: MinerWork<'_>, miner: &PublicKey, worker: &str, session_difficulty: &mut SessionDifficulty) -> Result<BlockResult, InternalRpcError> {
+        trace!("accept pool share");
+        if job.get_miner() != Some(pool.get_key()) {
+            return Err(InternalRpcError::InvalidParams("Share is not mined for the pool address"))
+        }
+
+        let (header, block_difficulty) = self.get_miner_header(job).await?;
+
+        // Spawn a thread for the CPU bound PoW computation
+        let algorithm = get_pow_algorithm_for_version(header.get_version());
+        let pow_header = header.clone();
+        let pow_hash = spawn_blocking(move || pow_header.get_pow_hash(algorithm)).await
+            .context("Error while computing share PoW hash")?
+            .map_err(BlockchainError::from)?;
+
+        // Shares of the jobs sent before the last adjustment are accepted at the previous difficulty
+        let mut share_difficulty = cap_share_difficulty(&block_difficulty, session_difficulty.get_current());
+        if !check_difficulty(&pow_hash, &share_difficulty).context("Error while checking share difficulty")? {
+            let previous = session_difficulty.get_previous()
+                .map(|previous| cap_share_difficulty(&block_difficulty, previous))
+                .ok_or(InternalRpcError::InvalidParams("Share difficulty is too low"))?;
+
+            if !check_difficulty(&pow_hash, &previous).context("Error while checking share difficulty")? {
+                return Err(InternalRpcError::InvalidParams("Share difficulty is too low"))
+            }
+            share_difficulty = previous;
+        }
+
+        let reward = self.get_miner_reward(&header).await?;
+        // With PPS, the share is paid its part of the block reward
+        let share_reward: u64 = match pool.get_scheme() {
+            PoolPayoutScheme::Pps => (Difficulty::from(reward) * u64::from(share_difficulty) / block_difficulty).into(),
+            PoolPayoutScheme::Pplns => 0
+        };
+
+        if !pool.add_share(miner, worker, pow_hash.clone(), share_difficulty.into(), share_reward).await {
+            return Err(InternalRpcError::InvalidParams("Share was already submitted"))
+        }
+        session_difficulty.add_share();
+
+        if !check_difficulty(&pow_hash, &block_difficulty).context("Error while checking block difficulty")? {
+            return Ok(BlockResult::ShareAccepted)
+        }
+
+        let height = header.get_height();
+        let result = self.add_miner_block(header, Some(pow_hash)).await?;
+        if let BlockResult::Accepted(hash) = &result {
+            // The block is kept in memory and the ledger is saved again later
+            if let Err(e) = pool.add_block(miner, worker, hash.as_ref().clone(), height, reward).await {
+                error!("Error while saving the pool ledger: {}", e);
+            }
+        }
+
+        Ok(result)
+    }
+
+    // submit the work of a miner
+    // in pool mode, it is accepted as a share and the block is added only if its PoW is enough
+    pub async fn submit_miner_work(&self, job: MinerWork<'_>, miner: &PublicKey, worker: &str, share_difficulty: Option<&mut SessionDifficulty>) -> Result<BlockResult, InternalRpcError> {
+        let (Some(pool), Some(share_difficulty)) = (self.pool.as_ref(), share_difficulty) else {
+            return self.accept_miner_job(job).await
+        };
+
+        let result = self.accept_pool_share(pool, job, miner, worker, share_difficulty).await;
+        if result.is_err() {
+            pool.mark_invalid_share(miner, worker).await;
+        }
+
+        result
+    }
+
     // handle the incoming mining job from the miner
     // decode the block miner, and using its header work hash, retrieve the block header
     // if its block is rejected, resend him the job
     pub async fn handle_block_for(&self, session: &WebSocketSessionShared<Self>, submitted_work: SubmitMinerWorkParams) -> Result<(), anyhow::Error> {
         trace!("handle block for");
+        let (key, worker, mut share_difficulty) = {
+            let miners = self.miners.lock().await;
+            let miner = miners.get(session)
+                .context("Miner not found in cache")?;
+            (miner.get_public_key().clone(), miner.get_name().clone(), miner.get_share_difficulty().cloned())
+        };
+
         let result = match MinerWork::from_hex(&submitted_work.miner_work) {
-            Ok(job) => match self.accept_miner_job(job).await {
+            Ok(job) => match self.submit_miner_work(job, &key, &worker, share_difficulty.as_mut()).await {
                 Ok(result) => result,
                 Err(e) => {
                     debug!("Error while accepting miner job: {}", e);
@@ -424,19 +668,38 @@ impl<S: Storage> GetWorkServer<S> {
         let miner = miners.get_mut(session)
             .context("Miner not found in cache")?;
 
+        // Save the shares counted and send a new job if the share difficulty is adjusted
+        let retarget = match (miner.get_share_difficulty_mut(), share_difficulty) {
+            (Some(current), Some(updated)) => {
+                *current = updated;
+                current.retarget(get_current_time_in_millis())
+            },
+            _ => false
+        };
+
         match result {
             BlockResult::Accepted(hash) => {
                 debug!("Miner {} found block {}!", miner, hash);
                 miner.add_new_accepted_block(hash);
 
                 session.send_json(Response::BlockAccepted).await?;
+                if retarget {
+                    self.send_new_job(session, miner.get_public_key(), miner.get_share_difficulty()).await?;
+                }
+            },
+            BlockResult::ShareAccepted => {
+                debug!("Miner {} sent a valid share", miner);
+                session.send_json(Response::ShareAccepted).await?;
+                if retarget {
+                    self.send_new_job(session, miner.get_public_key(), miner.get_share_difficulty()).await?;
+                }
             },
             BlockResult::Rejected(err) => {
                 debug!("Miner {} sent an invalid block", miner);
                 miner.mark_rejected_block();
 
                 session.send_json(Response::BlockRejected(err.to_string())).await?;
-                self.send_new_job(session, miner.get_public_key()).await?;
+                self.send_new_job(session, miner.get_public_key(), miner.get_share_difficulty()).await?;
             }
         };
 
@@ -497,12 +760,13 @@ impl<S: Storage> WebSocketHandler for GetWorkServer<S> {
 
         // We can directly send it here as it's buffered by the channel
         debug!("trying to send initial job to new miner");
-        self.send_new_job(session, &key).await?;
+        let share_difficulty = self.new_session_difficulty();
+        self.send_new_job(session, &key, share_difficulty.as_ref()).await?;
         debug!("initial job has been sent");
 
         {
             let mut miners = self.miners.lock().await;
-            miners.insert(session.clone(), Miner::new(network.is_mainnet(), key.clone(), worker.to_owned()));
+            miners.insert(session.clone(), Miner::new(network.is_mainnet(), key.clone(), worker.to_owned(), share_difficulty));
         }
 
         debug!("miner has been added in miners list");
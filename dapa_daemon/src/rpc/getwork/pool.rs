@@ -0,0 +1,615 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    fs,
+    num::NonZeroUsize,
+    path::Path
+};
+use log::{debug, trace};
+use lru::LruCache;
+use dapa_common::{
+    api::daemon::{
+        GetPoolPayoutsResult,
+        GetPoolSharesResult,
+        PoolBalanceEntry,
+        PoolBlockEntry,
+        PoolBlockStatus,
+        PoolPayoutScheme,
+        PoolWorkerEntry
+    },
+    crypto::{Hash, PublicKey},
+    difficulty::Difficulty,
+    serializer::{Reader, ReaderError, Serializer, Writer},
+    time::{get_current_time_in_millis, TimestampMillis},
+    tokio::{
+        sync::{Mutex, MutexGuard},
+        task::spawn_blocking
+    }
+};
+use crate::{
+    config::{
+        POOL_HASHRATE_WINDOW_MS,
+        POOL_MAX_BLOCKS_HISTORY,
+        POOL_MIN_SHARE_DIFFICULTY,
+        POOL_RETARGET_INTERVAL_MS,
+        POOL_SHARES_CACHE_SIZE,
+        POOL_TARGET_SHARE_TIME_MS
+    },
+    core::error::BlockchainError
+};
+
+// Share difficulty of a miner session
+// It is adjusted so each session submits a share every POOL_TARGET_SHARE_TIME_MS on average
+#[derive(Debug, Clone)]
+pub struct SessionDifficulty {
+    current: u64,
+    // Difficulty used before the last adjustment
+    // Shares of the jobs sent before it are still accepted at this difficulty
+    previous: Option<u64>,
+    last_retarget: TimestampMillis,
+    // Valid shares submitted since the last adjustment
+    shares: u64
+}
+
+impl SessionDifficulty {
+    pub fn new(difficulty: u64, now: TimestampMillis) -> Self {
+        Self {
+            current: difficulty.max(POOL_MIN_SHARE_DIFFICULTY),
+            previous: None,
+            last_retarget: now,
+            shares: 0
+        }
+    }
+
+    pub fn get_current(&self) -> u64 {
+        self.current
+    }
+
+    pub fn get_previous(&self) -> Option<u64> {
+        self.previous
+    }
+
+    pub fn add_share(&mut self) {
+        self.shares += 1;
+    }
+
+    // Adjust the difficulty based on the shares submitted since the last adjustment
+    // A session without any share is considered to have submitted half of one
+    // Returns true if the difficulty has changed
+    pub fn retarget(&mut self, now: TimestampMillis) -> bool {
+        let elapsed = now.saturating_sub(self.last_retarget);
+        if elapsed < POOL_RETARGET_INTERVAL_MS {
+            return false
+        }
+
+        let half_shares = (self.shares * 2).max(1) as u128;
+        let expected = self.current as u128 * half_shares * POOL_TARGET_SHARE_TIME_MS as u128 / (elapsed as u128 * 2);
+        // Each adjustment is limited to a factor of 4
+        let difficulty = (expected.clamp(self.current as u128 / 4, self.current as u128 * 4) as u64)
+            .max(POOL_MIN_SHARE_DIFFICULTY);
+
+        self.last_retarget = now;
+        self.shares = 0;
+        if difficulty == self.current {
+            self.previous = None;
+            return false
+        }
+
+        self.previous = Some(self.current);
+        self.current = difficulty;
+        true
+    }
+}
+
+// Share accepted in the PPLNS window
+struct Share {
+    miner: PublicKey,
+    difficulty: u64
+}
+
+#[derive(Default)]
+struct WorkerStats {
+    shares: u64,
+    invalid_shares: u64,
+    blocks_found: u64,
+    share_difficulty: u64,
+    last_share: TimestampMillis,
+    // Shares received in the hashrate window
+    recent_shares: VecDeque<(TimestampMillis, u64)>
+}
+
+impl WorkerStats {
+    // Remove the shares older than the hashrate window
+    fn prune_recent_shares(&mut self, now: TimestampMillis) {
+        while let Some((timestamp, _)) = self.recent_shares.front() {
+            if now.saturating_sub(*timestamp) <= POOL_HASHRATE_WINDOW_MS {
+                break;
+            }
+            self.recent_shares.pop_front();
+        }
+    }
+
+    // Each share represents on average `difficulty` hashes
+    fn estimate_hashrate(&self) -> u64 {
+        let work: u64 = self.recent_shares.iter()
+            .map(|(_, difficulty)| *difficulty)
+            .sum();
+
+        work * 1000 / POOL_HASHRATE_WINDOW_MS
+    }
+}
+
+struct PoolBlock {
+    hash: Hash,
+    height: u64,
+    // Expected reward when found, replaced by the real one once stable
+    reward: u64,
+    timestamp: TimestampMillis,
+    status: PoolBlockStatus,
+    // PPLNS weight of each miner when the block was found
+    // They are used to credit the reward once the block is stable
+    weights: Vec<(PublicKey, u128)>
+}
+
+impl Serializer for PoolBlock {
+    fn write(&self, writer: &mut Writer) {
+        self.hash.write(writer);
+        writer.write_u64(self.height);
+        writer.write_u64(self.reward);
+        writer.write_u64(self.timestamp);
+        writer.write_u8(match self.status {
+            PoolBlockStatus::Pending => 0,
+            PoolBlockStatus::Confirmed => 1,
+            PoolBlockStatus::Orphaned => 2
+        });
+        writer.write_u64(self.weights.len() as u64);
+        for (key, weight) in self.weights.iter() {
+            key.write(writer);
+            writer.write_u128(*weight);
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let hash = Hash::read(reader)?;
+        let height = reader.read_u64()?;
+        let reward = reader.read_u64()?;
+        let timestamp = reader.read_u64()?;
+        let status = match reader.read_u8()? {
+            0 => PoolBlockStatus::Pending,
+            1 => PoolBlockStatus::Confirmed,
+            2 => PoolBlockStatus::Orphaned,
+            _ => return Err(ReaderError::InvalidValue)
+        };
+
+        let count = reader.read_u64()?;
+        let mut weights = Vec::new();
+        for _ in 0..count {
+            weights.push((PublicKey::read(reader)?, reader.read_u128()?));
+        }
+
+        Ok(Self { hash, height, reward, timestamp, status, weights })
+    }
+
+    fn size(&self) -> usize {
+        self.hash.size() + 8 * 3 + 1 + 8 + self.weights.iter()
+            .map(|(key, weight)| key.size() + weight.size())
+            .sum::<usize>()
+    }
+}
+
+struct PoolState {
+    // Last shares rewarded using PPLNS
+    shares: VecDeque<Share>,
+    // Recent shares PoW hashes to reject duplicates
+    known_shares: LruCache<Hash, ()>,
+    workers: HashMap<(PublicKey, String), WorkerStats>,
+    // Amount credited to each miner
+    balances: HashMap<PublicKey, u64>,
+    blocks: VecDeque<PoolBlock>,
+    // Set when the ledger has changed since it was saved
+    dirty: bool
+}
+
+// Shares accounting of the GetWork server in pool mode
+// The payout ledger (balances and blocks found) is saved on disk,
+// the workers statistics and the PPLNS window are reset when the daemon restarts
+pub struct Pool {
+    // Key receiving the block rewards
+    key: PublicKey,
+    mainnet: bool,
+    // Starting share difficulty of each session
+    share_difficulty: u64,
+    scheme: PoolPayoutScheme,
+    pplns_window: usize,
+    // File storing the payout ledger
+    ledger_path: String,
+    // Taken before the state lock by the ledger writers
+    // and held until the write is done
+    ledger_lock: Mutex<()>,
+    state: Mutex<PoolState>
+}
+
+impl Pool {
+    pub fn new(key: PublicKey, mainnet: bool, share_difficulty: u64, scheme: PoolPayoutScheme, pplns_window: usize, ledger_path: String) -> Result<Self, BlockchainError> {
+        let (balances, blocks) = load_ledger(&ledger_path)?;
+        Ok(Self {
+            key,
+            mainnet,
+            share_difficulty,
+            scheme,
+            pplns_window,
+            ledger_path,
+            ledger_lock: Mutex::new(()),
+            state: Mutex::new(PoolState {
+                shares: VecDeque::new(),
+                known_shares: LruCache::new(NonZeroUsize::new(POOL_SHARES_CACHE_SIZE).expect("Non zero shares cache")),
+                workers: HashMap::new(),
+                balances,
+                blocks,
+                dirty: false
+            })
+        })
+    }
+
+    pub fn get_key(&self) -> &PublicKey {
+        &self.key
+    }
+
+    pub fn get_scheme(&self) -> PoolPayoutScheme {
+        self.scheme
+    }
+
+    // Share difficulty of a new miner session
+    pub fn new_session_difficulty(&self) -> SessionDifficulty {
+        SessionDifficulty::new(self.share_difficulty, get_current_time_in_millis())
+    }
+
+    // Starting share difficulty capped to the block difficulty
+    pub fn get_share_difficulty(&self, block_difficulty: &Difficulty) -> Difficulty {
+        cap_share_difficulty(block_difficulty, self.share_difficulty)
+    }
+
+    // Register a valid share
+    // Returns false if the share was already submitted
+    // With PPS, the reward is credited directly to the miner
+    pub async fn add_share(&self, miner: &PublicKey, worker: &str, pow_hash: Hash, difficulty: u64, reward: u64) -> bool {
+        let mut state = self.state.lock().await;
+        if state.known_shares.put(pow_hash, ()).is_some() {
+            debug!("Duplicated share from {}", worker);
+            return false
+        }
+
+        let state = &mut *state;
+        let stats = state.workers.entry((miner.clone(), worker.to_owned())).or_default();
+        let now = get_current_time_in_millis();
+        stats.shares += 1;
+        stats.share_difficulty = difficulty;
+        stats.last_share = now;
+        stats.recent_shares.push_back((now, difficulty));
+        stats.prune_recent_shares(now);
+
+        match self.scheme {
+            PoolPayoutScheme::Pps => {
+                *state.balances.entry(miner.clone()).or_default() += reward;
+                state.dirty = true;
+            },
+            PoolPayoutScheme::Pplns => {
+                state.shares.push_back(Share { miner: miner.clone(), difficulty });
+                while state.shares.len() > self.pplns_window {
+                    state.shares.pop_front();
+                }
+            }
+        }
+
+        true
+    }
+
+    pub async fn mark_invalid_share(&self, miner: &PublicKey, worker: &str) {
+        let mut state = self.state.lock().await;
+        state.workers.entry((miner.clone(), worker.to_owned()))
+            .or_default()
+            .invalid_shares += 1;
+    }
+
+    // Register a block found by the pool
+    // With PPLNS, the weight of each miner in the window is kept
+    // to split the block reward once it is stable
+    pub async fn add_block(&self, miner: &PublicKey, worker: &str, hash: Hash, height: u64, reward: u64) -> Result<(), BlockchainError> {
+        let ledger_lock = self.ledger_lock.lock().await;
+        let mut state = self.state.lock().await;
+        state.workers.entry((miner.clone(), worker.to_owned()))
+            .or_default()
+            .blocks_found += 1;
+
+        let mut weights: HashMap<PublicKey, u128> = HashMap::new();
+        if self.scheme == PoolPayoutScheme::Pplns {
+            for share in state.shares.iter() {
+                *weights.entry(share.miner.clone()).or_default() += share.difficulty as u128;
+            }
+        }
+
+        state.blocks.push_back(PoolBlock {
+            hash,
+            height,
+            reward,
+            timestamp: get_current_time_in_millis(),
+            status: PoolBlockStatus::Pending,
+            weights: weights.into_iter().collect()
+        });
+
+        // Pending blocks are never removed from the history
+        while state.blocks.len() > POOL_MAX_BLOCKS_HISTORY && state.blocks.front().is_some_and(|block| block.status != PoolBlockStatus::Pending) {
+            state.blocks.pop_front();
+        }
+
+        state.dirty = true;
+        let bytes = serialize_ledger(&mut state);
+        drop(state);
+
+        self.write_ledger(bytes, ledger_lock).await
+    }
+
+    // Hashes of the pending blocks at or below the stable height
+    pub async fn get_stable_pending_blocks(&self, stable_height: u64) -> Vec<(Hash, u64)> {
+        let state = self.state.lock().await;
+        state.blocks.iter()
+            .filter(|block| block.status == PoolBlockStatus::Pending && block.height <= stable_height)
+            .map(|block| (block.hash.clone(), block.height))
+            .collect()
+    }
+
+    // Settle the stable pending blocks
+    // A block with its real reward is confirmed and its reward credited (PPLNS)
+    // A block without reward was not ordered in the DAG and is orphaned
+    pub async fn settle_blocks(&self, results: Vec<(Hash, Option<u64>)>) -> Result<(), BlockchainError> {
+        let ledger_lock = self.ledger_lock.lock().await;
+        let mut guard = self.state.lock().await;
+        let state = &mut *guard;
+        for (hash, reward) in results {
+            let Some(block) = state.blocks.iter_mut().find(|block| block.hash == hash && block.status == PoolBlockStatus::Pending) else {
+                continue;
+            };
+
+            let weights = std::mem::take(&mut block.weights);
+            let Some(reward) = reward else {
+                debug!("Pool block {} at height {} is orphaned", hash, block.height);
+                block.status = PoolBlockStatus::Orphaned;
+                continue;
+            };
+
+            debug!("Pool block {} at height {} is confirmed with reward {}", hash, block.height, reward);
+            block.status = PoolBlockStatus::Confirmed;
+            block.reward = reward;
+
+            let total: u128 = weights.iter().map(|(_, weight)| *weight).sum();
+            if total > 0 {
+                for (key, weight) in weights {
+                    *state.balances.entry(key).or_default() += (reward as u128 * weight / total) as u64;
+                }
+            }
+        }
+
+        state.dirty = true;
+        let bytes = serialize_ledger(state);
+        drop(guard);
+
+        self.write_ledger(bytes, ledger_lock).await
+    }
+
+    // Save the ledger if it has changed since the last save
+    pub async fn flush_ledger(&self) -> Result<(), BlockchainError> {
+        let ledger_lock = self.ledger_lock.lock().await;
+        let mut state = self.state.lock().await;
+        if !state.dirty {
+            return Ok(())
+        }
+
+        let bytes = serialize_ledger(&mut state);
+        drop(state);
+
+        self.write_ledger(bytes, ledger_lock).await
+    }
+
+    // Write the serialized ledger in a temporary file that is renamed
+    // to not corrupt the previous ledger in case of a crash during the write
+    // It is called once the state lock is released, with the ledger lock held
+    // so the writes are done in the same order as the state changes
+    // The ledger is marked as dirty again if the write failed
+    async fn write_ledger(&self, bytes: Vec<u8>, _ledger_lock: MutexGuard<'_, ()>) -> Result<(), BlockchainError> {
+        trace!("saving pool ledger to {}", self.ledger_path);
+        let path = self.ledger_path.clone();
+        let res = spawn_blocking(move || {
+            let tmp = format!("{}.tmp", path);
+            fs::write(&tmp, &bytes)?;
+            fs::rename(&tmp, &path)
+        }).await;
+
+        if let Err(e) = res.map_err(BlockchainError::from).and_then(|res| res.map_err(BlockchainError::from)) {
+            self.state.lock().await.dirty = true;
+            return Err(e)
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_shares(&self, block_difficulty: &Difficulty) -> GetPoolSharesResult<'static> {
+        let mut state = self.state.lock().await;
+        let now = get_current_time_in_millis();
+        let workers = state.workers.iter_mut()
+            .map(|((key, worker), stats)| {
+                stats.prune_recent_shares(now);
+                PoolWorkerEntry {
+                    address: Cow::Owned(key.as_address(self.mainnet)),
+                    worker: Cow::Owned(worker.clone()),
+                    shares: stats.shares,
+                    invalid_shares: stats.invalid_shares,
+                    blocks_found: stats.blocks_found,
+                    hashrate: stats.estimate_hashrate(),
+                    share_difficulty: stats.share_difficulty,
+                    last_share: stats.last_share
+                }
+            })
+            .collect();
+
+        GetPoolSharesResult {
+            share_difficulty: self.get_share_difficulty(block_difficulty),
+            workers
+        }
+    }
+
+    pub async fn get_payouts(&self) -> GetPoolPayoutsResult<'static> {
+        let state = self.state.lock().await;
+        let balances = state.balances.iter()
+            .map(|(key, amount)| PoolBalanceEntry {
+                address: Cow::Owned(key.as_address(self.mainnet)),
+                amount: *amount
+            })
+            .collect();
+
+        let blocks = state.blocks.iter()
+            .map(|block| PoolBlockEntry {
+                hash: Cow::Owned(block.hash.clone()),
+                height: block.height,
+                reward: block.reward,
+                timestamp: block.timestamp,
+                status: block.status
+            })
+            .collect();
+
+        GetPoolPayoutsResult {
+            scheme: self.scheme,
+            address: Cow::Owned(self.key.as_address(self.mainnet)),
+            balances,
+            blocks
+        }
+    }
+}
+
+// Shares can't be harder than the block itself
+pub fn cap_share_difficulty(block_difficulty: &Difficulty, share_difficulty: u64) -> Difficulty {
+    let share_difficulty = Difficulty::from(share_difficulty);
+    if *block_difficulty < share_difficulty {
+        *block_difficulty
+    } else {
+        share_difficulty
+    }
+}
+
+// Serialize the balances and blocks of the ledger, which is then no longer dirty
+// Each section is written as [count u64][entries]
+fn serialize_ledger(state: &mut PoolState) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut writer = Writer::new(&mut bytes);
+    writer.write_u64(state.balances.len() as u64);
+    for (key, amount) in state.balances.iter() {
+        key.write(&mut writer);
+        writer.write_u64(*amount);
+    }
+
+    writer.write_u64(state.blocks.len() as u64);
+    for block in state.blocks.iter() {
+        block.write(&mut writer);
+    }
+
+    state.dirty = false;
+    bytes
+}
+
+// Read the balances and blocks from the ledger
+// Returns an empty ledger if no file was found
+fn load_ledger(path: &str) -> Result<(HashMap<PublicKey, u64>, VecDeque<PoolBlock>), BlockchainError> {
+    trace!("loading pool ledger from {}", path);
+    if !Path::new(path).exists() {
+        debug!("No pool ledger found at {}", path);
+        return Ok((HashMap::new(), VecDeque::new()))
+    }
+
+    let bytes = fs::read(path)?;
+    let mut reader = Reader::new(&bytes);
+    let mut balances = HashMap::new();
+    for _ in 0..reader.read_u64()? {
+        let key = PublicKey::read(&mut reader)?;
+        balances.insert(key, reader.read_u64()?);
+    }
+
+    let mut blocks = VecDeque::new();
+    for _ in 0..reader.read_u64()? {
+        blocks.push_back(PoolBlock::read(&mut reader)?);
+    }
+
+    debug!("Pool ledger loaded with {} balances and {} blocks", balances.len(), blocks.len());
+    Ok((balances, blocks))
+}
+
+#[cfg(test)]
+mod tests {
+    use dapa_common::crypto::KeyPair;
+    use super::*;
+
+    fn ledger_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("dapa-pool-{}-{}.bin", name, get_current_time_in_millis()));
+        path.to_string_lossy().into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_pplns_split_block_reward() {
+        let path = ledger_path("pplns");
+        let pool = Pool::new(KeyPair::new().get_public_key().compress(), false, 100, PoolPayoutScheme::Pplns, 3, path.clone()).unwrap();
+        let alice = KeyPair::new().get_public_key().compress();
+        let bob = KeyPair::new().get_public_key().compress();
+
+        // First share is out of the window
+        assert!(pool.add_share(&alice, "a", Hash::new([0u8; 32]), 100, 0).await);
+        assert!(pool.add_share(&alice, "a", Hash::new([1u8; 32]), 100, 0).await);
+        assert!(pool.add_share(&bob, "b", Hash::new([2u8; 32]), 100, 0).await);
+        assert!(pool.add_share(&bob, "b", Hash::new([3u8; 32]), 100, 0).await);
+        // Duplicated share
+        assert!(!pool.add_share(&bob, "b", Hash::new([3u8; 32]), 100, 0).await);
+
+        pool.add_block(&bob, "b", Hash::zero(), 1, 300).await.unwrap();
+        pool.add_block(&bob, "b", Hash::max(), 2, 300).await.unwrap();
+
+        // Nothing is credited until the blocks are stable
+        assert!(pool.get_payouts().await.balances.is_empty());
+        assert!(pool.get_stable_pending_blocks(0).await.is_empty());
+        assert_eq!(pool.get_stable_pending_blocks(2).await.len(), 2);
+
+        // Second block is orphaned and the first one got a lower reward
+        pool.settle_blocks(vec![(Hash::zero(), Some(150)), (Hash::max(), None)]).await.unwrap();
+
+        // Ledger is reloaded from the disk
+        let pool = Pool::new(KeyPair::new().get_public_key().compress(), false, 100, PoolPayoutScheme::Pplns, 3, path.clone()).unwrap();
+        let payouts = pool.get_payouts().await;
+        let statuses: Vec<_> = payouts.blocks.iter().map(|block| block.status).collect();
+        assert_eq!(statuses, vec![PoolBlockStatus::Confirmed, PoolBlockStatus::Orphaned]);
+
+        let amount_of = |key: &PublicKey| payouts.balances.iter()
+            .find(|entry| entry.address.get_public_key() == key)
+            .map(|entry| entry.amount);
+        assert_eq!(amount_of(&alice), Some(50));
+        assert_eq!(amount_of(&bob), Some(100));
+
+        let shares = pool.get_shares(&Difficulty::from(50u64)).await;
+        assert_eq!(shares.share_difficulty, Difficulty::from(50u64));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_session_difficulty_retarget() {
+        let mut difficulty = SessionDifficulty::new(10_000, 0);
+
+        // Twice the expected shares during the interval
+        for _ in 0..12 {
+            difficulty.add_share();
+        }
+        assert!(!difficulty.retarget(POOL_RETARGET_INTERVAL_MS - 1));
+        assert!(difficulty.retarget(POOL_RETARGET_INTERVAL_MS));
+        assert_eq!(difficulty.get_current(), 20_000);
+        assert_eq!(difficulty.get_previous(), Some(10_000));
+
+        // No share at all, the adjustment is limited
+        assert!(difficulty.retarget(POOL_RETARGET_INTERVAL_MS * 20));
+        assert_eq!(difficulty.get_current(), 5_000);
+    }
+}
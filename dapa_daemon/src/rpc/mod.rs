@@ -1,11 +1,16 @@
 pub mod rpc;
 pub mod getwork;
+pub mod stratum;
+pub mod auth;
 
-use crate::core::{
-    blockchain::Blockchain,
-    config::RPCConfig,
-    error::BlockchainError,
-    storage::Storage
+use crate::{
+    config::RPC_DEFAULT_METHOD_COSTS,
+    core::{
+        blockchain::Blockchain,
+        config::RPCConfig,
+        error::BlockchainError,
+        storage::Storage
+    }
 };
 use actix_web::{
     get,
@@ -21,35 +26,40 @@ use actix_web::{
     },
     http::header,
     dev::ServerHandle,
-    error::Error
+    error::{Error, ErrorForbidden, ErrorUnauthorized}
 };
 use actix_cors::Cors;
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use serde_json::{Value, json};
 use dapa_common::{
     tokio::sync::Mutex,
     api::daemon::NotifyEvent,
     config,
+    crypto::Address,
     rpc::{
         server::{
-            json_rpc,
-            websocket,
             websocket::{
                 EventWebSocketHandler,
                 WebSocketServer,
-                WebSocketServerShared
+                WebSocketServerShared,
+                WebSocketSessionShared
             },
             WebSocketServerHandler,
             RPCServerHandler,
         },
+        ClientKeyResolver,
         InternalRpcError,
+        MethodGuard,
+        RateLimiter,
+        RpcClientKey,
         RPCHandler,
     },
     tokio::spawn_task
 };
 use std::{
-    collections::HashSet,
+    borrow::Cow,
+    collections::{HashMap, HashSet},
     sync::Arc,
 };
 use log::{
@@ -57,14 +67,20 @@ use log::{
     warn,
     error,
 };
-use getwork::GetWorkServer;
+use getwork::{GetWorkServer, Pool};
+use stratum::StratumServer;
+use auth::{RpcAuth, RpcRole};
 
 pub type SharedDaemonRpcServer<S> = Arc<DaemonRpcServer<S>>;
 
 pub struct DaemonRpcServer<S: Storage> {
     handle: Mutex<Option<ServerHandle>>,
     websocket: WebSocketServerShared<EventWebSocketHandler<Arc<Blockchain<S>>, NotifyEvent>>,
-    getwork: Option<WebSocketServerShared<GetWorkServer<S>>>
+    getwork: Option<WebSocketServerShared<GetWorkServer<S>>>,
+    // Stratum server sharing the GetWork jobs
+    stratum: Option<Arc<StratumServer<S>>>,
+    // None if the authentication is disabled
+    auth: Option<Arc<RpcAuth>>
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -76,29 +92,137 @@ pub enum ApiError {
     #[error("P2p engine is not running")]
     NoP2p,
     #[error("WebSocket server is not started")]
-    NoWebSocketServer
+    NoWebSocketServer,
+    #[error("GetWork server is not enabled")]
+    NoGetWorkServer,
+    #[error("GetWork pool mode is not enabled")]
+    NoPoolMode
 }
 
 impl<S: Storage> DaemonRpcServer<S> {
     pub async fn new(
         blockchain: Arc<Blockchain<S>>,
-        config: RPCConfig
+        config: RPCConfig,
+        dir_path: Option<&str>
     ) -> Result<SharedDaemonRpcServer<S>, BlockchainError> {
+        // Shared with the Stratum server to authenticate the miners
+        let auth = RpcAuth::new(&config.auth)
+            .context("Invalid RPC authentication configuration")?
+            .map(Arc::new);
+
         let getwork = if !config.getwork.disable {
             info!("Creating GetWork server...");
-            Some(WebSocketServer::new(GetWorkServer::new(
+            let pool = if config.getwork.pool.enable {
+                let pool_config = &config.getwork.pool;
+                let address = pool_config.address.as_deref()
+                    .context("A pool address is required in pool mode")?;
+                let address = Address::from_string(address)
+                    .context("Invalid pool address")?;
+                if !address.is_normal() {
+                    return Err(anyhow!("Pool address must be in normal format").into())
+                }
+
+                let mainnet = blockchain.get_network().is_mainnet();
+                if address.is_mainnet() != mainnet {
+                    return Err(BlockchainError::InvalidNetwork)
+                }
+
+                // Ledger is stored next to the blockchain
+                let ledger_path = format!("{}pool-{}.bin", dir_path.unwrap_or_default(), blockchain.get_network().to_string().to_lowercase());
+                info!("GetWork pool mode enabled with {:?} payouts and share difficulty {}", pool_config.payout_scheme, pool_config.share_difficulty);
+                Some(Pool::new(address.to_public_key(), mainnet, pool_config.share_difficulty, pool_config.payout_scheme, pool_config.pplns_window, ledger_path)?)
+            } else {
+                None
+            };
+
+            Some(GetWorkServer::new(
                 blockchain.clone(),
                 config.getwork.rate_limit_ms,
-                config.getwork.notify_job_concurrency
-            )))
+                config.getwork.notify_job_concurrency,
+                pool
+            ))
         } else {
             None
         };
 
+        let stratum = match getwork.as_ref() {
+            Some(getwork) if config.stratum.enable => {
+                info!("Creating Stratum server...");
+                Some(StratumServer::new(
+                    blockchain.clone(),
+                    Arc::clone(getwork),
+                    auth.clone(),
+                    &config.stratum.bind_address,
+                    config.stratum.extra_nonce_size
+                ).await?)
+            },
+            None if config.stratum.enable => {
+                warn!("Stratum server requires the GetWork server to be enabled");
+                None
+            },
+            _ => None
+        };
+
+        let getwork = getwork.map(WebSocketServer::new);
+
         // create the RPC Handler which will register and contains all available methods
         let mut rpc_handler = RPCHandler::new(blockchain, config.batch_limit);
         rpc::register_methods(&mut rpc_handler, !config.getwork.disable, config.allow_private_methods);
 
+        if config.rate_limit.requests_per_second > 0 {
+            let mut method_costs: HashMap<Cow<'static, str>, u64> = RPC_DEFAULT_METHOD_COSTS.iter()
+                .map(|(method, cost)| (Cow::Borrowed(*method), *cost))
+                .collect();
+
+            for method_cost in config.rate_limit.method_costs.iter() {
+                method_costs.insert(Cow::Owned(method_cost.method.clone()), method_cost.cost);
+            }
+
+            info!("RPC rate limit enabled: {} requests per second with a burst of {}", config.rate_limit.requests_per_second, config.rate_limit.burst);
+            rpc_handler.set_rate_limiter(RateLimiter::new(config.rate_limit.requests_per_second, config.rate_limit.burst, method_costs));
+        }
+
+        if let Some(auth) = auth.clone() {
+            // Authenticated WebSocket sessions are rate limited per API key
+            // HTTP requests already have their key in the context
+            let auth_ws = Arc::clone(&auth);
+            let resolver: ClientKeyResolver = Box::new(move |context| {
+                if context.get::<RpcRole>().is_some() {
+                    return None
+                }
+
+                let session: &WebSocketSessionShared<EventWebSocketHandler<Arc<Blockchain<S>>, NotifyEvent>> = context.get()?;
+                auth_ws.authenticate(session.get_request().headers())
+                    .ok()
+                    .and_then(|(api_key, _)| api_key)
+                    .map(RpcClientKey::from_api_key)
+            });
+            rpc_handler.set_client_key_resolver(resolver);
+
+            let guard: MethodGuard = Box::new(move |context, method| {
+                // HTTP requests are authenticated before being handled
+                // WebSocket sessions are authenticated using their upgrade request
+                let role: &RpcRole = match context.get::<RpcRole>() {
+                    Some(role) => role,
+                    None => {
+                        let session: &WebSocketSessionShared<EventWebSocketHandler<Arc<Blockchain<S>>, NotifyEvent>> = context.get()
+                            .ok_or(InternalRpcError::InvalidContext)?;
+
+                        auth.authenticate(session.get_request().headers())
+                            .map(|(_, role)| role.as_ref())
+                            .map_err(|_| InternalRpcError::MethodNotAllowed(method.to_owned()))?
+                    }
+                };
+
+                if !role.is_method_allowed(method) {
+                    return Err(InternalRpcError::MethodNotAllowed(method.to_owned()))
+                }
+
+                Ok(())
+            });
+            rpc_handler.set_method_guard(guard);
+        }
+
         // create the default websocket server (support event & rpc methods)
         let ws = WebSocketServer::new(EventWebSocketHandler::new(rpc_handler, config.notify_events_concurrency));
 
@@ -106,6 +230,8 @@ impl<S: Storage> DaemonRpcServer<S> {
             handle: Mutex::new(None),
             websocket: ws,
             getwork,
+            stratum,
+            auth,
         });
 
         let prometheus = if config.prometheus.enable {
@@ -152,9 +278,9 @@ impl<S: Storage> DaemonRpcServer<S> {
                     .app_data(web::Data::from(server))
                     .app_data(web::Data::new(prometheus.as_ref().map(|(_, handle)| handle.clone())))
                     // Traditional HTTP
-                    .route("/json_rpc", web::post().to(json_rpc::<Arc<Blockchain<S>>, DaemonRpcServer<S>>))
+                    .route("/json_rpc", web::post().to(json_rpc_endpoint::<S>))
                     // WebSocket support
-                    .route("/json_rpc", web::get().to(websocket::<EventWebSocketHandler<Arc<Blockchain<S>>, NotifyEvent>, DaemonRpcServer<S>>))
+                    .route("/json_rpc", web::get().to(websocket_endpoint::<S>))
                     .route("/getwork/{address}/{worker}", web::get().to(getwork_endpoint::<S>))
                     .service(index)
                     .wrap(cors);
@@ -200,6 +326,10 @@ impl<S: Storage> DaemonRpcServer<S> {
     }
 
     pub async fn stop(&self) {
+        if let Some(stratum) = self.stratum.as_ref() {
+            stratum.stop();
+        }
+
         info!("Stopping RPC Server...");
         let mut handle = self.handle.lock().await;
         if let Some(handle) = handle.take() {
@@ -245,7 +375,50 @@ async fn prometheus_metrics(handle: Data<Option<PrometheusHandle>>) -> Result<Ht
     })
 }
 
+// JSON RPC endpoint authenticating the request before handling it
+async fn json_rpc_endpoint<S: Storage>(server: Data<DaemonRpcServer<S>>, request: HttpRequest, body: web::Bytes) -> Result<HttpResponse, Error> {
+    let handler = server.get_rpc_handler();
+    let mut context = handler.create_context();
+    let mut client_key = None;
+    if let Some(auth) = server.auth.as_ref() {
+        let (api_key, role) = auth.authenticate(request.headers())
+            .map_err(ErrorUnauthorized)?;
+
+        context.insert_ref(role.as_ref());
+        client_key = api_key.map(RpcClientKey::from_api_key);
+    }
+
+    // Authenticated clients are rate limited per API key, others per IP
+    context.insert(client_key.unwrap_or_else(|| RpcClientKey::from_ip(request.peer_addr())));
+
+    Ok(match handler.handle_request_with_context(context, &body).await? {
+        Some(result) => HttpResponse::Ok().json(result),
+        None => HttpResponse::Ok().finish(),
+    })
+}
+
+// WebSocket endpoint authenticating the client before the upgrade
+async fn websocket_endpoint<S: Storage>(server: Data<DaemonRpcServer<S>>, request: HttpRequest, stream: Payload) -> Result<HttpResponse, Error> {
+    if let Some(auth) = server.auth.as_ref() {
+        auth.authenticate(request.headers())
+            .map_err(ErrorUnauthorized)?;
+    }
+
+    server.get_websocket().handle_connection(request, stream).await
+}
+
+// GetWork endpoint authenticating the miner before the upgrade
+// Its role must allow the mining method
 async fn getwork_endpoint<S: Storage>(server: Data<DaemonRpcServer<S>>, request: HttpRequest, stream: Payload) -> Result<HttpResponse, Error> {
+    if let Some(auth) = server.auth.as_ref() {
+        let (_, role) = auth.authenticate(request.headers())
+            .map_err(ErrorUnauthorized)?;
+
+        if !role.is_mining_allowed() {
+            return Err(ErrorForbidden(format!("role '{}' is not allowed to mine", role.get_name())))
+        }
+    }
+
     match &server.getwork {
         Some(getwork) => getwork.handle_connection(request, stream).await,
         None => Ok(HttpResponse::NotFound().reason("GetWork server is not enabled").finish()) // getwork server is not started
@@ -7,6 +7,7 @@ use crate::{
     },
     core::{
         state,
+        backup,
         blockchain::{
             get_block_dev_fee,
             get_block_reward,
@@ -46,6 +47,7 @@ use dapa_common::{
         TopoHeight
     },
     config::{
+        MAX_GAS_USAGE_PER_TX,
         MAX_TRANSACTION_SIZE,
         MAXIMUM_SUPPLY,
         VERSION,
@@ -53,6 +55,8 @@ use dapa_common::{
     },
     contract::{
         ContractLog,
+        RegisteredContractAbi,
+        MAX_ABI_SIZE,
         ScheduledExecution,
         ScheduledExecutionKindLog,
     },
@@ -99,6 +103,10 @@ const MAX_BLOCKS: u64 = 20;
 const MAX_TXS: usize = 20;
 // Maximum history (versions fetched) per call
 const MAX_ACCOUNT_HISTORY: usize = 20;
+// Maximum indexed transactions per account fetched per call
+const MAX_ACCOUNT_TRANSACTIONS: usize = 100;
+// Maximum indexed contract events fetched per call
+const MAX_CONTRACT_EVENTS: usize = 100;
 // Maximum accounts to fetch per call
 const MAX_ACCOUNTS: usize = 100;
 // Maximum contracts to fetch per call
@@ -370,6 +378,7 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
     handler.register_method_with_params("has_balance", async_handler!(has_balance::<S>));
     handler.register_method_with_params("get_balance_at_topoheight", async_handler!(get_balance_at_topoheight::<S>));
     handler.register_method_with_params("get_balances_at_maximum_topoheight", async_handler!(get_balances_at_maximum_topoheight::<S>));
+    handler.register_method_with_params("verify_human_readable_proof", async_handler!(verify_human_readable_proof::<S>));
 
     handler.register_method_with_params("get_nonce", async_handler!(get_nonce::<S>));
     handler.register_method_with_params("has_nonce", async_handler!(has_nonce::<S>));
@@ -388,6 +397,7 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
 
     // Transactions
     handler.register_method_with_params("submit_transaction", async_handler!(submit_transaction::<S>));
+    handler.register_method_with_params("simulate_transaction", async_handler!(simulate_transaction::<S>));
     handler.register_method_with_params("get_transaction_executor", async_handler!(get_transaction_executor::<S>));
     handler.register_method_with_params_and_return_schema::<_, RPCTransaction>("get_transaction", async_handler!(get_transaction::<S>));
     handler.register_method_with_params_and_return_schema::<_, Vec<RPCTransaction>>("get_transactions", async_handler!(get_transactions::<S>));
@@ -413,6 +423,7 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
 
     // Accounts
     handler.register_method_with_params("get_account_history", async_handler!(get_account_history::<S>));
+    handler.register_method_with_params("get_account_transactions", async_handler!(get_account_transactions::<S>));
     handler.register_method_with_params("get_account_assets", async_handler!(get_account_assets::<S>));
     handler.register_method_with_params("get_accounts", async_handler!(get_accounts::<S>));
     handler.register_method_with_params("is_account_registered", async_handler!(is_account_registered::<S>));
@@ -434,11 +445,14 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
 
     // Contracts
     handler.register_method_with_params("get_contract_logs", async_handler!(get_contract_logs::<S>));
+    handler.register_method_with_params("get_contract_events", async_handler!(get_contract_events::<S>));
     handler.register_method_with_params("get_contract_scheduled_executions_at_topoheight", async_handler!(get_contract_scheduled_executions_at_topoheight::<S>));
     handler.register_method_with_params("get_contract_registered_executions_at_topoheight", async_handler!(get_contract_registered_executions_at_topoheight::<S>));
 
     handler.register_method_with_params("get_contracts_outputs", async_handler!(get_contracts_outputs::<S>));
     handler.register_method_with_params_and_return_schema::<_, RPCVersioned<Versioned<Option<Cow<xelis_vm::Module>>>>>("get_contract_module", async_handler!(get_contract_module::<S>));
+    handler.register_method_with_params("get_contract_abi", async_handler!(get_contract_abi::<S>));
+    handler.register_method_with_params("register_contract_abi", async_handler!(register_contract_abi::<S>));
     handler.register_method_with_params("get_contract_data", async_handler!(get_contract_data::<S>));
     handler.register_method_with_params("get_contract_data_at_topoheight", async_handler!(get_contract_data_at_topoheight::<S>));
     handler.register_method_with_params("get_contract_balance", async_handler!(get_contract_balance::<S>));
@@ -447,11 +461,14 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
     handler.register_method_with_params("get_contracts", async_handler!(get_contracts::<S>));
     handler.register_method_with_params("get_contract_data_entries", async_handler!(get_contract_data_entries::<S>));
     handler.register_method_with_params("get_contract_transactions", async_handler!(get_contract_transactions::<S>));
+    handler.register_method_with_params("call_contract_view", async_handler!(call_contract_view::<S>));
 
     if allow_mining_methods {
         handler.register_method_with_params("get_block_template", async_handler!(get_block_template::<S>));
         handler.register_method_with_params("get_miner_work", async_handler!(get_miner_work::<S>));
         handler.register_method_with_params("submit_block", async_handler!(submit_block::<S>));
+        handler.register_method_no_params("get_pool_shares", async_handler!(get_pool_shares::<S>, single));
+        handler.register_method_no_params("get_pool_payouts", async_handler!(get_pool_payouts::<S>, single));
     }
 
     if allow_private_methods {
@@ -459,6 +476,7 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
         handler.register_method_with_params("prune_chain", async_handler!(prune_chain::<S>));
         handler.register_method_with_params("rewind_chain", async_handler!(rewind_chain::<S>));
         handler.register_method_no_params("clear_caches", async_handler!(clear_caches::<S>, single));
+        handler.register_method_with_params("backup_db", async_handler!(backup_db::<S>));
     }
 }
 
@@ -605,6 +623,33 @@ async fn submit_block<S: Storage>(context: &Context<'_, '_>, params: SubmitBlock
     Ok(true)
 }
 
+async fn get_pool_shares<S: Storage>(context: &Context<'_, '_>) -> Result<GetPoolSharesResult<'static>, InternalRpcError> {
+    let blockchain = chain_from_context::<S>(context)?;
+    let difficulty = blockchain.get_difficulty().await;
+
+    let rpc = blockchain.get_rpc().read().await;
+    let getwork = rpc.as_ref()
+        .and_then(|rpc| rpc.getwork_server().as_ref())
+        .ok_or_else(|| InternalRpcError::InvalidParamsAny(ApiError::NoGetWorkServer.into()))?;
+    let pool = getwork.get_handler().get_pool()
+        .ok_or_else(|| InternalRpcError::InvalidParamsAny(ApiError::NoPoolMode.into()))?;
+
+    Ok(pool.get_shares(&difficulty).await)
+}
+
+async fn get_pool_payouts<S: Storage>(context: &Context<'_, '_>) -> Result<GetPoolPayoutsResult<'static>, InternalRpcError> {
+    let blockchain = chain_from_context::<S>(context)?;
+
+    let rpc = blockchain.get_rpc().read().await;
+    let getwork = rpc.as_ref()
+        .and_then(|rpc| rpc.getwork_server().as_ref())
+        .ok_or_else(|| InternalRpcError::InvalidParamsAny(ApiError::NoGetWorkServer.into()))?;
+    let pool = getwork.get_handler().get_pool()
+        .ok_or_else(|| InternalRpcError::InvalidParamsAny(ApiError::NoPoolMode.into()))?;
+
+    Ok(pool.get_payouts().await)
+}
+
 async fn get_balance<S: Storage>(context: &Context<'_, '_>, params: GetBalanceParams<'_>) -> Result<GetBalanceResult, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
     if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
@@ -748,6 +793,53 @@ async fn get_balance_at_topoheight<S: Storage>(context: &Context<'_, '_>, params
     Ok(balance)
 }
 
+// Verify a balance or ownership proof against the balance ciphertext of the prover at the proof topoheight
+async fn verify_human_readable_proof<S: Storage>(context: &Context<'_, '_>, params: VerifyHumanReadableProofParams<'_>) -> Result<VerifyHumanReadableProofResult, InternalRpcError> {
+    let blockchain = chain_from_context::<S>(context)?;
+
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    let proof = params.proof;
+    let asset = proof.asset().clone();
+    let topoheight = proof.topoheight();
+
+    let ciphertext = {
+        let storage = blockchain.get_storage().read().await;
+        let chain_cache = storage.chain_cache().await;
+        if topoheight > chain_cache.topoheight {
+            return Err(InternalRpcError::UnexpectedParams).context("Proof topoheight cannot be greater than current chain topoheight")?
+        }
+
+        let balance = storage.get_balance_at_exact_topoheight(params.address.get_public_key(), &asset, topoheight).await
+            .context("Error while retrieving balance at exact topo height")?;
+
+        balance.take_balance()
+            .take_ciphertext()
+            .context("Error while decompressing balance ciphertext")?
+    };
+
+    let public_key = params.address.get_public_key()
+        .decompress()
+        .context("Error while decompressing public key")?;
+
+    let valid = match proof.verify(&public_key, ciphertext) {
+        Ok(()) => true,
+        Err(e) => {
+            debug!("Invalid proof for {} at topoheight {}: {}", params.address, topoheight, e);
+            false
+        }
+    };
+
+    Ok(VerifyHumanReadableProofResult {
+        valid,
+        amount: proof.amount(),
+        asset,
+        topoheight
+    })
+}
+
 async fn get_balances_at_maximum_topoheight<S: Storage>(context: &Context<'_, '_>, params: GetBalancesAtMaximumTopoHeightParams<'_>) -> Result<Vec<Option<RPCVersioned<VersionedBalance>>>, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
 
@@ -941,6 +1033,23 @@ async fn submit_transaction<S: Storage>(context: &Context<'_, '_>, params: Submi
     Ok(true)
 }
 
+async fn simulate_transaction<S: Storage>(context: &Context<'_, '_>, params: SimulateTransactionParams) -> Result<SimulateTransactionResult<'static>, InternalRpcError> {
+    // x2 because of hex encoding
+    if params.data.len() > MAX_TRANSACTION_SIZE * 2 {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Transaction size cannot be greater than {}", human_bytes(MAX_TRANSACTION_SIZE as f64)))?
+    }
+
+    let transaction = Transaction::from_hex(&params.data)
+        .map_err(|err| InternalRpcError::InvalidParamsAny(err.into()))?;
+
+    let blockchain = chain_from_context::<S>(context)?;
+    let storage = blockchain.get_storage().read().await;
+    let result = blockchain.simulate_transaction(&*storage, Arc::new(transaction)).await
+        .context("Error while simulating transaction")?;
+
+    Ok(result)
+}
+
 async fn get_transaction<S: Storage>(context: &Context<'_, '_>, params: GetTransactionParams<'_>) -> Result<Value, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
     let storage = blockchain.get_storage().read().await;
@@ -1066,7 +1175,11 @@ async fn get_mempool_summary<S: Storage>(context: &Context<'_, '_>, params: GetM
 
     Ok(json!(GetMempoolSummaryResult {
         transactions,
-        total
+        total,
+        total_size: mempool.get_total_size(),
+        max_txs: mempool.get_max_txs(),
+        max_size: mempool.get_max_size(),
+        tx_ttl: mempool.get_tx_ttl(),
     }))
 }
 
@@ -1542,6 +1655,51 @@ async fn get_account_history<S: Storage>(context: &Context<'_, '_>, params: GetA
     Ok(history)
 }
 
+// Retrieve the transactions of an account from the archival index
+async fn get_account_transactions<S: Storage>(context: &Context<'_, '_>, params: GetAccountTransactionsParams<'_>) -> Result<Vec<AccountTransactionEntry>, InternalRpcError> {
+    let blockchain = chain_from_context::<S>(context)?;
+    if !blockchain.is_account_transactions_index_enabled() {
+        return Err(BlockchainError::AccountTransactionsIndexDisabled.into())
+    }
+
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    if !params.incoming_flow && !params.outgoing_flow {
+        return Err(InternalRpcError::InvalidParams("No transaction direction was selected"));
+    }
+
+    let maximum = check_maximum(params.maximum, MAX_ACCOUNT_TRANSACTIONS)?;
+    let minimum_topoheight = params.minimum_topoheight.unwrap_or(0);
+    let maximum_topoheight = params.maximum_topoheight.unwrap_or(TopoHeight::MAX);
+    if minimum_topoheight > maximum_topoheight {
+        return Err(InternalRpcError::InvalidParams("Minimum topoheight must be lower than maximum topoheight"));
+    }
+
+    let key = params.address.get_public_key();
+    let storage = blockchain.get_storage().read().await;
+    let after = params.after.map(|cursor| (cursor.topoheight, cursor.hash));
+    let transactions = storage.get_account_transactions(key, minimum_topoheight, maximum_topoheight, after).await
+        .context("Error while retrieving account transactions")?
+        .filter(|res| res.as_ref().map_or(true, |(_, _, entry)| {
+            ((params.incoming_flow && entry.incoming) || (params.outgoing_flow && entry.outgoing))
+                && params.asset.as_ref().is_none_or(|asset| entry.assets.contains(asset.as_ref()))
+        }))
+        .take(maximum)
+        .map(|res| res.map(|(topoheight, hash, entry)| AccountTransactionEntry {
+            topoheight,
+            hash,
+            incoming: entry.incoming,
+            outgoing: entry.outgoing,
+            assets: entry.assets,
+        }))
+        .collect::<Result<Vec<_>, BlockchainError>>()
+        .context("Error while collecting account transactions")?;
+
+    Ok(transactions)
+}
+
 async fn get_account_assets<S: Storage>(context: &Context<'_, '_>, params: GetAccountAssetsParams<'_>) -> Result<Vec<Hash>, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
     if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
@@ -1976,6 +2134,38 @@ async fn has_multisig_at_topoheight<S: Storage>(context: &Context<'_, '_>, param
     Ok(multisig)
 }
 
+// Retrieve the ABI registered for a contract only if it describes its latest module
+// An ABI registered for a previous module is kept but never used
+async fn get_latest_contract_abi<S: Storage>(storage: &S, contract: &Hash) -> Result<Option<RegisteredContractAbi>, BlockchainError> {
+    let Some(registered) = storage.get_contract_abi(contract).await? else {
+        return Ok(None)
+    };
+
+    let topoheight = storage.get_last_topoheight_for_contract(contract).await?;
+    Ok(topoheight.filter(|topoheight| *topoheight == registered.topoheight).map(|_| registered))
+}
+
+// Set the name of the event logs using the ABI registered for their contract
+pub async fn set_contract_logs_event_names<S: Storage>(storage: &S, logs: &mut [RPCContractLog<'_>]) -> Result<(), BlockchainError> {
+    let mut abis: HashMap<Hash, Option<RegisteredContractAbi>> = HashMap::new();
+    for log in logs.iter_mut() {
+        let Some(contract) = log.get_event_contract().cloned() else {
+            continue
+        };
+
+        if !abis.contains_key(&contract) {
+            let abi = get_latest_contract_abi(storage, &contract).await?;
+            abis.insert(contract.clone(), abi);
+        }
+
+        if let Some(registered) = &abis[&contract] {
+            log.set_event_name(&registered.abi);
+        }
+    }
+
+    Ok(())
+}
+
 async fn get_contract_logs<S: Storage>(context: &Context<'_, '_>, params: GetContractLogsParams<'_>) -> Result<Vec<RPCContractLog<'static>>, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
     let is_mainnet = blockchain.get_network().is_mainnet();
@@ -1983,14 +2173,68 @@ async fn get_contract_logs<S: Storage>(context: &Context<'_, '_>, params: GetCon
     let logs =  storage.get_contract_logs_for_caller(&params.caller).await
         .context("Error while retrieving contract logs")?;
 
-    let rpc_logs = logs
-        .into_iter()
+    let mut rpc_logs = logs.into_iter()
         .map(|log| RPCContractLog::from_owned(log, is_mainnet))
         .collect::<Vec<_>>();
 
+    // Events are named using the ABI of their contract when registered
+    set_contract_logs_event_names(&*storage, &mut rpc_logs).await
+        .context("Error while naming contract events")?;
+
     Ok(rpc_logs)
 }
 
+// Retrieve the events fired by a contract from the archival index
+async fn get_contract_events<S: Storage>(context: &Context<'_, '_>, params: GetContractEventsParams<'_>) -> Result<Vec<ContractEventEntry<'static>>, InternalRpcError> {
+    let blockchain = chain_from_context::<S>(context)?;
+    if !blockchain.is_contract_events_index_enabled() {
+        return Err(BlockchainError::ContractEventsIndexDisabled.into())
+    }
+
+    let maximum = check_maximum(params.maximum, MAX_CONTRACT_EVENTS)?;
+    let minimum_topoheight = params.minimum_topoheight.unwrap_or(0);
+    let maximum_topoheight = params.maximum_topoheight.unwrap_or(TopoHeight::MAX);
+    if minimum_topoheight > maximum_topoheight {
+        return Err(InternalRpcError::InvalidParams("Minimum topoheight must be lower than maximum topoheight"));
+    }
+
+    let storage = blockchain.get_storage().read().await;
+    let after = params.after.map(|cursor| (cursor.topoheight, cursor.event_id, cursor.index));
+    let events = storage.get_contract_events(&params.contract, params.id, minimum_topoheight, maximum_topoheight, after).await
+        .context("Error while retrieving contract events")?
+        .take(maximum)
+        .collect::<Result<Vec<_>, BlockchainError>>()
+        .context("Error while collecting contract events")?;
+
+    // Events are ordered by topoheight, so the block hash is only fetched once per topoheight
+    let mut entries = Vec::with_capacity(events.len());
+    let mut last_block: Option<(TopoHeight, Hash)> = None;
+    for IndexedContractEvent { topoheight, event_id, index, kind, data } in events {
+        let block_hash = match &last_block {
+            Some((topo, hash)) if *topo == topoheight => hash.clone(),
+            _ => {
+                let hash = storage.get_hash_at_topo_height(topoheight).await
+                    .context("Error while retrieving block hash at topoheight")?;
+                last_block = Some((topoheight, hash.clone()));
+                hash
+            }
+        };
+
+        entries.push(ContractEventEntry {
+            event: ContractEvent {
+                topoheight,
+                block_hash: Cow::Owned(block_hash),
+                event_id,
+                data: Cow::Owned(data)
+            },
+            index,
+            kind
+        });
+    }
+
+    Ok(entries)
+}
+
 async fn get_contract_scheduled_executions_at_topoheight<S: Storage>(context: &Context<'_, '_>, params: GetContractScheduledExecutionsAtTopoHeightParams) -> Result<Vec<ScheduledExecution>, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
 
@@ -2107,11 +2351,21 @@ async fn get_contracts_outputs<S: Storage>(context: &Context<'_, '_>, params: Ge
 async fn get_contract_module<S: Storage>(context: &Context<'_, '_>, params: GetContractModuleParams<'_>) -> Result<Value, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
     let storage = blockchain.get_storage().read().await;
-    let Some(topoheight) = storage.get_last_topoheight_for_contract(&params.contract).await? else {
-        return Err(InternalRpcError::InvalidParams("no contract module available"));
+    // Upgraded contracts keep their previous modules as older versions
+    let (topoheight, version) = match params.topoheight {
+        Some(maximum_topoheight) => storage.get_contract_at_maximum_topoheight_for(&params.contract, maximum_topoheight).await
+            .context("Error while retrieving contract module")?
+            .ok_or(InternalRpcError::InvalidParams("no contract module available"))?,
+        None => {
+            let Some(topoheight) = storage.get_last_topoheight_for_contract(&params.contract).await? else {
+                return Err(InternalRpcError::InvalidParams("no contract module available"));
+            };
+            let version = storage.get_contract_at_topoheight_for(&params.contract, topoheight).await
+                .context("Error while retrieving contract module")?;
+
+            (topoheight, version)
+        }
     };
-    let version = storage.get_contract_at_topoheight_for(&params.contract, topoheight).await
-        .context("Error while retrieving contract module")?;
 
     Ok(json!(RPCVersioned {
         topoheight,
@@ -2119,6 +2373,93 @@ async fn get_contract_module<S: Storage>(context: &Context<'_, '_>, params: GetC
     }))
 }
 
+async fn get_contract_abi<S: Storage>(context: &Context<'_, '_>, params: GetContractAbiParams<'_>) -> Result<Option<RegisteredContractAbi>, InternalRpcError> {
+    let blockchain = chain_from_context::<S>(context)?;
+    let storage = blockchain.get_storage().read().await;
+    let abi = get_latest_contract_abi(&*storage, &params.contract).await
+        .context("Error while retrieving contract ABI")?;
+
+    Ok(abi)
+}
+
+// Verify that the ABI describes the latest module of the contract
+// and is newer than the one registered
+async fn verify_contract_abi_version<S: Storage>(storage: &S, params: &RegisterContractAbiParams<'_>) -> Result<(), InternalRpcError> {
+    let Some(topoheight) = storage.get_last_topoheight_for_contract(&params.contract).await? else {
+        return Err(InternalRpcError::InvalidParams("no contract module available"));
+    };
+    if topoheight != params.topoheight {
+        return Err(InternalRpcError::InvalidParams("ABI doesn't describe the latest contract module"));
+    }
+
+    let registered = storage.get_contract_abi(&params.contract).await
+        .context("Error while retrieving contract ABI")?;
+    if registered.is_some_and(|registered| registered.topoheight == params.topoheight && registered.version >= params.version) {
+        return Err(InternalRpcError::InvalidParams("ABI version must be greater than the registered one"));
+    }
+
+    Ok(())
+}
+
+// Verify the ABI against the latest module of the contract
+// and that it is signed by the contract deployer
+async fn verify_contract_abi<S: Storage>(storage: &S, params: &RegisterContractAbiParams<'_>) -> Result<(), InternalRpcError> {
+    if params.abi.size() > MAX_ABI_SIZE {
+        return Err(InternalRpcError::InvalidParams("ABI is too big"));
+    }
+
+    verify_contract_abi_version(storage, params).await?;
+
+    let version = storage.get_contract_at_topoheight_for(&params.contract, params.topoheight).await
+        .context("Error while retrieving contract module")?;
+    let module = version.get()
+        .as_ref()
+        .ok_or(InternalRpcError::InvalidParams("no contract module available"))?;
+
+    params.abi.verify(&module.module)
+        .map_err(InternalRpcError::InvalidParamsAny)?;
+
+    // Contract hash is the hash of its deploy transaction
+    let tx = storage.get_transaction(&params.contract).await
+        .context("Error while retrieving contract deploy transaction")?;
+    if !matches!(tx.get_data(), TransactionType::DeployContract(_)) {
+        return Err(InternalRpcError::InvalidParams("contract is not deployed by a transaction"));
+    }
+
+    let key = tx.get_source()
+        .decompress()
+        .context("Error while decompressing deployer public key")?;
+    if !params.abi.verify_signature(&params.contract, params.topoheight, params.version, &params.signature, &key) {
+        return Err(InternalRpcError::InvalidParams("invalid ABI signature"));
+    }
+
+    Ok(())
+}
+
+// Register the ABI of a contract in this node
+// It must match the latest module and be signed by the contract deployer
+async fn register_contract_abi<S: Storage>(context: &Context<'_, '_>, params: RegisterContractAbiParams<'_>) -> Result<bool, InternalRpcError> {
+    let blockchain = chain_from_context::<S>(context)?;
+    {
+        let storage = blockchain.get_storage().read().await;
+        verify_contract_abi(&*storage, &params).await?;
+    }
+
+    // A block or another registration may have been applied in between
+    let mut storage = blockchain.get_storage().write().await;
+    verify_contract_abi_version(&*storage, &params).await?;
+
+    let registered = RegisteredContractAbi {
+        topoheight: params.topoheight,
+        version: params.version,
+        abi: params.abi.into_owned()
+    };
+    storage.set_contract_abi(&params.contract, &registered).await
+        .context("Error while storing contract ABI")?;
+
+    Ok(true)
+}
+
 async fn get_contract_data<S: Storage>(context: &Context<'_, '_>, params: GetContractDataParams<'_>) -> Result<RPCVersioned<Versioned<Option<ValueCell>>>, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
     let storage = blockchain.get_storage().read().await;
@@ -2269,6 +2610,43 @@ async fn get_contract_transactions<S: Storage>(context: &Context<'_, '_>, params
     Ok(transactions)
 }
 
+async fn call_contract_view<S: Storage>(context: &Context<'_, '_>, params: CallContractViewParams<'_>) -> Result<CallContractViewResult, InternalRpcError> {
+    let max_gas = params.max_gas.unwrap_or(MAX_GAS_USAGE_PER_TX);
+    if max_gas > MAX_GAS_USAGE_PER_TX {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Max gas cannot be greater than {}", MAX_GAS_USAGE_PER_TX))?
+    }
+
+    let blockchain = chain_from_context::<S>(context)?;
+    let storage = blockchain.get_storage().read().await;
+    let chain_cache = storage.chain_cache().await;
+
+    let topoheight = match params.topoheight {
+        Some(topoheight) => {
+            if topoheight > chain_cache.topoheight {
+                return Err(InternalRpcError::UnexpectedParams).context("Topoheight cannot be greater than current chain topoheight")?
+            }
+
+            let pruned_topoheight = storage.get_pruned_topoheight().await
+                .context("Error while retrieving pruned topoheight")?;
+            if pruned_topoheight.is_some_and(|pruned| topoheight < pruned) {
+                return Err(InternalRpcError::UnexpectedParams).context("Topoheight cannot be lower than pruned topoheight")?
+            }
+
+            topoheight
+        },
+        None => chain_cache.topoheight
+    };
+
+    let (used_gas, exit_value) = blockchain.call_contract_view(&*storage, &params.contract, params.chunk_id, params.parameters, max_gas, topoheight).await
+        .context("Error while calling contract view")?;
+
+    Ok(CallContractViewResult {
+        topoheight,
+        used_gas,
+        exit_value,
+    })
+}
+
 async fn get_contract_balance_at_topoheight<S: Storage>(context: &Context<'_, '_>, params: GetContractBalanceAtTopoHeightParams<'_>) -> Result<Versioned<u64>, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
     let storage = blockchain.get_storage().read().await;
@@ -2341,6 +2719,17 @@ async fn rewind_chain<S: Storage>(context: &Context<'_, '_>, params: RewindChain
     })
 }
 
+async fn backup_db<S: Storage>(context: &Context<'_, '_>, params: BackupDBParams<'_>) -> Result<BackupDBResult, InternalRpcError> {
+    let blockchain = chain_from_context::<S>(context)?;
+
+    let manifest = backup::backup_blockchain(blockchain, &params.path).await?;
+
+    Ok(BackupDBResult {
+        topoheight: manifest.topoheight,
+        top_hash: manifest.top_hash,
+    })
+}
+
 async fn clear_caches<S: Storage>(context: &Context<'_, '_>) -> Result<(), InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
     let mut storage = blockchain.get_storage().write().await;
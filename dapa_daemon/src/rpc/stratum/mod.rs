@@ -0,0 +1,584 @@
+use std::{
+    borrow::Cow,
+    future,
+    net::SocketAddr,
+    sync::Arc
+};
+use anyhow::{bail, Context};
+use log::{debug, error, info, trace};
+use rand::{rngs::OsRng, RngCore};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use dapa_common::{
+    block::{MinerWork, EXTRA_NONCE_SIZE},
+    crypto::{Address, Hash, PublicKey},
+    difficulty::Difficulty,
+    serializer::Serializer,
+    time::{get_current_time_in_millis, TimestampMillis},
+    tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::{
+            tcp::{OwnedReadHalf, OwnedWriteHalf},
+            TcpListener,
+            TcpStream
+        },
+        select,
+        spawn_task,
+        sync::broadcast::{self, error::RecvError}
+    }
+};
+use crate::{
+    config::STRATUM_MAX_MESSAGE_SIZE,
+    core::{blockchain::Blockchain, storage::Storage}
+};
+use super::{
+    auth::{RpcAuth, RpcAuthError},
+    getwork::{BlockResult, GetWorkServer, MiningJob, SessionDifficulty}
+};
+
+// Stratum server for mining proxies and pools
+// Messages are line delimited JSON-RPC requests:
+// - mining.subscribe: returns the server extra nonce and the size left to the miner
+// - mining.authorize: params are ["<address>.<worker>", <password>]
+//   the password is the API key of the miner when the RPC authentication is enabled
+// - mining.notify: params are [job_id, miner_work, algorithm, height, topoheight, clean_jobs]
+//   miner_work is the hex encoded work with the miner key and the server extra nonce set
+// - mining.set_difficulty: params are [difficulty], the share difficulty in pool mode
+// - mining.submit: params are [worker, job_id, extra_nonce, timestamp, nonce]
+//   extra_nonce is the hex encoded part of the extra nonce left to the miner
+//   nonce is the hex encoded big endian nonce
+// Jobs and blocks found are shared with the GetWork server
+pub struct StratumServer<S: Storage> {
+    blockchain: Arc<Blockchain<S>>,
+    getwork: Arc<GetWorkServer<S>>,
+    // RPC authentication, None if disabled
+    auth: Option<Arc<RpcAuth>>,
+    // Bytes of the extra nonce set by the server for each session
+    extra_nonce_size: usize,
+    // Used to stop the listener and all the sessions
+    exit: broadcast::Sender<()>
+}
+
+#[derive(Debug, thiserror::Error)]
+enum StratumError {
+    #[error("{}", _0)]
+    Other(String),
+    #[error("Invalid params: {}", _0)]
+    InvalidParams(&'static str),
+    #[error("Method not found")]
+    MethodNotFound,
+    #[error("Unauthorized worker")]
+    Unauthorized,
+    #[error("Not subscribed")]
+    NotSubscribed,
+    #[error("Authentication failed: {}", _0)]
+    Authentication(RpcAuthError),
+    #[error("Role '{}' is not allowed to mine", _0)]
+    MiningNotAllowed(String)
+}
+
+impl StratumError {
+    // Error codes used by the Stratum protocol
+    fn code(&self) -> u16 {
+        match self {
+            Self::Unauthorized | Self::Authentication(_) | Self::MiningNotAllowed(_) => 24,
+            Self::NotSubscribed => 25,
+            _ => 20
+        }
+    }
+
+    // The session is closed once the error is sent
+    fn closes_session(&self) -> bool {
+        matches!(self, Self::Authentication(_) | Self::MiningNotAllowed(_))
+    }
+}
+
+#[derive(Deserialize)]
+struct StratumRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value
+}
+
+struct StratumSession {
+    addr: SocketAddr,
+    // Extra nonce bytes set by the server, unique per session
+    extra_nonce: Vec<u8>,
+    subscribed: bool,
+    // Miner key and worker name once authorized
+    miner: Option<(PublicKey, String)>,
+    // Last difficulty sent to the miner
+    difficulty: Option<Difficulty>,
+    // Share difficulty of the session in pool mode
+    share_difficulty: Option<SessionDifficulty>,
+    // Set when the current job must be sent after the response
+    pending_job: bool,
+    // Jobs notified by the GetWork server once authorized
+    jobs: Option<broadcast::Receiver<MiningJob>>
+}
+
+impl StratumSession {
+    fn new(addr: SocketAddr, extra_nonce: Vec<u8>) -> Self {
+        Self {
+            addr,
+            extra_nonce,
+            subscribed: false,
+            miner: None,
+            difficulty: None,
+            share_difficulty: None,
+            pending_job: false,
+            jobs: None
+        }
+    }
+
+    // Returns the subscriptions with the extra nonce of the session
+    // and the number of extra nonce bytes left to the miner
+    fn subscribe(&mut self) -> Value {
+        self.subscribed = true;
+        let id = hex::encode(&self.extra_nonce);
+        json!([
+            [["mining.set_difficulty", id], ["mining.notify", id]],
+            id,
+            EXTRA_NONCE_SIZE - self.extra_nonce.len()
+        ])
+    }
+
+    // Send the job again with the new difficulty if the share difficulty is adjusted
+    fn retarget_share_difficulty(&mut self) {
+        if self.share_difficulty.as_mut().is_some_and(|difficulty| difficulty.retarget(get_current_time_in_millis())) {
+            self.pending_job = true;
+        }
+    }
+}
+
+impl<S: Storage> StratumServer<S> {
+    pub async fn new(blockchain: Arc<Blockchain<S>>, getwork: Arc<GetWorkServer<S>>, auth: Option<Arc<RpcAuth>>, bind_address: &str, extra_nonce_size: usize) -> Result<Arc<Self>, anyhow::Error> {
+        // The miner must have at least one byte of the extra nonce
+        if extra_nonce_size == 0 || extra_nonce_size >= EXTRA_NONCE_SIZE {
+            bail!("Stratum extra nonce size must be between 1 and {}", EXTRA_NONCE_SIZE - 1);
+        }
+
+        let listener = TcpListener::bind(bind_address).await
+            .with_context(|| format!("Error while binding Stratum server on {}", bind_address))?;
+        info!("Stratum server listening on {}", bind_address);
+
+        let server = Arc::new(Self {
+            blockchain,
+            getwork,
+            auth,
+            extra_nonce_size,
+            exit: broadcast::channel(1).0
+        });
+
+        let zelf = Arc::clone(&server);
+        spawn_task("stratum-server", zelf.listen(listener));
+
+        Ok(server)
+    }
+
+    // Stop accepting new miners and close all the sessions
+    pub fn stop(&self) {
+        info!("Stopping Stratum server...");
+        // a send error only means that nothing is running anymore
+        let _ = self.exit.send(());
+    }
+
+    async fn listen(self: Arc<Self>, listener: TcpListener) {
+        let mut exit = self.exit.subscribe();
+        loop {
+            select! {
+                _ = exit.recv() => break,
+                res = listener.accept() => match res {
+                    Ok((stream, addr)) => {
+                        debug!("New Stratum connection from {}", addr);
+                        let zelf = Arc::clone(&self);
+                        spawn_task(format!("stratum-{}", addr), async move {
+                            if let Err(e) = zelf.handle_connection(stream, addr).await {
+                                debug!("Stratum connection {} closed: {}", addr, e);
+                            }
+                        });
+                    },
+                    Err(e) => error!("Error while accepting Stratum connection: {}", e)
+                }
+            }
+        }
+
+        info!("Stratum server is now stopped!");
+    }
+
+    async fn handle_connection(&self, stream: TcpStream, addr: SocketAddr) -> Result<(), anyhow::Error> {
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        let mut exit = self.exit.subscribe();
+
+        let mut extra_nonce = vec![0u8; self.extra_nonce_size];
+        OsRng.fill_bytes(&mut extra_nonce);
+        let mut session = StratumSession::new(addr, extra_nonce);
+
+        let mut buffer = Vec::new();
+        loop {
+            select! {
+                _ = exit.recv() => break,
+                res = read_message(&mut reader, &mut buffer) => {
+                    if !res? {
+                        break;
+                    }
+
+                    let response = match serde_json::from_slice::<StratumRequest>(&buffer) {
+                        Ok(request) => {
+                            let result = self.handle_request(&mut session, &request).await;
+                            Some((request.id, result))
+                        },
+                        // Ignore the empty lines
+                        Err(_) if buffer.iter().all(u8::is_ascii_whitespace) => None,
+                        Err(e) => Some((Value::Null, Err(StratumError::Other(e.to_string()))))
+                    };
+                    buffer.clear();
+
+                    if let Some((id, result)) = response {
+                        let close = result.as_ref().is_err_and(StratumError::closes_session);
+                        let message = match result {
+                            Ok(result) => json!({ "id": id, "result": result, "error": null }),
+                            Err(e) => json!({ "id": id, "result": null, "error": [e.code(), e.to_string(), null] })
+                        };
+                        write_message(&mut writer, &message).await?;
+
+                        if close {
+                            debug!("Stratum session {} rejected", addr);
+                            break;
+                        }
+                    }
+
+                    if session.pending_job {
+                        session.pending_job = false;
+                        let job = self.getwork.get_current_job().await?;
+                        self.send_job(&mut writer, &mut session, &job).await?;
+                    }
+                },
+                res = next_job(&mut session.jobs) => match res {
+                    Ok(job) => self.send_job(&mut writer, &mut session, &job).await?,
+                    Err(RecvError::Lagged(skipped)) => debug!("Stratum session {} skipped {} jobs", addr, skipped),
+                    Err(RecvError::Closed) => break
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn handle_request(&self, session: &mut StratumSession, request: &StratumRequest) -> Result<Value, StratumError> {
+        trace!("Stratum request {} from {}", request.method, session.addr);
+        match request.method.as_str() {
+            "mining.subscribe" => Ok(session.subscribe()),
+            // The extra nonce of a session never changes
+            "mining.extranonce.subscribe" => Ok(json!(true)),
+            "mining.authorize" => self.authorize(session, &request.params).await.map(|_| json!(true)),
+            "mining.submit" => self.submit(session, &request.params).await.map(|_| json!(true)),
+            _ => Err(StratumError::MethodNotFound)
+        }
+    }
+
+    async fn authorize(&self, session: &mut StratumSession, params: &Value) -> Result<(), StratumError> {
+        if !session.subscribed {
+            return Err(StratumError::NotSubscribed)
+        }
+
+        let (address, worker, password) = parse_authorize_params(params)?;
+        authenticate_miner(self.auth.as_deref(), password)?;
+
+        let network = self.blockchain.get_network();
+        if address.is_mainnet() != network.is_mainnet() {
+            return Err(StratumError::Other(format!("Address is not in same network state, should be in {} mode", network.to_string().to_lowercase())))
+        }
+
+        // Reject miners if node is not yet synced with the network
+        {
+            let p2p = self.blockchain.get_p2p().read().await;
+            if let Some(p2p) = p2p.as_ref() {
+                if p2p.is_syncing_chain() {
+                    return Err(StratumError::Other("Node is not yet synced with the network. Please try again shortly.".to_owned()))
+                }
+            }
+        }
+
+        debug!("Stratum miner {} authorized as {}.{}", session.addr, address, worker);
+        session.miner = Some((address.to_public_key(), worker.to_owned()));
+        if session.share_difficulty.is_none() {
+            session.share_difficulty = self.getwork.new_session_difficulty();
+        }
+        if session.jobs.is_none() {
+            session.jobs = Some(self.getwork.subscribe_jobs());
+        }
+        session.pending_job = true;
+
+        Ok(())
+    }
+
+    async fn submit(&self, session: &mut StratumSession, params: &Value) -> Result<(), StratumError> {
+        let (key, worker) = session.miner.as_ref()
+            .ok_or(StratumError::Unauthorized)?;
+
+        let (job_id, miner_extra_nonce, timestamp, nonce) = parse_submit_params(params, EXTRA_NONCE_SIZE - session.extra_nonce.len())?;
+
+        let mut work = MinerWork::new(job_id, timestamp);
+        work.set_miner(Cow::Borrowed(self.getwork.get_job_miner(key)));
+        work.set_nonce(nonce);
+        {
+            let extra_nonce = work.get_extra_nonce();
+            let (server, miner) = extra_nonce.split_at_mut(session.extra_nonce.len());
+            server.copy_from_slice(&session.extra_nonce);
+            miner.copy_from_slice(&miner_extra_nonce);
+        }
+
+        let result = match self.getwork.submit_miner_work(work, key, worker, session.share_difficulty.as_mut()).await {
+            Ok(result) => result,
+            Err(e) => {
+                debug!("Error while accepting Stratum miner job: {}", e);
+                BlockResult::Rejected(e.into())
+            }
+        };
+
+        match result {
+            BlockResult::Accepted(hash) => {
+                debug!("Stratum miner {} ({}) found block {}!", session.addr, worker, hash);
+                session.retarget_share_difficulty();
+                Ok(())
+            },
+            BlockResult::ShareAccepted => {
+                debug!("Stratum miner {} ({}) sent a valid share", session.addr, worker);
+                session.retarget_share_difficulty();
+                Ok(())
+            },
+            BlockResult::Rejected(e) => {
+                debug!("Stratum miner {} ({}) sent an invalid block", session.addr, worker);
+                session.pending_job = true;
+                Err(StratumError::Other(e.to_string()))
+            }
+        }
+    }
+
+    // Send the job with the miner key and the session extra nonce
+    // The difficulty is only sent when it changes
+    async fn send_job(&self, writer: &mut OwnedWriteHalf, session: &mut StratumSession, job: &MiningJob) -> Result<(), anyhow::Error> {
+        // Sessions without any share are adjusted here
+        if let Some(share_difficulty) = session.share_difficulty.as_mut() {
+            share_difficulty.retarget(get_current_time_in_millis());
+        }
+
+        let difficulty = self.getwork.get_job_difficulty(&job.difficulty, session.share_difficulty.as_ref());
+        if session.difficulty != Some(difficulty) {
+            write_message(writer, &json!({ "id": null, "method": "mining.set_difficulty", "params": [difficulty] })).await?;
+            session.difficulty = Some(difficulty);
+        }
+
+        let Some((key, _)) = session.miner.as_ref() else {
+            return Ok(())
+        };
+
+        let mut work = MinerWork::new(job.header_work_hash.clone(), job.timestamp);
+        work.set_miner(Cow::Borrowed(self.getwork.get_job_miner(key)));
+        work.get_extra_nonce()[..session.extra_nonce.len()].copy_from_slice(&session.extra_nonce);
+
+        let params = json!([job.header_work_hash.to_hex(), work.to_hex(), job.algorithm, job.height, job.topoheight, true]);
+        write_message(writer, &json!({ "id": null, "method": "mining.notify", "params": params })).await
+    }
+}
+
+// Parse the miner address, the worker name and the password of a mining.authorize request
+fn parse_authorize_params(params: &Value) -> Result<(Address, &str, Option<&str>), StratumError> {
+    let user = params.get(0)
+        .and_then(Value::as_str)
+        .ok_or(StratumError::InvalidParams("expected <address>.<worker>"))?;
+
+    let (addr, worker) = user.split_once('.').unwrap_or((user, ""));
+    if worker.len() > 32 {
+        return Err(StratumError::InvalidParams("worker name must be less or equal to 32 chars"))
+    }
+
+    let address = Address::from_string(addr)
+        .map_err(|_| StratumError::InvalidParams("invalid miner address"))?;
+    if !address.is_normal() {
+        return Err(StratumError::InvalidParams("address should be in normal format"))
+    }
+
+    let password = params.get(1)
+        .and_then(Value::as_str)
+        .filter(|password| !password.is_empty());
+
+    Ok((address, worker, password))
+}
+
+// Check the password of a miner as its API key when the RPC authentication is enabled
+// Like for the GetWork server, its role must allow mining
+fn authenticate_miner(auth: Option<&RpcAuth>, password: Option<&str>) -> Result<(), StratumError> {
+    let Some(auth) = auth else {
+        return Ok(())
+    };
+
+    let (_, role) = auth.authenticate_api_key(password)
+        .map_err(StratumError::Authentication)?;
+
+    if !role.is_mining_allowed() {
+        return Err(StratumError::MiningNotAllowed(role.get_name().to_owned()))
+    }
+
+    Ok(())
+}
+
+// Parse the job id, the miner part of the extra nonce, the timestamp and the nonce of a mining.submit request
+fn parse_submit_params(params: &Value, miner_extra_nonce_size: usize) -> Result<(Hash, Vec<u8>, TimestampMillis, u64), StratumError> {
+    let job_id = params.get(1)
+        .and_then(Value::as_str)
+        .and_then(|id| Hash::from_hex(id).ok())
+        .ok_or(StratumError::InvalidParams("invalid job id"))?;
+
+    let miner_extra_nonce = params.get(2)
+        .and_then(Value::as_str)
+        .and_then(|v| hex::decode(v).ok())
+        .filter(|v| v.len() == miner_extra_nonce_size)
+        .ok_or(StratumError::InvalidParams("invalid extra nonce"))?;
+
+    let timestamp = params.get(3)
+        .and_then(Value::as_u64)
+        .ok_or(StratumError::InvalidParams("invalid timestamp"))?;
+
+    let nonce = params.get(4)
+        .and_then(Value::as_str)
+        .and_then(|v| hex::decode(v).ok())
+        .and_then(|v| <[u8; 8]>::try_from(v).ok())
+        .map(u64::from_be_bytes)
+        .ok_or(StratumError::InvalidParams("invalid nonce"))?;
+
+    Ok((job_id, miner_extra_nonce, timestamp, nonce))
+}
+
+// Read a line delimited message, returns false once the connection is closed
+// Partially read bytes are kept in the buffer so it can be used in a select
+async fn read_message(reader: &mut BufReader<OwnedReadHalf>, buffer: &mut Vec<u8>) -> Result<bool, anyhow::Error> {
+    let limit = STRATUM_MAX_MESSAGE_SIZE.saturating_sub(buffer.len()) as u64;
+    (&mut *reader).take(limit).read_until(b'\n', buffer).await?;
+
+    if buffer.last() == Some(&b'\n') {
+        return Ok(true)
+    }
+
+    if buffer.len() >= STRATUM_MAX_MESSAGE_SIZE {
+        bail!("Stratum message is too large");
+    }
+
+    Ok(false)
+}
+
+async fn write_message(writer: &mut OwnedWriteHalf, message: &Value) -> Result<(), anyhow::Error> {
+    let mut bytes = serde_json::to_vec(message)?;
+    bytes.push(b'\n');
+    writer.write_all(&bytes).await?;
+
+    Ok(())
+}
+
+// Wait for the next job, never resolves until the miner is authorized
+async fn next_job(jobs: &mut Option<broadcast::Receiver<MiningJob>>) -> Result<MiningJob, RecvError> {
+    match jobs {
+        Some(jobs) => jobs.recv().await,
+        None => future::pending().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use dapa_common::crypto::KeyPair;
+    use crate::core::config::{RPCApiKeyConfig, RPCAuthConfig, RPCRoleConfig};
+    use super::*;
+
+    #[test]
+    fn test_subscribe() {
+        let mut session = StratumSession::new(SocketAddr::from(([127, 0, 0, 1], 20103)), vec![0xAB, 0xCD]);
+        assert!(!session.subscribed);
+
+        let result = session.subscribe();
+        assert!(session.subscribed);
+        assert_eq!(result, json!([
+            [["mining.set_difficulty", "abcd"], ["mining.notify", "abcd"]],
+            "abcd",
+            EXTRA_NONCE_SIZE - 2
+        ]));
+    }
+
+    #[test]
+    fn test_parse_authorize_params() {
+        let address = KeyPair::new().get_public_key().to_address(false);
+        let (parsed, worker, password) = parse_authorize_params(&json!([format!("{}.rig-1", address), "secret"])).unwrap();
+        assert_eq!(parsed, address);
+        assert_eq!(worker, "rig-1");
+        assert_eq!(password, Some("secret"));
+
+        // Worker name and password are optional
+        let (_, worker, password) = parse_authorize_params(&json!([address.to_string(), ""])).unwrap();
+        assert_eq!(worker, "");
+        assert!(password.is_none());
+        assert!(parse_authorize_params(&json!([address.to_string()])).unwrap().2.is_none());
+
+        assert!(matches!(parse_authorize_params(&json!([])), Err(StratumError::InvalidParams(_))));
+        assert!(matches!(parse_authorize_params(&json!(["invalid.rig-1"])), Err(StratumError::InvalidParams(_))));
+        assert!(matches!(parse_authorize_params(&json!([format!("{}.{}", address, "a".repeat(33))])), Err(StratumError::InvalidParams(_))));
+    }
+
+    #[test]
+    fn test_authenticate_miner() {
+        // Everyone can mine without authentication
+        assert!(authenticate_miner(None, None).is_ok());
+
+        let config = RPCAuthConfig {
+            roles: vec![
+                RPCRoleConfig::from_str("miner=submit_block").unwrap(),
+                RPCRoleConfig::from_str("reader=get_*").unwrap(),
+            ],
+            api_keys: vec![
+                RPCApiKeyConfig::from_str("mining=miner").unwrap(),
+                RPCApiKeyConfig::from_str("reading=reader").unwrap(),
+            ],
+            public_role: None
+        };
+        let auth = RpcAuth::new(&config).unwrap().unwrap();
+        assert!(authenticate_miner(Some(&auth), Some("mining")).is_ok());
+
+        let err = authenticate_miner(Some(&auth), Some("reading")).unwrap_err();
+        assert!(matches!(err, StratumError::MiningNotAllowed(_)));
+        assert!(err.closes_session());
+
+        let err = authenticate_miner(Some(&auth), Some("invalid")).unwrap_err();
+        assert!(matches!(err, StratumError::Authentication(RpcAuthError::InvalidApiKey)));
+        assert_eq!(err.code(), 24);
+        assert!(err.closes_session());
+
+        assert!(matches!(authenticate_miner(Some(&auth), None), Err(StratumError::Authentication(RpcAuthError::MissingAuthorization))));
+    }
+
+    #[test]
+    fn test_parse_submit_params() {
+        let job_id = Hash::new([1; 32]);
+        let params = json!(["worker", job_id.to_hex(), "0102", 1000, "0000000000000005"]);
+        let (parsed_job_id, extra_nonce, timestamp, nonce) = parse_submit_params(&params, 2).unwrap();
+        assert_eq!(parsed_job_id, job_id);
+        assert_eq!(extra_nonce, vec![1, 2]);
+        assert_eq!(timestamp, 1000);
+        assert_eq!(nonce, 5);
+
+        // The miner part of the extra nonce must fill the space left by the server
+        assert!(matches!(parse_submit_params(&params, 3), Err(StratumError::InvalidParams("invalid extra nonce"))));
+
+        let params = json!(["worker", "invalid", "0102", 1000, "0000000000000005"]);
+        assert!(matches!(parse_submit_params(&params, 2), Err(StratumError::InvalidParams("invalid job id"))));
+
+        let params = json!(["worker", job_id.to_hex(), "0102", "1000", "0000000000000005"]);
+        assert!(matches!(parse_submit_params(&params, 2), Err(StratumError::InvalidParams("invalid timestamp"))));
+
+        // Nonce must be 8 bytes
+        let params = json!(["worker", job_id.to_hex(), "0102", 1000, "05"]);
+        assert!(matches!(parse_submit_params(&params, 2), Err(StratumError::InvalidParams("invalid nonce"))));
+    }
+}
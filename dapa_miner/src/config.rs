@@ -1,2 +1,12 @@
 // daemon address by default when no specified
-pub const DEFAULT_DAEMON_ADDRESS: &str = "ws://127.0.0.1:20101";
\ No newline at end of file
+pub const DEFAULT_DAEMON_ADDRESS: &str = "ws://127.0.0.1:20101";
+// consecutive blocks rejected before switching to the next daemon
+pub const DEFAULT_FAILOVER_REJECTED_BLOCKS: usize = 3;
+// interval in seconds to check if a higher priority daemon is available again
+pub const DEFAULT_FAILBACK_INTERVAL: u64 = 60;
+// timeout in seconds when checking if a daemon is available
+pub const DAEMON_HEALTH_CHECK_TIMEOUT: u64 = 5;
+// interval in seconds between each hashrate sample of the stats API
+pub const STATS_HASHRATE_SAMPLE_INTERVAL: u64 = 10;
+// timeout in seconds to read a request on the stats API
+pub const STATS_REQUEST_TIMEOUT: u64 = 5;
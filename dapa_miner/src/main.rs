@@ -1,4 +1,6 @@
 pub mod config;
+#[cfg(feature = "api_stats")]
+pub mod stats;
 
 use std::{
     fs::File,
@@ -16,7 +18,12 @@ use std::{
     thread,
     time::Duration
 };
-use crate::config::DEFAULT_DAEMON_ADDRESS;
+use crate::config::{
+    DEFAULT_DAEMON_ADDRESS,
+    DEFAULT_FAILOVER_REJECTED_BLOCKS,
+    DEFAULT_FAILBACK_INTERVAL,
+    DAEMON_HEALTH_CHECK_TIMEOUT
+};
 use futures_util::{StreamExt, SinkExt};
 use serde::{Serialize, Deserialize};
 use tokio::{
@@ -27,16 +34,32 @@ use tokio::{
         Mutex
     },
     task::JoinHandle,
-    time::Instant
+    time::{
+        interval_at,
+        timeout,
+        Instant
+    }
 };
 #[cfg(feature = "api_stats")]
+use std::sync::OnceLock;
+#[cfg(feature = "api_stats")]
 use tokio::{
-    io::AsyncWriteExt,
-    net::TcpListener
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::interval
+};
+#[cfg(feature = "api_stats")]
+use crate::{
+    config::{STATS_HASHRATE_SAMPLE_INTERVAL, STATS_REQUEST_TIMEOUT},
+    stats::{MinerStats, StatsReport}
 };
 use tokio_tungstenite::{
     connect_async,
+    MaybeTlsStream,
+    WebSocketStream,
     tungstenite::{
+        client::IntoClientRequest,
+        http::{header::AUTHORIZATION, HeaderValue},
         Message,
         Error as TungsteniteError
     }
@@ -101,6 +124,14 @@ fn default_daemon_address() -> String {
     DEFAULT_DAEMON_ADDRESS.to_owned()
 }
 
+fn default_failover_rejected_blocks() -> usize {
+    DEFAULT_FAILOVER_REJECTED_BLOCKS
+}
+
+fn default_failback_interval() -> u64 {
+    DEFAULT_FAILBACK_INTERVAL
+}
+
 fn default_iterations() -> usize {
     100
 }
@@ -208,6 +239,19 @@ pub struct Config {
     #[clap(long, default_value_t = String::from(DEFAULT_DAEMON_ADDRESS))]
     #[serde(default = "default_daemon_address")]
     daemon_address: String,
+    /// Fallback daemon addresses used when the daemon is unavailable
+    /// They are tried by order of priority, the daemon address being the primary one
+    #[clap(long = "fallback-daemon-address")]
+    #[serde(default)]
+    fallback_daemon_addresses: Vec<String>,
+    /// Consecutive blocks rejected before switching to the next daemon (0 to disable)
+    #[clap(long, default_value_t = DEFAULT_FAILOVER_REJECTED_BLOCKS)]
+    #[serde(default = "default_failover_rejected_blocks")]
+    failover_rejected_blocks: usize,
+    /// Interval in seconds to check if a higher priority daemon is available again
+    #[clap(long, default_value_t = DEFAULT_FAILBACK_INTERVAL)]
+    #[serde(default = "default_failback_interval")]
+    failback_interval: u64,
     /// Bind address for stats API
     #[cfg(feature = "api_stats")]
     #[clap(long)]
@@ -220,6 +264,11 @@ pub struct Config {
     #[clap(short, long, default_value_t = String::from("default"))]
     #[serde(default = "default_worker_name")]
     worker: String,
+    /// API key sent to the daemon when its RPC authentication is enabled
+    /// Its role must allow the `submit_block` method
+    #[clap(long)]
+    #[serde(default)]
+    api_key: Option<String>,
     /// JSON File to load the configuration from
     #[clap(long)]
     #[serde(skip)]
@@ -244,6 +293,7 @@ enum ThreadNotification<'a> {
 pub enum SocketMessage {
     NewJob(GetMinerWorkResult),
     BlockAccepted,
+    ShareAccepted,
     BlockRejected(String)
 }
 
@@ -253,14 +303,27 @@ static BLOCKS_FOUND: AtomicUsize = AtomicUsize::new(0);
 static BLOCKS_REJECTED: AtomicUsize = AtomicUsize::new(0);
 static HASHRATE_COUNTER: AtomicUsize = AtomicUsize::new(0);
 #[cfg(feature = "api_stats")]
-static HASHRATE: AtomicU64 = AtomicU64::new(0);
+static STATS: OnceLock<MinerStats> = OnceLock::new();
 static JOB_ELAPSED: RwLock<Option<Instant>> = RwLock::new(None);
+// Daemon currently used for mining
+static ACTIVE_DAEMON: RwLock<Option<String>> = RwLock::new(None);
 
 
 lazy_static! {
     static ref HASHRATE_LAST_TIME: Mutex<Instant> = Mutex::new(Instant::now());
 }
 
+// Result of a message received from the daemon
+enum DaemonEvent {
+    None,
+    BlockAccepted,
+    BlockRejected,
+    // The connection must be closed
+    Exit
+}
+
+type DaemonStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
 // After how many iterations we update the timestamp of the block to avoid too much CPU usage 
 const UPDATE_EVERY_NONCE: u64 = 10;
 
@@ -351,14 +414,20 @@ async fn main() -> Result<()> {
     }
 
     // start communication task
-    let task = spawn_task("communication", communication_task(config.daemon_address, sender.clone(), block_receiver, address, config.worker));
+    let mut daemon_addresses = vec![config.daemon_address];
+    daemon_addresses.extend(config.fallback_daemon_addresses);
+    let failback_interval = Duration::from_secs(config.failback_interval.max(1));
+    let task = spawn_task("communication", communication_task(daemon_addresses, config.failover_rejected_blocks, failback_interval, sender.clone(), block_receiver, address, config.worker, config.api_key));
     
     let stats_task: Option<JoinHandle<Result<()>>>;
     #[cfg(feature = "api_stats")]
     {
         // start stats task
         stats_task = match config.api_bind_address {
-            Some(addr) => Some(spawn_task("broadcast", broadcast_stats_task(addr))),
+            Some(addr) => {
+                STATS.get_or_init(|| MinerStats::new(threads as usize));
+                Some(spawn_task("broadcast", broadcast_stats_task(addr)))
+            },
             None => None,
         };
     }
@@ -401,43 +470,79 @@ async fn main() -> Result<()> {
 }
 
 // This Tokio task will runs indefinitely until the user stops the miner himself.
-// It maintains a http listener and sends stats on connection in json.
+// It maintains a http listener and sends stats on connection in json,
+// or in Prometheus text format when requested on the `/metrics` path.
 #[cfg(feature = "api_stats")]
 async fn broadcast_stats_task(broadcast_address: String) -> Result<()> {
     info!("Starting broadcast task");
+    // Start TCP listener
+    let listener = TcpListener::bind(broadcast_address).await?;
+    let mut sample = interval(Duration::from_secs(STATS_HASHRATE_SAMPLE_INTERVAL));
     loop {
-        // Start TCP listener
-        let listener = TcpListener::bind(broadcast_address).await?;
-        loop {
-            let (mut socket, _) = listener.accept().await?;
-
-            let blocks_found = BLOCKS_FOUND.load(Ordering::SeqCst);
-            let blocks_rejected = BLOCKS_REJECTED.load(Ordering::SeqCst);
-            let hashrate = HASHRATE.load(Ordering::SeqCst);
-
-            // Build JSON data
-            let data = serde_json::json!({
-                "accepted": blocks_found,
-                "rejected": blocks_rejected,
-                "hashrate": hashrate,
-                "hashrate_formatted": format_hashrate(hashrate as f64),
-            });
-
-            // Build HTTP response
-            let status_line = "HTTP/1.1 200 OK\r\n";
-            let content_type = "Content-Type: application/json\r\n";
-            let contents = data.to_string();
-            let length = contents.len();
-            let response = format!("{status_line}{content_type}Content-Length: {length}\r\n\r\n{contents}");
-
-            // Send HTTP repsonse and close socket
-            AsyncWriteExt::write_all(&mut socket, response.as_bytes())
-                .await?;
-            socket.shutdown().await?;
+        select! {
+            _ = sample.tick() => {
+                if let Some(stats) = STATS.get() {
+                    stats.sample_hashrates();
+                }
+            },
+            res = listener.accept() => {
+                let (socket, _) = res?;
+                spawn_task("stats-request", async move {
+                    if let Err(e) = handle_stats_request(socket).await {
+                        debug!("Error while handling stats request: {}", e);
+                    }
+                });
+            }
         }
     }
 }
 
+#[cfg(feature = "api_stats")]
+async fn handle_stats_request(mut socket: TcpStream) -> Result<()> {
+    // Only the request line is needed to route the request
+    let mut buffer = [0u8; 1024];
+    let read = timeout(Duration::from_secs(STATS_REQUEST_TIMEOUT), socket.read(&mut buffer)).await
+        .context("Timed out while reading stats request")??;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+    let report = build_stats_report();
+    let (content_type, contents) = if path == "/metrics" {
+        ("text/plain; version=0.0.4", report.to_prometheus())
+    } else {
+        ("application/json", serde_json::to_string(&report)?)
+    };
+
+    // Build HTTP response
+    let status_line = "HTTP/1.1 200 OK\r\n";
+    let length = contents.len();
+    let response = format!("{status_line}Content-Type: {content_type}\r\nContent-Length: {length}\r\n\r\n{contents}");
+
+    // Send HTTP repsonse and close socket
+    socket.write_all(response.as_bytes()).await?;
+    socket.shutdown().await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "api_stats")]
+fn build_stats_report() -> StatsReport {
+    let mut report = STATS.get()
+        .map(MinerStats::build_report)
+        .unwrap_or_default();
+
+    report.accepted = BLOCKS_FOUND.load(Ordering::SeqCst);
+    report.rejected = BLOCKS_REJECTED.load(Ordering::SeqCst);
+    report.hashrate_formatted = format_hashrate(report.hashrate as f64);
+    report.topoheight = CURRENT_TOPO_HEIGHT.load(Ordering::SeqCst);
+    report.job_elapsed_ms = JOB_ELAPSED.read().unwrap()
+        .map(|instant| instant.elapsed().as_millis() as u64);
+    report.connected = WEBSOCKET_CONNECTED.load(Ordering::SeqCst);
+    report.daemon_address = ACTIVE_DAEMON.read().unwrap().clone();
+
+    report
+}
+
 
 // Benchmark the miner with the specified number of threads and iterations
 // It will output the total time, total iterations, time per PoW and hashrate for each number of threads
@@ -474,51 +579,62 @@ fn benchmark(threads: usize, iterations: usize, algorithm: Algorithm) {
 // It maintains a WebSocket connection with the daemon and notify all threads when it receive a new job.
 // Its also the task who have the job to send directly the new block found by one of the threads.
 // This allow mining threads to only focus on mining and receiving jobs through memory channels.
-async fn communication_task(daemon_address: String, job_sender: broadcast::Sender<ThreadNotification<'_>>, mut block_receiver: mpsc::Receiver<MinerWork<'_>>, address: Address, worker: String) {
+// Daemons are used by order of priority: on disconnect or too many blocks rejected, the next one is used,
+// and higher priority daemons are checked periodically to switch back to them once available again.
+async fn communication_task(daemon_addresses: Vec<String>, failover_rejected_blocks: usize, failback_interval: Duration, job_sender: broadcast::Sender<ThreadNotification<'_>>, mut block_receiver: mpsc::Receiver<MinerWork<'_>>, address: Address, worker: String, api_key: Option<String>) {
     info!("Starting communication task");
-    let daemon_address = sanitize_ws_address(&daemon_address);
+    let daemon_addresses: Vec<String> = daemon_addresses.iter()
+        .map(|daemon_address| sanitize_ws_address(daemon_address))
+        .collect();
+    let mut index = 0;
     'main: loop {
+        let daemon_address = &daemon_addresses[index];
         info!("Trying to connect to {}", daemon_address);
-        let client = match connect_async(format!("{}/getwork/{}/{}", daemon_address, address.to_string(), worker)).await {
-            Ok((client, response)) => {
-                let status = response.status();
-                if status.is_server_error() || status.is_client_error() {
-                    error!("Error while connecting to {}, got an unexpected response: {}", daemon_address, status.as_str());
+        let client = match connect_to_daemon(daemon_address, &address, &worker, api_key.as_deref()).await {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Error while connecting to {}: {}", daemon_address, e);
+                index = (index + 1) % daemon_addresses.len();
+                if index == 0 {
                     warn!("Trying to connect to WebSocket again in 10 seconds...");
                     tokio::time::sleep(Duration::from_secs(10)).await;
-                    continue 'main;
-                }
-                client
-            },
-            Err(e) => {
-                if let TungsteniteError::Http(e) = e {
-                    error!("Error while connecting to {}, got an unexpected response: {}", daemon_address, e.status());
                 } else {
-                    error!("Error while connecting to {}: {}", daemon_address, e);
+                    warn!("Switching to the next daemon {}", daemon_addresses[index]);
                 }
-
-                warn!("Trying to connect to WebSocket again in 10 seconds...");
-                tokio::time::sleep(Duration::from_secs(10)).await;
                 continue 'main;
             }
         };
         WEBSOCKET_CONNECTED.store(true, Ordering::SeqCst);
+        ACTIVE_DAEMON.write().unwrap().replace(daemon_address.clone());
         info!("Connected successfully to {}", daemon_address);
+
         let (mut write, mut read) = client.split();
-        loop {
+        let mut rejected_blocks = 0;
+        let mut failback = interval_at(Instant::now() + failback_interval, failback_interval);
+        // Daemon to use once disconnected, none to reconnect to the same one
+        let next = loop {
             select! {
                 Some(message) = read.next() => { // read all messages from daemon
                     debug!("Received message from daemon: {:?}", message);
                     match handle_websocket_message(message, &job_sender).await {
-                        Ok(exit) => {
-                            if exit {
-                                debug!("Exiting communication task");
-                                break;
+                        Ok(DaemonEvent::None) => {},
+                        Ok(DaemonEvent::BlockAccepted) => {
+                            rejected_blocks = 0;
+                        },
+                        Ok(DaemonEvent::BlockRejected) => {
+                            rejected_blocks += 1;
+                            if failover_rejected_blocks > 0 && rejected_blocks >= failover_rejected_blocks && daemon_addresses.len() > 1 {
+                                warn!("{} consecutive blocks rejected by {}", rejected_blocks, daemon_address);
+                                break Some((index + 1) % daemon_addresses.len());
                             }
                         },
+                        Ok(DaemonEvent::Exit) => {
+                            debug!("Closing connection with {}", daemon_address);
+                            break Some((index + 1) % daemon_addresses.len());
+                        },
                         Err(e) => {
                             error!("Error while handling message from WebSocket: {}", e);
-                            break;
+                            break Some((index + 1) % daemon_addresses.len());
                         }
                     }
                 },
@@ -527,24 +643,88 @@ async fn communication_task(daemon_address: String, job_sender: broadcast::Sende
                     let submit = serde_json::json!(SubmitMinerWorkParams { miner_work: work.to_hex() }).to_string();
                     if let Err(e) = write.send(Message::Text(submit.into())).await {
                         error!("Error while sending the block found to the daemon: {}", e);
-                        break;
+                        break Some((index + 1) % daemon_addresses.len());
                     }
                     debug!("Block found has been sent to daemon");
+                },
+                _ = failback.tick(), if index > 0 => { // check if a higher priority daemon is back
+                    if let Some(primary) = find_available_daemon(&daemon_addresses[..index], &address, &worker, api_key.as_deref()).await {
+                        info!("Daemon {} is available again, switching back to it", daemon_addresses[primary]);
+                        break Some(primary);
+                    }
                 }
             }
+        };
+
+        if let Err(e) = write.close().await {
+            debug!("Error while closing the WebSocket connection: {}", e);
         }
 
         WEBSOCKET_CONNECTED.store(false, Ordering::SeqCst);
+        ACTIVE_DAEMON.write().unwrap().take();
         if job_sender.send(ThreadNotification::WebSocketClosed).is_err() {
             error!("Error while sending WebSocketClosed message to threads");
         }
 
-        warn!("Trying to connect to WebSocket again in 10 seconds...");
-        tokio::time::sleep(Duration::from_secs(10)).await;
+        match next {
+            Some(next) if next != index => {
+                warn!("Switching to daemon {}", daemon_addresses[next]);
+                index = next;
+            },
+            _ => {
+                warn!("Trying to connect to WebSocket again in 10 seconds...");
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            }
+        }
     }
 }
 
-async fn handle_websocket_message(message: Result<Message, TungsteniteError>, job_sender: &broadcast::Sender<ThreadNotification<'_>>) -> Result<bool, Error> {
+// Open the GetWork WebSocket connection with the daemon
+// The API key is sent as a Bearer token if provided
+async fn connect_to_daemon(daemon_address: &str, address: &Address, worker: &str, api_key: Option<&str>) -> Result<DaemonStream> {
+    let mut request = format!("{}/getwork/{}/{}", daemon_address, address.to_string(), worker).into_client_request()?;
+    if let Some(api_key) = api_key {
+        let value = HeaderValue::from_str(&format!("Bearer {}", api_key))
+            .context("Invalid API key")?;
+        request.headers_mut().insert(AUTHORIZATION, value);
+    }
+
+    let (client, response) = match connect_async(request).await {
+        Ok(res) => res,
+        Err(TungsteniteError::Http(e)) => {
+            return Err(Error::msg(format!("got an unexpected response: {}", e.status())));
+        },
+        Err(e) => return Err(e.into())
+    };
+
+    let status = response.status();
+    if status.is_server_error() || status.is_client_error() {
+        return Err(Error::msg(format!("got an unexpected response: {}", status.as_str())));
+    }
+
+    Ok(client)
+}
+
+// Search the first daemon available by order of priority
+async fn find_available_daemon(daemon_addresses: &[String], address: &Address, worker: &str, api_key: Option<&str>) -> Option<usize> {
+    for (index, daemon_address) in daemon_addresses.iter().enumerate() {
+        debug!("Checking if daemon {} is available", daemon_address);
+        match timeout(Duration::from_secs(DAEMON_HEALTH_CHECK_TIMEOUT), connect_to_daemon(daemon_address, address, worker, api_key)).await {
+            Ok(Ok(mut client)) => {
+                if let Err(e) = client.close(None).await {
+                    debug!("Error while closing the health check connection: {}", e);
+                }
+                return Some(index)
+            },
+            Ok(Err(e)) => debug!("Daemon {} is still unavailable: {}", daemon_address, e),
+            Err(_) => debug!("Daemon {} is still unavailable: timed out", daemon_address)
+        }
+    }
+
+    None
+}
+
+async fn handle_websocket_message(message: Result<Message, TungsteniteError>, job_sender: &broadcast::Sender<ThreadNotification<'_>>) -> Result<DaemonEvent, Error> {
     match message? {
         Message::Text(text) => {
             debug!("new message from daemon: {}", text);
@@ -554,6 +734,10 @@ async fn handle_websocket_message(message: Result<Message, TungsteniteError>, jo
                     let block = MinerWork::from_hex(&job.miner_work).context("Error while decoding new job received from daemon")?;
                     CURRENT_TOPO_HEIGHT.store(job.topoheight, Ordering::SeqCst);
                     JOB_ELAPSED.write().unwrap().replace(Instant::now());
+                    #[cfg(feature = "api_stats")]
+                    if let Some(stats) = STATS.get() {
+                        stats.on_new_job(job.algorithm);
+                    }
 
                     if let Err(e) = job_sender.send(ThreadNotification::NewJob(job.algorithm, block, job.difficulty, job.height)) {
                         error!("Error while sending new job to threads: {}", e);
@@ -562,10 +746,20 @@ async fn handle_websocket_message(message: Result<Message, TungsteniteError>, jo
                 SocketMessage::BlockAccepted => {
                     BLOCKS_FOUND.fetch_add(1, Ordering::SeqCst);
                     info!("Block submitted has been accepted by network !");
+                    return Ok(DaemonEvent::BlockAccepted);
+                },
+                SocketMessage::ShareAccepted => {
+                    info!("Share submitted has been accepted by pool !");
+                    return Ok(DaemonEvent::BlockAccepted);
                 },
                 SocketMessage::BlockRejected(err) => {
                     BLOCKS_REJECTED.fetch_add(1, Ordering::SeqCst);
                     error!("Block submitted has been rejected by network: {}", err);
+                    #[cfg(feature = "api_stats")]
+                    if let Some(stats) = STATS.get() {
+                        stats.add_rejection(&err);
+                    }
+                    return Ok(DaemonEvent::BlockRejected);
                 }
             }
         },
@@ -576,18 +770,18 @@ async fn handle_websocket_message(message: Result<Message, TungsteniteError>, jo
                 "No reason".into()
             };
             warn!("Daemon has closed the WebSocket connection with us: {}", reason);
-            return Ok(true);
+            return Ok(DaemonEvent::Exit);
         },
         Message::Ping(_) => {
             trace!("received ping");
         },
         msg => {
             warn!("Unexpected message from WebSocket: {:?}", msg);
-            return Ok(true);
+            return Ok(DaemonEvent::Exit);
         }
     };
 
-    Ok(false)
+    Ok(DaemonEvent::None)
 }
 
 fn start_thread(id: u16, mut job_receiver: broadcast::Receiver<ThreadNotification<'static>>, block_sender: mpsc::Sender<MinerWork<'static>>) -> Result<std::thread::JoinHandle<Result<(), Error>>, Error> {
@@ -655,6 +849,10 @@ fn start_thread(id: u16, mut job_receiver: broadcast::Receiver<ThreadNotificatio
                                 }
                             }
                             HASHRATE_COUNTER.fetch_add(UPDATE_EVERY_NONCE as usize, Ordering::SeqCst);
+                            #[cfg(feature = "api_stats")]
+                            if let Some(stats) = STATS.get() {
+                                stats.add_hashes(id, UPDATE_EVERY_NONCE);
+                            }
                         }
 
                         hash = worker.get_pow_hash()?;
@@ -665,6 +863,11 @@ fn start_thread(id: u16, mut job_receiver: broadcast::Receiver<ThreadNotificatio
                     let block_hash = worker.get_block_hash().unwrap();
                     info!("Thread #{}: block {} found at height {} with difficulty {}", id, block_hash, height, format_difficulty(difficulty_from_hash(&hash)));
 
+                    #[cfg(feature = "api_stats")]
+                    if let Some(stats) = STATS.get() {
+                        stats.add_block_found(id);
+                    }
+
                     let job = worker.take_work().unwrap();
                     if let Err(_) = block_sender.blocking_send(job) {
                         error!("Mining Thread #{}: error while sending block found with hash {}", id, block_hash);
@@ -702,7 +905,12 @@ async fn run_prompt(prompt: ShareablePrompt) -> Result<()> {
             prompt.colorize_string(Color::Green, &format!("{}", BLOCKS_REJECTED.load(Ordering::SeqCst))),
         );
         let status = if WEBSOCKET_CONNECTED.load(Ordering::SeqCst) {
-            prompt.colorize_string(Color::Green, "Online")
+            let daemon_address = ACTIVE_DAEMON.read().unwrap().clone().unwrap_or_default();
+            format!(
+                "{} {}",
+                prompt.colorize_string(Color::Green, "Online"),
+                prompt.colorize_string(Color::BrightBlack, &daemon_address)
+            )
         } else {
             prompt.colorize_string(Color::Red, "Offline")
         };
@@ -713,9 +921,6 @@ async fn run_prompt(prompt: ShareablePrompt) -> Result<()> {
             let hashrate = 1000f64 / (last_time.elapsed().as_millis() as f64 / counter as f64);
             *last_time = Instant::now();
 
-            #[cfg(feature = "api_stats")]
-            HASHRATE.store(hashrate as u64, Ordering::SeqCst);
-
             prompt.colorize_string(Color::Green, &format!("{}", format_hashrate(hashrate)))
         };
 
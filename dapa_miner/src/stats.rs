@@ -0,0 +1,277 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+        RwLock
+    }
+};
+use serde::Serialize;
+use tokio::time::Instant;
+use dapa_common::block::Algorithm;
+
+// Maximum distinct rejection reasons tracked
+// Any other reason is counted as "other"
+const MAX_REJECTION_REASONS: usize = 32;
+
+#[derive(Default)]
+struct ThreadStats {
+    // Total hashes computed
+    hashes: AtomicU64,
+    // Blocks found and sent to the daemon
+    blocks_found: AtomicU64,
+    // Hashrate computed at the last sample
+    hashrate: AtomicU64
+}
+
+// Stats collected by the mining threads and the communication task
+pub struct MinerStats {
+    started_at: Instant,
+    threads: Vec<ThreadStats>,
+    algorithm: RwLock<Option<Algorithm>>,
+    jobs_received: AtomicU64,
+    rejections: Mutex<BTreeMap<String, u64>>,
+    // Last hashrate sample: time and hashes of each thread
+    last_sample: Mutex<(Instant, Vec<u64>)>
+}
+
+impl MinerStats {
+    pub fn new(threads: usize) -> Self {
+        let now = Instant::now();
+        Self {
+            started_at: now,
+            threads: (0..threads).map(|_| ThreadStats::default()).collect(),
+            algorithm: RwLock::new(None),
+            jobs_received: AtomicU64::new(0),
+            rejections: Mutex::new(BTreeMap::new()),
+            last_sample: Mutex::new((now, vec![0; threads]))
+        }
+    }
+
+    pub fn add_hashes(&self, thread: u16, hashes: u64) {
+        if let Some(stats) = self.threads.get(thread as usize) {
+            stats.hashes.fetch_add(hashes, Ordering::Relaxed);
+        }
+    }
+
+    pub fn add_block_found(&self, thread: u16) {
+        if let Some(stats) = self.threads.get(thread as usize) {
+            stats.blocks_found.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn on_new_job(&self, algorithm: Algorithm) {
+        self.jobs_received.fetch_add(1, Ordering::Relaxed);
+        self.algorithm.write().unwrap().replace(algorithm);
+    }
+
+    pub fn add_rejection(&self, reason: &str) {
+        let mut rejections = self.rejections.lock().unwrap();
+        if let Some(count) = rejections.get_mut(reason) {
+            *count += 1;
+        } else if rejections.len() < MAX_REJECTION_REASONS {
+            rejections.insert(reason.to_owned(), 1);
+        } else {
+            *rejections.entry("other".to_owned()).or_default() += 1;
+        }
+    }
+
+    // Compute the hashrate of each thread since the last sample
+    pub fn sample_hashrates(&self) {
+        let mut last_sample = self.last_sample.lock().unwrap();
+        let (last_time, last_hashes) = &mut *last_sample;
+        let elapsed = last_time.elapsed().as_millis() as u64;
+        if elapsed == 0 {
+            return;
+        }
+
+        for (stats, last) in self.threads.iter().zip(last_hashes.iter_mut()) {
+            let hashes = stats.hashes.load(Ordering::Relaxed);
+            stats.hashrate.store(hashes.saturating_sub(*last) * 1000 / elapsed, Ordering::Relaxed);
+            *last = hashes;
+        }
+        *last_time = Instant::now();
+    }
+
+    pub fn build_report(&self) -> StatsReport {
+        let threads: Vec<ThreadReport> = self.threads.iter()
+            .enumerate()
+            .map(|(id, stats)| ThreadReport {
+                id,
+                hashrate: stats.hashrate.load(Ordering::Relaxed),
+                hashes: stats.hashes.load(Ordering::Relaxed),
+                blocks_found: stats.blocks_found.load(Ordering::Relaxed)
+            })
+            .collect();
+
+        StatsReport {
+            hashrate: threads.iter().map(|thread| thread.hashrate).sum(),
+            threads,
+            algorithm: *self.algorithm.read().unwrap(),
+            jobs_received: self.jobs_received.load(Ordering::Relaxed),
+            rejections: self.rejections.lock().unwrap().clone(),
+            uptime: self.started_at.elapsed().as_secs(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ThreadReport {
+    pub id: usize,
+    pub hashrate: u64,
+    pub hashes: u64,
+    pub blocks_found: u64
+}
+
+#[derive(Serialize, Default)]
+pub struct StatsReport {
+    pub accepted: usize,
+    pub rejected: usize,
+    pub rejections: BTreeMap<String, u64>,
+    pub hashrate: u64,
+    pub hashrate_formatted: String,
+    pub threads: Vec<ThreadReport>,
+    pub algorithm: Option<Algorithm>,
+    pub topoheight: u64,
+    pub jobs_received: u64,
+    // Time elapsed since the last job received
+    pub job_elapsed_ms: Option<u64>,
+    pub connected: bool,
+    pub daemon_address: Option<String>,
+    // Uptime in seconds
+    pub uptime: u64
+}
+
+impl StatsReport {
+    // Render the report using the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, values: Vec<(String, u64)>| {
+            let _ = writeln!(out, "# HELP dapa_miner_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE dapa_miner_{} {}", name, kind);
+            for (labels, value) in values {
+                let _ = writeln!(out, "dapa_miner_{}{} {}", name, labels, value);
+            }
+        };
+
+        metric("blocks_accepted_total", "counter", "Blocks accepted by the daemon", vec![(String::new(), self.accepted as u64)]);
+        metric("blocks_rejected_total", "counter", "Blocks rejected by the daemon", vec![(String::new(), self.rejected as u64)]);
+        metric("rejections_total", "counter", "Blocks rejected by reason", self.rejections.iter()
+            .map(|(reason, count)| (format!("{{reason=\"{}\"}}", escape_label(reason)), *count))
+            .collect());
+        metric("hashrate", "gauge", "Hashrate in H/s", vec![(String::new(), self.hashrate)]);
+        metric("thread_hashrate", "gauge", "Hashrate of each thread in H/s", self.threads.iter()
+            .map(|thread| (format!("{{thread=\"{}\"}}", thread.id), thread.hashrate))
+            .collect());
+        metric("thread_hashes_total", "counter", "Hashes computed by each thread", self.threads.iter()
+            .map(|thread| (format!("{{thread=\"{}\"}}", thread.id), thread.hashes))
+            .collect());
+        metric("thread_blocks_found_total", "counter", "Blocks found by each thread", self.threads.iter()
+            .map(|thread| (format!("{{thread=\"{}\"}}", thread.id), thread.blocks_found))
+            .collect());
+        metric("algorithm", "gauge", "Algorithm of the current job", self.algorithm.iter()
+            .map(|algorithm| (format!("{{algorithm=\"{}\"}}", algorithm), 1))
+            .collect());
+        metric("topoheight", "gauge", "Topoheight of the current job", vec![(String::new(), self.topoheight)]);
+        metric("jobs_received_total", "counter", "Jobs received from the daemon", vec![(String::new(), self.jobs_received)]);
+        metric("job_elapsed_milliseconds", "gauge", "Time elapsed since the last job received", self.job_elapsed_ms.iter()
+            .map(|elapsed| (String::new(), *elapsed))
+            .collect());
+        metric("connected", "gauge", "Connection state with the daemon", vec![(
+            self.daemon_address.as_ref()
+                .map(|address| format!("{{daemon=\"{}\"}}", escape_label(address)))
+                .unwrap_or_default(),
+            self.connected as u64
+        )]);
+        metric("uptime_seconds", "counter", "Time elapsed since the miner started", vec![(String::new(), self.uptime)]);
+
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_prometheus() {
+        let report = StatsReport {
+            accepted: 3,
+            rejected: 1,
+            rejections: BTreeMap::from([("invalid \"pow\"".to_owned(), 1)]),
+            hashrate: 1500,
+            threads: vec![
+                ThreadReport { id: 0, hashrate: 1000, hashes: 20000, blocks_found: 2 },
+                ThreadReport { id: 1, hashrate: 500, hashes: 10000, blocks_found: 1 },
+            ],
+            algorithm: Some(Algorithm::V3),
+            topoheight: 42,
+            jobs_received: 7,
+            job_elapsed_ms: None,
+            connected: true,
+            daemon_address: Some("127.0.0.1:8080".to_owned()),
+            uptime: 60,
+            ..Default::default()
+        };
+
+        let output = report.to_prometheus();
+        let lines: Vec<&str> = output.lines().collect();
+
+        // Each metric is described before its samples
+        for (name, kind) in [
+            ("blocks_accepted_total", "counter"),
+            ("blocks_rejected_total", "counter"),
+            ("rejections_total", "counter"),
+            ("hashrate", "gauge"),
+            ("thread_hashrate", "gauge"),
+            ("thread_hashes_total", "counter"),
+            ("thread_blocks_found_total", "counter"),
+            ("algorithm", "gauge"),
+            ("topoheight", "gauge"),
+            ("jobs_received_total", "counter"),
+            ("job_elapsed_milliseconds", "gauge"),
+            ("connected", "gauge"),
+            ("uptime_seconds", "counter"),
+        ] {
+            let help = lines.iter()
+                .position(|line| line.starts_with(&format!("# HELP dapa_miner_{} ", name)))
+                .unwrap_or_else(|| panic!("missing HELP line for {}", name));
+            assert_eq!(lines[help + 1], format!("# TYPE dapa_miner_{} {}", name, kind));
+        }
+
+        for sample in [
+            "dapa_miner_blocks_accepted_total 3",
+            "dapa_miner_blocks_rejected_total 1",
+            "dapa_miner_rejections_total{reason=\"invalid \\\"pow\\\"\"} 1",
+            "dapa_miner_hashrate 1500",
+            "dapa_miner_thread_hashrate{thread=\"0\"} 1000",
+            "dapa_miner_thread_hashrate{thread=\"1\"} 500",
+            "dapa_miner_thread_hashes_total{thread=\"0\"} 20000",
+            "dapa_miner_thread_blocks_found_total{thread=\"1\"} 1",
+            "dapa_miner_algorithm{algorithm=\"xel/v3\"} 1",
+            "dapa_miner_topoheight 42",
+            "dapa_miner_jobs_received_total 7",
+            "dapa_miner_connected{daemon=\"127.0.0.1:8080\"} 1",
+            "dapa_miner_uptime_seconds 60",
+        ] {
+            assert!(lines.contains(&sample), "missing sample {}", sample);
+        }
+
+        // No sample without a job received yet
+        assert!(!lines.iter().any(|line| line.starts_with("dapa_miner_job_elapsed_milliseconds")));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("plain"), "plain");
+        assert_eq!(escape_label("a\\b\"c\nd"), "a\\\\b\\\"c\\nd");
+    }
+}
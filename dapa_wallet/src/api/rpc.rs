@@ -66,6 +66,7 @@ pub fn register_methods(handler: &mut RPCHandler<Arc<Wallet>>) {
     handler.register_method_with_params("search_transaction", async_handler!(search_transaction));
     handler.register_method_with_params("dump_transaction", async_handler!(dump_transaction));
     handler.register_method_with_params("build_transaction", async_handler!(build_transaction));
+    handler.register_method_with_params("bump_fee", async_handler!(bump_fee));
     handler.register_method_with_params("build_transaction_offline", async_handler!(build_transaction_offline));
     handler.register_method_with_params("build_unsigned_transaction", async_handler!(build_unsigned_transaction));
     handler.register_method_with_params("finalize_unsigned_transaction", async_handler!(finalize_unsigned_transaction));
@@ -78,6 +79,8 @@ pub fn register_methods(handler: &mut RPCHandler<Arc<Wallet>>) {
     handler.register_method_no_params("set_offline_mode", async_handler!(set_offline_mode, single));
     handler.register_method_with_params("sign_data", async_handler!(sign_data));
     handler.register_method_with_params("verify_signed_data", async_handler!(verify_signed_data));
+    handler.register_method_with_params("create_balance_proof", async_handler!(create_balance_proof));
+    handler.register_method_with_params("create_ownership_proof", async_handler!(create_ownership_proof));
     handler.register_method_with_params("estimate_fees", async_handler!(estimate_fees));
     handler.register_method_with_params("estimate_extra_data_size", async_handler!(estimate_extra_data_size));
     handler.register_method_no_params("network_info", async_handler!(network_info, single));
@@ -458,6 +461,31 @@ async fn build_transaction(context: &Context<'_, '_>, params: BuildTransactionPa
     })
 }
 
+// Rebuild the last pending transaction with a new fee
+// The new TX is broadcasted to replace the pending one in the daemon mempool
+async fn bump_fee(context: &Context<'_, '_>, params: BumpFeeParams) -> Result<TransactionResponse<'static>, InternalRpcError> {
+    let wallet = wallet_from_context(context)?;
+    if !wallet.is_online().await {
+        return Err(WalletError::NotOnlineMode)?
+    }
+
+    let mut storage = wallet.get_storage().write().await;
+    let tx = wallet.bump_fee(&mut storage, &params.hash, params.fee, params.base_fee, params.fee_limit).await?;
+
+    // returns the created TX and its hash
+    Ok(TransactionResponse {
+        tx_as_hex: if params.tx_as_hex {
+            Some(hex::encode(tx.to_bytes()))
+        } else {
+            None
+        },
+        inner: DataHash {
+            hash: Cow::Owned(tx.hash()),
+            data: Cow::Owned(tx)
+        }
+    })
+}
+
 // Build a transaction by giving the encrypted balances directly
 async fn build_transaction_offline(context: &Context<'_, '_>, params: BuildTransactionOfflineParams) -> Result<TransactionResponse<'static>, InternalRpcError> {
     let wallet = wallet_from_context(context)?;
@@ -632,11 +660,16 @@ async fn clear_tx_cache(context: &Context<'_, '_>) -> Result<bool, InternalRpcEr
 }
 
 // Estimate fees for a transaction
-async fn estimate_fees(context: &Context<'_, '_>, params: EstimateFeesParams) -> Result<u64, InternalRpcError> {
+// A max gas is also recommended for contract calls if requested
+async fn estimate_fees(context: &Context<'_, '_>, params: EstimateFeesParams) -> Result<EstimateFeesResponse, InternalRpcError> {
     let wallet = wallet_from_context(context)?;
-    let fees = wallet.estimate_fees(params.tx_type, params.fee, params.base_fee).await?;
+    if params.recommend_max_gas {
+        let result = wallet.estimate_fees(params.tx_type, params.fee, params.base_fee).await?;
+        return Ok(EstimateFeesResponse::WithMaxGas(result))
+    }
 
-    Ok(fees)
+    let fees = wallet.estimate_tx_fees(params.tx_type, params.fee, params.base_fee).await?;
+    Ok(EstimateFeesResponse::Fee(fees))
 }
 
 // List transactions from the wallet storage
@@ -732,6 +765,43 @@ async fn verify_signed_data(_: &Context<'_, '_>, params: VerifySignedDataParams)
     Ok(is_valid)
 }
 
+// Create a proof of the whole balance of an asset using its on-chain ciphertext
+async fn create_balance_proof(context: &Context<'_, '_>, params: CreateBalanceProofParams) -> Result<CreateProofResult, InternalRpcError> {
+    let wallet = wallet_from_context(context)?;
+    let asset = params.asset.unwrap_or(DAPA_ASSET);
+
+    cfg_if! {
+        if #[cfg(feature = "network_handler")] {
+            let proof = wallet.create_balance_proof(asset, params.topoheight).await?;
+            let topoheight = proof.topoheight();
+            Ok(CreateProofResult { proof, topoheight })
+        } else {
+            Err(InternalRpcError::InvalidRequestStr("Wallet must be in online mode to create a proof"))
+        }
+    }
+}
+
+// Create a proof that the wallet owns at least the requested amount of an asset
+// without revealing its balance
+async fn create_ownership_proof(context: &Context<'_, '_>, params: CreateOwnershipProofParams) -> Result<CreateProofResult, InternalRpcError> {
+    if params.amount == 0 {
+        return Err(InternalRpcError::InvalidParams("Amount must be greater than zero"))
+    }
+
+    let wallet = wallet_from_context(context)?;
+    let asset = params.asset.unwrap_or(DAPA_ASSET);
+
+    cfg_if! {
+        if #[cfg(feature = "network_handler")] {
+            let proof = wallet.create_ownership_proof(asset, params.amount, params.topoheight).await?;
+            let topoheight = proof.topoheight();
+            Ok(CreateProofResult { proof, topoheight })
+        } else {
+            Err(InternalRpcError::InvalidRequestStr("Wallet must be in online mode to create a proof"))
+        }
+    }
+}
+
 // In EncryptedStorage, custom trees are already prefixed
 async fn get_tree_name(context: &Context<'_, '_>, tree: String) -> Result<String, InternalRpcError> {
     // If the API is not used through XSWD, we don't need to prefix the tree name with the app id
@@ -31,6 +31,12 @@ pub const KEY_SIZE: usize = 32;
 pub const DEFAULT_DAEMON_ADDRESS: &str = "http://node.dapahe.com:20101";
 // Auto reconnect interval in seconds for Network Handler
 pub const AUTO_RECONNECT_INTERVAL: u64 = 5;
+// Extra gas in percent added to the gas used by a simulated contract call
+// as the contract state may change before the TX is executed
+pub const GAS_ESTIMATION_MARGIN_PERCENT: u64 = 10;
+// Fee multiplier used by default to replace a pending TX
+// It must be above the replace fee ratio required by the daemon mempool
+pub const DEFAULT_BUMP_FEE_MULTIPLIER: f64 = 1.25;
 
 lazy_static! {
     pub static ref PASSWORD_ALGORITHM: Argon2<'static> = {
@@ -11,9 +11,12 @@ use dapa_common::{
     account::VersionedBalance,
     api::{daemon::*, RPCContractLog},
     asset::RPCAssetData,
+    block::TopoHeight,
+    contract::{ContractAbi, RegisteredContractAbi},
     crypto::{
         Address,
-        Hash
+        Hash,
+        Signature
     },
     rpc::client::{
         BatchRequest,
@@ -278,6 +281,14 @@ impl DaemonAPI {
         Ok(())
     }
 
+    pub async fn simulate_transaction(&self, transaction: &Transaction) -> Result<SimulateTransactionResult<'static>> {
+        trace!("simulate_transaction");
+        let result = self.client.call_with("simulate_transaction", &SimulateTransactionParams {
+            data: transaction.to_hex()
+        }).await?;
+        Ok(result)
+    }
+
     pub async fn get_nonce(&self, address: &Address) -> Result<GetNonceResult> {
         trace!("get_nonce");
         let nonce = self.client.call_with("get_nonce", &GetNonceParams {
@@ -357,6 +368,36 @@ impl DaemonAPI {
         Ok(outputs)
     }
 
+    // Retrieve the topoheight at which the latest module of a contract was deployed
+    pub async fn get_contract_module_topoheight(&self, contract: &Hash) -> Result<TopoHeight> {
+        trace!("get contract module topoheight");
+        let module: RPCVersioned<serde_json::Value> = self.client.call_with("get_contract_module", &GetContractModuleParams {
+            contract: Cow::Borrowed(contract),
+            topoheight: None
+        }).await?;
+        Ok(module.topoheight)
+    }
+
+    pub async fn get_contract_abi(&self, contract: &Hash) -> Result<Option<RegisteredContractAbi>> {
+        trace!("get contract abi");
+        let abi = self.client.call_with("get_contract_abi", &GetContractAbiParams {
+            contract: Cow::Borrowed(contract)
+        }).await?;
+        Ok(abi)
+    }
+
+    pub async fn register_contract_abi(&self, contract: &Hash, abi: &ContractAbi, topoheight: TopoHeight, version: u64, signature: Signature) -> Result<bool> {
+        trace!("register contract abi");
+        let registered = self.client.call_with("register_contract_abi", &RegisterContractAbiParams {
+            contract: Cow::Borrowed(contract),
+            abi: Cow::Borrowed(abi),
+            topoheight,
+            version,
+            signature
+        }).await?;
+        Ok(registered)
+    }
+
     pub async fn get_contracts_outputs(&self, address: &Address, topoheight: u64) -> Result<GetContractsOutputsResult<'static>> {
         trace!("get contracts logs");
         let outputs = self.client.call_with("get_contracts_outputs", &GetContractOutputsParams {
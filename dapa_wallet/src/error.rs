@@ -4,7 +4,7 @@ use chacha20poly1305::Error as CryptoError;
 #[cfg(feature = "network_handler")]
 use super::network_handler::NetworkError;
 use dapa_common::{
-    crypto::Hash,
+    crypto::{proofs::ProofGenerationError, Hash},
     transaction::extra_data::CipherFormatError,
     utils::{format_coin, format_dapa}
 };
@@ -99,7 +99,13 @@ pub enum WalletError {
     #[error("Poison error")]
     PoisonError,
     #[error("unsupported operation")]
-    Unsupported
+    Unsupported,
+    #[error("Gas estimation failed: {}", _0)]
+    GasEstimation(String),
+    #[error("Transaction {} can't be replaced, only the last transaction created can be while no other is pending", _0)]
+    TxNotReplaceable(Hash),
+    #[error(transparent)]
+    ProofGeneration(#[from] ProofGenerationError)
 }
 
 impl WalletError {
@@ -47,11 +47,16 @@ use dapa_common::{
     tokio,
     contract::{
         vm::HOOK_CONSTRUCTOR_ID,
+        parse_contract_argument,
         Module,
+        ContractAbi,
         ContractVersion,
+        ABI_ANY_TYPE,
+        CONTRACT_ARGUMENT_TYPES,
     },
     transaction::{
         builder::{
+            ExtraFeeMode,
             FeeBuilder,
             MultiSigBuilder,
             TransactionTypeBuilder,
@@ -59,6 +64,7 @@ use dapa_common::{
             DeployContractBuilder,
             DeployContractInvokeBuilder,
             ContractDepositBuilder,
+            InvokeContractBuilder,
         },
         multisig::{MultiSig, SignatureId},
         BurnPayload,
@@ -72,7 +78,7 @@ use dapa_common::{
     }
 };
 use dapa_wallet::{
-    config::{Config, LogProgressTableGenerationReportFunction, DIR_PATH},
+    config::{Config, LogProgressTableGenerationReportFunction, DEFAULT_BUMP_FEE_MULTIPLIER, DIR_PATH},
     precomputed_tables,
     wallet::{
         RecoverOption,
@@ -447,6 +453,17 @@ async fn setup_wallet_command_manager(wallet: Arc<Wallet>, command_manager: &Com
         ],
         CommandHandler::Async(async_handler!(deploy_contract))
     ))?;
+    command_manager.add_command(Command::with_optional_arguments(
+        "invoke_contract",
+        "Invoke a contract deployed on the blockchain",
+        vec![
+            Arg::new("contract", ArgType::Hash),
+            Arg::new("entry_id", ArgType::Number),
+            Arg::new("max_gas", ArgType::Number),
+            Arg::new("confirm", ArgType::Bool)
+        ],
+        CommandHandler::Async(async_handler!(invoke_contract))
+    ))?;
     command_manager.add_command(Command::new(
         "display_address",
         "Show your wallet address",
@@ -497,6 +514,15 @@ async fn setup_wallet_command_manager(wallet: Arc<Wallet>, command_manager: &Com
         "Logout from existing wallet",
         CommandHandler::Async(async_handler!(logout)))
     )?;
+    command_manager.add_command(Command::with_optional_arguments(
+        "bump_fee",
+        "Rebuild the last pending transaction with a higher fee to replace it, only if no other transaction is pending",
+        vec![
+            Arg::new("multiplier", ArgType::String),
+            Arg::new("confirm", ArgType::Bool)
+        ],
+        CommandHandler::Async(async_handler!(bump_fee))
+    ))?;
     command_manager.add_command(Command::new(
         "clear_tx_cache",
         "Clear the current TX cache",
@@ -570,6 +596,34 @@ async fn setup_wallet_command_manager(wallet: Arc<Wallet>, command_manager: &Com
             vec![Arg::new("topoheight", ArgType::Number)],
             CommandHandler::Async(async_handler!(rescan))
         ))?;
+        command_manager.add_command(Command::with_optional_arguments(
+            "balance_proof",
+            "Create a proof of your whole balance for an asset",
+            vec![
+                Arg::new("asset", ArgType::Hash),
+                Arg::new("topoheight", ArgType::Number)
+            ],
+            CommandHandler::Async(async_handler!(balance_proof))
+        ))?;
+        command_manager.add_command(Command::with_optional_arguments(
+            "register_contract_abi",
+            "Register the ABI of a contract deployed by this wallet on the daemon",
+            vec![
+                Arg::new("contract", ArgType::Hash),
+                Arg::new("file", ArgType::String)
+            ],
+            CommandHandler::Async(async_handler!(register_contract_abi))
+        ))?;
+        command_manager.add_command(Command::with_optional_arguments(
+            "ownership_proof",
+            "Create a proof that you own at least an amount of an asset",
+            vec![
+                Arg::new("asset", ArgType::Hash),
+                Arg::new("amount", ArgType::String),
+                Arg::new("topoheight", ArgType::Number)
+            ],
+            CommandHandler::Async(async_handler!(ownership_proof))
+        ))?;
     }
 
     #[cfg(feature = "api_server")]
@@ -1301,7 +1355,7 @@ async fn transfer(manager: &CommandManager, mut args: ArgumentManager) -> Result
         encrypt_extra_data: true
     };
     let tx_type = TransactionTypeBuilder::Transfers(vec![transfer]);
-    let estimated_fee = wallet.estimate_fees(tx_type.clone(), Default::default(), Default::default()).await
+    let estimated_fee = wallet.estimate_tx_fees(tx_type.clone(), Default::default(), Default::default()).await
         .context("Error while estimating TX fee")?;
 
     manager.message(format!("Estimated TX fee is {} DAPA", format_dapa(estimated_fee)));
@@ -1357,7 +1411,7 @@ async fn transfer_all(manager: &CommandManager, mut args: ArgumentManager) -> Re
         encrypt_extra_data: true
     };
     let tx_type = TransactionTypeBuilder::Transfers(vec![transfer]);
-    let estimated_fees = wallet.estimate_fees(tx_type.clone(), FeeBuilder::default(), Default::default()).await.context("Error while estimating fees")?;
+    let estimated_fees = wallet.estimate_tx_fees(tx_type.clone(), FeeBuilder::default(), Default::default()).await.context("Error while estimating fees")?;
 
     if asset == DAPA_ASSET {
         amount = amount.checked_sub(estimated_fees).context("Insufficient balance to pay fees")?;
@@ -1429,6 +1483,76 @@ async fn burn(manager: &CommandManager, mut args: ArgumentManager) -> Result<(),
     Ok(())
 }
 
+// Read interactively the deposits to send to a contract
+// Private deposits are only asked if allowed
+async fn read_contract_deposits(manager: &CommandManager, prompt: &Prompt, wallet: &Wallet, target: &str, allow_private: bool) -> Result<IndexMap<Hash, ContractDepositBuilder>, CommandError> {
+    let mut deposits = IndexMap::new();
+    let mut first = true;
+
+    loop {
+        let msg = if first {
+            format!("Do you want to add a deposit for {}? (Y/N): ", target)
+        } else {
+            format!("Do you want to add another deposit for {}? (Y/N): ", target)
+        };
+        let add_deposit = prompt.read_valid_str_value(
+                msg,
+                &["y", "n"],
+            )
+            .await
+            .context("Error while asking confirmation")? == "y";
+
+        if !add_deposit {
+            break;
+        }
+
+        first = false;
+
+        let asset = read_asset_name(prompt, wallet).await?;
+        if deposits.contains_key(&asset) {
+            // ask if he want to overwrite it
+            manager.message(format!("Deposit for this asset already exists for {}.", target));
+            let overwrite = prompt.read_valid_str_value(
+                    "Do you want to overwrite it? (Y/N): ".to_owned(),
+                    &["y", "n"],
+                )
+                .await
+                .context("Error while asking confirmation")? == "y";
+            if !overwrite {
+                continue;
+            }
+        }
+
+        let (amount, asset_data) = read_asset_amount(prompt, wallet, &asset).await?;
+        let private = allow_private && prompt.read_valid_str_value(
+                "Do you want this deposit to be private? (Y/N): ".to_owned(),
+                &["y", "n"],
+            )
+            .await
+            .context("Error while asking confirmation")? == "y";
+
+        manager.message(format!("Adding {}deposit of {} of {} ({})", if private { "private " } else { "" }, format_coin(amount, asset_data.get_decimals()), asset_data.get_name(), asset));
+
+        deposits.insert(asset, ContractDepositBuilder {
+            amount,
+            private,
+        });
+    }
+
+    if deposits.is_empty() {
+        manager.message(format!("No deposits added for {}.", target));
+    } else {
+        manager.message(format!("Total {} deposits added for {}.", deposits.len(), target));
+        let storage = wallet.get_storage().read().await;
+        for (asset, deposit) in deposits.iter() {
+            let asset_data = storage.get_asset(asset).await?;
+            manager.message(format!("- {}{} of {} ({})", if deposit.private { "private " } else { "" }, format_coin(deposit.amount, asset_data.get_decimals()), asset_data.get_name(), asset));
+        }
+    }
+
+    Ok(deposits)
+}
+
 async fn deploy_contract(manager: &CommandManager, mut args: ArgumentManager) -> Result<(), CommandError> {
     let prompt = manager.get_prompt();
     let context = manager.get_context().lock()?;
@@ -1465,62 +1589,7 @@ async fn deploy_contract(manager: &CommandManager, mut args: ArgumentManager) ->
             read_asset_amount(prompt, wallet, &DAPA_ASSET).await?.0
         };
 
-        let mut deposits = IndexMap::new();
-        let mut first = true;
-
-        loop {
-            let msg = if first {
-                "Do you want to add a deposit for the constructor? (Y/N): "
-            } else {
-                "Do you want to add another deposit for the constructor? (Y/N): "
-            };
-            let add_deposit = prompt.read_valid_str_value(
-                    msg.to_owned(),
-                    &["y", "n"],
-                )
-                .await
-                .context("Error while asking confirmation")? == "y";
-
-            if !add_deposit {
-                break;
-            }
-
-            first = false;
-
-            let asset = read_asset_name(&prompt, wallet).await?;
-            if deposits.contains_key(&asset) {
-                // ask if he want to overwrite it
-                manager.message("Deposit for this asset already exists for the constructor.");
-                let overwrite = prompt.read_valid_str_value(
-                        "Do you want to overwrite it? (Y/N): ".to_owned(),
-                        &["y", "n"],
-                    )
-                    .await
-                    .context("Error while asking confirmation")? == "y";
-                if !overwrite {
-                    continue;
-                }
-            }
-
-            let (amount, asset_data) = read_asset_amount(&prompt, wallet, &asset).await?;
-            manager.message(format!("Adding deposit of {} of {} ({})", format_coin(amount, asset_data.get_decimals()), asset_data.get_name(), asset));
-
-            deposits.insert(asset, ContractDepositBuilder {
-                amount,
-                private: false,
-            });
-        }
-
-        if deposits.is_empty() {
-            manager.message("No deposits added for the constructor.");
-        } else {
-            manager.message(format!("Total {} deposits added for the constructor.", deposits.len()));
-            let storage = wallet.get_storage().read().await;
-            for (asset, deposit) in deposits.iter() {
-                let asset_data = storage.get_asset(&asset).await?;
-                manager.message(format!("- {} of {} ({})", format_coin(deposit.amount, asset_data.get_decimals()), asset_data.get_name(), asset));
-            }
-        }
+        let deposits = read_contract_deposits(manager, prompt, wallet, "the constructor", false).await?;
 
         Some(DeployContractInvokeBuilder {
             deposits,
@@ -1550,6 +1619,194 @@ async fn deploy_contract(manager: &CommandManager, mut args: ArgumentManager) ->
     Ok(())
 }
 
+async fn invoke_contract(manager: &CommandManager, mut args: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let contract = if args.has_argument("contract") {
+        args.get_value("contract")?.to_hash()?
+    } else {
+        prompt.read_hash(
+            prompt.colorize_string(Color::Green, "Contract hash: ")
+        ).await.context("Error while reading contract hash")?
+    };
+
+    // Entries and parameters are named if the contract has a registered ABI
+    let abi = get_contract_abi(wallet, &contract).await;
+
+    let entry_id = if args.has_argument("entry_id") {
+        let value = args.get_value("entry_id")?.to_number()?;
+        u16::try_from(value).map_err(|_| CommandError::InvalidArgument(format!("Entry id must be lower or equal to {}", u16::MAX)))?
+    } else if let Some(abi) = abi.as_ref() {
+        manager.message("Entries available:");
+        for (id, entry) in abi.entries.iter() {
+            let params = entry.params.iter()
+                .map(|param| format!("{}: {}", param.name, param.kind))
+                .collect::<Vec<_>>();
+            manager.message(format!("- #{} {}({})", id, entry.name, params.join(", ")));
+        }
+
+        let input = prompt.read_input(prompt.colorize_string(Color::Green, "Entry name or id: "), false).await
+            .context("Error while reading entry")?;
+        let input = input.trim();
+        match u16::from_str(input) {
+            Ok(id) => id,
+            Err(_) => abi.get_entry_by_name(input)
+                .map(|(id, _)| id)
+                .ok_or_else(|| CommandError::InvalidArgument(format!("Unknown entry '{}'", input)))?
+        }
+    } else {
+        prompt.read(prompt.colorize_string(Color::Green, "Entry id: ")).await
+            .context("Error while reading entry id")?
+    };
+
+    let entry = abi.as_ref().and_then(|abi| abi.get_entry(entry_id));
+    let mut parameters = Vec::new();
+    if let Some(entry) = entry {
+        manager.message(format!("Enter the parameters of {}", entry.name));
+        for param in entry.params.iter() {
+            let value = loop {
+                let input = prompt.read_input(
+                    prompt.colorize_string(Color::Green, &format!("{} ({}): ", param.name, param.kind)),
+                    false
+                ).await.context("Error while reading parameter")?;
+
+                // Parameters without a known type use the <type>:<value> format
+                let input = if param.kind == ABI_ANY_TYPE {
+                    input
+                } else {
+                    format!("{}:{}", param.kind, input)
+                };
+
+                match parse_contract_argument(&input) {
+                    Ok(value) => break value,
+                    Err(e) => manager.error(format!("Invalid parameter: {:#}", e))
+                };
+            };
+            parameters.push(value);
+        }
+    } else {
+        manager.message(format!("Enter the parameters using the <type>:<value> format, supported types: {}", CONTRACT_ARGUMENT_TYPES.join(", ")));
+        manager.message("Leave empty to stop adding parameters.");
+        loop {
+            let input = prompt.read_input(
+                prompt.colorize_string(Color::Green, &format!("Parameter #{}: ", parameters.len())),
+                false
+            ).await.context("Error while reading parameter")?;
+
+            if input.trim().is_empty() {
+                break;
+            }
+
+            match parse_contract_argument(&input) {
+                Ok(value) => parameters.push(value),
+                Err(e) => manager.error(format!("Invalid parameter: {:#}", e))
+            };
+        }
+    }
+
+    let deposits = read_contract_deposits(manager, prompt, wallet, "the invocation", true).await?;
+
+    let max_gas = if args.has_argument("max_gas") {
+        args.get_value("max_gas")?.to_number()?
+    } else {
+        manager.message("Maximum gas you are willing to pay for the contract invocation.");
+        manager.message("If the contract uses more gas than this value, the invocation will fail.");
+        manager.message("If you allocate too much gas, it will be refunded to your account.");
+        manager.message(format!("Maximum gas per transaction is set to {} DAPA, you cannot exceed this value.", format_dapa(MAX_GAS_USAGE_PER_TX)));
+        read_asset_amount(prompt, wallet, &DAPA_ASSET).await?.0
+    };
+
+    if max_gas > MAX_GAS_USAGE_PER_TX {
+        return Err(CommandError::InvalidArgument(format!("Maximum gas cannot be above {} DAPA", format_dapa(MAX_GAS_USAGE_PER_TX))));
+    }
+
+    let mut tx_type = TransactionTypeBuilder::InvokeContract(InvokeContractBuilder {
+        contract: contract.clone(),
+        max_gas,
+        entry_id,
+        parameters,
+        deposits,
+        permission: Default::default(),
+    });
+
+    // The call is simulated to recommend a max gas, the fee shown is the one of the max gas that will be signed
+    let estimated = wallet.estimate_fees(tx_type.clone(), Default::default(), Default::default()).await
+        .context("Error while estimating TX fee")?;
+    let mut estimated_fee = estimated.fee;
+    let mut max_gas = max_gas;
+    match estimated.max_gas {
+        Some(recommended) if recommended > max_gas => {
+            manager.warn(format!("Maximum gas of {} DAPA is too low, the simulated call recommends {} DAPA", format_dapa(max_gas), format_dapa(recommended)));
+            let message = prompt.colorize_string(Color::Blue, "Use the recommended maximum gas ? (Y/N): ");
+            if args.get_flag("confirm")? || prompt.read_valid_str_value(message, &["y", "n"]).await.context("Error while reading answer")? == "y" {
+                max_gas = recommended;
+                if let Some(tx_max_gas) = tx_type.max_gas_mut() {
+                    *tx_max_gas = recommended;
+                }
+            } else {
+                estimated_fee = wallet.estimate_tx_fees(tx_type.clone(), Default::default(), Default::default()).await
+                    .context("Error while estimating TX fee")?;
+            }
+        },
+        Some(recommended) => {
+            // Unused gas is refunded, the provided max gas is kept
+            manager.message(format!("Simulated call recommends a maximum gas of {} DAPA", format_dapa(recommended)));
+            estimated_fee = wallet.estimate_tx_fees(tx_type.clone(), Default::default(), Default::default()).await
+                .context("Error while estimating TX fee")?;
+        },
+        None => manager.warn("Contract call couldn't be simulated, keeping the provided maximum gas")
+    };
+
+    match entry {
+        Some(entry) => manager.message(format!("Invoking entry {} (#{}) of contract {} with {} DAPA of maximum gas", entry.name, entry_id, contract, format_dapa(max_gas))),
+        None => manager.message(format!("Invoking entry {} of contract {} with {} DAPA of maximum gas", entry_id, contract, format_dapa(max_gas)))
+    };
+    if let Some(abi) = abi.as_ref().filter(|abi| !abi.events.is_empty()) {
+        let events = abi.events.iter()
+            .map(|(id, event)| format!("{} (#{})", event.name, id))
+            .collect::<Vec<_>>();
+        manager.message(format!("Contract events: {}", events.join(", ")));
+    }
+    manager.message(format!("Estimated TX fee is {} DAPA", format_dapa(estimated_fee)));
+    if !args.get_flag("confirm")? && !prompt.ask_confirmation().await.context("Error while confirming action")? {
+        manager.message("Transaction has been aborted");
+        return Ok(())
+    }
+
+    manager.message("Building transaction...");
+    let tx = create_transaction_with_multisig(manager, prompt, wallet, tx_type).await?;
+
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+// Retrieve the ABI registered on the daemon for a contract, if any
+// It is only used if it describes the latest module of the contract
+#[cfg_attr(not(feature = "network_handler"), allow(unused_variables))]
+async fn get_contract_abi(wallet: &Wallet, contract: &Hash) -> Option<ContractAbi> {
+    #[cfg(feature = "network_handler")]
+    if let Some(network_handler) = wallet.get_network_handler().lock().await.as_ref() {
+        let api = network_handler.get_api();
+        let registered = match api.get_contract_abi(contract).await {
+            Ok(registered) => registered?,
+            Err(e) => {
+                debug!("Error while retrieving ABI of contract {}: {:#}", contract, e);
+                return None
+            }
+        };
+
+        match api.get_contract_module_topoheight(contract).await {
+            Ok(topoheight) if topoheight == registered.topoheight => return Some(registered.abi),
+            Ok(topoheight) => debug!("ABI of contract {} describes the module at topoheight {} instead of {}", contract, registered.topoheight, topoheight),
+            Err(e) => debug!("Error while retrieving module topoheight of contract {}: {:#}", contract, e)
+        };
+    }
+
+    None
+}
+
 // Show current wallet address
 async fn display_address(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
@@ -1663,6 +1920,42 @@ async fn export_transactions_csv(manager: &CommandManager, mut arguments: Argume
     Ok(())
 }
 
+async fn bump_fee(manager: &CommandManager, mut args: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    if !wallet.is_online().await {
+        return Err(CommandError::InvalidArgument("Wallet must be online to replace a pending transaction".to_owned()))
+    }
+
+    let mut storage = wallet.get_storage().write().await;
+    let tx_hash = storage.get_tx_cache()
+        .and_then(|cache| cache.last_tx_hash_created.clone())
+        .context("No pending transaction found")?;
+
+    let multiplier = if args.has_argument("multiplier") {
+        args.get_value("multiplier")?.to_string_value()?
+            .parse::<f64>()
+            .context("Invalid fee multiplier")?
+    } else {
+        DEFAULT_BUMP_FEE_MULTIPLIER
+    };
+
+    manager.message(format!("Replacing transaction {} with a fee multiplied by {}", tx_hash, multiplier));
+    if !args.get_flag("confirm")? && !prompt.ask_confirmation().await.context("Error while confirming action")? {
+        manager.message("Transaction has been aborted");
+        return Ok(())
+    }
+
+    let fee = FeeBuilder::Extra(ExtraFeeMode::Multiplier(multiplier));
+    let tx = wallet.bump_fee(&mut storage, &tx_hash, fee, Default::default(), None).await
+        .context("Error while replacing transaction")?;
+
+    manager.message(format!("Transaction {} has been replaced by {} with a fee of {} DAPA", tx_hash, tx.hash(), format_dapa(tx.get_fee())));
+    Ok(())
+}
+
 async fn clear_tx_cache(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
@@ -1725,6 +2018,127 @@ async fn rescan(manager: &CommandManager, mut arguments: ArgumentManager) -> Res
     Ok(())
 }
 
+// Register the ABI of a contract deployed by this wallet
+// The ABI is read from a JSON file and signed with the wallet key
+#[cfg(feature = "network_handler")]
+async fn register_contract_abi(manager: &CommandManager, mut args: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let contract = if args.has_argument("contract") {
+        args.get_value("contract")?.to_hash()?
+    } else {
+        prompt.read_hash(
+            prompt.colorize_string(Color::Green, "Contract hash: ")
+        ).await.context("Error while reading contract hash")?
+    };
+
+    let path = if args.has_argument("file") {
+        args.get_value("file")?.to_string_value()?
+    } else {
+        prompt.read_input(
+            prompt.colorize_string(Color::Green, "ABI JSON file: "),
+            false
+        ).await.context("Error while reading ABI file path")?
+    };
+
+    let content = std::fs::read_to_string(&path).context("Error while reading ABI file")?;
+    let abi: ContractAbi = serde_json::from_str(&content).context("Invalid ABI JSON")?;
+
+    let network_handler = wallet.get_network_handler().lock().await;
+    let Some(network_handler) = network_handler.as_ref() else {
+        return Err(CommandError::Static("Wallet must be online to register a contract ABI"));
+    };
+    let api = network_handler.get_api();
+
+    // The ABI is signed for the latest module with a version greater than the registered one
+    let topoheight = api.get_contract_module_topoheight(&contract).await
+        .context("Error while retrieving contract module")?;
+    let version = api.get_contract_abi(&contract).await
+        .context("Error while retrieving registered contract ABI")?
+        .filter(|registered| registered.topoheight == topoheight)
+        .map_or(1, |registered| registered.version + 1);
+
+    let signature = wallet.sign_data(&abi.get_signing_bytes(&contract, topoheight, version));
+    api.register_contract_abi(&contract, &abi, topoheight, version, signature).await
+        .context("Error while registering contract ABI")?;
+
+    manager.message(format!("ABI version {} with {} entries and {} events registered for contract {}", version, abi.entries.len(), abi.events.len(), contract));
+    Ok(())
+}
+
+// Create a proof of the whole balance of an asset
+#[cfg(feature = "network_handler")]
+async fn balance_proof(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let asset = if arguments.has_argument("asset") {
+        arguments.get_value("asset")?.to_hash()?
+    } else {
+        read_asset_name(&prompt, wallet).await?
+    };
+    let topoheight = if arguments.has_argument("topoheight") {
+        Some(arguments.get_value("topoheight")?.to_number()?)
+    } else {
+        None
+    };
+
+    manager.message("Creating balance proof...");
+    let proof = wallet.create_balance_proof(asset, topoheight).await
+        .context("Error while creating balance proof")?;
+
+    manager.message(format!("Balance proof at topoheight {}: {}", proof.topoheight(), proof));
+    Ok(())
+}
+
+// Create a proof of ownership of an amount of an asset without revealing the balance
+#[cfg(feature = "network_handler")]
+async fn ownership_proof(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let asset = if arguments.has_argument("asset") {
+        arguments.get_value("asset")?.to_hash()?
+    } else {
+        read_asset_name(&prompt, wallet).await?
+    };
+
+    let (amount, asset_data) = if arguments.has_argument("amount") {
+        let value = arguments.get_value("amount")?.to_string_value()?;
+
+        let storage = wallet.get_storage().read().await;
+        let data = storage.get_asset(&asset).await?;
+
+        (
+            from_coin(value, data.get_decimals()).context("Invalid amount")?,
+            data
+        )
+    } else {
+        read_asset_amount(&prompt, wallet, &asset).await?
+    };
+
+    if amount == 0 {
+        return Err(CommandError::InvalidArgument("Amount must be greater than zero".to_string()))
+    }
+
+    let topoheight = if arguments.has_argument("topoheight") {
+        Some(arguments.get_value("topoheight")?.to_number()?)
+    } else {
+        None
+    };
+
+    manager.message(format!("Creating ownership proof of {} {}...", format_coin(amount, asset_data.get_decimals()), asset_data.get_name()));
+    let proof = wallet.create_ownership_proof(asset, amount, topoheight).await
+        .context("Error while creating ownership proof")?;
+
+    manager.message(format!("Ownership proof at topoheight {}: {}", proof.topoheight(), proof));
+    Ok(())
+}
+
 async fn seed(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
@@ -11,7 +11,11 @@ use dapa_common::{
         Serializer,
         Writer
     },
-    transaction::{MultiSigPayload, Reference}
+    transaction::{
+        builder::TransactionTypeBuilder,
+        MultiSigPayload,
+        Reference
+    }
 };
 
 
@@ -63,6 +67,9 @@ pub struct TxCache {
     pub last_tx_hash_created: Option<Hash>,
     // Set of assets used in the last transaction
     pub assets: HashSet<Hash>,
+    // Transaction type of the last transaction created
+    // This is used to rebuild it with a higher fee
+    pub last_tx_type_created: Option<TransactionTypeBuilder>,
 }
 
 // A multisig state in the wallet DB
@@ -3,7 +3,7 @@ use log::{debug, trace};
 use dapa_common::{
     account::CiphertextCache,
     crypto::{elgamal::Ciphertext, Hash, Hashable, PublicKey},
-    transaction::{builder::{AccountState, FeeHelper}, Reference, Transaction}
+    transaction::{builder::{AccountState, FeeHelper, TransactionTypeBuilder}, Reference, Transaction}
 };
 use crate::{error::WalletError, storage::{Balance, EncryptedStorage, TxCache}};
 
@@ -64,6 +64,8 @@ pub struct TransactionBuilderState {
     nonce: u64,
     // The hash of the transaction that has been built
     tx_hash_built: Option<Hash>,
+    // The type of the transaction that has been built
+    tx_type_built: Option<TransactionTypeBuilder>,
     // The stable topoheight detected during the TX building
     // This is used to update the last coinbase reward topoheight
     stable_topoheight: Option<u64>,
@@ -81,6 +83,7 @@ impl TransactionBuilderState {
             nonce,
             fee_limit,
             tx_hash_built: None,
+            tx_type_built: None,
             stable_topoheight: None,
         }
     }
@@ -141,6 +144,10 @@ impl TransactionBuilderState {
         self.tx_hash_built = Some(tx_hash);
     }
 
+    pub fn set_tx_type_built(&mut self, tx_type: TransactionTypeBuilder) {
+        self.tx_type_built = Some(tx_type);
+    }
+
     // Set the stable topoheight detected during the TX building
     pub fn set_stable_topoheight(&mut self, stable_topoheight: u64) {
         self.stable_topoheight = Some(stable_topoheight);
@@ -154,6 +161,7 @@ impl TransactionBuilderState {
             reference: self.reference.clone(),
             nonce: self.nonce,
             last_tx_hash_created: self.tx_hash_built.take(),
+            last_tx_type_created: self.tx_type_built.take(),
             assets: self.balances.keys().cloned().collect(),
         });
 
@@ -93,6 +93,7 @@ use crate::{
 use {
     log::warn,
     crate::{
+        config::GAS_ESTIMATION_MARGIN_PERCENT,
         network_handler::{
             NetworkHandler,
             SharedNetworkHandler
@@ -100,7 +101,13 @@ use {
         daemon_api::DaemonAPI,
         storage::Balance,
     },
-    dapa_common::config::{DAPA_ASSET, FEE_PER_KB},
+    dapa_common::{
+        config::{DAPA_ASSET, FEE_PER_KB, MAX_GAS_USAGE_PER_TX},
+        crypto::{
+            proofs::{BalanceProof, OwnershipProof},
+            HumanReadableProof
+        }
+    },
 };
 
 #[cfg(feature = "xswd")]
@@ -1067,7 +1074,7 @@ impl Wallet {
     // Create the transaction with all needed parameters
     pub fn create_transaction_with(&self, state: &mut TransactionBuilderState, threshold: Option<u8>, tx_version: TxVersion, transaction_type: TransactionTypeBuilder, fee: FeeBuilder) -> Result<Transaction, WalletError> {
         // Create the transaction builder
-        let builder = TransactionBuilder::new(tx_version, self.get_public_key().clone(), threshold, transaction_type, fee);
+        let builder = TransactionBuilder::new(tx_version, self.get_public_key().clone(), threshold, transaction_type.clone(), fee);
 
         // Build the final transaction
         let transaction = builder.build(state, self.get_keypair())
@@ -1076,6 +1083,56 @@ impl Wallet {
         let tx_hash = transaction.hash();
         debug!("Transaction created: {} with nonce {} and reference {}", tx_hash, transaction.get_nonce(), transaction.get_reference());
         state.set_tx_hash_built(tx_hash);
+        state.set_tx_type_built(transaction_type);
+
+        Ok(transaction)
+    }
+
+    // Rebuild the last created transaction with the same nonce and a new fee
+    // and submit it to replace the pending one in the daemon mempool
+    // It is only possible if this transaction is the only one pending:
+    // the TX cache only keeps the type of the last transaction created,
+    // and it is rebuilt on top of the confirmed balances
+    // Returns the new transaction once accepted by the daemon
+    pub async fn bump_fee(&self, storage: &mut EncryptedStorage, tx_hash: &Hash, fee: FeeBuilder, base_fee: BaseFeeMode, max_fee: Option<u64>) -> Result<Transaction, WalletError> {
+        trace!("bump fee for {} with {:?} and base fee {:?}", tx_hash, fee, base_fee);
+        let nonce = storage.get_nonce()?;
+        let transaction_type = match storage.get_tx_cache() {
+            Some(cache) if cache.last_tx_hash_created.as_ref() == Some(tx_hash) && cache.nonce == nonce + 1 => cache.last_tx_type_created.clone()
+                .ok_or_else(|| WalletError::TxNotReplaceable(tx_hash.clone()))?,
+            _ => return Err(WalletError::TxNotReplaceable(tx_hash.clone()))
+        };
+
+        // The replaced TX is not executed yet, so we build on top of our confirmed balances
+        let reference = Reference {
+            topoheight: storage.get_synced_topoheight()?,
+            hash: storage.get_top_block_hash()?
+        };
+        let mut state = TransactionBuilderState::new(self.network.is_mainnet(), reference, nonce, max_fee);
+
+        #[cfg(feature = "network_handler")]
+        self.retrieve_data_for_fees_estimation(state.as_mut(), fee, base_fee, &transaction_type).await?;
+
+        for asset in transaction_type.used_assets() {
+            if !storage.has_balance_for(asset).await? {
+                return Err(WalletError::BalanceNotFound(asset.clone()));
+            }
+
+            let balance = storage.get_balance_for(asset).await?;
+            debug!("Using confirmed balance for asset {} with amount {}, ciphertext: {}", asset, balance.amount, balance.ciphertext);
+            state.add_balance(asset.clone(), balance);
+        }
+
+        let threshold = storage.get_multisig_state().await?
+            .map(|m| m.payload.threshold);
+        let tx_version = storage.get_tx_version().await?;
+        let transaction = self.create_transaction_with(&mut state, threshold, tx_version, transaction_type, fee)?;
+
+        self.submit_transaction(&transaction).await?;
+
+        // Drop the unconfirmed balances of the replaced TX
+        storage.clear_tx_cache().await;
+        state.apply_changes(storage).await?;
 
         Ok(transaction)
     }
@@ -1166,10 +1223,116 @@ impl Wallet {
         Ok(())
     }
 
+    // Ask the daemon to execute the contract call of the transaction type
+    // against its current state and returns a recommended max gas
+    // Returns None if the TX type has no contract call, if we are not connected
+    // or if the simulation failed: the provided max gas must be kept in this case
+    #[cfg(feature = "network_handler")]
+    pub async fn estimate_contract_gas(&self, tx_type: &TransactionTypeBuilder, fee: FeeBuilder, base_fee: BaseFeeMode) -> Option<u64> {
+        trace!("estimate contract gas");
+        match self.simulate_contract_gas(tx_type, fee, base_fee).await {
+            Ok(max_gas) => max_gas,
+            Err(e) => {
+                warn!("Couldn't estimate the contract gas, keeping the provided max gas: {}", e);
+                None
+            }
+        }
+    }
+
+    #[cfg(feature = "network_handler")]
+    async fn simulate_contract_gas(&self, tx_type: &TransactionTypeBuilder, fee: FeeBuilder, base_fee: BaseFeeMode) -> Result<Option<u64>, WalletError> {
+        // The call is simulated with the maximum gas allowed
+        // so a max gas set too low by the user doesn't make it fail
+        let mut tx_type = tx_type.clone();
+        let Some(max_gas) = tx_type.max_gas_mut() else {
+            return Ok(None)
+        };
+        let user_max_gas = *max_gas;
+        *max_gas = MAX_GAS_USAGE_PER_TX;
+
+        let Some(network_handler) = ({ self.network_handler.lock().await.clone() }) else {
+            warn!("Network handler is not available, can't estimate the contract gas");
+            return Ok(None)
+        };
+
+        if !network_handler.is_running().await {
+            warn!("Network handler is not running, can't estimate the contract gas");
+            return Ok(None)
+        }
+
+        // Build a TX that is only simulated by the daemon, it is never submitted
+        // The confirmed nonce is used as the daemon verifies it against the chain state
+        let tx = {
+            let storage = self.storage.read().await;
+            // A multisig TX can't be signed by us alone, so it can't be simulated
+            if storage.has_multisig_state().await? {
+                return Ok(None)
+            }
+
+            let nonce = storage.get_nonce()?;
+            let tx_version = storage.get_tx_version().await?;
+            let mut state = self.create_transaction_state_with_storage(&storage, &tx_type, fee, base_fee, Some(nonce), None).await?;
+            match self.create_transaction_with(&mut state, None, tx_version, tx_type.clone(), fee) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    // The balance may not cover the maximum gas, retry with the user max gas
+                    debug!("Couldn't build the TX with the maximum gas, simulating with {}: {}", user_max_gas, e);
+                    if let Some(max_gas) = tx_type.max_gas_mut() {
+                        *max_gas = user_max_gas;
+                    }
+
+                    let mut state = self.create_transaction_state_with_storage(&storage, &tx_type, fee, base_fee, Some(nonce), None).await?;
+                    self.create_transaction_with(&mut state, None, tx_version, tx_type, fee)?
+                }
+            }
+        };
+
+        let result = network_handler.get_api().simulate_transaction(&tx).await?;
+        if !result.success {
+            let error = result.error.unwrap_or_else(|| "contract call failed".to_owned());
+            return Err(WalletError::GasEstimation(error))
+        }
+
+        // Keep a margin in case the contract state changes before the TX execution
+        let max_gas = result.used_gas
+            .saturating_add(result.used_gas * GAS_ESTIMATION_MARGIN_PERCENT / 100)
+            .min(MAX_GAS_USAGE_PER_TX);
+
+        debug!("Simulated contract call used {} gas, recommended max gas is {}", result.used_gas, max_gas);
+
+        Ok(Some(max_gas))
+    }
+
     // Estimate fees for a given transaction type
     // Estimated fees returned are the minimum required to be valid on chain
-    pub async fn estimate_fees(&self, tx_type: TransactionTypeBuilder, fee: FeeBuilder, base_fee: BaseFeeMode) -> Result<u64, WalletError> {
+    // For contract calls, a max gas is also recommended based on its execution by the daemon
+    // and the fees are estimated with it
+    pub async fn estimate_fees(&self, tx_type: TransactionTypeBuilder, fee: FeeBuilder, base_fee: BaseFeeMode) -> Result<EstimateFeesResult, WalletError> {
         trace!("estimate fees with {:?} and base fee {:?}", fee, base_fee);
+
+        #[allow(unused_mut)]
+        let (mut tx_type, mut max_gas) = (tx_type, None);
+
+        #[cfg(feature = "network_handler")]
+        if let Some(gas) = self.estimate_contract_gas(&tx_type, fee, base_fee).await {
+            if let Some(tx_max_gas) = tx_type.max_gas_mut() {
+                *tx_max_gas = gas;
+            }
+            max_gas = Some(gas);
+        }
+
+        let fee = self.estimate_tx_fees(tx_type, fee, base_fee).await?;
+
+        Ok(EstimateFeesResult {
+            fee,
+            max_gas
+        })
+    }
+
+    // Estimate the fees for a given transaction type as it is
+    // Contract calls are not simulated and their max gas is kept
+    pub async fn estimate_tx_fees(&self, tx_type: TransactionTypeBuilder, fee: FeeBuilder, base_fee: BaseFeeMode) -> Result<u64, WalletError> {
+        trace!("estimate tx fees with {:?} and base fee {:?}", fee, base_fee);
         let mut state = EstimateFeesState::new();
 
         #[cfg(feature = "network_handler")]
@@ -1275,6 +1438,57 @@ impl Wallet {
         Ok(())
     }
 
+    // Fetch from the daemon the balance ciphertext of an asset at the requested topoheight
+    // If no topoheight is provided, the latest balance is used
+    // Returns the ciphertext, its decrypted amount and its topoheight
+    #[cfg(feature = "network_handler")]
+    async fn get_onchain_balance(&self, asset: &Hash, topoheight: Option<u64>) -> Result<(Ciphertext, u64, u64), WalletError> {
+        trace!("get onchain balance for asset {} at topoheight {:?}", asset, topoheight);
+        let Some(network_handler) = ({ self.network_handler.lock().await.clone() }) else {
+            return Err(WalletError::NotOnlineMode)
+        };
+
+        let api = network_handler.get_api();
+        let address = self.get_address();
+        let (version, topoheight) = match topoheight {
+            Some(topoheight) => (api.get_balance_at_topoheight(&address, asset, topoheight).await?, topoheight),
+            None => {
+                let result = api.get_balance(&address, asset).await?;
+                (result.version, result.topoheight)
+            }
+        };
+
+        let ciphertext = version.take_balance()
+            .take_ciphertext()
+            .context("Error while decompressing balance ciphertext")?;
+
+        let amount = self.decrypt_ciphertext_of_asset(ciphertext.clone(), asset).await?
+            .ok_or(WalletError::CiphertextDecode)?;
+
+        Ok((ciphertext, amount, topoheight))
+    }
+
+    // Create a proof revealing the whole balance of an asset at the requested topoheight
+    #[cfg(feature = "network_handler")]
+    pub async fn create_balance_proof(&self, asset: Hash, topoheight: Option<u64>) -> Result<HumanReadableProof, WalletError> {
+        trace!("create balance proof for asset {}", asset);
+        let (ciphertext, amount, topoheight) = self.get_onchain_balance(&asset, topoheight).await?;
+        let proof = BalanceProof::new(self.get_keypair(), amount, ciphertext);
+
+        Ok(HumanReadableProof::Balance { proof, asset, topoheight })
+    }
+
+    // Create a proof that the wallet owns at least the amount of an asset at the requested topoheight
+    // The balance is not revealed
+    #[cfg(feature = "network_handler")]
+    pub async fn create_ownership_proof(&self, asset: Hash, amount: u64, topoheight: Option<u64>) -> Result<HumanReadableProof, WalletError> {
+        trace!("create ownership proof of {} for asset {}", amount, asset);
+        let (ciphertext, balance, topoheight) = self.get_onchain_balance(&asset, topoheight).await?;
+        let proof = OwnershipProof::new(self.get_keypair(), balance, amount, ciphertext)?;
+
+        Ok(HumanReadableProof::Ownership { proof, asset, topoheight })
+    }
+
     // set wallet in online mode: start a communication task which will keep the wallet synced
     #[cfg(feature = "network_handler")]
     pub async fn set_online_mode(self: &Arc<Self>, daemon_address: &String, auto_reconnect: bool) -> Result<(), WalletError> {